@@ -1,17 +1,30 @@
 //! Undo/Redo history management
 
-use super::state::Candidate;
+use super::state::{Candidate, Filters, GenerationRun};
 use serde::{Serialize, Deserialize};
 
 /// Action types that can be undone/redone
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Action {
-    Generate { candidates: Vec<Candidate> },
+    Generate {
+        candidates: Vec<Candidate>,
+        #[serde(default)]
+        run: Option<Box<GenerationRun>>,  // run entry of a generation, None for evolution
+    },
     Clear { candidates: Vec<Candidate> },
     Import { candidates: Vec<Candidate> },
     Delete { candidate: Candidate },
+    DeleteMany { candidates: Vec<Candidate> },
     UpdateAnnotation { id: usize, old_note: Option<String>, new_note: Option<String> },
     ToggleFavorite { id: usize },
+    ToggleFavorites { ids: Vec<usize> },  // bulk toggle, only ids whose state changed
+    RecomputeObjectives { old: Vec<Candidate> },
+    AddTag { id: usize, tag: String },
+    RemoveTag { id: usize, tag: String },
+    SetWeights { old: [f32; 4], new: [f32; 4] },  // [w_eff, w_tox, w_syn, w_mfg]
+    SetFilters { old: Box<Filters>, new: Box<Filters> },  // boxed: far larger than the other variants
+    MergeDuplicates { removed: Vec<Candidate>, old_annotations: Box<Annotations>, new_annotations: Box<Annotations> },
+    ImportAnnotations { matched: usize, old_annotations: Box<Annotations>, new_annotations: Box<Annotations> },
 }
 
 /// History manager for undo/redo
@@ -31,6 +44,15 @@ impl History {
         }
     }
 
+    /// Change the undo depth, dropping the oldest actions if needed
+    pub fn set_max_history(&mut self, n: usize) {
+        self.max_history = n;
+        if self.undo_stack.len() > n {
+            let excess = self.undo_stack.len() - n;
+            self.undo_stack.drain(..excess);
+        }
+    }
+
     /// Record a new action (clears redo stack)
     pub fn push(&mut self, action: Action) {
         self.redo_stack.clear();
@@ -91,12 +113,21 @@ impl History {
     /// Get description of last action for undo
     pub fn last_action_description(&self) -> Option<String> {
         self.undo_stack.last().map(|a| match a {
-            Action::Generate { candidates } => format!("Generate {} candidates", candidates.len()),
+            Action::Generate { candidates, .. } => format!("Generate {} candidates", candidates.len()),
             Action::Clear { candidates } => format!("Clear {} candidates", candidates.len()),
             Action::Import { candidates } => format!("Import {} candidates", candidates.len()),
             Action::Delete { candidate } => format!("Delete candidate {}", candidate.id),
+            Action::DeleteMany { candidates } => format!("Delete {} candidates", candidates.len()),
             Action::UpdateAnnotation { id, .. } => format!("Update annotation for #{}", id),
             Action::ToggleFavorite { id } => format!("Toggle favorite for #{}", id),
+            Action::ToggleFavorites { ids } => format!("Toggle favorite for {} candidates", ids.len()),
+            Action::RecomputeObjectives { old } => format!("Recompute objectives for {} candidates", old.len()),
+            Action::AddTag { id, tag } => format!("Add tag '{}' to #{}", tag, id),
+            Action::RemoveTag { id, tag } => format!("Remove tag '{}' from #{}", tag, id),
+            Action::SetWeights { .. } => "Weight change".to_string(),
+            Action::SetFilters { .. } => "Filter change".to_string(),
+            Action::MergeDuplicates { removed, .. } => format!("Merge {} duplicates", removed.len()),
+            Action::ImportAnnotations { matched, .. } => format!("Import annotations for {} candidates", matched),
         })
     }
 }
@@ -106,6 +137,10 @@ impl History {
 pub struct Annotations {
     notes: std::collections::HashMap<usize, String>,
     favorites: std::collections::HashSet<usize>,
+    #[serde(default)]
+    tags: std::collections::HashMap<usize, std::collections::HashSet<String>>,
+    #[serde(default)]
+    locked: std::collections::HashSet<usize>,  // kept by Clear
 }
 
 impl Annotations {
@@ -151,9 +186,109 @@ impl Annotations {
         self.notes.len()
     }
 
+    /// Lock or unlock a candidate, returns the new state
+    pub fn toggle_lock(&mut self, id: usize) -> bool {
+        if !self.locked.remove(&id) {
+            self.locked.insert(id);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn is_locked(&self, id: usize) -> bool {
+        self.locked.contains(&id)
+    }
+
+    pub fn locked_count(&self) -> usize {
+        self.locked.len()
+    }
+
+    /// Add a tag to a candidate, returns false if it was already present
+    pub fn add_tag(&mut self, id: usize, tag: &str) -> bool {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return false;
+        }
+        self.tags.entry(id).or_default().insert(tag.to_string())
+    }
+
+    /// Remove a tag from a candidate, returns false if it wasn't present
+    pub fn remove_tag(&mut self, id: usize, tag: &str) -> bool {
+        let Some(set) = self.tags.get_mut(&id) else {
+            return false;
+        };
+        let removed = set.remove(tag);
+        if set.is_empty() {
+            self.tags.remove(&id);
+        }
+        removed
+    }
+
+    pub fn has_tag(&self, id: usize, tag: &str) -> bool {
+        self.tags.get(&id).is_some_and(|set| set.contains(tag))
+    }
+
+    /// Tags of one candidate, sorted
+    pub fn tags_for(&self, id: usize) -> Vec<String> {
+        let mut tags: Vec<String> = self.tags.get(&id).map(|set| set.iter().cloned().collect()).unwrap_or_default();
+        tags.sort();
+        tags
+    }
+
+    /// Number of candidates carrying a tag
+    pub fn tag_count(&self, tag: &str) -> usize {
+        self.tags.values().filter(|set| set.contains(tag)).count()
+    }
+
+    /// Every tag in use, sorted and deduplicated
+    pub fn all_tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self.tags.values().flatten().cloned().collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    /// Add the lines of `note` to the candidate's note, skipping lines it already has
+    pub fn append_note(&mut self, id: usize, note: &str) {
+        for line in note.lines() {
+            match self.notes.get_mut(&id) {
+                Some(kept) if kept.lines().any(|l| l == line) => {}
+                Some(kept) => {
+                    kept.push('\n');
+                    kept.push_str(line);
+                }
+                None => {
+                    self.notes.insert(id, line.to_string());
+                }
+            }
+        }
+    }
+
+    /// Give `keep` the notes, tags, favorite and lock of the `merged` candidates.
+    /// Distinct notes are joined line by line; the merged ids keep their own annotations.
+    pub fn merge_into(&mut self, keep: usize, merged: &[usize]) {
+        for &id in merged {
+            if let Some(note) = self.notes.get(&id).cloned() {
+                self.append_note(keep, &note);
+            }
+            if self.favorites.contains(&id) {
+                self.favorites.insert(keep);
+            }
+            if self.locked.contains(&id) {
+                self.locked.insert(keep);
+            }
+            if let Some(tags) = self.tags.get(&id).cloned() {
+                self.tags.entry(keep).or_default().extend(tags);
+            }
+        }
+    }
+
     pub fn clear(&mut self) {
         self.notes.clear();
         self.favorites.clear();
+        self.tags.clear();
+        self.locked.clear();
     }
 }
 
@@ -170,6 +305,7 @@ mod tests {
             synthesis_cost: 0.2,
             manufacturing_cost: 0.2,
             pareto: false,
+            ..Default::default()
         }
     }
 
@@ -178,7 +314,8 @@ mod tests {
         let mut history = History::new(10);
         
         history.push(Action::Generate { 
-            candidates: vec![make_candidate(0)] 
+            candidates: vec![make_candidate(0)],
+            run: None,
         });
         
         assert!(history.can_undo());
@@ -194,6 +331,29 @@ mod tests {
         assert!(history.can_undo());
     }
 
+    #[test]
+    fn test_set_max_history() {
+        let mut history = History::new(10);
+        for id in 0..8 {
+            history.push(Action::Delete { candidate: make_candidate(id) });
+        }
+
+        history.set_max_history(3);
+        assert_eq!(history.undo_count(), 3);
+
+        // Oldest actions are gone, newest remain
+        match history.undo() {
+            Some(Action::Delete { candidate }) => assert_eq!(candidate.id, 7),
+            other => panic!("unexpected action: {:?}", other),
+        }
+        history.undo();
+        match history.undo() {
+            Some(Action::Delete { candidate }) => assert_eq!(candidate.id, 5),
+            other => panic!("unexpected action: {:?}", other),
+        }
+        assert!(!history.can_undo());
+    }
+
     #[test]
     fn test_annotations() {
         let mut annotations = Annotations::new();
@@ -207,4 +367,45 @@ mod tests {
         annotations.toggle_favorite(1);
         assert!(!annotations.is_favorite(1));
     }
+
+    #[test]
+    fn test_tags() {
+        let mut annotations = Annotations::new();
+
+        assert!(annotations.add_tag(1, "series-A"));
+        assert!(!annotations.add_tag(1, "series-A"));
+        assert!(!annotations.add_tag(1, "  "));
+        annotations.add_tag(1, "toxic");
+        annotations.add_tag(2, "series-A");
+
+        assert_eq!(annotations.tags_for(1), vec!["series-A", "toxic"]);
+        assert_eq!(annotations.all_tags(), vec!["series-A", "toxic"]);
+
+        assert!(annotations.remove_tag(1, "toxic"));
+        assert!(!annotations.remove_tag(1, "toxic"));
+        assert!(!annotations.has_tag(1, "toxic"));
+        assert_eq!(annotations.all_tags(), vec!["series-A"]);
+    }
+
+    #[test]
+    fn test_annotations_serde_roundtrip() {
+        let mut annotations = Annotations::new();
+        annotations.set_note(3, "check purity".to_string());
+        annotations.toggle_favorite(3);
+        annotations.add_tag(3, "to-synthesize");
+        annotations.add_tag(4, "series-B");
+
+        let json = serde_json::to_string(&annotations).unwrap();
+        let restored: Annotations = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.get_note(3), Some(&"check purity".to_string()));
+        assert!(restored.is_favorite(3));
+        assert_eq!(restored.tags_for(3), vec!["to-synthesize"]);
+        assert_eq!(restored.tags_for(4), vec!["series-B"]);
+
+        // Sessions saved before tags existed still load
+        let old: Annotations = serde_json::from_str(r#"{"notes":{},"favorites":[1]}"#).unwrap();
+        assert!(old.is_favorite(1));
+        assert!(old.all_tags().is_empty());
+    }
 }
@@ -1,85 +1,242 @@
-//! Import/Export functionality: SMILES files, SDF format
+//! Import/Export functionality: SMILES files, SDF format, annotations
 
-use super::state::Candidate;
+use super::history::Action;
+use super::state::{AppState, Candidate, GenSource};
+use crate::optimization::dedup::dedup_key;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::{BufRead, Write};
 
+/// Outcome of an import: candidates for the valid SMILES, and the rejected ones
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub added: Vec<Candidate>,
+    pub rejected: Vec<(String, String)>,  // (SMILES, reason)
+}
+
+/// Why an imported SMILES is unusable, if it is. Beyond `validate_smiles_detailed`
+/// the structure must parse, so no candidate is built from unreadable text.
+fn import_error(smiles: &str) -> Option<String> {
+    crate::chemistry::smiles::validate_smiles_detailed(smiles)
+        .and_then(|()| crate::chemistry::graph::parse_smiles(smiles).map(|_| ()))
+        .err()
+}
+
+/// Validate every SMILES, then build candidates with consecutive ids for the valid ones
+fn build_report<S: AsRef<str> + Sync>(smiles: &[S], start_id: usize) -> ImportReport {
+    ObjectiveCache::default().build_report(smiles, start_id)
+}
+
 /// Import SMILES from a text file (one SMILES per line)
-pub fn import_smiles_file(path: &str, start_id: usize) -> Result<Vec<Candidate>, String> {
+pub fn import_smiles_file(path: &str, start_id: usize) -> Result<ImportReport, String> {
     let file = std::fs::File::open(path)
         .map_err(|e| format!("Failed to open file: {}", e))?;
     
     let reader = std::io::BufReader::new(file);
-    let mut candidates = Vec::new();
-    let mut id = start_id;
+    let mut smiles_list = Vec::new();
     
     for line in reader.lines() {
         let line = line.map_err(|e| format!("Read error: {}", e))?;
-        let line = line.trim();
-        
-        if line.is_empty() || line.starts_with('#') {
-            continue;
-        }
         
         // Handle TSV/CSV: take first column as SMILES
-        let smiles = line.split(|c| c == '\t' || c == ',' || c == ' ')
-            .next()
-            .unwrap_or(line)
-            .trim();
-        
-        if !smiles.is_empty() {
-            let candidate = create_candidate_from_smiles(id, smiles);
-            candidates.push(candidate);
-            id += 1;
+        if let Some(smiles) = first_column(&line) {
+            smiles_list.push(smiles.to_string());
         }
     }
     
-    Ok(candidates)
+    Ok(build_report(&smiles_list, start_id))
+}
+
+/// Import SMILES from a CSV file. Reads the column headed `SMILES` (any case),
+/// or the first column when there is no such header.
+pub fn import_csv(path: &str, start_id: usize) -> Result<ImportReport, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let lines: Vec<&str> = content.lines().filter(|l| !l.trim().is_empty()).collect();
+    let header_column = lines.first()
+        .and_then(|header| header.split(',').position(|h| h.trim().eq_ignore_ascii_case("smiles")));
+    // Without a SMILES header the first line is data
+    let (column, rows) = match header_column {
+        Some(column) => (column, &lines[1..]),
+        None => (0, &lines[..]),
+    };
+
+    let smiles_list: Vec<&str> = rows.iter()
+        .filter_map(|line| line.split(',').nth(column))
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect();
+    Ok(build_report(&smiles_list, start_id))
+}
+
+/// Importer for a file dropped onto the window, chosen by extension
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DroppedFileKind {
+    Smiles,
+    Csv,
+    Sdf,
+    Session,
+}
+
+/// Pick the importer for a dropped file; None for unsupported extensions
+pub fn dropped_file_kind(path: &std::path::Path) -> Option<DroppedFileKind> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "smi" | "txt" => Some(DroppedFileKind::Smiles),
+        "csv" => Some(DroppedFileKind::Csv),
+        "sdf" => Some(DroppedFileKind::Sdf),
+        "json" => Some(DroppedFileKind::Session),
+        _ => None,
+    }
+}
+
+/// Lines read before each chunk of candidates is built and reported
+const STREAM_CHUNK_LINES: usize = 1000;
+
+/// Import a large SMILES file line-by-line, validating and building candidates one chunk
+/// at a time. `progress` receives the running candidate count after every chunk.
+pub fn import_smiles_file_streaming(
+    path: &str,
+    start_id: usize,
+    mut progress: impl FnMut(usize),
+) -> Result<ImportReport, String> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+    let reader = std::io::BufReader::new(file);
+
+    let mut report = ImportReport::default();
+    let mut cache = ObjectiveCache::default();
+    let mut chunk: Vec<String> = Vec::with_capacity(STREAM_CHUNK_LINES);
+    let mut lines = reader.lines();
+
+    loop {
+        let line = lines.next().transpose().map_err(|e| format!("Read error: {}", e))?;
+        if let Some(smiles) = line.as_deref().and_then(first_column) {
+            chunk.push(smiles.to_string());
+        }
+
+        let done = line.is_none();
+        if chunk.len() >= STREAM_CHUNK_LINES || (done && !chunk.is_empty()) {
+            let built = cache.build_report(&chunk, start_id + report.added.len());
+            report.added.extend(built.added);
+            report.rejected.extend(built.rejected);
+            chunk.clear();
+            progress(report.added.len());
+        }
+        if done {
+            break;
+        }
+    }
+
+    Ok(report)
+}
+
+/// Drop incoming candidates whose canonical SMILES already exists, either among
+/// `existing` or earlier in `incoming`. Returns the kept candidates and the skip count.
+pub fn dedup_candidates(existing: &[Candidate], incoming: Vec<Candidate>) -> (Vec<Candidate>, usize) {
+    use rayon::prelude::*;
+
+    let mut seen: HashSet<String> = existing.par_iter()
+        .map(|c| dedup_key(&c.smiles))
+        .collect();
+    let keys: Vec<String> = incoming.par_iter()
+        .map(|c| dedup_key(&c.smiles))
+        .collect();
+
+    let total = incoming.len();
+    let kept: Vec<Candidate> = incoming.into_iter()
+        .zip(keys)
+        .filter_map(|(c, key)| seen.insert(key).then_some(c))
+        .collect();
+    let skipped = total - kept.len();
+    (kept, skipped)
+}
+
+/// SMILES from the first TSV/CSV/space-separated column, skipping blanks and comments
+fn first_column(line: &str) -> Option<&str> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let smiles = line.split(['\t', ',', ' '])
+        .next()
+        .unwrap_or(line)
+        .trim();
+    (!smiles.is_empty()).then_some(smiles)
 }
 
 /// Import SMILES from a string (one per line or separated by newlines)
-pub fn import_smiles_text(text: &str, start_id: usize) -> Vec<Candidate> {
-    let mut candidates = Vec::new();
-    let mut id = start_id;
-    
-    for line in text.lines() {
-        let line = line.trim();
-        
-        if line.is_empty() || line.starts_with('#') {
-            continue;
+pub fn import_smiles_text(text: &str, start_id: usize) -> ImportReport {
+    let smiles_list: Vec<&str> = text.lines().filter_map(first_column).collect();
+    build_report(&smiles_list, start_id)
+}
+
+/// Objective values keyed by SMILES, so structures repeated within an import are scored once
+#[derive(Default)]
+struct ObjectiveCache {
+    by_smiles: HashMap<String, [f32; 4]>,
+}
+
+impl ObjectiveCache {
+    /// Candidates with consecutive ids from `start_id`; uncached SMILES are scored in parallel
+    fn build<S: AsRef<str> + Sync>(&mut self, smiles: &[S], start_id: usize) -> Vec<Candidate> {
+        use rayon::prelude::*;
+
+        let mut pending: Vec<&str> = smiles.iter()
+            .map(|s| s.as_ref())
+            .filter(|s| !self.by_smiles.contains_key(*s))
+            .collect();
+        pending.sort_unstable();
+        pending.dedup();
+        let scored: Vec<(String, [f32; 4])> = pending.par_iter()
+            .map(|s| (s.to_string(), import_objectives(s)))
+            .collect();
+        self.by_smiles.extend(scored);
+
+        smiles.iter()
+            .enumerate()
+            .map(|(i, s)| candidate_with_objectives(start_id + i, s.as_ref(), self.by_smiles[s.as_ref()]))
+            .collect()
+    }
+
+    /// Validate every SMILES, then build candidates with consecutive ids for the valid ones
+    fn build_report<S: AsRef<str> + Sync>(&mut self, smiles: &[S], start_id: usize) -> ImportReport {
+        use rayon::prelude::*;
+
+        let errors: Vec<Option<String>> = smiles.par_iter().map(|s| import_error(s.as_ref())).collect();
+        let mut valid = Vec::with_capacity(smiles.len());
+        let mut rejected = Vec::new();
+        for (s, error) in smiles.iter().zip(errors) {
+            match error {
+                Some(reason) => rejected.push((s.as_ref().to_string(), reason)),
+                None => valid.push(s.as_ref()),
+            }
         }
-        
-        let smiles = line.split(|c| c == '\t' || c == ',' || c == ' ')
-            .next()
-            .unwrap_or(line)
-            .trim();
-        
-        if !smiles.is_empty() {
-            let candidate = create_candidate_from_smiles(id, smiles);
-            candidates.push(candidate);
-            id += 1;
+        ImportReport {
+            added: self.build(&valid, start_id),
+            rejected,
         }
     }
-    
-    candidates
 }
 
-fn create_candidate_from_smiles(id: usize, smiles: &str) -> Candidate {
+/// [efficacy, toxicity, synthesis cost, manufacturing cost] for an imported SMILES.
+/// Descriptors and PAINS alerts are computed once and shared by every objective.
+fn import_objectives(smiles: &str) -> [f32; 4] {
     use crate::chemistry::{descriptors, druglikeness};
     
-    let mw = descriptors::molecular_weight_from_smiles(smiles);
-    let logp = descriptors::logp_from_smiles(smiles);
-    let psa = descriptors::polar_surface_area_from_smiles(smiles);
-    let (hbd, hba) = descriptors::hbd_hba_count(smiles);
+    let d = descriptors::compute_descriptors(smiles);
+    let alerts = druglikeness::check_pains(smiles);
+    let mw = d.molecular_weight;
     
     // Calculate properties based on descriptors
-    let dl_score = druglikeness::quick_druglikeness_score(smiles);
+    let dl_score = druglikeness::druglikeness_score_from(smiles, &d, &alerts);
     
     // Efficacy based on drug-likeness
     let efficacy = dl_score * 0.8 + 0.2 * if mw >= 200.0 && mw <= 500.0 { 1.0 } else { 0.5 };
     
     // Toxicity based on logP and alerts
-    let alerts = druglikeness::check_pains(smiles);
-    let toxicity = 0.1 + (logp.max(0.0) / 10.0) + (alerts.len() as f32 * 0.1);
+    let toxicity = 0.1 + (d.logp.max(0.0) / 10.0) + (alerts.len() as f32 * 0.1);
     
     // Synthesis cost based on complexity
     let complexity = smiles.len() as f32 / 50.0;
@@ -88,14 +245,29 @@ fn create_candidate_from_smiles(id: usize, smiles: &str) -> Candidate {
     // Manufacturing cost
     let manufacturing_cost = 0.15 + (mw / 1000.0).min(0.5);
     
+    [
+        efficacy.clamp(0.0, 1.0),
+        toxicity.clamp(0.0, 1.0),
+        synthesis_cost.clamp(0.0, 1.0),
+        manufacturing_cost.clamp(0.0, 1.0),
+    ]
+}
+
+fn candidate_with_objectives(id: usize, smiles: &str, objectives: [f32; 4]) -> Candidate {
+    let [efficacy, toxicity, synthesis_cost, manufacturing_cost] = objectives;
     Candidate {
         id,
         smiles: smiles.to_string(),
-        efficacy: efficacy.clamp(0.0, 1.0),
-        toxicity: toxicity.clamp(0.0, 1.0),
-        synthesis_cost: synthesis_cost.clamp(0.0, 1.0),
-        manufacturing_cost: manufacturing_cost.clamp(0.0, 1.0),
+        efficacy,
+        toxicity,
+        synthesis_cost,
+        manufacturing_cost,
         pareto: false,
+        run_id: None,
+        source: GenSource::Imported,
+        efficacy_sigma: 0.0,
+        toxicity_sigma: 0.0,
+        parent_ids: Vec::new(),
     }
 }
 
@@ -134,6 +306,10 @@ fn write_sdf_entry<W: Write>(writer: &mut W, candidate: &Candidate) -> std::io::
     writeln!(writer, "{}", candidate.smiles)?;
     writeln!(writer, "")?;
     
+    writeln!(writer, ">  <Formula>")?;
+    writeln!(writer, "{}", crate::chemistry::descriptors::molecular_formula(&candidate.smiles))?;
+    writeln!(writer)?;
+    
     writeln!(writer, ">  <ID>")?;
     writeln!(writer, "{}", candidate.id)?;
     writeln!(writer, "")?;
@@ -186,13 +362,335 @@ pub fn export_smiles_file(candidates: &[Candidate], path: &str) -> Result<(), St
     Ok(())
 }
 
-/// Parse SDF file and extract SMILES from properties
-pub fn import_sdf_file(path: &str, start_id: usize) -> Result<Vec<Candidate>, String> {
+const CSV_HEADER: &str = "ID,SMILES,Efficacy,Toxicity,SynthesisCost,ManufacturingCost,Pareto,Score,Favorite";
+const EXTENDED_CSV_HEADER: &str = ",MW,LogP,PSA,HBD,HBA,QED,LipinskiViolations,PainsAlerts";
+
+/// One candidate in the CSV column order (without extended columns), joined by `separator`
+pub fn candidate_row(c: &Candidate, score: f32, favorite: bool, separator: char) -> String {
+    [
+        c.id.to_string(),
+        c.smiles.clone(),
+        format!("{:.4}", c.efficacy),
+        format!("{:.4}", c.toxicity),
+        format!("{:.4}", c.synthesis_cost),
+        format!("{:.4}", c.manufacturing_cost),
+        c.pareto.to_string(),
+        format!("{:.4}", score),
+        if favorite { "1" } else { "0" }.to_string(),
+    ].join(&separator.to_string())
+}
+
+/// The basic CSV export as text, for the clipboard
+pub fn candidates_csv(
+    candidates: &[Candidate],
+    score: impl Fn(&Candidate) -> f32,
+    is_favorite: impl Fn(usize) -> bool,
+) -> String {
+    let mut text = format!("{}\n", CSV_HEADER);
+    for c in candidates {
+        text.push_str(&candidate_row(c, score(c), is_favorite(c.id), ','));
+        text.push('\n');
+    }
+    text
+}
+
+/// Export to CSV with objectives, Pareto flag, score and favorite marker.
+/// `extended` appends descriptor and drug-likeness columns.
+pub fn export_csv(
+    candidates: &[Candidate],
+    path: &str,
+    score: impl Fn(&Candidate) -> f32,
+    is_favorite: impl Fn(usize) -> bool,
+    extended: bool,
+) -> Result<(), String> {
+    let mut file = std::io::BufWriter::new(
+        std::fs::File::create(path).map_err(|e| format!("Failed to create file: {}", e))?,
+    );
+
+    let header = if extended { format!("{}{}", CSV_HEADER, EXTENDED_CSV_HEADER) } else { CSV_HEADER.to_string() };
+    writeln!(file, "{}", header).map_err(|e| format!("Write error: {}", e))?;
+
+    let columns = if extended { extended_columns(candidates) } else { HashMap::new() };
+    for c in candidates {
+        write!(file, "{}", candidate_row(c, score(c), is_favorite(c.id), ','))
+            .map_err(|e| format!("Write error: {}", e))?;
+        if let Some(extra) = columns.get(c.smiles.as_str()) {
+            write!(file, "{}", extra).map_err(|e| format!("Write error: {}", e))?;
+        }
+        writeln!(file).map_err(|e| format!("Write error: {}", e))?;
+    }
+
+    file.flush().map_err(|e| format!("Write error: {}", e))
+}
+
+/// Extended CSV columns per unique SMILES, computed in parallel
+fn extended_columns(candidates: &[Candidate]) -> HashMap<&str, String> {
+    use crate::chemistry::{descriptors, druglikeness};
+    use rayon::prelude::*;
+
+    let mut unique: Vec<&str> = candidates.iter().map(|c| c.smiles.as_str()).collect();
+    unique.sort_unstable();
+    unique.dedup();
+    unique.into_par_iter()
+        .map(|smiles| {
+            let d = descriptors::compute_descriptors(smiles);
+            let assessment = druglikeness::assess_druglikeness(smiles);
+            let columns = format!(
+                ",{:.2},{:.2},{:.2},{},{},{:.3},{},{}",
+                d.molecular_weight, d.logp, d.psa, d.hbd, d.hba,
+                druglikeness::qed(smiles),
+                assessment.lipinski.violations,
+                assessment.pains_hits.len()
+            );
+            (smiles, columns)
+        })
+        .collect()
+}
+
+/// Export to Parquet with typed columns: id, SMILES, the four objectives, the Pareto flag
+/// and the MW, LogP, PSA, HBD, HBA and QED descriptors
+pub fn export_parquet(candidates: &[Candidate], path: &str) -> Result<(), String> {
+    use crate::chemistry::{descriptors, druglikeness};
+    use arrow::array::{ArrayRef, BooleanArray, Float32Array, RecordBatch, StringArray, UInt32Array, UInt64Array};
+    use arrow::datatypes::{Field, Schema};
+    use parquet::arrow::ArrowWriter;
+    use rayon::prelude::*;
+    use std::sync::Arc;
+
+    let mut unique: Vec<&str> = candidates.iter().map(|c| c.smiles.as_str()).collect();
+    unique.sort_unstable();
+    unique.dedup();
+    let computed: HashMap<&str, (descriptors::MolecularDescriptors, f32)> = unique.into_par_iter()
+        .map(|smiles| (smiles, (descriptors::compute_descriptors(smiles), druglikeness::qed(smiles))))
+        .collect();
+    let rows: Vec<(&Candidate, &(descriptors::MolecularDescriptors, f32))> = candidates.iter()
+        .map(|c| (c, &computed[c.smiles.as_str()]))
+        .collect();
+
+    let float = |f: fn(&Candidate, &descriptors::MolecularDescriptors, f32) -> f32| -> ArrayRef {
+        Arc::new(Float32Array::from_iter_values(rows.iter().map(|(c, (d, qed))| f(c, d, *qed))))
+    };
+    let count = |f: fn(&descriptors::MolecularDescriptors) -> usize| -> ArrayRef {
+        Arc::new(UInt32Array::from_iter_values(rows.iter().map(|(_, (d, _))| f(d) as u32)))
+    };
+    let columns: Vec<(&str, ArrayRef)> = vec![
+        ("id", Arc::new(UInt64Array::from_iter_values(rows.iter().map(|(c, _)| c.id as u64)))),
+        ("smiles", Arc::new(StringArray::from_iter_values(rows.iter().map(|(c, _)| c.smiles.as_str())))),
+        ("efficacy", float(|c, _, _| c.efficacy)),
+        ("toxicity", float(|c, _, _| c.toxicity)),
+        ("synthesis_cost", float(|c, _, _| c.synthesis_cost)),
+        ("manufacturing_cost", float(|c, _, _| c.manufacturing_cost)),
+        ("pareto", Arc::new(rows.iter().map(|(c, _)| Some(c.pareto)).collect::<BooleanArray>())),
+        ("mw", float(|_, d, _| d.molecular_weight)),
+        ("logp", float(|_, d, _| d.logp)),
+        ("psa", float(|_, d, _| d.psa)),
+        ("hbd", count(|d| d.hbd)),
+        ("hba", count(|d| d.hba)),
+        ("qed", float(|_, _, qed| qed)),
+    ];
+
+    let schema = Schema::new(columns.iter()
+        .map(|(name, array)| Field::new(*name, array.data_type().clone(), false))
+        .collect::<Vec<Field>>());
+    let batch = RecordBatch::try_new(Arc::new(schema), columns.into_iter().map(|(_, array)| array).collect())
+        .map_err(|e| format!("Arrow error: {}", e))?;
+
+    let file = std::fs::File::create(path).map_err(|e| format!("Failed to create file: {}", e))?;
+    let mut writer = ArrowWriter::try_new(file, batch.schema(), None)
+        .map_err(|e| format!("Parquet error: {}", e))?;
+    writer.write(&batch).map_err(|e| format!("Write error: {}", e))?;
+    writer.close().map_err(|e| format!("Write error: {}", e))?;
+    Ok(())
+}
+
+/// Candidates beyond this are left out of the similarity graph (~4.5M pairs)
+pub const MAX_GRAPH_NODES: usize = 3000;
+
+/// Export a GraphML similarity network: one node per candidate with its objectives,
+/// one undirected edge per pair whose Tanimoto similarity exceeds `threshold`.
+/// Only the first `MAX_GRAPH_NODES` candidates are included.
+pub fn export_similarity_graph(candidates: &[Candidate], threshold: f32, path: &str) -> Result<(), String> {
+    use crate::chemistry::similarity::{generate_fingerprint, tanimoto_coefficient, Fingerprint};
+    use rayon::prelude::*;
+
+    let candidates = &candidates[..candidates.len().min(MAX_GRAPH_NODES)];
+
+    // One fingerprint per unique SMILES
+    let mut unique: Vec<&str> = candidates.iter().map(|c| c.smiles.as_str()).collect();
+    unique.sort_unstable();
+    unique.dedup();
+    let by_smiles: HashMap<&str, Fingerprint> = unique.into_par_iter()
+        .map(|s| (s, generate_fingerprint(s, 2048)))
+        .collect();
+    let fingerprints: Vec<&Fingerprint> = candidates.iter().map(|c| &by_smiles[c.smiles.as_str()]).collect();
+
+    let edges: Vec<(usize, usize, f32)> = (0..candidates.len())
+        .into_par_iter()
+        .flat_map_iter(|i| {
+            let fingerprints = &fingerprints;
+            ((i + 1)..candidates.len()).filter_map(move |j| {
+                let sim = tanimoto_coefficient(fingerprints[i], fingerprints[j]);
+                (sim > threshold).then_some((i, j, sim))
+            })
+        })
+        .collect();
+
+    let mut file = std::io::BufWriter::new(
+        std::fs::File::create(path).map_err(|e| format!("Failed to create file: {}", e))?,
+    );
+    write_graphml(&mut file, candidates, &edges)
+        .and_then(|()| file.flush())
+        .map_err(|e| format!("Write error: {}", e))
+}
+
+fn write_graphml<W: Write>(writer: &mut W, candidates: &[Candidate], edges: &[(usize, usize, f32)]) -> std::io::Result<()> {
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(writer, r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#)?;
+    writeln!(writer, r#"  <key id="smiles" for="node" attr.name="smiles" attr.type="string"/>"#)?;
+    for name in ["efficacy", "toxicity", "synthesis_cost", "manufacturing_cost"] {
+        writeln!(writer, r#"  <key id="{0}" for="node" attr.name="{0}" attr.type="double"/>"#, name)?;
+    }
+    writeln!(writer, r#"  <key id="pareto" for="node" attr.name="pareto" attr.type="boolean"/>"#)?;
+    writeln!(writer, r#"  <key id="similarity" for="edge" attr.name="similarity" attr.type="double"/>"#)?;
+    writeln!(writer, r#"  <graph id="similarity" edgedefault="undirected">"#)?;
+
+    for c in candidates {
+        writeln!(writer, r#"    <node id="n{}">"#, c.id)?;
+        writeln!(writer, r#"      <data key="smiles">{}</data>"#, xml_escape(&c.smiles))?;
+        writeln!(writer, r#"      <data key="efficacy">{:.4}</data>"#, c.efficacy)?;
+        writeln!(writer, r#"      <data key="toxicity">{:.4}</data>"#, c.toxicity)?;
+        writeln!(writer, r#"      <data key="synthesis_cost">{:.4}</data>"#, c.synthesis_cost)?;
+        writeln!(writer, r#"      <data key="manufacturing_cost">{:.4}</data>"#, c.manufacturing_cost)?;
+        writeln!(writer, r#"      <data key="pareto">{}</data>"#, c.pareto)?;
+        writeln!(writer, "    </node>")?;
+    }
+    for &(i, j, sim) in edges {
+        writeln!(
+            writer,
+            r#"    <edge source="n{}" target="n{}"><data key="similarity">{:.4}</data></edge>"#,
+            candidates[i].id, candidates[j].id, sim
+        )?;
+    }
+
+    writeln!(writer, "  </graph>")?;
+    writeln!(writer, "</graphml>")
+}
+
+/// Escape text for XML/HTML element content and attribute values
+pub fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Annotations of one molecule, portable across candidate sets
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct SmilesAnnotation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+    #[serde(default)]
+    favorite: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+}
+
+impl SmilesAnnotation {
+    /// Fold in the annotations of another candidate with the same structure: note lines
+    /// not yet present are appended, favorites and tags combined
+    fn merge(&mut self, other: SmilesAnnotation) {
+        for line in other.note.iter().flat_map(|note| note.lines()) {
+            match &mut self.note {
+                Some(note) if note.lines().any(|l| l == line) => {}
+                Some(note) => {
+                    note.push('\n');
+                    note.push_str(line);
+                }
+                None => self.note = Some(line.to_string()),
+            }
+        }
+        self.favorite |= other.favorite;
+        self.tags.extend(other.tags);
+        self.tags.sort();
+        self.tags.dedup();
+    }
+}
+
+/// Export notes, favorites and tags as JSON keyed by canonical SMILES. Annotated
+/// candidates sharing a structure are merged into one entry; returns how many were.
+pub fn export_annotations(state: &AppState, path: &str) -> Result<usize, String> {
+    let mut entries: BTreeMap<String, SmilesAnnotation> = BTreeMap::new();
+    let mut merged = 0;
+    for c in &state.candidates {
+        let entry = SmilesAnnotation {
+            note: state.annotations.get_note(c.id).cloned(),
+            favorite: state.annotations.is_favorite(c.id),
+            tags: state.annotations.tags_for(c.id),
+        };
+        if entry == SmilesAnnotation::default() {
+            continue;
+        }
+        match entries.get_mut(&dedup_key(&c.smiles)) {
+            Some(existing) => {
+                existing.merge(entry);
+                merged += 1;
+            }
+            None => {
+                entries.insert(dedup_key(&c.smiles), entry);
+            }
+        }
+    }
+
+    let json = serde_json::to_string_pretty(&entries)
+        .map_err(|e| format!("Serialization error: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write file: {}", e))?;
+    Ok(merged)
+}
+
+/// Reattach exported annotations to candidates with the same canonical SMILES (undoable).
+/// Imported notes are added as new lines of existing ones. Returns how many candidates
+/// received annotations.
+pub fn import_annotations(state: &mut AppState, path: &str) -> Result<usize, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let entries: BTreeMap<String, SmilesAnnotation> = serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid annotations file: {}", e))?;
+
+    let old_annotations = state.annotations.clone();
+    let mut matched = 0;
+    for c in &state.candidates {
+        let Some(entry) = entries.get(&dedup_key(&c.smiles)) else {
+            continue;
+        };
+        if let Some(note) = &entry.note {
+            state.annotations.append_note(c.id, note);
+        }
+        if entry.favorite && !state.annotations.is_favorite(c.id) {
+            state.annotations.toggle_favorite(c.id);
+        }
+        for tag in &entry.tags {
+            state.annotations.add_tag(c.id, tag);
+        }
+        matched += 1;
+    }
+
+    if matched > 0 {
+        state.history.push(Action::ImportAnnotations {
+            matched,
+            old_annotations: Box::new(old_annotations),
+            new_annotations: Box::new(state.annotations.clone()),
+        });
+    }
+    Ok(matched)
+}
+
+/// Parse SDF file and extract SMILES from properties. Records without a SMILES
+/// property are skipped; invalid SMILES are rejected like any other import.
+pub fn import_sdf_file(path: &str, start_id: usize) -> Result<ImportReport, String> {
     let content = std::fs::read_to_string(path)
         .map_err(|e| format!("Failed to read file: {}", e))?;
     
-    let mut candidates = Vec::new();
-    let mut id = start_id;
+    let mut smiles_list = Vec::new();
     
     // Split by $$$$ record separator
     for record in content.split("$$$$") {
@@ -203,13 +701,11 @@ pub fn import_sdf_file(path: &str, start_id: usize) -> Result<Vec<Candidate>, St
         
         // Try to find SMILES property
         if let Some(smiles) = extract_sdf_property(record, "SMILES") {
-            let candidate = create_candidate_from_smiles(id, &smiles);
-            candidates.push(candidate);
-            id += 1;
+            smiles_list.push(smiles);
         }
     }
     
-    Ok(candidates)
+    Ok(build_report(&smiles_list, start_id))
 }
 
 fn extract_sdf_property(record: &str, property: &str) -> Option<String> {
@@ -238,15 +734,310 @@ mod tests {
     #[test]
     fn test_import_smiles_text() {
         let text = "CCO\nCCCC\nc1ccccc1";
-        let candidates = import_smiles_text(text, 0);
+        let candidates = import_smiles_text(text, 0).added;
         assert_eq!(candidates.len(), 3);
         assert_eq!(candidates[0].smiles, "CCO");
     }
 
+    #[test]
+    fn test_import_rejects_invalid_smiles() {
+        let text = "CCO\nC((C\nc1ccccc1\nC1CCC\nhello\nCC(=O)O";
+        let report = import_smiles_text(text, 3);
+        let smiles: Vec<&str> = report.added.iter().map(|c| c.smiles.as_str()).collect();
+        assert_eq!(smiles, ["CCO", "c1ccccc1", "CC(=O)O"]);
+        // Ids stay consecutive across the rejected lines
+        assert_eq!(report.added.iter().map(|c| c.id).collect::<Vec<_>>(), vec![3, 4, 5]);
+
+        let rejected: Vec<&str> = report.rejected.iter().map(|(s, _)| s.as_str()).collect();
+        assert_eq!(rejected, ["C((C", "C1CCC", "hello"]);
+        assert!(report.rejected[1].1.contains("Ring closure 1"), "{}", report.rejected[1].1);
+        assert!(report.rejected.iter().all(|(_, reason)| !reason.is_empty()));
+    }
+
+    #[test]
+    fn test_dropped_file_kind() {
+        use std::path::Path;
+        assert_eq!(dropped_file_kind(Path::new("library.smi")), Some(DroppedFileKind::Smiles));
+        assert_eq!(dropped_file_kind(Path::new("/tmp/list.TXT")), Some(DroppedFileKind::Smiles));
+        assert_eq!(dropped_file_kind(Path::new("candidates.csv")), Some(DroppedFileKind::Csv));
+        assert_eq!(dropped_file_kind(Path::new("export.sdf")), Some(DroppedFileKind::Sdf));
+        assert_eq!(dropped_file_kind(Path::new("session.json")), Some(DroppedFileKind::Session));
+        assert_eq!(dropped_file_kind(Path::new("notes.pdf")), None);
+        assert_eq!(dropped_file_kind(Path::new("README")), None);
+    }
+
+    #[test]
+    fn test_import_csv() {
+        let dir = std::env::temp_dir();
+        let with_header = dir.join("dcs_import_header.csv");
+        std::fs::write(&with_header, "ID,SMILES,Efficacy\n0,CCO,0.5\n1,c1ccccc1,0.7\n\n").unwrap();
+        let candidates = import_csv(with_header.to_str().unwrap(), 10).unwrap().added;
+        let smiles: Vec<&str> = candidates.iter().map(|c| c.smiles.as_str()).collect();
+        assert_eq!(smiles, ["CCO", "c1ccccc1"]);
+        assert_eq!(candidates[0].id, 10);
+
+        let headerless = dir.join("dcs_import_plain.csv");
+        std::fs::write(&headerless, "CCN,amine\nCCC,propane\n").unwrap();
+        assert_eq!(import_csv(headerless.to_str().unwrap(), 0).unwrap().added.len(), 2);
+
+        let _ = std::fs::remove_file(with_header);
+        let _ = std::fs::remove_file(headerless);
+    }
+
+    #[test]
+    fn test_import_streaming() {
+        let path = std::env::temp_dir().join(format!("dcs_stream_{}.smi", std::process::id()));
+        let mut text = String::from("# header\nC1CC\nc1(c1ccncc1)ccccc1\n");
+        for i in 0..10_000 {
+            text.push_str(if i % 2 == 0 { "CCO\tethanol\n" } else { "c1ccccc1\n" });
+        }
+        std::fs::write(&path, text).unwrap();
+
+        let mut calls = Vec::new();
+        let report = import_smiles_file_streaming(path.to_str().unwrap(), 5, |n| calls.push(n)).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let candidates = report.added;
+
+        // Invalid lines are reported, not imported
+        let rejected: Vec<&str> = report.rejected.iter().map(|(s, _)| s.as_str()).collect();
+        assert_eq!(rejected, ["C1CC", "c1(c1ccncc1)ccccc1"]);
+
+        assert_eq!(candidates.len(), 10_000);
+        assert_eq!(candidates[0].id, 5);
+        assert_eq!(candidates[9_999].id, 10_004);
+        assert_eq!(candidates[0].smiles, "CCO");
+        assert_eq!(calls.len(), 11);
+        assert_eq!(calls.last(), Some(&10_000));
+        assert!(calls.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_import_sdf_rejects_invalid() {
+        let path = std::env::temp_dir().join(format!("dcs_import_{}.sdf", std::process::id()));
+        let record = |smiles: &str| format!("mol\n\n  0  0  0  0  0  0  0  0  0  0999 V2000\nM  END\n>  <SMILES>\n{}\n\n$$$$\n", smiles);
+        std::fs::write(&path, [record("CCO"), record("C1CC"), record("c1ccccc1")].concat()).unwrap();
+
+        let report = import_sdf_file(path.to_str().unwrap(), 2).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let smiles: Vec<&str> = report.added.iter().map(|c| c.smiles.as_str()).collect();
+        assert_eq!(smiles, ["CCO", "c1ccccc1"]);
+        assert_eq!(report.added[1].id, 3);
+        assert_eq!(report.rejected.len(), 1);
+        assert_eq!(report.rejected[0].0, "C1CC");
+    }
+
+    #[test]
+    fn test_dedup_candidates() {
+        let existing = import_smiles_text("c1ccccc1", 0).added;
+        let incoming = import_smiles_text("CCO\nOCC\nc1ccccc1\nCCN\nCCO", 1).added;
+        let (kept, skipped) = dedup_candidates(&existing, incoming);
+        assert_eq!(skipped, 3);
+        let smiles: Vec<&str> = kept.iter().map(|c| c.smiles.as_str()).collect();
+        assert_eq!(smiles, ["CCO", "CCN"]);
+
+        // Stereoisomers are not duplicates of each other
+        let incoming = import_smiles_text("C[C@H](N)O\nC[C@@H](N)O\nF/C=C/F\nF/C=C\\F", 1).added;
+        let (kept, skipped) = dedup_candidates(&existing, incoming);
+        assert_eq!((kept.len(), skipped), (4, 0));
+    }
+
+    #[test]
+    fn test_annotations_roundtrip_by_smiles() {
+        let path = std::env::temp_dir().join(format!("dcs_annotations_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let mut source = AppState {
+            candidates: import_smiles_text("CCO\nc1ccccc1\nCCN", 0).added,
+            ..Default::default()
+        };
+        source.annotations.set_note(0, "solvent".into());
+        source.annotations.toggle_favorite(1);
+        source.annotations.add_tag(1, "aromatic");
+        export_annotations(&source, path).unwrap();
+
+        // Different ids and atom order for the same molecules
+        let mut target = AppState {
+            candidates: import_smiles_text("NCC\nC(O)C\nC1=CC=CC=C1\nc1ccccc1", 10).added,
+            ..Default::default()
+        };
+        let matched = import_annotations(&mut target, path).unwrap();
+        let _ = std::fs::remove_file(path);
+
+        assert_eq!(matched, 2);
+        assert_eq!(target.annotations.get_note(11).map(String::as_str), Some("solvent"));
+        assert!(target.annotations.is_favorite(13));
+        assert!(target.annotations.has_tag(13, "aromatic"));
+        assert_eq!(target.annotations.get_note(10), None);
+        assert!(!target.annotations.is_favorite(10));
+
+        // One undo step takes the whole import back
+        target.undo();
+        assert_eq!(target.annotations.get_note(11), None);
+        assert!(!target.annotations.is_favorite(13));
+        target.redo();
+        assert!(target.annotations.has_tag(13, "aromatic"));
+    }
+
+    #[test]
+    fn test_export_annotations_merges_duplicates() {
+        let path = std::env::temp_dir().join(format!("dcs_annotations_dup_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        // Two annotated copies of ethanol share one canonical SMILES key
+        let mut source = AppState {
+            candidates: import_smiles_text("CCO\nOCC\nc1ccccc1", 0).added,
+            ..Default::default()
+        };
+        source.annotations.set_note(0, "solvent".into());
+        source.annotations.set_note(1, "cheap".into());
+        source.annotations.add_tag(1, "bulk");
+        source.annotations.toggle_favorite(1);
+        assert_eq!(export_annotations(&source, path).unwrap(), 1);
+
+        let mut target = AppState {
+            candidates: import_smiles_text("CCO", 5).added,
+            ..Default::default()
+        };
+        target.annotations.set_note(5, "solvent".into());
+        assert_eq!(import_annotations(&mut target, path).unwrap(), 1);
+        let _ = std::fs::remove_file(path);
+
+        // Nothing from either copy is lost, and the existing note line isn't repeated
+        assert_eq!(target.annotations.get_note(5).map(String::as_str), Some("solvent\ncheap"));
+        assert!(target.annotations.is_favorite(5));
+        assert!(target.annotations.has_tag(5, "bulk"));
+    }
+
+    #[test]
+    fn test_export_extended_csv() {
+        let path = std::env::temp_dir().join(format!("dcs_extended_{}.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+        let candidates = import_smiles_text("CCO\nc1ccccc1O\nCCO", 0).added;
+
+        export_csv(&candidates, path, |_| 0.5, |id| id == 1, true).unwrap();
+        let text = std::fs::read_to_string(path).unwrap();
+        let mut lines = text.lines();
+        let header: Vec<&str> = lines.next().unwrap().split(',').collect();
+        assert_eq!(header.len(), 17);
+        assert_eq!(&header[9..], ["MW", "LogP", "PSA", "HBD", "HBA", "QED", "LipinskiViolations", "PainsAlerts"]);
+        let rows: Vec<&str> = lines.collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows.iter().all(|r| r.split(',').count() == 17));
+
+        // The basic format is unchanged
+        export_csv(&candidates, path, |_| 0.5, |id| id == 1, false).unwrap();
+        let text = std::fs::read_to_string(path).unwrap();
+        assert_eq!(text.lines().next().unwrap(), CSV_HEADER);
+        assert!(text.lines().all(|l| l.split(',').count() == 9));
+        assert_eq!(text, candidates_csv(&candidates, |_| 0.5, |id| id == 1));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_candidate_row() {
+        let c = Candidate {
+            id: 7,
+            smiles: "CCO".into(),
+            efficacy: 0.8,
+            toxicity: 0.25,
+            synthesis_cost: 0.5,
+            manufacturing_cost: 0.125,
+            pareto: true,
+            ..Default::default()
+        };
+        assert_eq!(candidate_row(&c, -0.1, false, '\t'), "7\tCCO\t0.8000\t0.2500\t0.5000\t0.1250\ttrue\t-0.1000\t0");
+        assert_eq!(candidate_row(&c, 0.3, true, ','), "7,CCO,0.8000,0.2500,0.5000,0.1250,true,0.3000,1");
+
+        let text = candidates_csv(&[c], |_| 0.3, |_| true);
+        assert_eq!(text.lines().collect::<Vec<_>>(), [CSV_HEADER, "7,CCO,0.8000,0.2500,0.5000,0.1250,true,0.3000,1"]);
+    }
+
+    #[test]
+    fn test_export_parquet_roundtrip() {
+        use arrow::array::{Array, BooleanArray, Float32Array, StringArray};
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let path = std::env::temp_dir().join(format!("dcs_export_{}.parquet", std::process::id()));
+        let mut candidates = import_smiles_text("CCO\nc1ccccc1O\nCCN\nCCO", 0).added;
+        candidates[1].pareto = true;
+        export_parquet(&candidates, path.to_str().unwrap()).unwrap();
+
+        let file = std::fs::File::open(&path).unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file).unwrap().build().unwrap();
+        let batches: Vec<_> = reader.collect::<Result<_, _>>().unwrap();
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 4);
+
+        let batch = &batches[0];
+        assert_eq!(batch.num_columns(), 13);
+        let column = |name: &str| batch.column_by_name(name).unwrap().clone();
+        let smiles = column("smiles");
+        let smiles = smiles.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(smiles.value(1), "c1ccccc1O");
+        let toxicity = column("toxicity");
+        assert_eq!(toxicity.as_any().downcast_ref::<Float32Array>().unwrap().value(2), candidates[2].toxicity);
+        let pareto = column("pareto");
+        let pareto = pareto.as_any().downcast_ref::<BooleanArray>().unwrap();
+        assert!(pareto.value(1) && !pareto.value(0));
+        let mw = column("mw");
+        let mw = mw.as_any().downcast_ref::<Float32Array>().unwrap();
+        assert_eq!(mw.value(0), mw.value(3));
+        assert!((mw.value(0) - 46.07).abs() < 0.1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_export_similarity_graph() {
+        let path = std::env::temp_dir().join(format!("dcs_graph_{}.graphml", std::process::id()));
+        let path = path.to_str().unwrap();
+        // Two homologous pairs (Tanimoto >= 0.9) and one unrelated acid
+        let candidates = import_smiles_text(
+            "c1ccc2ccccc2c1CCN\nc1ccc2ccccc2c1CCCN\nCCCCCC\nCCCCCCC\nO=C(O)c1ccccc1", 0).added;
+        assert_eq!(candidates.len(), 5);
+
+        export_similarity_graph(&candidates, 0.85, path).unwrap();
+        let xml = std::fs::read_to_string(path).unwrap();
+        assert_eq!(xml.matches("<node ").count(), 5);
+        assert_eq!(xml.matches("<edge ").count(), 2);
+        assert!(xml.contains(r#"<edge source="n0" target="n1">"#));
+        assert!(xml.contains(r#"<edge source="n2" target="n3">"#));
+
+        // Edges need similarity strictly above the threshold
+        export_similarity_graph(&candidates, 1.0, path).unwrap();
+        let xml = std::fs::read_to_string(path).unwrap();
+        assert_eq!(xml.matches("<edge ").count(), 0);
+        std::fs::remove_file(path).unwrap();
+    }
+
     #[test]
     fn test_create_candidate() {
-        let c = create_candidate_from_smiles(0, "CCO");
+        let c = candidate_with_objectives(0, "CCO", import_objectives("CCO"));
         assert!(!c.smiles.is_empty());
         assert!(c.efficacy >= 0.0 && c.efficacy <= 1.0);
     }
+
+    #[test]
+    fn test_import_objectives_golden() {
+        // Values produced before descriptors and PAINS were shared across objectives
+        let golden: [(&str, [f32; 4]); 6] = [
+            ("CCO", [0.90000004, 0.1, 0.16, 0.19606805]),
+            ("CC(=O)Oc1ccccc1C(=O)O", [0.66, 0.43101, 0.52, 0.3301577]),
+            ("CN1C=NC2=C1C(=O)N(C(=O)N2C)C", [0.82, 0.2, 0.66, 0.3441912]),
+            ("c1ccc(O)c(O)c1", [0.82, 0.30978, 0.38, 0.26011103]),
+            ("CCCCCCCCCCCCCCCCCCCC(=O)NN", [0.64, 0.9018001, 0.62, 0.47656155]),
+            ("not a smiles", [0.90000004, 0.1, 0.34, 0.15]),
+        ];
+        for (smiles, expected) in golden {
+            let actual = import_objectives(smiles);
+            for (a, e) in actual.iter().zip(expected) {
+                assert!((a - e).abs() < 1e-6, "{}: {:?} vs {:?}", smiles, actual, expected);
+            }
+        }
+
+        // Repeated SMILES reuse the cached values and still get their own ids
+        let text = "CCO\nc1ccc(O)c(O)c1\nCCO";
+        let candidates = import_smiles_text(text, 5).added;
+        assert_eq!(candidates.iter().map(|c| c.id).collect::<Vec<_>>(), vec![5, 6, 7]);
+        assert_eq!(candidates[0].efficacy, candidates[2].efficacy);
+        assert_eq!(candidates[1].toxicity, import_objectives("c1ccc(O)c(O)c1")[1]);
+    }
 }
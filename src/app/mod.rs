@@ -3,11 +3,16 @@ pub mod ui;
 pub mod history;
 pub mod theme;
 pub mod io;
+pub mod shortcuts;
+pub mod report;
 
 use eframe::egui;
-use state::AppState;
+use state::{AppState, WeightPreset};
 use theme::ThemeSettings;
 
+const SETTINGS_KEY: &str = "theme_settings";
+const WEIGHT_PRESET_KEY: &str = "weight_preset";
+
 pub struct App {
     state: AppState,
     theme: ThemeSettings,
@@ -24,7 +29,37 @@ impl Default for App {
     }
 }
 
+impl App {
+    /// Create the app, restoring persisted settings if available
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let mut app = Self::default();
+        if let Some(theme) = cc.storage.and_then(|s| eframe::get_value::<ThemeSettings>(s, SETTINGS_KEY)) {
+            app.state.history.set_max_history(theme.max_history);
+            app.state.autosave_interval_secs = theme.autosave_secs;
+            app.state.display_decimals = theme.display_decimals.clamp(*state::DISPLAY_DECIMALS_RANGE.start(), *state::DISPLAY_DECIMALS_RANGE.end());
+            app.theme = theme;
+        }
+        app.state.show_recover_dialog = ui::top_bar::autosave_is_newer();
+        if let Some(preset) = cc.storage.and_then(|s| eframe::get_value::<WeightPreset>(s, WEIGHT_PRESET_KEY)) {
+            app.state.apply_weight_preset(preset);
+        }
+        app
+    }
+}
+
 impl eframe::App for App {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, SETTINGS_KEY, &self.theme);
+        if let Some(preset) = self.state.weight_preset {
+            eframe::set_value(storage, WEIGHT_PRESET_KEY, &preset);
+        }
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // A normal exit leaves nothing to recover
+        self.state.remove_autosave();
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Apply theme on first frame or when changed
         if !self.theme_applied {
@@ -34,16 +69,48 @@ impl eframe::App for App {
 
         // Process worker messages first
         self.state.process_worker_messages();
+        self.state.recompute_pareto_if_dirty();
+        self.state.refresh_property_cache();
+        self.state.refresh_formula_cache();
 
-        // Request repaint if generating (to update progress bar)
-        if self.state.is_generating {
+        // Request repaint if generating, importing or sweeping (to update progress)
+        if self.state.is_generating || self.state.import_progress.is_some() || self.state.sweep_progress.is_some() {
             ctx.request_repaint();
         }
 
+        // Periodic crash-recovery snapshot; wake up for it even when idle
+        self.state.autosave_if_due();
+        if self.state.autosave_interval_secs > 0 {
+            ctx.request_repaint_after(std::time::Duration::from_secs(self.state.autosave_interval_secs));
+        }
+
+        // Files dropped onto the window
+        let dropped = ctx.input(|i| i.raw.dropped_files.clone());
+        for path in dropped.iter().filter_map(|f| f.path.as_deref()) {
+            self.state.import_dropped_file(path);
+        }
+
+        // Keyboard shortcuts
+        shortcuts::handle(ctx, &mut self.state);
+
         // Render UI
         ui::top_bar::render(ctx, &mut self.state, &mut self.theme);
         ui::side_panel::render(ctx, &mut self.state);
+        ui::log_panel::render(ctx, &mut self.state);
         ui::candidates::render(ctx, &mut self.state);
+        ui::inspector::render(ctx, &mut self.state);
+        ui::compare::render(ctx, &mut self.state);
+        ui::command_palette::render(ctx, &mut self.state);
+
+        // Weight and filter edits become undo steps once the drag or typing is over
+        if !ctx.input(|i| i.pointer.any_down()) && !ctx.wants_keyboard_input() {
+            self.state.record_setting_changes();
+        }
+
+        // Changes made while rendering are picked up next frame
+        if self.state.pareto_dirty {
+            ctx.request_repaint();
+        }
 
         // Apply theme if changed
         if self.state.theme_changed {
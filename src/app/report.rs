@@ -0,0 +1,121 @@
+//! Self-contained HTML report of the current session
+
+use std::fmt::Write;
+use super::io::xml_escape;
+use super::state::{scaffold_diversity, AppState, Candidate};
+use crate::chemistry::{druglikeness, layout};
+
+const REPORT_STYLE: &str = "body{font-family:sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin-bottom:1.5em}\
+th,td{border:1px solid #ccc;padding:4px 8px;text-align:left;vertical-align:middle}\
+th{background:#f0f0f0}\
+td.smiles{font-family:monospace;max-width:20em;word-break:break-all}";
+
+/// Write an HTML report with run metadata, summary statistics and the top
+/// `state.top_n` candidates by weighted score, structures inlined as SVG
+pub fn generate_html_report(state: &AppState, path: &str) -> Result<(), String> {
+    std::fs::write(path, html_report(state)).map_err(|e| format!("Write error: {}", e))
+}
+
+fn html_report(state: &AppState) -> String {
+    let mut html = String::new();
+    let _ = writeln!(html, "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">");
+    let _ = writeln!(html, "<title>Drug Candidate Studio report</title>\n<style>{}</style>\n</head>\n<body>", REPORT_STYLE);
+    let _ = writeln!(html, "<h1>Drug Candidate Studio report</h1>");
+    let _ = writeln!(html, "<p>Generated {}</p>", chrono::Local::now().format("%Y-%m-%d %H:%M"));
+
+    write_summary(&mut html, state);
+    write_runs(&mut html, state);
+    write_top_candidates(&mut html, state);
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn write_summary(html: &mut String, state: &AppState) {
+    let total = state.candidates.len();
+    let pareto = state.candidates.iter().filter(|c| c.pareto).count();
+    let _ = writeln!(html, "<h2>Summary</h2>");
+    let _ = writeln!(html, "<p>{} candidates, {} on the Pareto front, {} favorites. Weights: efficacy {:.1}, toxicity {:.1}, synthesis {:.1}, manufacturing {:.1}.</p>",
+        total, pareto, state.annotations.favorite_count(), state.w_eff, state.w_tox, state.w_syn, state.w_mfg);
+    if total == 0 {
+        return;
+    }
+    let (scaffolds, per_candidate) = scaffold_diversity(&state.candidates);
+    let _ = writeln!(html, "<p>{} distinct scaffolds ({:.2} per candidate).</p>", scaffolds, per_candidate);
+
+    let _ = writeln!(html, "<table>\n<tr><th>Objective</th><th>Mean</th><th>Min</th><th>Max</th></tr>");
+    for (k, name) in ["Efficacy", "Toxicity", "Synthesis cost", "Manufacturing cost"].into_iter().enumerate() {
+        let values = state.candidates.iter()
+            .map(|c| [c.efficacy, c.toxicity, c.synthesis_cost, c.manufacturing_cost][k]);
+        let mean = values.clone().sum::<f32>() / total as f32;
+        let min = values.clone().fold(f32::INFINITY, f32::min);
+        let max = values.fold(f32::NEG_INFINITY, f32::max);
+        let _ = writeln!(html, "<tr><td>{}</td><td>{:.3}</td><td>{:.3}</td><td>{:.3}</td></tr>", name, mean, min, max);
+    }
+    let _ = writeln!(html, "</table>");
+}
+
+fn write_runs(html: &mut String, state: &AppState) {
+    let _ = writeln!(html, "<h2>Generation runs</h2>");
+    if state.runs.is_empty() {
+        let _ = writeln!(html, "<p>No generation runs (imported candidates only).</p>");
+        return;
+    }
+    let _ = writeln!(html, "<table>\n<tr><th>Run</th><th>Time</th><th>Seed</th><th>Candidates</th><th>Mode</th><th>Category</th></tr>");
+    for run in &state.runs {
+        let _ = writeln!(html, "<tr><td>#{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            run.run_id,
+            run.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            run.seed,
+            run.candidate_ids.len(),
+            if run.parallel { "parallel" } else { "sequential" },
+            xml_escape(run.category.as_deref().unwrap_or("All")));
+    }
+    let _ = writeln!(html, "</table>");
+}
+
+fn write_top_candidates(html: &mut String, state: &AppState) {
+    let mut ranked: Vec<(&Candidate, f32)> = state.candidates.iter()
+        .map(|c| (c, state.weighted_score(c)))
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.id.cmp(&b.0.id)));
+    ranked.truncate(state.top_n);
+
+    let _ = writeln!(html, "<h2>Top {} candidates</h2>", ranked.len());
+    let _ = writeln!(html, "<table>\n<tr><th>Rank</th><th>ID</th><th>Structure</th><th>SMILES</th><th>Efficacy</th><th>Toxicity</th><th>Synthesis</th><th>Mfg</th><th>Score</th><th>QED</th><th>PAINS</th></tr>");
+    for (rank, (c, score)) in ranked.into_iter().enumerate() {
+        let structure = layout::molecule_to_svg(&c.smiles, 160, 120).unwrap_or_default();
+        let _ = writeln!(html, "<tr class=\"candidate\"><td>{}</td><td>#{}</td><td>{}</td><td class=\"smiles\">{}</td><td>{:.3}</td><td>{:.3}</td><td>{:.3}</td><td>{:.3}</td><td>{:.3}</td><td>{:.3}</td><td>{}</td></tr>",
+            rank + 1, c.id, structure, xml_escape(&c.smiles),
+            c.efficacy, c.toxicity, c.synthesis_cost, c.manufacturing_cost, score,
+            druglikeness::qed(&c.smiles),
+            druglikeness::check_pains(&c.smiles).len());
+    }
+    let _ = writeln!(html, "</table>");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_report_rows() {
+        let mut state = AppState { top_n: 3, ..Default::default() };
+        state.import_from_text("CCO\nc1ccccc1O\nCC(=O)O\nCCN\nc1ccncc1");
+
+        let html = html_report(&state);
+        assert_eq!(html.matches("<tr class=\"candidate\">").count(), 3);
+        assert_eq!(html.matches("<svg").count(), 3);
+        assert!(html.contains("5 candidates"));
+
+        // Fewer candidates than top_n lists them all
+        state.top_n = 10;
+        let path = std::env::temp_dir().join(format!("dcs_report_{}.html", std::process::id()));
+        let path = path.to_str().unwrap();
+        generate_html_report(&state, path).unwrap();
+        let html = std::fs::read_to_string(path).unwrap();
+        assert_eq!(html.matches("<tr class=\"candidate\">").count(), 5);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,175 @@
+//! Global keyboard shortcuts
+
+use eframe::egui;
+use super::state::AppState;
+use super::ui::top_bar;
+
+/// Actions reachable from the keyboard
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shortcut {
+    Undo,
+    Redo,
+    Save,
+    Generate,
+    CommandPalette,
+    Escape,
+    Navigate(NavDir),
+}
+
+/// Movement through the candidate table
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NavDir {
+    Prev,
+    Next,
+    First,
+    Last,
+}
+
+/// Map a key press to a shortcut (Ctrl or Cmd as the command modifier)
+pub fn map_key(key: egui::Key, modifiers: egui::Modifiers) -> Option<Shortcut> {
+    use egui::Key;
+
+    if key == Key::Escape {
+        return Some(Shortcut::Escape);
+    }
+    if modifiers.is_none() {
+        let dir = match key {
+            Key::ArrowUp => Some(NavDir::Prev),
+            Key::ArrowDown => Some(NavDir::Next),
+            Key::Home => Some(NavDir::First),
+            Key::End => Some(NavDir::Last),
+            _ => None,
+        };
+        if let Some(dir) = dir {
+            return Some(Shortcut::Navigate(dir));
+        }
+    }
+    if !modifiers.command {
+        return None;
+    }
+
+    match key {
+        Key::Z if modifiers.shift => Some(Shortcut::Redo),
+        Key::Z => Some(Shortcut::Undo),
+        Key::Y => Some(Shortcut::Redo),
+        Key::S => Some(Shortcut::Save),
+        Key::G => Some(Shortcut::Generate),
+        Key::P => Some(Shortcut::CommandPalette),
+        _ => None,
+    }
+}
+
+/// Id of the row reached from row `current` (None = nothing selected). Stops at
+/// the ends instead of wrapping; with no selection Next starts at the top, Prev at the bottom.
+pub fn neighbor_id(ordered: &[usize], current: Option<usize>, dir: NavDir) -> Option<usize> {
+    let row = match (dir, current) {
+        (NavDir::First, _) | (NavDir::Next, None) => 0,
+        (NavDir::Last, _) | (NavDir::Prev, None) => ordered.len().checked_sub(1)?,
+        (NavDir::Prev, Some(row)) => row.checked_sub(1)?,
+        (NavDir::Next, Some(row)) => row + 1,
+    };
+    ordered.get(row).copied()
+}
+
+/// Consume this frame's key presses and apply the matching shortcuts
+pub fn handle(ctx: &egui::Context, state: &mut AppState) {
+    // Leave keys to focused text fields (search box, import box, notes)
+    if ctx.wants_keyboard_input() {
+        return;
+    }
+
+    let shortcuts: Vec<Shortcut> = ctx.input(|i| {
+        i.events.iter()
+            .filter_map(|e| match e {
+                egui::Event::Key { key, pressed: true, modifiers, .. } => map_key(*key, *modifiers),
+                _ => None,
+            })
+            .collect()
+    });
+
+    for shortcut in shortcuts {
+        apply(shortcut, state);
+    }
+}
+
+fn apply(shortcut: Shortcut, state: &mut AppState) {
+    match shortcut {
+        Shortcut::Undo => {
+            if state.history.can_undo() {
+                state.undo();
+            }
+        }
+        Shortcut::Redo => {
+            if state.history.can_redo() {
+                state.redo();
+            }
+        }
+        Shortcut::Save => top_bar::save_session_dialog(state),
+        Shortcut::Generate => {
+            if !state.is_generating {
+                state.generate();
+            }
+        }
+        Shortcut::CommandPalette => {
+            state.show_command_palette = !state.show_command_palette;
+            state.command_query.clear();
+            state.command_index = 0;
+        }
+        Shortcut::Escape => {
+            if state.is_generating {
+                state.cancel_generation();
+            } else if state.show_import_dialog {
+                state.import_text.clear();
+                state.show_import_dialog = false;
+            } else {
+                state.show_inspector = false;
+            }
+        }
+        Shortcut::Navigate(dir) => state.navigate_table(dir),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::{Key, Modifiers};
+
+    #[test]
+    fn test_map_key() {
+        assert_eq!(map_key(Key::Z, Modifiers::COMMAND), Some(Shortcut::Undo));
+        assert_eq!(map_key(Key::Z, Modifiers::COMMAND | Modifiers::SHIFT), Some(Shortcut::Redo));
+        assert_eq!(map_key(Key::Y, Modifiers::COMMAND), Some(Shortcut::Redo));
+        assert_eq!(map_key(Key::S, Modifiers::COMMAND), Some(Shortcut::Save));
+        assert_eq!(map_key(Key::G, Modifiers::COMMAND), Some(Shortcut::Generate));
+        assert_eq!(map_key(Key::P, Modifiers::COMMAND), Some(Shortcut::CommandPalette));
+        assert_eq!(map_key(Key::Escape, Modifiers::NONE), Some(Shortcut::Escape));
+    }
+
+    #[test]
+    fn test_neighbor_id() {
+        let ordered = [7, 3, 9];
+        assert_eq!(neighbor_id(&ordered, Some(0), NavDir::Next), Some(3));
+        assert_eq!(neighbor_id(&ordered, Some(1), NavDir::Prev), Some(7));
+        assert_eq!(neighbor_id(&ordered, Some(1), NavDir::First), Some(7));
+        assert_eq!(neighbor_id(&ordered, Some(1), NavDir::Last), Some(9));
+
+        // No wrap-around at either end
+        assert_eq!(neighbor_id(&ordered, Some(2), NavDir::Next), None);
+        assert_eq!(neighbor_id(&ordered, Some(0), NavDir::Prev), None);
+
+        // Nothing selected yet
+        assert_eq!(neighbor_id(&ordered, None, NavDir::Next), Some(7));
+        assert_eq!(neighbor_id(&ordered, None, NavDir::Prev), Some(9));
+        assert_eq!(neighbor_id(&[], None, NavDir::Last), None);
+
+        assert_eq!(map_key(Key::ArrowDown, Modifiers::NONE), Some(Shortcut::Navigate(NavDir::Next)));
+        assert_eq!(map_key(Key::ArrowUp, Modifiers::SHIFT), None);
+    }
+
+    #[test]
+    fn test_map_key_requires_modifier() {
+        assert_eq!(map_key(Key::Z, Modifiers::NONE), None);
+        assert_eq!(map_key(Key::S, Modifiers::SHIFT), None);
+        assert_eq!(map_key(Key::A, Modifiers::COMMAND), None);
+    }
+}
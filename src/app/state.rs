@@ -1,16 +1,34 @@
-use crate::{generation, optimization};
+use crate::{chemistry, generation, optimization};
+use crate::chemistry::scaffolds::OwnedScaffold;
+use crate::generation::generator::{GenerationMix, GenerationOptions};
+use crate::optimization::pareto::{ObjectiveMask, ALL_OBJECTIVES};
+use crate::optimization::preference::{self, PreferenceChoice};
+use crate::optimization::sweep::SeedRunStats;
 use serde::{Serialize, Deserialize};
-use crossbeam_channel::{unbounded, Receiver, Sender};
+use crossbeam_channel::{unbounded, Receiver, Sender, TryRecvError};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::thread;
+use std::time::{Duration, Instant};
+use rayon::prelude::*;
+use chrono::{DateTime, Local};
 use super::history::{History, Annotations, Action};
+use super::io::ImportReport;
+use super::shortcuts::{neighbor_id, NavDir};
 
 #[derive(Debug)]
 pub enum WorkerMessage {
-    GenerateCandidates { n: usize, seed: u64, start_id: usize, parallel: bool },
+    GenerateCandidates { n: usize, seed: u64, start_id: usize, parallel: bool, batch_size: usize, options: GenerationOptions },
     CancelGeneration,
     GenerationProgress { current: usize, total: usize },
-    GenerationComplete { candidates: Vec<Candidate> },
+    GenerationComplete { candidates: Vec<Candidate>, cancelled: bool },  // cancelled: a partial batch
     GenerationError(String),
+    ImportFile { path: String, start_id: usize },
+    ImportProgress { imported: usize },
+    ImportComplete { report: ImportReport },
+    ImportError(String),
+    SeedSweep { base_seed: u64, k: usize, n: usize, options: GenerationOptions, weights: [f32; 4], objectives: ObjectiveMask },
+    SeedSweepProgress { done: usize, k: usize },
+    SeedSweepComplete { runs: Vec<SeedRunStats> },
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -22,8 +40,535 @@ pub struct Candidate {
     pub synthesis_cost: f32,      // lower better
     pub manufacturing_cost: f32,  // lower better
     pub pareto: bool,
+    #[serde(default)]
+    pub run_id: Option<usize>,    // generation run that produced it, None if imported
+    #[serde(default)]
+    pub source: GenSource,
+    #[serde(default)]
+    pub efficacy_sigma: f32,      // std deviation of the generation jitter, 0 = exact
+    #[serde(default)]
+    pub toxicity_sigma: f32,
+    #[serde(default)]
+    pub parent_ids: Vec<usize>,   // molecules it was mutated or crossed over from, empty if fresh
+}
+
+/// How a candidate's structure was obtained
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub enum GenSource {
+    Scaffold(&'static str),  // name from DRUG_SCAFFOLDS
+    CustomScaffold(String),  // user-defined scaffold name
+    Hybrid,
+    Random,
+    Mutation,   // one parent with a substituent added
+    Crossover,  // two parents joined
+    #[default]
+    Imported,
+}
+
+impl std::fmt::Display for GenSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenSource::Scaffold(name) => write!(f, "Scaffold: {}", name),
+            GenSource::CustomScaffold(name) => write!(f, "Custom: {}", name),
+            GenSource::Hybrid => write!(f, "Hybrid"),
+            GenSource::Random => write!(f, "Random"),
+            GenSource::Mutation => write!(f, "Mutation"),
+            GenSource::Crossover => write!(f, "Crossover"),
+            GenSource::Imported => write!(f, "Imported"),
+        }
+    }
+}
+
+/// Owned serde form of `GenSource`; scaffold names are resolved back
+/// against the scaffold library on load
+#[derive(Serialize, Deserialize)]
+enum GenSourceRepr {
+    Scaffold(String),
+    CustomScaffold(String),
+    Hybrid,
+    Random,
+    Mutation,
+    Crossover,
+    Imported,
+}
+
+impl Serialize for GenSource {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GenSourceRepr::from(self.clone()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for GenSource {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        GenSourceRepr::deserialize(deserializer).map(GenSource::from)
+    }
+}
+
+impl From<GenSourceRepr> for GenSource {
+    fn from(repr: GenSourceRepr) -> Self {
+        match repr {
+            GenSourceRepr::Scaffold(name) => chemistry::scaffolds::get_scaffold_by_name(&name)
+                .map(|s| GenSource::Scaffold(s.name))
+                .unwrap_or(GenSource::Imported),
+            GenSourceRepr::CustomScaffold(name) => GenSource::CustomScaffold(name),
+            GenSourceRepr::Hybrid => GenSource::Hybrid,
+            GenSourceRepr::Random => GenSource::Random,
+            GenSourceRepr::Mutation => GenSource::Mutation,
+            GenSourceRepr::Crossover => GenSource::Crossover,
+            GenSourceRepr::Imported => GenSource::Imported,
+        }
+    }
+}
+
+impl From<GenSource> for GenSourceRepr {
+    fn from(source: GenSource) -> Self {
+        match source {
+            GenSource::Scaffold(name) => GenSourceRepr::Scaffold(name.to_string()),
+            GenSource::CustomScaffold(name) => GenSourceRepr::CustomScaffold(name),
+            GenSource::Hybrid => GenSourceRepr::Hybrid,
+            GenSource::Random => GenSourceRepr::Random,
+            GenSource::Mutation => GenSourceRepr::Mutation,
+            GenSource::Crossover => GenSourceRepr::Crossover,
+            GenSource::Imported => GenSourceRepr::Imported,
+        }
+    }
+}
+
+/// Parameters and output of one generation request
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GenerationRun {
+    pub run_id: usize,
+    pub seed: u64,
+    pub n: usize,
+    pub parallel: bool,
+    pub used_scaffolds: bool,
+    #[serde(default)]
+    pub category: Option<String>,  // scaffold category, None = all
+    pub timestamp: DateTime<Local>,
+    pub candidate_ids: Vec<usize>,
+}
+
+/// Severity of a status message
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogLevel {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+/// Maximum number of entries kept in the status log
+pub const MAX_STATUS_LOG: usize = 100;
+
+/// Crash-recovery snapshot written periodically in the working directory
+pub const AUTOSAVE_PATH: &str = ".autosave.json";
+
+/// Whether an autosave is due `since_last` the previous one; an interval of 0 disables it
+pub fn should_autosave(since_last: Duration, interval_secs: u64) -> bool {
+    interval_secs > 0 && since_last >= Duration::from_secs(interval_secs)
+}
+
+/// Column used to order the candidate table
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum SortColumn {
+    Id,
+    Efficacy,
+    Toxicity,
+    SynthesisCost,
+    ManufacturingCost,
+    #[default]
+    Score,
+}
+
+impl SortColumn {
+    /// Natural direction when a column is first selected (best values on top)
+    pub fn default_ascending(self) -> bool {
+        match self {
+            SortColumn::Id => true,
+            SortColumn::Efficacy | SortColumn::Score => false,
+            SortColumn::Toxicity | SortColumn::SynthesisCost | SortColumn::ManufacturingCost => true,
+        }
+    }
+}
+
+/// Named weight profile for the scoring sliders, optionally tightening filters
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WeightPreset {
+    Balanced,
+    PotencyFirst,
+    SafetyFirst,
+    CheapToMake,
+}
+
+impl WeightPreset {
+    pub const ALL: [WeightPreset; 4] = [
+        WeightPreset::Balanced,
+        WeightPreset::PotencyFirst,
+        WeightPreset::SafetyFirst,
+        WeightPreset::CheapToMake,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WeightPreset::Balanced => "Balanced",
+            WeightPreset::PotencyFirst => "Potency-first",
+            WeightPreset::SafetyFirst => "Safety-first",
+            WeightPreset::CheapToMake => "Cheap-to-make",
+        }
+    }
+
+    /// [w_eff, w_tox, w_syn, w_mfg]
+    pub fn weights(self) -> [f32; 4] {
+        match self {
+            WeightPreset::Balanced => [1.0, 1.0, 1.0, 1.0],
+            WeightPreset::PotencyFirst => [3.0, 1.0, 0.5, 0.5],
+            WeightPreset::SafetyFirst => [1.0, 3.0, 0.5, 0.5],
+            WeightPreset::CheapToMake => [1.0, 1.0, 2.5, 2.5],
+        }
+    }
+
+    /// Filter bounds the preset applies: (minimum efficacy, maximum toxicity)
+    pub fn filter_bounds(self) -> Option<(f32, f32)> {
+        match self {
+            WeightPreset::PotencyFirst => Some((0.5, 1.0)),
+            WeightPreset::SafetyFirst => Some((0.0, 0.4)),
+            WeightPreset::Balanced | WeightPreset::CheapToMake => None,
+        }
+    }
+}
+
+/// Hypervolume of the Pareto front for the current reference point
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Hypervolume {
+    pub hv_2d: f32,        // efficacy vs toxicity
+    pub hv_4d: f32,        // all four objectives
+    pub outside: usize,    // front members outside the reference box
+}
+
+/// Variable shown on a scatter plot axis
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum AxisVar {
+    #[default]
+    Efficacy,
+    Toxicity,
+    SynthesisCost,
+    ManufacturingCost,
+    MolecularWeight,
+    LogP,
+    Psa,
+    Qed,
+    Novelty,
+}
+
+impl AxisVar {
+    pub const ALL: [AxisVar; 9] = [
+        AxisVar::Efficacy,
+        AxisVar::Toxicity,
+        AxisVar::SynthesisCost,
+        AxisVar::ManufacturingCost,
+        AxisVar::MolecularWeight,
+        AxisVar::LogP,
+        AxisVar::Psa,
+        AxisVar::Qed,
+        AxisVar::Novelty,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AxisVar::Efficacy => "Efficacy",
+            AxisVar::Toxicity => "Toxicity",
+            AxisVar::SynthesisCost => "Synthesis Cost",
+            AxisVar::ManufacturingCost => "Manufacturing Cost",
+            AxisVar::MolecularWeight => "MW",
+            AxisVar::LogP => "LogP",
+            AxisVar::Psa => "PSA",
+            AxisVar::Qed => "QED",
+            AxisVar::Novelty => "Novelty",
+        }
+    }
+
+    /// Position in `objective_values`, None for descriptor axes
+    fn objective_index(self) -> Option<usize> {
+        match self {
+            AxisVar::Efficacy => Some(0),
+            AxisVar::Toxicity => Some(1),
+            AxisVar::SynthesisCost => Some(2),
+            AxisVar::ManufacturingCost => Some(3),
+            _ => None,
+        }
+    }
+
+    /// Position in the descriptor cache entry
+    fn descriptor_index(self) -> Option<usize> {
+        match self {
+            AxisVar::MolecularWeight => Some(0),
+            AxisVar::LogP => Some(1),
+            AxisVar::Psa => Some(2),
+            AxisVar::Qed => Some(3),
+            AxisVar::Novelty => Some(4),
+            _ => None,
+        }
+    }
+}
+
+/// [MW, logP, PSA, QED, novelty] for one SMILES
+fn axis_descriptors(smiles: &str) -> [f32; 5] {
+    [
+        chemistry::descriptors::molecular_weight_from_smiles(smiles),
+        chemistry::descriptors::logp_from_smiles(smiles),
+        chemistry::descriptors::polar_surface_area_from_smiles(smiles),
+        chemistry::druglikeness::qed(smiles),
+        chemistry::similarity::novelty_score(smiles, chemistry::similarity::default_novelty_reference()),
+    ]
+}
+
+/// Raw value of an axis variable, computing descriptors from the SMILES when needed
+pub fn axis_value(c: &Candidate, var: AxisVar) -> f32 {
+    match var {
+        AxisVar::Efficacy => c.efficacy,
+        AxisVar::Toxicity => c.toxicity,
+        AxisVar::SynthesisCost => c.synthesis_cost,
+        AxisVar::ManufacturingCost => c.manufacturing_cost,
+        AxisVar::MolecularWeight => chemistry::descriptors::molecular_weight_from_smiles(&c.smiles),
+        AxisVar::LogP => chemistry::descriptors::logp_from_smiles(&c.smiles),
+        AxisVar::Psa => chemistry::descriptors::polar_surface_area_from_smiles(&c.smiles),
+        AxisVar::Qed => chemistry::druglikeness::qed(&c.smiles),
+        AxisVar::Novelty => chemistry::similarity::novelty_score(&c.smiles, chemistry::similarity::default_novelty_reference()),
+    }
+}
+
+/// Smallest value a log-scaled axis resolves; zero and negatives are floored to it
+pub const LOG_FLOOR: f32 = 1e-3;
+
+/// log10 for plotting, flooring at `LOG_FLOOR` so zero and negative values stay finite
+pub fn safe_log10(value: f32) -> f32 {
+    value.max(LOG_FLOOR).log10()
+}
+
+/// Physicochemical descriptors the property filters test
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PropertyValues {
+    pub molecular_weight: f32,
+    pub logp: f32,
+    pub rotatable_bonds: usize,
+}
+
+impl PropertyValues {
+    pub fn from_smiles(smiles: &str) -> Self {
+        let d = chemistry::descriptors::compute_descriptors(smiles);
+        Self { molecular_weight: d.molecular_weight, logp: d.logp, rotatable_bonds: d.rotatable_bonds }
+    }
+}
+
+/// Inclusive descriptor ranges; ignored unless `enabled`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PropertyFilters {
+    pub enabled: bool,
+    pub mw: (f32, f32),
+    pub logp: (f32, f32),
+    pub rotatable_bonds: (usize, usize),
+}
+
+impl Default for PropertyFilters {
+    /// Lipinski / Veber limits as a starting point
+    fn default() -> Self {
+        Self { enabled: false, mw: (0.0, 500.0), logp: (-2.0, 5.0), rotatable_bonds: (0, 10) }
+    }
+}
+
+/// The user-set filter values, as recorded by undo. Derived data such as the
+/// substructure matches is rebuilt when a snapshot is restored.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Filters {
+    pub pareto_only: bool,
+    pub smiles: String,
+    pub eff_range: (f32, f32),
+    pub tox_range: (f32, f32),
+    pub favorites_only: bool,
+    pub tags: Vec<String>,
+    pub tags_match_all: bool,
+    pub run: Option<usize>,
+    pub latest_batch: bool,
+    pub source: Option<GenSource>,
+    pub substructure: String,
+    pub formula: String,
+    pub property: PropertyFilters,
+}
+
+pub fn passes_property_filters(values: &PropertyValues, filters: &PropertyFilters) -> bool {
+    if !filters.enabled {
+        return true;
+    }
+    let in_range = |v: f32, (min, max): (f32, f32)| v >= min && v <= max;
+    in_range(values.molecular_weight, filters.mw)
+        && in_range(values.logp, filters.logp)
+        && (filters.rotatable_bonds.0..=filters.rotatable_bonds.1).contains(&values.rotatable_bonds)
+}
+
+/// Per-objective min/max over the current candidate set,
+/// in [efficacy, toxicity, synthesis, manufacturing] order
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjectiveBounds {
+    pub min: [f32; 4],
+    pub max: [f32; 4],
+}
+
+impl Default for ObjectiveBounds {
+    fn default() -> Self {
+        Self { min: [0.0; 4], max: [1.0; 4] }
+    }
+}
+
+impl ObjectiveBounds {
+    pub fn from_candidates(candidates: &[Candidate]) -> Self {
+        if candidates.is_empty() {
+            return Self::default();
+        }
+        let mut min = [f32::INFINITY; 4];
+        let mut max = [f32::NEG_INFINITY; 4];
+        for c in candidates {
+            for (k, v) in raw_objectives(c).into_iter().enumerate() {
+                min[k] = min[k].min(v);
+                max[k] = max[k].max(v);
+            }
+        }
+        Self { min, max }
+    }
+
+    /// Min-max scale to [0, 1]; a constant objective maps to 0
+    pub fn normalize(&self, values: [f32; 4]) -> [f32; 4] {
+        let mut out = [0.0; 4];
+        for k in 0..4 {
+            let range = self.max[k] - self.min[k];
+            if range > f32::EPSILON {
+                out[k] = (values[k] - self.min[k]) / range;
+            }
+        }
+        out
+    }
+}
+
+fn raw_objectives(c: &Candidate) -> [f32; 4] {
+    [c.efficacy, c.toxicity, c.synthesis_cost, c.manufacturing_cost]
+}
+
+/// Distinct Bemis-Murcko scaffolds and scaffolds per candidate.
+/// Acyclic molecules share the empty scaffold and count as one.
+pub fn scaffold_diversity(candidates: &[Candidate]) -> (usize, f32) {
+    let scaffolds: Vec<String> = candidates.par_iter()
+        .map(|c| chemistry::scaffold_extract::murcko_scaffold(&c.smiles))
+        .collect();
+    diversity_from_scaffolds(scaffolds.iter().map(String::as_str), candidates.len())
+}
+
+fn diversity_from_scaffolds<'a>(scaffolds: impl Iterator<Item = &'a str>, n: usize) -> (usize, f32) {
+    if n == 0 {
+        return (0, 0.0);
+    }
+    let distinct = scaffolds.collect::<HashSet<&str>>().len();
+    (distinct, distinct as f32 / n as f32)
+}
+
+/// Digits after the point for displayed values, and the range offered in Settings
+pub const DEFAULT_DISPLAY_DECIMALS: usize = 3;
+pub const DISPLAY_DECIMALS_RANGE: std::ops::RangeInclusive<usize> = 2..=6;
+
+/// Grid resolution of the efficacy × toxicity coverage metric
+pub const COVERAGE_BINS: usize = 10;
+
+/// Candidates per cell of a `bins`×`bins` grid over efficacy × toxicity, both on [0, 1].
+/// Indexed `[efficacy_bin * bins + toxicity_bin]`; out-of-range values land in the edge cells.
+pub fn coverage_occupancy(candidates: &[&Candidate], bins: usize) -> Vec<usize> {
+    let mut counts = vec![0; bins * bins];
+    if bins == 0 {
+        return counts;
+    }
+    let bin = |v: f32| ((v.clamp(0.0, 1.0) * bins as f32) as usize).min(bins - 1);
+    for c in candidates {
+        counts[bin(c.efficacy) * bins + bin(c.toxicity)] += 1;
+    }
+    counts
+}
+
+/// Fraction of non-empty cells in a `bins`×`bins` efficacy × toxicity grid
+pub fn coverage_grid(candidates: &[&Candidate], bins: usize) -> f32 {
+    let counts = coverage_occupancy(candidates, bins);
+    if counts.is_empty() {
+        return 0.0;
+    }
+    counts.iter().filter(|&&n| n > 0).count() as f32 / counts.len() as f32
+}
+
+/// Min and max of each objective over a set of candidates
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ObjectiveStats {
+    pub count: usize,
+    pub min: [f32; 4],               // efficacy, toxicity, synthesis, manufacturing
+    pub max: [f32; 4],
+    pub score: Option<(f32, f32)>,   // weighted score (min, max), filled by `AppState`
+}
+
+/// Per-objective ranges; the score range depends on the weights and is left empty.
+/// An empty set gives zero ranges.
+pub fn objective_stats(candidates: &[&Candidate]) -> ObjectiveStats {
+    if candidates.is_empty() {
+        return ObjectiveStats::default();
+    }
+    let mut min = [f32::INFINITY; 4];
+    let mut max = [f32::NEG_INFINITY; 4];
+    for c in candidates {
+        for (k, v) in raw_objectives(c).into_iter().enumerate() {
+            min[k] = min[k].min(v);
+            max[k] = max[k].max(v);
+        }
+    }
+    ObjectiveStats { count: candidates.len(), min, max, score: None }
+}
+
+/// Candidates sharing one Bemis-Murcko scaffold
+#[derive(Clone, Debug)]
+pub struct ScaffoldGroup {
+    pub scaffold: String,    // canonical SMILES, empty for acyclic molecules
+    pub ids: Vec<usize>,
+    pub mean: [f32; 4],      // efficacy, toxicity, synthesis, manufacturing
+}
+
+/// Group candidates by scaffold, largest groups first
+pub fn group_by_scaffold(candidates: &[Candidate]) -> Vec<ScaffoldGroup> {
+    let scaffolds: Vec<String> = candidates.par_iter()
+        .map(|c| chemistry::scaffold_extract::murcko_scaffold(&c.smiles))
+        .collect();
+
+    let mut by_scaffold: HashMap<String, Vec<&Candidate>> = HashMap::new();
+    for (c, scaffold) in candidates.iter().zip(scaffolds) {
+        by_scaffold.entry(scaffold).or_default().push(c);
+    }
+
+    let mut groups: Vec<ScaffoldGroup> = by_scaffold.into_iter()
+        .map(|(scaffold, members)| {
+            let mut mean = [0.0f32; 4];
+            for c in &members {
+                for (m, v) in mean.iter_mut().zip(raw_objectives(c)) {
+                    *m += v;
+                }
+            }
+            for m in &mut mean {
+                *m /= members.len() as f32;
+            }
+            ScaffoldGroup {
+                scaffold,
+                ids: members.iter().map(|c| c.id).collect(),
+                mean,
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| b.ids.len().cmp(&a.ids.len()).then_with(|| a.scaffold.cmp(&b.scaffold)));
+    groups
 }
 
+/// Inputs weighted scores depend on: candidate revision, weights, normalization
+pub type ScoreKey = (u64, [f32; 4], bool, ObjectiveMask);
+
 /// Session data for save/load
 #[derive(Serialize, Deserialize)]
 pub struct SessionData {
@@ -38,6 +583,16 @@ pub struct SessionData {
     pub filter_pareto_only: bool,
     #[serde(default)]
     pub annotations: Annotations,
+    #[serde(default)]
+    pub runs: Vec<GenerationRun>,
+    #[serde(default)]
+    pub custom_scaffolds: Vec<OwnedScaffold>,
+    #[serde(default = "all_objectives")]
+    pub active_objectives: ObjectiveMask,
+}
+
+fn all_objectives() -> ObjectiveMask {
+    ALL_OBJECTIVES
 }
 
 pub struct AppState {
@@ -45,18 +600,60 @@ pub struct AppState {
     pub next_id: usize,
     pub candidates: Vec<Candidate>,
     pub selected_id: Option<usize>,
+    pub selected_ids: HashSet<usize>,  // multi-selection, e.g. from box-select on a plot
+    pub compare_id: Option<usize>,
+    pub runs: Vec<GenerationRun>,
+    pub pending_run: Option<GenerationRun>,  // filled in when the worker completes
 
     // generation
     pub n_generate: usize,
     pub seed: u64,
     pub use_parallel: bool,
+    pub thread_count: usize,       // parallel generation threads, 0 = all cores
+    pub batch_size: usize,         // candidates per progress step in sequential generation
+    pub generation_mix: GenerationMix,  // scaffold / hybrid / random shares
+    pub deterministic_objectives: bool, // no random jitter on generated objectives
+    pub novelty_search: bool,           // favor candidates far from existing ones in objective space
     pub use_scaffolds: bool,
+    pub scaffold_category: Option<String>,  // None = all categories
+    pub custom_scaffolds: Vec<OwnedScaffold>,
+    pub scaffold_input: OwnedScaffold,       // custom scaffold editor buffer
 
     // weights (optionnel: score unique pour tri)
     pub w_eff: f32,
     pub w_tox: f32,
     pub w_syn: f32,
     pub w_mfg: f32,
+    pub normalize_objectives: bool,
+    pub active_objectives: ObjectiveMask,  // inactive ones are left out of dominance, scores and plots
+    pub objective_bounds: ObjectiveBounds,
+    pub weight_preset: Option<WeightPreset>,  // last applied preset, persisted across launches
+    pub preference_choices: Vec<PreferenceChoice>,   // answers to "which do you prefer?"
+    pub preference_pair: Option<(usize, usize)>,     // Pareto candidate ids currently compared
+    pub show_preference: bool,
+
+    // leaderboard
+    pub top_n: usize,
+    pub top_cache: Option<(ScoreKey, Vec<usize>)>,  // candidate ids, best score first
+    pub score_cache: HashMap<usize, f32>,
+    pub score_cache_key: Option<ScoreKey>,
+    pub candidates_revision: u64,
+
+    // main scatter plot axes, descriptor values cached by SMILES
+    pub scatter_x: AxisVar,
+    pub scatter_y: AxisVar,
+    pub axis_cache: HashMap<String, [f32; 5]>,
+    pub axis_cache_revision: Option<u64>,
+
+    // Pareto flags (and the data refreshed with them) are stale. Set through
+    // `mark_pareto_dirty` by changes that alter objective values in place
+    // without adding or removing candidates: objective recomputation and its
+    // undo/redo. Consumed once per frame at the top of `App::update`; adding
+    // or removing candidates still recomputes immediately.
+    pub pareto_dirty: bool,
+    pub scaffold_groups: Option<(u64, Vec<ScaffoldGroup>)>,  // cached for a candidates revision
+    pub baseline_front: Option<Vec<Candidate>>,   // Pareto set frozen for comparison
+    pub baseline_improvements: Option<((u64, ObjectiveMask), HashSet<usize>)>,  // ids dominating a baseline point
 
     // filters
     pub filter_pareto_only: bool,
@@ -66,24 +663,80 @@ pub struct AppState {
     pub filter_tox_min: f32,
     pub filter_tox_max: f32,
     pub filter_favorites_only: bool,
+    pub pin_locked: bool,          // locked candidates pass every filter
+    pub filter_tags: Vec<String>,
+    pub filter_tags_match_all: bool,  // AND when true, OR when false
+    pub filter_run: Option<usize>,
+    pub filter_latest_batch: bool,   // only the most recent generation run
+    pub filter_source: Option<GenSource>,
+    pub filter_substructure: String,                  // SMILES pattern(s), ',' separates alternatives
+    pub filter_formula: String,                       // minimum element counts, e.g. "C6N"
+    pub substructure_matches: Option<HashSet<usize>>, // ids matching the pattern, None = no filter
+    pub property_filters: PropertyFilters,
+    pub committed_settings: Option<([f32; 4], Filters)>,  // weights and filters as of the last undo step, None = not yet taken
+    pub property_cache: HashMap<String, PropertyValues>,  // by SMILES, filled while property filters are on
+    pub property_cache_revision: Option<u64>,
+    pub formula_cache: HashMap<String, String>,  // molecular formula by SMILES, filled while the formula filter is set
+    pub formula_cache_revision: Option<u64>,
+    pub scaffold_cache: HashMap<String, String>,  // Murcko scaffold by SMILES
+    pub scaffold_cache_revision: Option<u64>,
+
+    // table sorting
+    pub sort_column: SortColumn,
+    pub sort_ascending: bool,
+    pub table_order: Vec<usize>,                 // ids as last shown in the table
+    pub table_positions: HashMap<usize, usize>,  // id -> row in table_order
+    pub scroll_to_selected: bool,                // keyboard moved the selection
 
     // status
     pub status: String,
+    pub status_log: VecDeque<(DateTime<Local>, String, LogLevel)>,
+    pub show_log: bool,
+
+    // autosave
+    pub autosave_interval_secs: u64,  // 0 = off
+    pub last_autosave: Instant,
+    pub show_recover_dialog: bool,    // autosave newer than any session file
+    pub autosave_result: Option<Receiver<Result<(), String>>>,  // outcome of the snapshot being written
 
     // worker thread communication
     pub worker_sender: Option<Sender<WorkerMessage>>,
     pub worker_receiver: Option<Receiver<WorkerMessage>>,
     pub is_generating: bool,
+    pub sweep_k: usize,                          // seeds per sweep
+    pub sweep_progress: Option<(usize, usize)>,  // (done, k) while a sweep runs
+    pub sweep_runs: Vec<SeedRunStats>,           // results of the last sweep
+    pub show_seed_sweep: bool,
     pub generation_progress: Option<(usize, usize)>,
+    pub generation_started: Option<Instant>,
     
     // UI state
     pub show_histograms: bool,
+    pub histogram_split_pareto: bool,
+    pub histogram_bins: usize,     // 5-100 bins over the 0-1 objective range
     pub show_parallel_coords: bool,
     pub show_3d_plot: bool,
     pub show_heatmap: bool,
+    pub heatmap_include_descriptors: bool,
+    pub plot_yaw: f32,             // 3D plot rotation, radians
+    pub plot_pitch: f32,
+    pub cluster_threshold: f32,    // Tanimoto threshold in the clustering view
+    pub mds_cache: Option<(u64, Vec<usize>, Vec<[f32; 2]>)>,  // (revision, embedded ids, coordinates)
+    pub similarity_query: String,
     pub show_clustering: bool,
+    pub show_scaffold_groups: bool,
+    pub show_reference_drugs: bool,
+    pub knee_4d: bool,             // knee over all four objectives instead of eff/tox
+    pub show_error_bars: bool,     // ±1σ jitter bars on the main scatter plot
+    pub show_coverage_grid: bool,  // occupancy heatmap behind the eff/tox scatter
+    pub log_x: bool,               // log10 x axis on scatter plots and histograms
+    pub log_y: bool,               // log10 y axis on scatter plots
+    pub hv_ref: [f32; 4],          // hypervolume reference: min eff, max tox/synth/mfg
+    pub hv_cache: Option<((u64, [f32; 4]), Hypervolume)>,  // keyed by (revision, hv_ref)
+    pub selected_reference: Option<&'static str>,  // reference drug clicked on a scatter plot
     pub show_druglikeness: bool,
     pub show_similarity_search: bool,
+    pub show_inspector: bool,
 
     // History & Annotations
     pub history: History,
@@ -95,6 +748,23 @@ pub struct AppState {
     // Import text buffer
     pub import_text: String,
     pub show_import_dialog: bool,
+    pub show_command_palette: bool,
+    pub display_decimals: usize,          // digits after the point for displayed values
+    pub command_query: String,
+    pub command_index: usize,             // highlighted row among the palette matches
+    pub duplicate_groups: Option<Vec<Vec<usize>>>,  // last duplicate report, shown while Some
+    pub near_duplicate_threshold: f32,
+    pub near_duplicate_groups: Option<Vec<Vec<usize>>>,
+    pub import_path: String,
+    pub import_progress: Option<usize>,  // candidates read so far while streaming
+    pub import_dedup: bool,               // skip SMILES already present (canonical match)
+    pub import_issues: Vec<(String, String)>,  // (SMILES, reason) rejected by the last import
+    pub export_extended_csv: bool,        // descriptor columns in the CSV export
+    pub graph_threshold: f32,             // Tanimoto cutoff for the similarity graph export
+    pub last_export_path: Option<std::path::PathBuf>,  // absolute path of the latest export, for "Reveal"
+
+    // Tag editor input buffer
+    pub tag_input: String,
 }
 
 impl Default for Candidate {
@@ -107,6 +777,11 @@ impl Default for Candidate {
             synthesis_cost: 0.0,
             manufacturing_cost: 0.0,
             pareto: false,
+            run_id: None,
+            source: GenSource::Imported,
+            efficacy_sigma: 0.0,
+            toxicity_sigma: 0.0,
+            parent_ids: Vec::new(),
         }
     }
 }
@@ -125,14 +800,46 @@ impl Default for AppState {
             next_id: 0,
             candidates: vec![],
             selected_id: None,
+            selected_ids: HashSet::new(),
+            compare_id: None,
+            runs: Vec::new(),
+            pending_run: None,
             n_generate: 300,
             seed: 42,
             use_parallel: true,
+            thread_count: 0,
+            batch_size: 50,
+            generation_mix: GenerationMix::default(),
+            deterministic_objectives: false,
+            novelty_search: false,
             use_scaffolds: true,
+            scaffold_category: None,
+            custom_scaffolds: Vec::new(),
+            scaffold_input: OwnedScaffold::default(),
             w_eff: 1.0,
             w_tox: 1.0,
             w_syn: 1.0,
             w_mfg: 1.0,
+            normalize_objectives: false,
+            active_objectives: ALL_OBJECTIVES,
+            weight_preset: None,
+            preference_choices: Vec::new(),
+            preference_pair: None,
+            show_preference: false,
+            objective_bounds: ObjectiveBounds::default(),
+            top_n: 10,
+            top_cache: None,
+            score_cache: HashMap::new(),
+            score_cache_key: None,
+            scatter_x: AxisVar::Toxicity,
+            scatter_y: AxisVar::Efficacy,
+            axis_cache: HashMap::new(),
+            axis_cache_revision: None,
+            candidates_revision: 0,
+            pareto_dirty: false,
+            scaffold_groups: None,
+            baseline_front: None,
+            baseline_improvements: None,
             filter_pareto_only: false,
             filter_smiles: String::new(),
             filter_eff_min: 0.0,
@@ -140,156 +847,810 @@ impl Default for AppState {
             filter_tox_min: 0.0,
             filter_tox_max: 1.0,
             filter_favorites_only: false,
+            pin_locked: true,
+            filter_tags: Vec::new(),
+            filter_tags_match_all: true,
+            filter_run: None,
+            filter_latest_batch: false,
+            filter_source: None,
+            filter_substructure: String::new(),
+            filter_formula: String::new(),
+            substructure_matches: None,
+            property_filters: PropertyFilters::default(),
+            committed_settings: None,
+            property_cache: HashMap::new(),
+            property_cache_revision: None,
+            formula_cache: HashMap::new(),
+            formula_cache_revision: None,
+            scaffold_cache: HashMap::new(),
+            scaffold_cache_revision: None,
+            sort_column: SortColumn::default(),
+            sort_ascending: false,
+            table_order: Vec::new(),
+            table_positions: HashMap::new(),
+            scroll_to_selected: false,
             status: "Ready - Click 'Generate' to start".into(),
+            status_log: VecDeque::new(),
+            show_log: false,
+            autosave_interval_secs: 120,
+            last_autosave: Instant::now(),
+            show_recover_dialog: false,
+            autosave_result: None,
             worker_sender: Some(to_worker_sender),
             worker_receiver: Some(to_main_receiver),
             is_generating: false,
+            sweep_k: 5,
+            sweep_progress: None,
+            sweep_runs: Vec::new(),
+            show_seed_sweep: false,
             generation_progress: None,
+            generation_started: None,
             show_histograms: false,
+            histogram_split_pareto: false,
+            histogram_bins: 20,
             show_parallel_coords: false,
             show_3d_plot: false,
             show_heatmap: false,
+            heatmap_include_descriptors: false,
+            plot_yaw: 0.6,
+            plot_pitch: 0.35,
+            cluster_threshold: 0.5,
+            mds_cache: None,
+            similarity_query: String::new(),
             show_clustering: false,
+            show_scaffold_groups: false,
+            show_reference_drugs: false,
+            knee_4d: false,
+            show_error_bars: false,
+            show_coverage_grid: false,
+            log_x: false,
+            log_y: false,
+            hv_ref: [0.0, 1.0, 1.0, 1.0],
+            hv_cache: None,
+            selected_reference: None,
             show_druglikeness: true,
             show_similarity_search: false,
+            show_inspector: false,
             history: History::new(50),
             annotations: Annotations::new(),
             theme_changed: false,
             import_text: String::new(),
             show_import_dialog: false,
+            show_command_palette: false,
+            display_decimals: DEFAULT_DISPLAY_DECIMALS,
+            command_query: String::new(),
+            command_index: 0,
+            duplicate_groups: None,
+            near_duplicate_threshold: 0.95,
+            near_duplicate_groups: None,
+            import_path: String::new(),
+            import_progress: None,
+            import_dedup: true,
+            import_issues: Vec::new(),
+            export_extended_csv: false,
+            graph_threshold: 0.7,
+            last_export_path: None,
+            tag_input: String::new(),
         }
     }
 }
 
 impl AppState {
-    pub fn weighted_score(&self, c: &Candidate) -> f32 {
-        self.w_eff * c.efficacy
-            - self.w_tox * c.toxicity
-            - self.w_syn * c.synthesis_cost
-            - self.w_mfg * c.manufacturing_cost
+    /// Set the status line and append it to the log
+    pub fn push_status(&mut self, level: LogLevel, msg: impl Into<String>) {
+        let msg = msg.into();
+        if self.status_log.len() >= MAX_STATUS_LOG {
+            self.status_log.pop_front();
+        }
+        self.status_log.push_back((Local::now(), msg.clone(), level));
+        self.status = msg;
     }
 
-    pub fn generate(&mut self) {
-        if self.is_generating {
-            return;
+    /// Objective values used for scoring and plotting, normalized when enabled
+    pub fn objective_values(&self, c: &Candidate) -> [f32; 4] {
+        let raw = raw_objectives(c);
+        if self.normalize_objectives {
+            self.objective_bounds.normalize(raw)
+        } else {
+            raw
         }
+    }
 
-        if let Some(sender) = &self.worker_sender {
-            self.is_generating = true;
-            self.generation_progress = Some((0, self.n_generate));
-            let mode = if self.use_parallel { "parallel" } else { "sequential" };
-            self.status = format!("Generating {} candidates ({})...", self.n_generate, mode);
+    /// Knee of the Pareto front, over eff/tox or all four objectives per `knee_4d`
+    pub fn knee_id(&self) -> Option<usize> {
+        if self.knee_4d {
+            super::ui::pareto::find_knee_point_4d(&self.candidates)
+        } else {
+            super::ui::pareto::find_knee_point(&self.candidates)
+        }
+    }
 
-            let _ = sender.send(WorkerMessage::GenerateCandidates {
-                n: self.n_generate,
-                seed: self.seed,
-                start_id: self.next_id,
-                parallel: self.use_parallel,
-            });
+    /// Hypervolume for `hv_ref`, recomputed only when the front or the reference changes
+    pub fn hypervolume(&mut self) -> Hypervolume {
+        let key = (self.candidates_revision, self.hv_ref);
+        if let Some((cached_key, hv)) = self.hv_cache {
+            if cached_key == key {
+                return hv;
+            }
         }
+        let hv = Hypervolume {
+            hv_2d: super::ui::pareto::hypervolume_2d(&self.candidates, (self.hv_ref[0], self.hv_ref[1])),
+            hv_4d: super::ui::pareto::hypervolume_4d(&self.candidates, self.hv_ref),
+            outside: super::ui::pareto::outside_reference(&self.candidates, self.hv_ref),
+        };
+        self.hv_cache = Some((key, hv));
+        hv
     }
 
-    /// Filter candidates based on current filter settings
-    pub fn filtered_candidates(&self) -> Vec<&Candidate> {
-        self.candidates
-            .iter()
-            .filter(|c| {
-                // Pareto filter
-                if self.filter_pareto_only && !c.pareto {
-                    return false;
-                }
-                
-                // Favorites filter
-                if self.filter_favorites_only && !self.annotations.is_favorite(c.id) {
-                    return false;
-                }
-                
-                // SMILES search
-                if !self.filter_smiles.is_empty() {
-                    let search = self.filter_smiles.to_lowercase();
-                    if !c.smiles.to_lowercase().contains(&search) {
-                        return false;
-                    }
-                }
-                
-                // Efficacy range
-                if c.efficacy < self.filter_eff_min || c.efficacy > self.filter_eff_max {
-                    return false;
-                }
-                
-                // Toxicity range
-                if c.toxicity < self.filter_tox_min || c.toxicity > self.filter_tox_max {
-                    return false;
-                }
-                
-                true
-            })
-            .collect()
+    /// Scaffold groups for the current candidates, recomputed only after they change
+    pub fn scaffold_groups(&mut self) -> &[ScaffoldGroup] {
+        let stale = self.scaffold_groups.as_ref().is_none_or(|(rev, _)| *rev != self.candidates_revision);
+        if stale {
+            self.scaffold_groups = Some((self.candidates_revision, group_by_scaffold(&self.candidates)));
+        }
+        self.scaffold_groups.as_ref().map(|(_, g)| g.as_slice()).unwrap_or_default()
     }
 
-    /// Save session to file
-    pub fn save_session(&self, path: &str) -> Result<(), String> {
-        let session = SessionData {
-            candidates: self.candidates.clone(),
-            next_id: self.next_id,
-            n_generate: self.n_generate,
-            seed: self.seed,
-            w_eff: self.w_eff,
-            w_tox: self.w_tox,
-            w_syn: self.w_syn,
-            w_mfg: self.w_mfg,
-            filter_pareto_only: self.filter_pareto_only,
-            annotations: self.annotations.clone(),
+    /// Current filter values
+    pub fn filters(&self) -> Filters {
+        Filters {
+            pareto_only: self.filter_pareto_only,
+            smiles: self.filter_smiles.clone(),
+            eff_range: (self.filter_eff_min, self.filter_eff_max),
+            tox_range: (self.filter_tox_min, self.filter_tox_max),
+            favorites_only: self.filter_favorites_only,
+            tags: self.filter_tags.clone(),
+            tags_match_all: self.filter_tags_match_all,
+            run: self.filter_run,
+            latest_batch: self.filter_latest_batch,
+            source: self.filter_source.clone(),
+            substructure: self.filter_substructure.clone(),
+            formula: self.filter_formula.clone(),
+            property: self.property_filters.clone(),
+        }
+    }
+
+    /// Restore filter values, refreshing the substructure matches
+    pub fn set_filters(&mut self, filters: Filters) {
+        self.filter_pareto_only = filters.pareto_only;
+        self.filter_smiles = filters.smiles;
+        (self.filter_eff_min, self.filter_eff_max) = filters.eff_range;
+        (self.filter_tox_min, self.filter_tox_max) = filters.tox_range;
+        self.filter_favorites_only = filters.favorites_only;
+        self.filter_tags = filters.tags;
+        self.filter_tags_match_all = filters.tags_match_all;
+        self.filter_run = filters.run;
+        self.filter_latest_batch = filters.latest_batch;
+        self.filter_source = filters.source;
+        self.filter_substructure = filters.substructure;
+        self.filter_formula = filters.formula;
+        self.property_filters = filters.property;
+        self.update_substructure_filter();
+    }
+
+    /// Record weight and filter edits as undo steps once they settle. Call it only while
+    /// no pointer button is held and no text field has focus, so that dragging a slider
+    /// or typing a search records one step.
+    pub fn record_setting_changes(&mut self) {
+        let weights = [self.w_eff, self.w_tox, self.w_syn, self.w_mfg];
+        let filters = self.filters();
+        let Some((old_weights, old_filters)) = self.committed_settings.take() else {
+            self.committed_settings = Some((weights, filters));
+            return;
         };
-        
-        let json = serde_json::to_string_pretty(&session)
-            .map_err(|e| format!("Serialization error: {}", e))?;
-        
-        std::fs::write(path, json)
-            .map_err(|e| format!("Write error: {}", e))?;
-        
-        Ok(())
+        if weights != old_weights {
+            self.history.push(Action::SetWeights { old: old_weights, new: weights });
+        }
+        if filters != old_filters {
+            self.history.push(Action::SetFilters { old: Box::new(old_filters), new: Box::new(filters.clone()) });
+        }
+        self.committed_settings = Some((weights, filters));
     }
 
-    /// Load session from file
-    pub fn load_session(&mut self, path: &str) -> Result<(), String> {
-        let json = std::fs::read_to_string(path)
-            .map_err(|e| format!("Read error: {}", e))?;
-        
-        let session: SessionData = serde_json::from_str(&json)
-            .map_err(|e| format!("Parse error: {}", e))?;
-        
-        self.candidates = session.candidates;
-        self.next_id = session.next_id;
-        self.n_generate = session.n_generate;
-        self.seed = session.seed;
-        self.w_eff = session.w_eff;
-        self.w_tox = session.w_tox;
-        self.w_syn = session.w_syn;
+    /// Apply weights restored by undo/redo without recording them again
+    fn restore_weights(&mut self, weights: [f32; 4]) {
+        [self.w_eff, self.w_tox, self.w_syn, self.w_mfg] = weights;
+        self.committed_settings = Some((weights, self.filters()));
+    }
+
+    /// Apply filters restored by undo/redo without recording them again
+    fn restore_filters(&mut self, filters: Filters) {
+        self.set_filters(filters);
+        self.committed_settings = Some(([self.w_eff, self.w_tox, self.w_syn, self.w_mfg], self.filters()));
+    }
+
+    /// Forget a generation run, and stop filtering by it. Not an undo step of its own.
+    fn remove_run(&mut self, run_id: usize) {
+        self.runs.retain(|r| r.run_id != run_id);
+        if self.filter_run == Some(run_id) {
+            self.filter_run = None;
+            if let Some((_, filters)) = &mut self.committed_settings {
+                filters.run = None;
+            }
+        }
+    }
+
+    /// Drop selected tag filters that no current candidate carries, so a selection the
+    /// panel may no longer offer can't hide every row. Not an undo step of its own.
+    fn prune_filter_tags(&mut self) {
+        let in_use: Vec<String> = self.filter_tags.iter()
+            .filter(|tag| self.candidates.iter().any(|c| self.annotations.has_tag(c.id, tag)))
+            .cloned()
+            .collect();
+        if in_use.len() == self.filter_tags.len() {
+            return;
+        }
+        if let Some((_, filters)) = &mut self.committed_settings {
+            filters.tags.retain(|tag| in_use.contains(tag));
+        }
+        self.filter_tags = in_use;
+    }
+
+    /// Freeze the current Pareto set as the baseline later candidates are compared against
+    pub fn freeze_baseline(&mut self) {
+        let front: Vec<Candidate> = self.candidates.iter().filter(|c| c.pareto).cloned().collect();
+        self.push_status(LogLevel::Info, format!("Froze a baseline of {} Pareto candidates", front.len()));
+        self.baseline_front = Some(front);
+        self.baseline_improvements = None;
+    }
+
+    pub fn clear_baseline(&mut self) {
+        self.baseline_front = None;
+        self.baseline_improvements = None;
+    }
+
+    /// Recompute which candidates dominate a baseline point after the candidates or the
+    /// active objectives changed
+    pub fn refresh_baseline_improvements(&mut self) {
+        let Some(baseline) = &self.baseline_front else {
+            return;
+        };
+        let key = (self.candidates_revision, self.active_objectives);
+        if self.baseline_improvements.as_ref().is_some_and(|(k, _)| *k == key) {
+            return;
+        }
+        let mask = self.active_objectives;
+        let ids = self.candidates.par_iter()
+            .filter(|c| optimization::pareto::improves_on_baseline(c, baseline, mask))
+            .map(|c| c.id)
+            .collect();
+        self.baseline_improvements = Some((key, ids));
+    }
+
+    /// Whether a candidate was flagged by the last `refresh_baseline_improvements`
+    pub fn is_baseline_improvement(&self, id: usize) -> bool {
+        self.baseline_improvements.as_ref().is_some_and(|(_, ids)| ids.contains(&id))
+    }
+
+    pub fn score_key(&self) -> ScoreKey {
+        (
+            self.candidates_revision,
+            [self.w_eff, self.w_tox, self.w_syn, self.w_mfg],
+            self.normalize_objectives,
+            self.active_objectives,
+        )
+    }
+
+    /// Rebuild the score cache if weights or candidates changed since the last call
+    pub fn refresh_score_cache(&mut self) {
+        let key = self.score_key();
+        if self.score_cache_key == Some(key) {
+            return;
+        }
+        self.score_cache = self.candidates.iter()
+            .map(|c| (c.id, self.weighted_score(c)))
+            .collect();
+        self.score_cache_key = Some(key);
+    }
+
+    /// Weighted score from the cache, computed directly if the id isn't cached
+    pub fn cached_score(&self, id: usize) -> f32 {
+        match self.score_cache.get(&id) {
+            Some(&score) => score,
+            None => self.candidates.iter()
+                .find(|c| c.id == id)
+                .map_or(0.0, |c| self.weighted_score(c)),
+        }
+    }
+
+    /// Objective and score ranges of the filtered table rows. Computed every frame: the rows
+    /// also change with annotation edits, and one pass over them is cheap next to filtering.
+    pub fn table_objective_stats(&self, rows: &[Candidate]) -> ObjectiveStats {
+        let refs: Vec<&Candidate> = rows.iter().collect();
+        let mut stats = objective_stats(&refs);
+        stats.score = rows.iter()
+            .map(|c| self.cached_score(c.id))
+            .fold(None, |range, s| match range {
+                None => Some((s, s)),
+                Some((lo, hi)) => Some((f32::min(lo, s), f32::max(hi, s))),
+            });
+        stats
+    }
+
+    /// Fill descriptor values for the scatter axes and heatmap. Only runs while a
+    /// descriptor is shown; entries are kept across revisions and computed for new SMILES only.
+    pub fn refresh_axis_cache(&mut self) {
+        let needs_descriptors = self.scatter_x.descriptor_index().is_some()
+            || self.scatter_y.descriptor_index().is_some()
+            || (self.show_heatmap && self.heatmap_include_descriptors);
+        if !needs_descriptors || self.axis_cache_revision == Some(self.candidates_revision) {
+            return;
+        }
+
+        let mut wanted: HashSet<&str> = self.candidates.iter().map(|c| c.smiles.as_str()).collect();
+        wanted.extend(optimization::objectives::reference_drugs().iter().map(|r| r.candidate.smiles.as_str()));
+        self.axis_cache.retain(|smiles, _| wanted.contains(smiles.as_str()));
+
+        let missing: Vec<&str> = wanted.into_iter()
+            .filter(|smiles| !self.axis_cache.contains_key(*smiles))
+            .collect();
+        let computed: Vec<(String, [f32; 5])> = missing.par_iter()
+            .map(|smiles| (smiles.to_string(), axis_descriptors(smiles)))
+            .collect();
+        self.axis_cache.extend(computed);
+        self.axis_cache_revision = Some(self.candidates_revision);
+    }
+
+    /// Standard deviation of a candidate's plotted value on an axis (objective jitter only),
+    /// scaled like the value when objectives are normalized
+    pub fn axis_sigma(&self, c: &Candidate, var: AxisVar) -> f32 {
+        let (sigma, k) = match var {
+            AxisVar::Efficacy => (c.efficacy_sigma, 0),
+            AxisVar::Toxicity => (c.toxicity_sigma, 1),
+            _ => return 0.0,
+        };
+        if !self.normalize_objectives {
+            return sigma;
+        }
+        let range = self.objective_bounds.max[k] - self.objective_bounds.min[k];
+        if range > f32::EPSILON { sigma / range } else { 0.0 }
+    }
+
+    /// Compute molecular formulas for new SMILES while the formula filter is set
+    pub fn refresh_formula_cache(&mut self) {
+        if self.filter_formula.trim().is_empty() || self.formula_cache_revision == Some(self.candidates_revision) {
+            return;
+        }
+
+        let wanted: HashSet<&str> = self.candidates.iter().map(|c| c.smiles.as_str()).collect();
+        self.formula_cache.retain(|smiles, _| wanted.contains(smiles.as_str()));
+        let missing: Vec<&str> = wanted.into_iter()
+            .filter(|smiles| !self.formula_cache.contains_key(*smiles))
+            .collect();
+        let computed: Vec<(String, String)> = missing.par_iter()
+            .map(|smiles| (smiles.to_string(), chemistry::descriptors::molecular_formula(smiles)))
+            .collect();
+        self.formula_cache.extend(computed);
+        self.formula_cache_revision = Some(self.candidates_revision);
+    }
+
+    /// Compute filter descriptors for new SMILES while the property filters are on
+    pub fn refresh_property_cache(&mut self) {
+        if !self.property_filters.enabled || self.property_cache_revision == Some(self.candidates_revision) {
+            return;
+        }
+
+        let wanted: HashSet<&str> = self.candidates.iter().map(|c| c.smiles.as_str()).collect();
+        self.property_cache.retain(|smiles, _| wanted.contains(smiles.as_str()));
+        let missing: Vec<&str> = wanted.into_iter()
+            .filter(|smiles| !self.property_cache.contains_key(*smiles))
+            .collect();
+        let computed: Vec<(String, PropertyValues)> = missing.par_iter()
+            .map(|smiles| (smiles.to_string(), PropertyValues::from_smiles(smiles)))
+            .collect();
+        self.property_cache.extend(computed);
+        self.property_cache_revision = Some(self.candidates_revision);
+    }
+
+    /// Scaffold diversity of all candidates, extracting scaffolds for new SMILES only
+    pub fn cached_scaffold_diversity(&mut self) -> (usize, f32) {
+        if self.scaffold_cache_revision != Some(self.candidates_revision) {
+            let wanted: HashSet<&str> = self.candidates.iter().map(|c| c.smiles.as_str()).collect();
+            self.scaffold_cache.retain(|smiles, _| wanted.contains(smiles.as_str()));
+            let missing: Vec<&str> = wanted.into_iter()
+                .filter(|smiles| !self.scaffold_cache.contains_key(*smiles))
+                .collect();
+            let computed: Vec<(String, String)> = missing.par_iter()
+                .map(|smiles| (smiles.to_string(), chemistry::scaffold_extract::murcko_scaffold(smiles)))
+                .collect();
+            self.scaffold_cache.extend(computed);
+            self.scaffold_cache_revision = Some(self.candidates_revision);
+        }
+
+        let scaffolds = self.candidates.iter()
+            .map(|c| self.scaffold_cache.get(&c.smiles).map_or("", String::as_str));
+        diversity_from_scaffolds(scaffolds, self.candidates.len())
+    }
+
+    /// Axis value as plotted: objectives follow the normalization setting,
+    /// descriptors come from the cache when available
+    pub fn axis_value(&self, c: &Candidate, var: AxisVar) -> f32 {
+        if let Some(i) = var.objective_index() {
+            return self.objective_values(c)[i];
+        }
+        match (self.axis_cache.get(&c.smiles), var.descriptor_index()) {
+            (Some(values), Some(i)) => values[i],
+            _ => axis_value(c, var),
+        }
+    }
+
+    /// Set the four weights (and the preset's filter bounds, if any) from a named preset
+    pub fn apply_weight_preset(&mut self, preset: WeightPreset) {
+        [self.w_eff, self.w_tox, self.w_syn, self.w_mfg] = preset.weights();
+        if let Some((eff_min, tox_max)) = preset.filter_bounds() {
+            self.filter_eff_min = eff_min;
+            self.filter_tox_max = tox_max;
+        }
+        self.weight_preset = Some(preset);
+    }
+
+    /// Weights with inactive objectives zeroed, as [w_eff, w_tox, w_syn, w_mfg]
+    pub fn active_weights(&self) -> [f32; 4] {
+        let weights = [self.w_eff, self.w_tox, self.w_syn, self.w_mfg];
+        std::array::from_fn(|k| if self.active_objectives[k] { weights[k] } else { 0.0 })
+    }
+
+    /// A displayed value with `display_decimals` digits after the point
+    pub fn fmt_val(&self, v: f32) -> String {
+        format!("{:.*}", self.display_decimals, v)
+    }
+
+    pub fn weighted_score(&self, c: &Candidate) -> f32 {
+        let [eff, tox, syn, mfg] = self.objective_values(c);
+        let [w_eff, w_tox, w_syn, w_mfg] = self.active_weights();
+        w_eff * eff
+            - w_tox * tox
+            - w_syn * syn
+            - w_mfg * mfg
+    }
+
+    /// Signed contribution of each weighted objective; the terms sum to `weighted_score`
+    pub fn score_breakdown(&self, c: &Candidate) -> [(&'static str, f32); 4] {
+        let [eff, tox, syn, mfg] = self.objective_values(c);
+        let [w_eff, w_tox, w_syn, w_mfg] = self.active_weights();
+        [
+            ("Eff", w_eff * eff),
+            ("Tox", -w_tox * tox),
+            ("Syn", -w_syn * syn),
+            ("Mfg", -w_mfg * mfg),
+        ]
+    }
+
+    /// Set preference_pair to two distinct Pareto candidates, or clear it with fewer than two on the front
+    pub fn next_preference_pair(&mut self) {
+        use rand::seq::SliceRandom;
+        let pareto: Vec<usize> = self.candidates.iter().filter(|c| c.pareto).map(|c| c.id).collect();
+        let pair: Vec<usize> = pareto.choose_multiple(&mut rand::thread_rng(), 2).copied().collect();
+        self.preference_pair = match pair[..] {
+            [a, b] => Some((a, b)),
+            _ => None,
+        };
+    }
+
+    /// Record a pairwise choice, nudge the weights towards it and show the next pair
+    pub fn record_preference(&mut self, preferred_id: usize, rejected_id: usize) {
+        let objectives = |id| self.candidates.iter().find(|c| c.id == id).map(|c| self.objective_values(c));
+        let (Some(preferred), Some(rejected)) = (objectives(preferred_id), objectives(rejected_id)) else {
+            return;
+        };
+        let choice = PreferenceChoice { preferred, rejected };
+        let weights = [self.w_eff, self.w_tox, self.w_syn, self.w_mfg];
+        [self.w_eff, self.w_tox, self.w_syn, self.w_mfg] =
+            preference::update_weights(weights, &choice, preference::PREFERENCE_LEARNING_RATE);
+        self.weight_preset = None;
+        self.preference_choices.push(choice);
+        self.next_preference_pair();
+    }
+
+    pub fn generate(&mut self) {
+        // A running import has already claimed ids from next_id; a sweep keeps the worker busy
+        if self.is_generating || self.import_progress.is_some() || self.sweep_progress.is_some() {
+            return;
+        }
+
+        if let Some(sender) = &self.worker_sender {
+            self.pending_run = Some(GenerationRun {
+                run_id: self.runs.last().map(|r| r.run_id + 1).unwrap_or(0),
+                seed: self.seed,
+                n: self.n_generate,
+                parallel: self.use_parallel,
+                used_scaffolds: self.use_scaffolds,
+                category: self.scaffold_category.clone(),
+                timestamp: Local::now(),
+                candidate_ids: Vec::new(),
+            });
+            self.is_generating = true;
+            self.generation_progress = Some((0, self.n_generate));
+            self.generation_started = Some(Instant::now());
+            let _ = sender.send(WorkerMessage::GenerateCandidates {
+                n: self.n_generate,
+                seed: self.seed,
+                start_id: self.next_id,
+                parallel: self.use_parallel,
+                batch_size: self.batch_size,
+                options: self.generation_options(),
+            });
+
+            let mode = if self.use_parallel { "parallel" } else { "sequential" };
+            self.push_status(LogLevel::Info, format!("Generating {} candidates ({})...", self.n_generate, mode));
+        } else {
+            self.push_status(LogLevel::Error, "Generation worker is not running");
+        }
+    }
+
+    /// Generator settings from the UI; novelty search snapshots the current objectives
+    fn generation_options(&self) -> GenerationOptions {
+        GenerationOptions {
+            category: self.scaffold_category.clone(),
+            custom_scaffolds: self.custom_scaffolds.clone(),
+            thread_count: self.thread_count,
+            mix: self.generation_mix,
+            deterministic: self.deterministic_objectives,
+            novelty_search: self.novelty_search,
+            existing_objectives: if self.novelty_search {
+                self.candidates.iter().map(raw_objectives).collect()
+            } else {
+                Vec::new()
+            },
+        }
+    }
+
+    /// Add up to `n_generate` mutation and crossover children of the multi-selection,
+    /// or of the Pareto front when nothing is multi-selected
+    pub fn evolve(&mut self) {
+        if self.is_generating || self.import_progress.is_some() || self.sweep_progress.is_some() {
+            return;
+        }
+        let parents: Vec<Candidate> = self.candidates.iter()
+            .filter(|c| if self.selected_ids.is_empty() { c.pareto } else { self.selected_ids.contains(&c.id) })
+            .cloned()
+            .collect();
+        if parents.is_empty() {
+            self.push_status(LogLevel::Warning, "Nothing to evolve: select candidates or compute a Pareto front");
+            return;
+        }
+
+        // Offset by next_id so repeated rounds don't produce the same children
+        let seed = self.seed.wrapping_add(self.next_id as u64);
+        let children = generation::evolution::evolve(&parents, self.n_generate, self.next_id, seed, &self.generation_options());
+        if children.is_empty() {
+            self.push_status(LogLevel::Warning, "Evolution produced no valid children");
+            return;
+        }
+
+        let count = children.len();
+        self.history.push(Action::Generate { candidates: children.clone(), run: None });
+        self.next_id += count;
+        self.candidates.extend(children);
+        self.recompute_pareto();
+        self.push_status(LogLevel::Success, format!("Evolved {} children from {} parents", count, parents.len()));
+    }
+
+    /// Generate `sweep_k` batches of `n_generate` candidates on the worker, seeds counting
+    /// up from `seed`, and record per-seed statistics. Candidates are not kept.
+    pub fn start_seed_sweep(&mut self) {
+        if self.is_generating || self.import_progress.is_some() || self.sweep_progress.is_some() {
+            return;
+        }
+
+        if let Some(sender) = &self.worker_sender {
+            self.sweep_progress = Some((0, self.sweep_k));
+            let _ = sender.send(WorkerMessage::SeedSweep {
+                base_seed: self.seed,
+                k: self.sweep_k,
+                n: self.n_generate,
+                options: self.generation_options(),
+                weights: self.active_weights(),
+                objectives: self.active_objectives,
+            });
+            self.push_status(LogLevel::Info, format!(
+                "Seed sweep: {} seeds × {} candidates from seed {}...", self.sweep_k, self.n_generate, self.seed));
+        }
+    }
+
+    /// Filter candidates based on current filter settings
+    pub fn filtered_candidates(&self) -> Vec<&Candidate> {
+        let latest_run = self.runs.last().map(|r| r.run_id);
+        self.candidates
+            .iter()
+            .filter(|c| {
+                if self.pin_locked && self.annotations.is_locked(c.id) {
+                    return true;
+                }
+
+                // Pareto filter
+                if self.filter_pareto_only && !c.pareto {
+                    return false;
+                }
+                
+                // Favorites filter
+                if self.filter_favorites_only && !self.annotations.is_favorite(c.id) {
+                    return false;
+                }
+                
+                // Run filter
+                if self.filter_run.is_some() && c.run_id != self.filter_run {
+                    return false;
+                }
+                
+                // Latest batch filter
+                if self.filter_latest_batch && (c.run_id.is_none() || c.run_id != latest_run) {
+                    return false;
+                }
+
+                // Source filter
+                if self.filter_source.as_ref().is_some_and(|s| *s != c.source) {
+                    return false;
+                }
+                
+                // Substructure filter
+                if self.substructure_matches.as_ref().is_some_and(|ids| !ids.contains(&c.id)) {
+                    return false;
+                }
+                
+                // Tag filter
+                if !tag_filter_matches(&self.annotations, c.id, &self.filter_tags, self.filter_tags_match_all) {
+                    return false;
+                }
+                
+                // SMILES search
+                if !self.filter_smiles.is_empty() {
+                    let search = self.filter_smiles.to_lowercase();
+                    if !c.smiles.to_lowercase().contains(&search) {
+                        return false;
+                    }
+                }
+                
+                // Formula search, cached formulas when available
+                if !self.filter_formula.trim().is_empty() {
+                    let matches = match self.formula_cache.get(&c.smiles) {
+                        Some(formula) => chemistry::descriptors::formula_contains(formula, &self.filter_formula),
+                        None => chemistry::descriptors::formula_matches(&c.smiles, &self.filter_formula),
+                    };
+                    if !matches {
+                        return false;
+                    }
+                }
+
+                // Efficacy range
+                if c.efficacy < self.filter_eff_min || c.efficacy > self.filter_eff_max {
+                    return false;
+                }
+                
+                // Toxicity range
+                if c.toxicity < self.filter_tox_min || c.toxicity > self.filter_tox_max {
+                    return false;
+                }
+
+                // Physicochemical ranges, cached descriptors when available
+                if self.property_filters.enabled {
+                    let values = self.property_cache.get(&c.smiles)
+                        .copied()
+                        .unwrap_or_else(|| PropertyValues::from_smiles(&c.smiles));
+                    if !passes_property_filters(&values, &self.property_filters) {
+                        return false;
+                    }
+                }
+                
+                true
+            })
+            .collect()
+    }
+
+    /// Save session to file
+    pub fn save_session(&self, path: &str) -> Result<(), String> {
+        write_session(&self.session_data(), path)
+    }
+
+    /// Snapshot the session on a background thread if the autosave interval has passed.
+    /// Skipped while empty or while an older autosave is waiting to be recovered.
+    pub fn autosave_if_due(&mut self) {
+        if let Some(result) = self.autosave_result.as_ref().and_then(|r| r.try_recv().ok()) {
+            self.autosave_result = None;
+            if let Err(e) = result {
+                self.push_status(LogLevel::Warning, format!("Autosave failed: {}", e));
+            }
+        }
+        if !should_autosave(self.last_autosave.elapsed(), self.autosave_interval_secs) {
+            return;
+        }
+        self.last_autosave = Instant::now();
+        if self.candidates.is_empty() || self.show_recover_dialog {
+            return;
+        }
+
+        let session = self.session_data();
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        self.autosave_result = Some(receiver);
+        thread::spawn(move || {
+            // Write then rename so a crash mid-write keeps the previous snapshot
+            let tmp = format!("{}.tmp", AUTOSAVE_PATH);
+            let result = write_session(&session, &tmp)
+                .and_then(|()| std::fs::rename(&tmp, AUTOSAVE_PATH).map_err(|e| format!("Rename error: {}", e)));
+            let _ = sender.send(result);
+        });
+    }
+
+    /// Delete the crash-recovery snapshot once the session is saved or closed normally
+    pub fn remove_autosave(&mut self) {
+        match std::fs::remove_file(AUTOSAVE_PATH) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => self.push_status(LogLevel::Warning, format!("Could not remove autosave: {}", e)),
+        }
+    }
+
+    fn session_data(&self) -> SessionData {
+        SessionData {
+            candidates: self.candidates.clone(),
+            next_id: self.next_id,
+            n_generate: self.n_generate,
+            seed: self.seed,
+            w_eff: self.w_eff,
+            w_tox: self.w_tox,
+            w_syn: self.w_syn,
+            w_mfg: self.w_mfg,
+            filter_pareto_only: self.filter_pareto_only,
+            annotations: self.annotations.clone(),
+            runs: self.runs.clone(),
+            custom_scaffolds: self.custom_scaffolds.clone(),
+            active_objectives: self.active_objectives,
+        }
+    }
+
+    /// Load session from file
+    pub fn load_session(&mut self, path: &str) -> Result<(), String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Read error: {}", e))?;
+        
+        let session: SessionData = serde_json::from_str(&json)
+            .map_err(|e| format!("Parse error: {}", e))?;
+        
+        self.candidates = session.candidates;
+        self.next_id = session.next_id;
+        self.n_generate = session.n_generate;
+        self.seed = session.seed;
+        self.w_eff = session.w_eff;
+        self.w_tox = session.w_tox;
+        self.w_syn = session.w_syn;
         self.w_mfg = session.w_mfg;
         self.filter_pareto_only = session.filter_pareto_only;
         self.annotations = session.annotations;
+        self.runs = session.runs;
+        self.custom_scaffolds = session.custom_scaffolds;
+        self.active_objectives = session.active_objectives;
+        self.filter_run = None;
+        self.committed_settings = None;
         self.selected_id = None;
+        self.selected_ids.clear();
+        self.compare_id = None;
         
         self.recompute_pareto();
         
         Ok(())
     }
 
+    /// Ask the worker to stop; the candidates produced so far arrive as a partial batch
     pub fn cancel_generation(&mut self) {
         if let Some(sender) = &self.worker_sender {
             let _ = sender.send(WorkerMessage::CancelGeneration);
-            self.is_generating = false;
-            self.generation_progress = None;
-            self.status = "Generation cancelled".into();
+            self.push_status(LogLevel::Info, "Cancelling...");
         }
     }
 
     pub fn process_worker_messages(&mut self) {
+        let mut disconnected = false;
         let messages: Vec<WorkerMessage> = if let Some(receiver) = &self.worker_receiver {
             let mut msgs = Vec::new();
-            while let Ok(msg) = receiver.try_recv() {
-                msgs.push(msg);
+            loop {
+                match receiver.try_recv() {
+                    Ok(msg) => msgs.push(msg),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        disconnected = true;
+                        break;
+                    }
+                }
             }
             msgs
         } else {
@@ -300,38 +1661,132 @@ impl AppState {
             match msg {
                 WorkerMessage::GenerationProgress { current, total } => {
                     self.generation_progress = Some((current, total));
+                    // Progress is transient, keep it out of the log
                     self.status = format!("Generating... {}/{}", current, total);
                 }
-                WorkerMessage::GenerationComplete { candidates } => {
+                WorkerMessage::GenerationComplete { mut candidates, cancelled } => {
                     let count = candidates.len();
+                    let requested = self.generation_progress.map_or(count, |(_, total)| total);
+                    
+                    // A cancelled run records what it produced; with per-candidate seeding,
+                    // its seed and the reduced n reproduce exactly that partial batch
+                    let run = self.pending_run.take().filter(|_| count > 0).map(|mut run| {
+                        run.n = count;
+                        run.timestamp = Local::now();
+                        run.candidate_ids = candidates.iter().map(|c| c.id).collect();
+                        for c in &mut candidates {
+                            c.run_id = Some(run.run_id);
+                        }
+                        self.runs.push(run.clone());
+                        Box::new(run)
+                    });
                     
                     // Record for undo
                     self.history.push(Action::Generate { 
-                        candidates: candidates.clone() 
+                        candidates: candidates.clone(),
+                        run,
                     });
                     
+                    // Only the new batch needs testing against the current front
+                    let existing_front: HashSet<usize> = self.candidates.iter()
+                        .filter(|c| c.pareto)
+                        .map(|c| c.id)
+                        .collect();
+                    let new_ids: Vec<usize> = candidates.iter().map(|c| c.id).collect();
+                    
                     self.next_id += count;
                     self.candidates.extend(candidates);
-                    self.recompute_pareto();
+                    let front_ids = optimization::pareto::update_pareto_incremental(
+                        &existing_front,
+                        &self.candidates,
+                        &new_ids,
+                        self.active_objectives,
+                    );
+                    self.apply_pareto_front(&front_ids);
                     self.is_generating = false;
                     self.generation_progress = None;
+                    self.generation_started = None;
                     let pareto_count = self.candidates.iter().filter(|c| c.pareto).count();
-                    self.status = format!(
-                        "Generated {} candidates (total: {}, pareto: {})",
-                        count, self.candidates.len(), pareto_count
-                    );
+                    if cancelled {
+                        self.push_status(LogLevel::Warning, format!(
+                            "Cancelled after {}/{}, kept {} candidates (total: {}, pareto: {})",
+                            count, requested, count, self.candidates.len(), pareto_count
+                        ));
+                    } else {
+                        self.push_status(LogLevel::Success, format!(
+                            "Generated {} candidates (total: {}, pareto: {})",
+                            count, self.candidates.len(), pareto_count
+                        ));
+                    }
                 }
                 WorkerMessage::GenerationError(error) => {
+                    self.pending_run = None;
+                    self.sweep_progress = None;
                     self.is_generating = false;
                     self.generation_progress = None;
-                    self.status = format!("Error: {}", error);
+                    self.generation_started = None;
+                    self.push_status(LogLevel::Error, format!("Error: {}", error));
+                }
+                WorkerMessage::ImportProgress { imported } => {
+                    self.import_progress = Some(imported);
+                    self.status = format!("Importing... {}", imported);
+                }
+                WorkerMessage::ImportComplete { report } => {
+                    self.import_progress = None;
+                    self.add_imported(report);
+                }
+                WorkerMessage::ImportError(error) => {
+                    self.import_progress = None;
+                    self.push_status(LogLevel::Error, format!("Import failed: {}", error));
+                }
+                WorkerMessage::SeedSweepProgress { done, k } => {
+                    self.sweep_progress = Some((done, k));
+                    self.status = format!("Seed sweep... {}/{}", done, k);
+                }
+                WorkerMessage::SeedSweepComplete { runs } => {
+                    self.sweep_progress = None;
+                    self.push_status(LogLevel::Success, format!("Seed sweep finished: {} seeds", runs.len()));
+                    self.sweep_runs = runs;
                 }
                 _ => {}
             }
         }
-    }
 
+        if disconnected {
+            self.worker_lost();
+        }
+    }
+
+    /// The worker thread is gone: drop the channels and unblock anything waiting on it
+    fn worker_lost(&mut self) {
+        self.worker_sender = None;
+        self.worker_receiver = None;
+        self.pending_run = None;
+        self.is_generating = false;
+        self.generation_progress = None;
+        self.generation_started = None;
+        self.import_progress = None;
+        self.sweep_progress = None;
+        self.push_status(LogLevel::Error, "Generation worker stopped unexpectedly; restart the app to generate again");
+    }
+
+    /// Remove every candidate except the locked ones
     pub fn clear(&mut self) {
+        let locked = self.candidates.iter().filter(|c| self.annotations.is_locked(c.id)).count();
+        if locked > 0 {
+            let removed: Vec<Candidate> = self.candidates.iter()
+                .filter(|c| !self.annotations.is_locked(c.id))
+                .cloned()
+                .collect();
+            let ids: HashSet<usize> = removed.iter().map(|c| c.id).collect();
+            if !removed.is_empty() {
+                self.history.push(Action::Clear { candidates: removed });
+            }
+            self.remove_candidates(&ids);
+            self.push_status(LogLevel::Info, format!("Cleared {} candidates, kept {} locked", ids.len(), locked));
+            return;
+        }
+
         // Record for undo
         if !self.candidates.is_empty() {
             self.history.push(Action::Clear { 
@@ -340,47 +1795,139 @@ impl AppState {
         }
         
         self.candidates.clear();
+        self.objective_bounds = ObjectiveBounds::default();
+        self.candidates_revision += 1;
+        self.update_substructure_filter();
         self.selected_id = None;
+        self.selected_ids.clear();
+        self.compare_id = None;
         self.next_id = 0;
-        self.status = "Cleared all candidates".into();
+        self.prune_filter_tags();
+        self.push_status(LogLevel::Info, "Cleared all candidates");
+    }
+
+    /// Recompute all objectives with the descriptor-based model (undoable)
+    pub fn recompute_objectives(&mut self) {
+        if self.candidates.is_empty() {
+            self.push_status(LogLevel::Warning, "No candidates to recompute");
+            return;
+        }
+
+        self.history.push(Action::RecomputeObjectives { old: self.candidates.clone() });
+        self.apply_objectives_model();
+        self.push_status(LogLevel::Success, format!("Recomputed objectives for {} candidates", self.candidates.len()));
+    }
+
+    fn apply_objectives_model(&mut self) {
+        self.candidates
+            .par_iter_mut()
+            .for_each(optimization::objectives::compute_objectives);
+        self.mark_pareto_dirty();
+    }
+
+    /// Request a Pareto recompute on the next frame
+    pub fn mark_pareto_dirty(&mut self) {
+        self.pareto_dirty = true;
+    }
+
+    /// Recompute the front if something marked it dirty. Returns whether it ran.
+    pub fn recompute_pareto_if_dirty(&mut self) -> bool {
+        if !self.pareto_dirty {
+            return false;
+        }
+        self.pareto_dirty = false;
+        self.recompute_pareto();
+        true
     }
 
+    /// Refresh derived data after the candidate set changed
     pub fn recompute_pareto(&mut self) {
-        let front_ids = optimization::pareto::pareto_front_ids(&self.candidates);
+        let front_ids = optimization::pareto::pareto_front_ids_fast(&self.candidates, self.active_objectives);
+        self.apply_pareto_front(&front_ids);
+    }
+
+    /// Flag front members and refresh the other data derived from the candidates
+    fn apply_pareto_front(&mut self, front_ids: &HashSet<usize>) {
+        self.objective_bounds = ObjectiveBounds::from_candidates(&self.candidates);
+        self.candidates_revision += 1;
+        self.update_substructure_filter();
         for c in &mut self.candidates {
             c.pareto = front_ids.contains(&c.id);
         }
     }
 
+    /// Re-run the substructure search after the pattern or the candidates changed.
+    /// An invalid pattern matches nothing.
+    pub fn update_substructure_filter(&mut self) {
+        if self.filter_substructure.trim().is_empty() {
+            self.substructure_matches = None;
+            return;
+        }
+
+        let patterns = parse_substructure_patterns(&self.filter_substructure).unwrap_or_default();
+        let ids = self.candidates
+            .par_iter()
+            .filter(|c| {
+                chemistry::graph::parse_smiles(&c.smiles)
+                    .is_ok_and(|g| patterns.iter().any(|p| g.contains_substructure(p)))
+            })
+            .map(|c| c.id)
+            .collect();
+        self.substructure_matches = Some(ids);
+    }
+
     /// Undo last action
     pub fn undo(&mut self) {
         if let Some(action) = self.history.undo() {
             match action {
-                Action::Generate { candidates } => {
-                    // Remove the generated candidates
+                Action::Generate { candidates, run } => {
+                    // Remove the generated candidates and their run entry
                     let ids: std::collections::HashSet<usize> = candidates.iter().map(|c| c.id).collect();
                     self.candidates.retain(|c| !ids.contains(&c.id));
+                    if let Some(run) = run {
+                        self.remove_run(run.run_id);
+                    }
                     self.next_id = self.candidates.iter().map(|c| c.id).max().map(|m| m + 1).unwrap_or(0);
                     self.recompute_pareto();
-                    self.status = format!("Undone: Generated {} candidates", candidates.len());
+                    self.push_status(LogLevel::Info, format!("Undone: Generated {} candidates", candidates.len()));
                 }
                 Action::Clear { candidates } => {
-                    // Restore cleared candidates
-                    self.candidates = candidates;
+                    // Restore cleared candidates next to the locked ones that stayed
+                    self.candidates.extend(candidates);
+                    self.candidates.sort_by_key(|c| c.id);
                     self.next_id = self.candidates.iter().map(|c| c.id).max().map(|m| m + 1).unwrap_or(0);
                     self.recompute_pareto();
-                    self.status = "Undone: Clear".into();
+                    self.push_status(LogLevel::Info, "Undone: Clear");
                 }
                 Action::Import { candidates } => {
                     let ids: std::collections::HashSet<usize> = candidates.iter().map(|c| c.id).collect();
                     self.candidates.retain(|c| !ids.contains(&c.id));
                     self.recompute_pareto();
-                    self.status = format!("Undone: Import {} candidates", candidates.len());
+                    self.push_status(LogLevel::Info, format!("Undone: Import {} candidates", candidates.len()));
                 }
                 Action::Delete { candidate } => {
                     self.candidates.push(candidate);
                     self.recompute_pareto();
-                    self.status = "Undone: Delete".into();
+                    self.push_status(LogLevel::Info, "Undone: Delete");
+                }
+                Action::DeleteMany { candidates } => {
+                    let count = candidates.len();
+                    self.candidates.extend(candidates);
+                    self.candidates.sort_by_key(|c| c.id);
+                    self.recompute_pareto();
+                    self.push_status(LogLevel::Info, format!("Undone: Delete {} candidates", count));
+                }
+                Action::MergeDuplicates { removed, old_annotations, .. } => {
+                    let count = removed.len();
+                    self.candidates.extend(removed);
+                    self.candidates.sort_by_key(|c| c.id);
+                    self.annotations = *old_annotations;
+                    self.recompute_pareto();
+                    self.push_status(LogLevel::Info, format!("Undone: Merge {} duplicates", count));
+                }
+                Action::ImportAnnotations { matched, old_annotations, .. } => {
+                    self.annotations = *old_annotations;
+                    self.push_status(LogLevel::Info, format!("Undone: Import annotations for {} candidates", matched));
                 }
                 Action::UpdateAnnotation { id, old_note, .. } => {
                     if let Some(note) = old_note {
@@ -392,9 +1939,34 @@ impl AppState {
                 Action::ToggleFavorite { id } => {
                     self.annotations.toggle_favorite(id);
                 }
+                Action::ToggleFavorites { ids } => {
+                    for id in ids {
+                        self.annotations.toggle_favorite(id);
+                    }
+                }
+                Action::AddTag { id, tag } => {
+                    self.annotations.remove_tag(id, &tag);
+                }
+                Action::RemoveTag { id, tag } => {
+                    self.annotations.add_tag(id, &tag);
+                }
+                Action::RecomputeObjectives { old } => {
+                    self.candidates = old;
+                    self.mark_pareto_dirty();
+                    self.push_status(LogLevel::Info, "Undone: Recompute objectives");
+                }
+                Action::SetWeights { old, .. } => {
+                    self.restore_weights(old);
+                    self.push_status(LogLevel::Info, "Undone: Weight change");
+                }
+                Action::SetFilters { old, .. } => {
+                    self.restore_filters(*old);
+                    self.push_status(LogLevel::Info, "Undone: Filter change");
+                }
             }
+            self.prune_filter_tags();
         } else {
-            self.status = "Nothing to undo".into();
+            self.push_status(LogLevel::Warning, "Nothing to undo");
         }
     }
 
@@ -402,26 +1974,48 @@ impl AppState {
     pub fn redo(&mut self) {
         if let Some(action) = self.history.redo() {
             match action {
-                Action::Generate { candidates } => {
+                Action::Generate { candidates, run } => {
                     self.candidates.extend(candidates.clone());
+                    if let Some(run) = run {
+                        self.runs.push(*run);
+                        self.runs.sort_by_key(|r| r.run_id);
+                    }
                     self.next_id = self.candidates.iter().map(|c| c.id).max().map(|m| m + 1).unwrap_or(0);
                     self.recompute_pareto();
-                    self.status = format!("Redone: Generated {} candidates", candidates.len());
+                    self.push_status(LogLevel::Info, format!("Redone: Generated {} candidates", candidates.len()));
                 }
-                Action::Clear { .. } => {
-                    self.candidates.clear();
-                    self.next_id = 0;
-                    self.status = "Redone: Clear".into();
+                Action::Clear { candidates } => {
+                    let ids: HashSet<usize> = candidates.iter().map(|c| c.id).collect();
+                    self.remove_candidates(&ids);
+                    if self.candidates.is_empty() {
+                        self.next_id = 0;
+                    }
+                    self.push_status(LogLevel::Info, "Redone: Clear");
                 }
                 Action::Import { candidates } => {
                     self.candidates.extend(candidates.clone());
                     self.recompute_pareto();
-                    self.status = format!("Redone: Import {} candidates", candidates.len());
+                    self.push_status(LogLevel::Info, format!("Redone: Import {} candidates", candidates.len()));
                 }
                 Action::Delete { candidate } => {
                     self.candidates.retain(|c| c.id != candidate.id);
                     self.recompute_pareto();
-                    self.status = "Redone: Delete".into();
+                    self.push_status(LogLevel::Info, "Redone: Delete");
+                }
+                Action::DeleteMany { candidates } => {
+                    let ids: HashSet<usize> = candidates.iter().map(|c| c.id).collect();
+                    self.remove_candidates(&ids);
+                    self.push_status(LogLevel::Info, format!("Redone: Delete {} candidates", ids.len()));
+                }
+                Action::MergeDuplicates { removed, new_annotations, .. } => {
+                    let ids: HashSet<usize> = removed.iter().map(|c| c.id).collect();
+                    self.annotations = *new_annotations;
+                    self.remove_candidates(&ids);
+                    self.push_status(LogLevel::Info, format!("Redone: Merge {} duplicates", ids.len()));
+                }
+                Action::ImportAnnotations { matched, new_annotations, .. } => {
+                    self.annotations = *new_annotations;
+                    self.push_status(LogLevel::Info, format!("Redone: Import annotations for {} candidates", matched));
                 }
                 Action::UpdateAnnotation { id, new_note, .. } => {
                     if let Some(note) = new_note {
@@ -431,33 +2025,302 @@ impl AppState {
                 Action::ToggleFavorite { id } => {
                     self.annotations.toggle_favorite(id);
                 }
+                Action::ToggleFavorites { ids } => {
+                    for id in ids {
+                        self.annotations.toggle_favorite(id);
+                    }
+                }
+                Action::AddTag { id, tag } => {
+                    self.annotations.add_tag(id, &tag);
+                }
+                Action::RemoveTag { id, tag } => {
+                    self.annotations.remove_tag(id, &tag);
+                }
+                Action::RecomputeObjectives { .. } => {
+                    self.apply_objectives_model();
+                    self.push_status(LogLevel::Info, "Redone: Recompute objectives");
+                }
+                Action::SetWeights { new, .. } => {
+                    self.restore_weights(new);
+                    self.push_status(LogLevel::Info, "Redone: Weight change");
+                }
+                Action::SetFilters { new, .. } => {
+                    self.restore_filters(*new);
+                    self.push_status(LogLevel::Info, "Redone: Filter change");
+                }
             }
+            self.prune_filter_tags();
         } else {
-            self.status = "Nothing to redo".into();
+            self.push_status(LogLevel::Warning, "Nothing to redo");
+        }
+    }
+
+    /// Delete candidates by id (undoable)
+    pub fn delete_candidates(&mut self, ids: &HashSet<usize>) {
+        let removed: Vec<Candidate> = self.candidates.iter()
+            .filter(|c| ids.contains(&c.id))
+            .cloned()
+            .collect();
+        if removed.is_empty() {
+            return;
+        }
+        self.remove_candidates(ids);
+        self.push_status(LogLevel::Success, format!("Deleted {} candidates", removed.len()));
+        self.history.push(Action::DeleteMany { candidates: removed });
+    }
+
+    fn remove_candidates(&mut self, ids: &HashSet<usize>) {
+        self.candidates.retain(|c| !ids.contains(&c.id));
+        self.selected_ids.retain(|id| !ids.contains(id));
+        if self.selected_id.is_some_and(|id| ids.contains(&id)) {
+            self.selected_id = None;
+        }
+        if self.compare_id.is_some_and(|id| ids.contains(&id)) {
+            self.compare_id = None;
+        }
+        self.prune_filter_tags();
+        self.recompute_pareto();
+    }
+
+    /// Group candidates by `dedup_key` (canonical SMILES, stereo-aware) and keep the report
+    /// for the duplicates window
+    pub fn find_duplicates(&mut self) {
+        let groups = optimization::dedup::find_duplicates(&self.candidates);
+        let redundant: usize = groups.iter().map(|g| g.len() - 1).sum();
+        self.push_status(LogLevel::Info, format!(
+            "Found {} duplicate groups ({} redundant candidates)", groups.len(), redundant));
+        self.duplicate_groups = Some(groups);
+    }
+
+    /// Keep the lowest id of each duplicate group and delete the rest in one undoable step.
+    /// The kept candidate takes over the notes, tags, favorite and lock of the deleted ones.
+    pub fn merge_duplicates(&mut self) {
+        let groups = optimization::dedup::find_duplicates(&self.candidates);
+        self.duplicate_groups = None;
+        if groups.is_empty() {
+            return;
+        }
+
+        let old_annotations = self.annotations.clone();
+        for group in &groups {
+            self.annotations.merge_into(group[0], &group[1..]);
+        }
+        let ids: HashSet<usize> = groups.iter()
+            .flat_map(|g| g[1..].iter().copied())
+            .collect();
+        let removed: Vec<Candidate> = self.candidates.iter()
+            .filter(|c| ids.contains(&c.id))
+            .cloned()
+            .collect();
+        self.remove_candidates(&ids);
+        self.push_status(LogLevel::Success, format!("Merged {} duplicates", removed.len()));
+        self.history.push(Action::MergeDuplicates {
+            removed,
+            old_annotations: Box::new(old_annotations),
+            new_annotations: Box::new(self.annotations.clone()),
+        });
+    }
+
+    /// Group structurally near-identical candidates at `near_duplicate_threshold`
+    pub fn find_near_duplicates(&mut self) {
+        let groups = optimization::dedup::cluster_near_duplicates(&self.candidates, self.near_duplicate_threshold);
+        self.push_status(LogLevel::Info, format!(
+            "Found {} near-duplicate groups at Tanimoto ≥ {:.2}", groups.len(), self.near_duplicate_threshold));
+        self.near_duplicate_groups = Some(groups);
+    }
+
+    /// Keep the best-scoring member of each near-duplicate group, deleting the rest (undoable)
+    pub fn collapse_near_duplicates(&mut self) {
+        let groups = optimization::dedup::cluster_near_duplicates(&self.candidates, self.near_duplicate_threshold);
+        let mut ids = HashSet::new();
+        for group in &groups {
+            let best = group.iter()
+                .copied()
+                .max_by(|&a, &b| self.cached_score(a).total_cmp(&self.cached_score(b)).then(b.cmp(&a)));
+            ids.extend(group.iter().copied().filter(|&id| Some(id) != best));
         }
+        self.delete_candidates(&ids);
+        self.near_duplicate_groups = None;
     }
 
     /// Import candidates from SMILES text
     pub fn import_from_text(&mut self, text: &str) {
-        let candidates = super::io::import_smiles_text(text, self.next_id);
+        if self.import_progress.is_some() {
+            self.push_status(LogLevel::Warning, "A file import is still running");
+            return;
+        }
+        let report = super::io::import_smiles_text(text, self.next_id);
+        self.add_imported(report);
+    }
+
+    /// Stream a SMILES file on the worker thread; progress arrives as worker messages
+    pub fn import_file_streaming(&mut self, path: &str) {
+        if self.is_generating || self.import_progress.is_some() || self.sweep_progress.is_some() {
+            return;
+        }
+
+        if let Some(sender) = &self.worker_sender {
+            self.import_progress = Some(0);
+            let _ = sender.send(WorkerMessage::ImportFile {
+                path: path.to_string(),
+                start_id: self.next_id,
+            });
+            self.push_status(LogLevel::Info, format!("Importing {}...", path));
+        }
+    }
+
+    /// Import a file dropped onto the window, picking the importer by extension
+    pub fn import_dropped_file(&mut self, path: &std::path::Path) {
+        use super::io::{self, DroppedFileKind};
+
+        let name = path.display().to_string();
+        let Some(kind) = io::dropped_file_kind(path) else {
+            self.push_status(LogLevel::Warning, format!("Unsupported file type: {}", name));
+            return;
+        };
+        if self.import_progress.is_some() {
+            self.push_status(LogLevel::Warning, "A file import is still running");
+            return;
+        }
+        // The running batch was given ids from next_id, and a session load would replace it
+        if self.is_generating || self.sweep_progress.is_some() {
+            self.push_status(LogLevel::Warning, format!("Generation is running, {} was not imported", name));
+            return;
+        }
+
+        let imported = match kind {
+            DroppedFileKind::Smiles => io::import_smiles_file(&name, self.next_id),
+            DroppedFileKind::Csv => io::import_csv(&name, self.next_id),
+            DroppedFileKind::Sdf => io::import_sdf_file(&name, self.next_id),
+            DroppedFileKind::Session => {
+                match self.load_session(&name) {
+                    Ok(()) => self.push_status(LogLevel::Success,
+                        format!("Loaded session {} ({} candidates)", name, self.candidates.len())),
+                    Err(e) => self.push_status(LogLevel::Error, format!("Failed to load {}: {}", name, e)),
+                }
+                return;
+            }
+        };
+        match imported {
+            Ok(report) => self.add_imported(report),
+            Err(e) => self.push_status(LogLevel::Error, format!("Failed to import {}: {}", name, e)),
+        }
+    }
+
+    fn add_imported(&mut self, report: ImportReport) {
+        let ImportReport { added: candidates, rejected } = report;
+        self.import_issues = rejected;
+        let rejected_note = match self.import_issues.len() {
+            0 => String::new(),
+            n => format!(", rejected {} invalid (see Import issues)", n),
+        };
+        let (mut candidates, skipped) = if self.import_dedup {
+            super::io::dedup_candidates(&self.candidates, candidates)
+        } else {
+            (candidates, 0)
+        };
+
+        // Dropped duplicates leave gaps, so keep ids contiguous from next_id
+        for (i, c) in candidates.iter_mut().enumerate() {
+            c.id = self.next_id + i;
+        }
+
         if !candidates.is_empty() {
             self.history.push(Action::Import { candidates: candidates.clone() });
             let count = candidates.len();
             self.next_id += count;
             self.candidates.extend(candidates);
             self.recompute_pareto();
-            self.status = format!("Imported {} candidates", count);
+            let msg = if skipped > 0 {
+                format!("Imported {}, skipped {} duplicates{}", count, skipped, rejected_note)
+            } else {
+                format!("Imported {} candidates{}", count, rejected_note)
+            };
+            self.push_status(LogLevel::Success, msg);
+        } else if skipped > 0 {
+            self.push_status(LogLevel::Warning, format!("Imported 0, skipped {} duplicates{}", skipped, rejected_note));
         } else {
-            self.status = "No valid SMILES found".into();
+            self.push_status(LogLevel::Warning, format!("No valid SMILES found{}", rejected_note));
+        }
+    }
+
+    /// Validate and register a user-defined scaffold for generation
+    pub fn add_custom_scaffold(&mut self, scaffold: OwnedScaffold) -> Result<(), String> {
+        let name = scaffold.name.trim();
+        let smiles = scaffold.smiles.trim();
+        if name.is_empty() {
+            return Err("Scaffold name is required".into());
+        }
+        if chemistry::scaffolds::get_scaffold_by_name(name).is_some()
+            || self.custom_scaffolds.iter().any(|s| s.name.eq_ignore_ascii_case(name))
+        {
+            return Err(format!("Scaffold '{}' already exists", name));
+        }
+        // Generation decorates and scores the scaffold through the graph, so it must parse
+        if let Err(e) = chemistry::graph::parse_smiles(smiles) {
+            return Err(format!("Invalid SMILES {}: {}", smiles, e));
+        }
+
+        let category = match scaffold.category.trim() {
+            "" => "Custom".to_string(),
+            c => c.to_string(),
+        };
+        self.custom_scaffolds.push(OwnedScaffold {
+            name: name.to_string(),
+            smiles: smiles.to_string(),
+            category,
+        });
+        self.push_status(LogLevel::Success, format!("Added custom scaffold '{}'", name));
+        Ok(())
+    }
+
+    /// Remember the sorted, filtered row order so keyboard navigation is O(1)
+    pub fn set_table_order(&mut self, ids: Vec<usize>) {
+        if ids != self.table_order {
+            self.table_positions = ids.iter().enumerate().map(|(row, &id)| (id, row)).collect();
+            self.table_order = ids;
+        }
+    }
+
+    /// Move the selection through the table rows
+    pub fn navigate_table(&mut self, dir: NavDir) {
+        let current = self.selected_id.and_then(|id| self.table_positions.get(&id).copied());
+        if let Some(id) = neighbor_id(&self.table_order, current, dir) {
+            self.selected_id = Some(id);
+            self.scroll_to_selected = true;
         }
     }
 
+    /// Lock or unlock a candidate; locked ones survive Clear and can be pinned past filters
+    pub fn toggle_lock(&mut self, id: usize) {
+        let locked = self.annotations.toggle_lock(id);
+        self.push_status(LogLevel::Info, format!("#{} {}", id, if locked { "locked" } else { "unlocked" }));
+    }
+
     /// Toggle favorite status
     pub fn toggle_favorite(&mut self, id: usize) {
         self.history.push(Action::ToggleFavorite { id });
         self.annotations.toggle_favorite(id);
     }
 
+    /// Favorite every id, or unfavorite them all if they already are (one undo step)
+    pub fn toggle_favorites(&mut self, ids: &[usize]) {
+        let favorite = !ids.iter().all(|&id| self.annotations.is_favorite(id));
+        let changed: Vec<usize> = ids.iter()
+            .copied()
+            .filter(|&id| self.annotations.is_favorite(id) != favorite)
+            .collect();
+        if changed.is_empty() {
+            return;
+        }
+        for &id in &changed {
+            self.annotations.toggle_favorite(id);
+        }
+        let verb = if favorite { "Favorited" } else { "Unfavorited" };
+        self.push_status(LogLevel::Info, format!("{} {} candidates", verb, changed.len()));
+        self.history.push(Action::ToggleFavorites { ids: changed });
+    }
+
     /// Set annotation note
     pub fn set_note(&mut self, id: usize, note: String) {
         let old_note = self.annotations.get_note(id).cloned();
@@ -468,64 +2331,1190 @@ impl AppState {
         });
         self.annotations.set_note(id, note);
     }
+
+    /// Tag a candidate (undoable)
+    pub fn add_tag(&mut self, id: usize, tag: &str) {
+        let tag = tag.trim().to_string();
+        if self.annotations.add_tag(id, &tag) {
+            self.history.push(Action::AddTag { id, tag });
+        }
+    }
+
+    /// Remove a tag from a candidate (undoable)
+    pub fn remove_tag(&mut self, id: usize, tag: &str) {
+        if self.annotations.remove_tag(id, tag) {
+            self.history.push(Action::RemoveTag { id, tag: tag.to_string() });
+            self.prune_filter_tags();
+        }
+    }
+}
+
+/// Named substructure patterns offered as quick filters; ',' separates alternatives
+pub const SUBSTRUCTURE_PRESETS: &[(&str, &str)] = &[
+    ("Carboxyl", "C(=O)[OH]"),
+    ("Amine", "C[NH2],C[NH]C"),
+    ("Aromatic ring", "c1ccccc1,c1ccncc1,c1ccsc1,c1ccoc1,c1cc[nH]c1"),
+    ("Halogen", "F,Cl,Br,I"),
+];
+
+/// Parse a comma-separated list of SMILES substructure patterns
+pub fn parse_substructure_patterns(text: &str) -> Result<Vec<chemistry::graph::MolGraph>, String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| chemistry::graph::parse_smiles(p).map_err(|e| format!("'{}': {}", p, e)))
+        .collect()
+}
+
+/// Tag filter: with `match_all` the candidate needs every tag, otherwise any.
+/// An empty tag list matches everything.
+pub fn tag_filter_matches(annotations: &Annotations, id: usize, tags: &[String], match_all: bool) -> bool {
+    if tags.is_empty() {
+        return true;
+    }
+    if match_all {
+        tags.iter().all(|t| annotations.has_tag(id, t))
+    } else {
+        tags.iter().any(|t| annotations.has_tag(id, t))
+    }
+}
+
+fn write_session(session: &SessionData, path: &str) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(session)
+        .map_err(|e| format!("Serialization error: {}", e))?;
+    
+    std::fs::write(path, json)
+        .map_err(|e| format!("Write error: {}", e))?;
+    
+    Ok(())
 }
 
 fn generation_worker(receiver: Receiver<WorkerMessage>, sender: Sender<WorkerMessage>) {
     while let Ok(msg) = receiver.recv() {
-        match msg {
-            WorkerMessage::GenerateCandidates { n, seed, start_id, parallel } => {
-                if parallel {
-                    let _ = sender.send(WorkerMessage::GenerationProgress {
-                        current: 0,
-                        total: n,
-                    });
+        // A panicking task is reported as a failure instead of killing the worker
+        let is_import = matches!(msg, WorkerMessage::ImportFile { .. });
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            handle_worker_task(msg, &receiver, &sender);
+        }));
+        if let Err(payload) = result {
+            let error = format!("worker task panicked: {}", panic_message(payload.as_ref()));
+            let _ = sender.send(if is_import {
+                WorkerMessage::ImportError(error)
+            } else {
+                WorkerMessage::GenerationError(error)
+            });
+        }
+    }
+}
 
-                    let candidates = generation::generator::generate_candidates_parallel(
-                        start_id,
-                        n,
-                        seed,
-                    );
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s
+    } else {
+        "unknown panic"
+    }
+}
 
-                    let _ = sender.send(WorkerMessage::GenerationComplete { candidates });
-                } else {
-                    let batch_size = 50;
-                    let mut candidates = Vec::with_capacity(n);
-                    let mut cancelled = false;
+/// Shortest gap between progress messages, so fast batches don't flood the channel (~30/s)
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(33);
 
-                    for batch_start in (0..n).step_by(batch_size) {
-                        if let Ok(WorkerMessage::CancelGeneration) = receiver.try_recv() {
-                            cancelled = true;
-                            break;
-                        }
+/// Candidates per step of a parallel generation, large enough to keep every core busy
+/// while still checking for a cancel request every fraction of a second
+const PARALLEL_BATCH: usize = 2048;
 
-                        let batch_end = (batch_start + batch_size).min(n);
-                        let batch_count = batch_end - batch_start;
+/// What a generation produces and in how many steps
+#[derive(Clone, Copy, Debug)]
+struct GenerationBatches {
+    n: usize,
+    seed: u64,
+    start_id: usize,
+    batch_size: usize,  // sequential only; parallel steps are `PARALLEL_BATCH`
+    parallel: bool,
+}
 
-                        let batch_candidates = generation::generator::generate_candidates(
-                            start_id + batch_start,
-                            batch_count,
-                            seed + batch_start as u64,
-                        );
+/// Generate in batches, reporting progress at most every `PROGRESS_INTERVAL`. A cancel
+/// request stops after the current batch; the candidates so far are returned with `true`.
+fn generate_in_batches(
+    batches: GenerationBatches,
+    options: &GenerationOptions,
+    receiver: &Receiver<WorkerMessage>,
+    sender: &Sender<WorkerMessage>,
+) -> (Vec<Candidate>, bool) {
+    let GenerationBatches { n, seed, start_id, batch_size, parallel } = batches;
+    let step = if parallel { PARALLEL_BATCH } else { batch_size.max(1) };
+    let mut candidates = Vec::with_capacity(n);
+    let mut last_progress: Option<Instant> = None;
 
-                        candidates.extend(batch_candidates);
+    if parallel {
+        let _ = sender.send(WorkerMessage::GenerationProgress { current: 0, total: n });
+        last_progress = Some(Instant::now());
+    }
 
-                        let _ = sender.send(WorkerMessage::GenerationProgress {
-                            current: batch_end,
-                            total: n,
-                        });
+    for batch_start in (0..n).step_by(step) {
+        let batch_end = (batch_start + step).min(n);
 
-                        std::thread::sleep(std::time::Duration::from_millis(2));
-                    }
+        // Seeded per candidate, so the batch size only changes how often progress is reported
+        let batch_candidates = if parallel {
+            generation::generator::generate_candidates_parallel(start_id, batch_start..batch_end, seed, options)
+        } else {
+            generation::generator::generate_candidates(start_id, batch_start..batch_end, seed, options)
+        };
 
-                    if !cancelled {
-                        let _ = sender.send(WorkerMessage::GenerationComplete { candidates });
-                    } else {
-                        let _ = sender.send(WorkerMessage::GenerationError("Cancelled".into()));
-                    }
+        candidates.extend(batch_candidates);
+
+        if last_progress.is_none_or(|t| t.elapsed() >= PROGRESS_INTERVAL) {
+            let _ = sender.send(WorkerMessage::GenerationProgress {
+                current: batch_end,
+                total: n,
+            });
+            last_progress = Some(Instant::now());
+        }
+
+        if batch_end < n && matches!(receiver.try_recv(), Ok(WorkerMessage::CancelGeneration)) {
+            return (candidates, true);
+        }
+    }
+
+    (candidates, false)
+}
+
+/// Run `f` on a pool of `thread_count` threads, or on all cores for 0
+fn in_generation_pool<T: Send>(thread_count: usize, f: impl FnOnce() -> T + Send) -> Result<T, String> {
+    Ok(match generation::generator::generation_pool(thread_count)? {
+        Some(pool) => pool.install(f),
+        None => f(),
+    })
+}
+
+fn handle_worker_task(msg: WorkerMessage, receiver: &Receiver<WorkerMessage>, sender: &Sender<WorkerMessage>) {
+    match msg {
+        WorkerMessage::GenerateCandidates { n, seed, start_id, parallel, batch_size, options } => {
+            let batches = GenerationBatches { n, seed, start_id, batch_size, parallel };
+            let threads = if parallel { options.thread_count } else { 0 };
+            let _ = sender.send(match in_generation_pool(threads, || generate_in_batches(batches, &options, receiver, sender)) {
+                Ok((candidates, cancelled)) => WorkerMessage::GenerationComplete { candidates, cancelled },
+                Err(e) => WorkerMessage::GenerationError(e),
+            });
+        }
+        WorkerMessage::ImportFile { path, start_id } => {
+            let result = super::io::import_smiles_file_streaming(&path, start_id, |imported| {
+                let _ = sender.send(WorkerMessage::ImportProgress { imported });
+            });
+            let _ = sender.send(match result {
+                Ok(report) => WorkerMessage::ImportComplete { report },
+                Err(e) => WorkerMessage::ImportError(e),
+            });
+        }
+        WorkerMessage::SeedSweep { base_seed, k, n, options, weights, objectives } => {
+            let sweep = || {
+                let mut runs = Vec::with_capacity(k);
+                for i in 0..k {
+                    let seed = base_seed + i as u64;
+                    let candidates = generation::generator::generate_candidates_parallel(0, 0..n, seed, &options);
+                    runs.push(optimization::sweep::seed_run_stats(seed, &candidates, weights, objectives));
+                    let _ = sender.send(WorkerMessage::SeedSweepProgress { done: i + 1, k });
                 }
+                runs
+            };
+            let _ = sender.send(match in_generation_pool(options.thread_count, sweep) {
+                Ok(runs) => WorkerMessage::SeedSweepComplete { runs },
+                Err(e) => WorkerMessage::GenerationError(e),
+            });
+        }
+        WorkerMessage::CancelGeneration => {}
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_log_bounded() {
+        let mut state = AppState::default();
+        for i in 0..(MAX_STATUS_LOG + 25) {
+            state.push_status(LogLevel::Info, format!("message {}", i));
+        }
+        assert_eq!(state.status_log.len(), MAX_STATUS_LOG);
+        assert_eq!(state.status_log.front().unwrap().1, "message 25");
+        assert_eq!(state.status, format!("message {}", MAX_STATUS_LOG + 24));
+    }
+
+    #[test]
+    fn test_import_reports_invalid_smiles() {
+        let mut state = AppState::default();
+        state.import_from_text("CCO\nC1CC\nc1ccccc1\n)(");
+        assert_eq!(state.candidates.len(), 2);
+        assert_eq!(state.status, "Imported 2 candidates, rejected 2 invalid (see Import issues)");
+        let rejected: Vec<&str> = state.import_issues.iter().map(|(s, _)| s.as_str()).collect();
+        assert_eq!(rejected, ["C1CC", ")("]);
+
+        // The next import replaces the report
+        state.import_from_text("CCN");
+        assert!(state.import_issues.is_empty());
+    }
+
+    #[test]
+    fn test_import_skips_duplicates() {
+        let mut state = AppState::default();
+        state.import_from_text("c1ccccc1");
+        state.import_from_text("CCO\nOCC\nc1ccccc1\nCCN");
+        assert_eq!(state.candidates.len(), 3);
+        assert_eq!(state.status, "Imported 2, skipped 2 duplicates");
+        let ids: Vec<usize> = state.candidates.iter().map(|c| c.id).collect();
+        assert_eq!(ids, [0, 1, 2]);
+        assert_eq!(state.next_id, 3);
+
+        state.import_dedup = false;
+        state.import_from_text("CCO\nCCO");
+        assert_eq!(state.candidates.len(), 5);
+    }
+
+    #[test]
+    fn test_safe_log10() {
+        assert_eq!(safe_log10(1.0), 0.0);
+        assert!((safe_log10(100.0) - 2.0).abs() < 1e-6);
+        assert!((safe_log10(0.01) + 2.0).abs() < 1e-6);
+        // Zero, negatives and tiny values floor instead of going to -inf or NaN
+        let floor = LOG_FLOOR.log10();
+        assert_eq!(safe_log10(0.0), floor);
+        assert_eq!(safe_log10(-5.0), floor);
+        assert_eq!(safe_log10(1e-9), floor);
+        assert!(safe_log10(f32::MIN_POSITIVE).is_finite());
+    }
+
+    #[test]
+    fn test_axis_value() {
+        let c = Candidate {
+            smiles: "CCO".into(),
+            efficacy: 0.9,
+            toxicity: 0.2,
+            synthesis_cost: 0.3,
+            manufacturing_cost: 0.4,
+            ..Default::default()
+        };
+        assert_eq!(axis_value(&c, AxisVar::Efficacy), 0.9);
+        assert_eq!(axis_value(&c, AxisVar::Toxicity), 0.2);
+        assert_eq!(axis_value(&c, AxisVar::SynthesisCost), 0.3);
+        assert_eq!(axis_value(&c, AxisVar::ManufacturingCost), 0.4);
+        assert!((axis_value(&c, AxisVar::MolecularWeight) - 46.07).abs() < 0.1);
+        assert!(axis_value(&c, AxisVar::LogP) < 0.5);
+        assert!((axis_value(&c, AxisVar::Psa) - 20.23).abs() < 0.1);
+        let qed = axis_value(&c, AxisVar::Qed);
+        assert!(qed > 0.0 && qed < 1.0);
+
+        // Cached values match direct computation for every variant
+        let mut state = AppState {
+            candidates: vec![c.clone()],
+            scatter_x: AxisVar::Qed,
+            ..Default::default()
+        };
+        state.refresh_axis_cache();
+        assert!(state.axis_cache.contains_key("CCO"));
+        for var in AxisVar::ALL {
+            assert!((state.axis_value(&c, var) - axis_value(&c, var)).abs() < 1e-6, "{:?}", var);
+        }
+    }
+
+    #[test]
+    fn test_property_filters() {
+        let values = PropertyValues { molecular_weight: 500.0, logp: -2.0, rotatable_bonds: 10 };
+        let mut filters = PropertyFilters::default();
+        assert!(passes_property_filters(&PropertyValues { molecular_weight: 900.0, ..values }, &filters));
+
+        // Bounds are inclusive at both ends
+        filters.enabled = true;
+        assert!(passes_property_filters(&values, &filters));
+        assert!(!passes_property_filters(&PropertyValues { molecular_weight: 500.5, ..values }, &filters));
+        assert!(!passes_property_filters(&PropertyValues { logp: -2.5, ..values }, &filters));
+        assert!(!passes_property_filters(&PropertyValues { rotatable_bonds: 11, ..values }, &filters));
+        filters.rotatable_bonds = (10, 10);
+        assert!(passes_property_filters(&values, &filters));
+        filters.rotatable_bonds = (11, 20);
+        assert!(!passes_property_filters(&values, &filters));
+
+        // Wired into filtered_candidates through the cache
+        let mut state = AppState::default();
+        state.import_from_text("CCO\nCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCC");
+        state.property_filters.enabled = true;
+        state.refresh_property_cache();
+        assert_eq!(state.property_cache.len(), 2);
+        let kept: Vec<&str> = state.filtered_candidates().iter().map(|c| c.smiles.as_str()).collect();
+        assert_eq!(kept, ["CCO"]);
+    }
+
+    #[test]
+    fn test_should_autosave() {
+        assert!(!should_autosave(Duration::from_secs(59), 60));
+        assert!(should_autosave(Duration::from_secs(60), 60));
+        assert!(should_autosave(Duration::from_secs(600), 60));
+
+        // Interval 0 turns autosave off
+        assert!(!should_autosave(Duration::from_secs(600), 0));
+
+        // A failed background write is reported in the status log
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        sender.send(Err("Write error: disk full".to_string())).unwrap();
+        let mut state = AppState { autosave_interval_secs: 0, autosave_result: Some(receiver), ..Default::default() };
+        state.autosave_if_due();
+        assert!(state.autosave_result.is_none());
+        assert_eq!(state.status_log.back().unwrap().2, LogLevel::Warning);
+        assert_eq!(state.status, "Autosave failed: Write error: disk full");
+    }
+
+    #[test]
+    fn test_active_objectives() {
+        let mut state = AppState {
+            candidates: vec![
+                Candidate { id: 0, efficacy: 0.9, toxicity: 0.2, synthesis_cost: 0.1, manufacturing_cost: 0.1, ..Default::default() },
+                Candidate { id: 1, efficacy: 0.9, toxicity: 0.1, synthesis_cost: 0.8, manufacturing_cost: 0.8, ..Default::default() },
+            ],
+            ..Default::default()
+        };
+        state.recompute_pareto();
+        assert!(state.candidates.iter().all(|c| c.pareto));
+        let full_score = state.weighted_score(&state.candidates[1]);
+
+        // Dropping the costs takes the cheap candidate off the front and out of the score
+        state.active_objectives = [true, true, false, false];
+        state.recompute_pareto();
+        let front: Vec<usize> = state.candidates.iter().filter(|c| c.pareto).map(|c| c.id).collect();
+        assert_eq!(front, vec![1]);
+        assert!((state.weighted_score(&state.candidates[1]) - (0.9 - 0.1)).abs() < 1e-6);
+        assert!(state.weighted_score(&state.candidates[1]) > full_score);
+        assert_eq!(state.score_breakdown(&state.candidates[1])[2], ("Syn", 0.0));
+    }
+
+    #[test]
+    fn test_baseline_improvements() {
+        let at = |id, efficacy, toxicity| Candidate { id, efficacy, toxicity, ..Default::default() };
+        let mut state = AppState { candidates: vec![at(0, 0.6, 0.4), at(1, 0.4, 0.2)], ..Default::default() };
+        state.recompute_pareto();
+        state.freeze_baseline();
+        assert_eq!(state.baseline_front.as_ref().map(Vec::len), Some(2));
+
+        state.candidates.extend([at(2, 0.7, 0.3), at(3, 0.9, 0.9)]);
+        state.recompute_pareto();
+        state.refresh_baseline_improvements();
+        assert!(state.is_baseline_improvement(2));
+        assert!(!state.is_baseline_improvement(3) && !state.is_baseline_improvement(0));
+
+        state.clear_baseline();
+        state.refresh_baseline_improvements();
+        assert!(!state.is_baseline_improvement(2));
+    }
+
+    #[test]
+    fn test_undo_weight_change() {
+        let mut state = AppState::default();
+        state.record_setting_changes();
+
+        // A drag passes through many values but records only where it settled
+        for step in 1..=10 {
+            state.w_tox = 1.0 + step as f32 * 0.2;
+        }
+        state.record_setting_changes();
+        state.record_setting_changes();
+        assert_eq!(state.history.undo_count(), 1);
+
+        state.undo();
+        assert_eq!([state.w_eff, state.w_tox, state.w_syn, state.w_mfg], [1.0, 1.0, 1.0, 1.0]);
+        // Restoring is not itself recorded
+        state.record_setting_changes();
+        assert_eq!((state.history.undo_count(), state.history.redo_count()), (0, 1));
+
+        state.redo();
+        assert_eq!(state.w_tox, 3.0);
+        state.record_setting_changes();
+        assert_eq!((state.history.undo_count(), state.history.redo_count()), (1, 0));
+    }
+
+    #[test]
+    fn test_undo_filter_change() {
+        let mut state = AppState {
+            candidates: vec![
+                Candidate { id: 0, smiles: "c1ccccc1O".into(), ..Default::default() },
+                Candidate { id: 1, smiles: "CCO".into(), ..Default::default() },
+            ],
+            ..Default::default()
+        };
+        state.record_setting_changes();
+        let original = state.filters();
+
+        state.filter_pareto_only = true;
+        state.filter_tox_max = 0.4;
+        state.filter_substructure = "c1ccccc1".into();
+        state.update_substructure_filter();
+        state.record_setting_changes();
+        let edited = state.filters();
+        assert_eq!(state.history.last_action_description().as_deref(), Some("Filter change"));
+
+        state.undo();
+        assert_eq!(state.filters(), original);
+        assert!(state.substructure_matches.is_none());
+
+        state.redo();
+        assert_eq!(state.filters(), edited);
+        assert_eq!(state.substructure_matches, Some(HashSet::from([0])));
+    }
+
+    #[test]
+    fn test_fmt_val() {
+        let mut state = AppState::default();
+        assert_eq!(state.fmt_val(0.123456), "0.123");
+        state.display_decimals = 5;
+        assert_eq!(state.fmt_val(0.123456), "0.12346");
+        assert_eq!(state.fmt_val(-1.5), "-1.50000");
+        state.display_decimals = *DISPLAY_DECIMALS_RANGE.start();
+        assert_eq!(state.fmt_val(2.0 / 3.0), "0.67");
+    }
+
+    #[test]
+    fn test_score_breakdown_sums_to_score() {
+        let c = Candidate { efficacy: 0.75, toxicity: 0.25, synthesis_cost: 0.5, manufacturing_cost: 0.125, ..Default::default() };
+        let mut state = AppState { w_eff: 2.0, w_tox: 1.5, w_syn: 0.5, w_mfg: 1.0, ..Default::default() };
+        for normalize in [false, true] {
+            state.normalize_objectives = normalize;
+            let parts = state.score_breakdown(&c);
+            let total: f32 = parts.iter().map(|(_, v)| v).sum();
+            assert!((total - state.weighted_score(&c)).abs() < 1e-6);
+        }
+
+        state.normalize_objectives = false;
+        let parts = state.score_breakdown(&c);
+        assert_eq!(parts[0], ("Eff", 1.5));
+        assert_eq!(parts[1], ("Tox", -0.375));
+        assert!(parts[1..].iter().all(|(_, v)| *v <= 0.0));
+    }
+
+    #[test]
+    fn test_clear_keeps_locked() {
+        let mut state = AppState::default();
+        state.import_from_text("CCO\nc1ccccc1\nCCN\nCCCl");
+        state.toggle_lock(1);
+        state.toggle_lock(3);
+
+        state.clear();
+        let ids: Vec<usize> = state.candidates.iter().map(|c| c.id).collect();
+        assert_eq!(ids, [1, 3]);
+        assert_eq!(state.next_id, 4);
+
+        // Locked candidates ignore filters while pinned
+        state.filter_eff_min = 1.0;
+        state.filter_eff_max = 1.0;
+        assert_eq!(state.filtered_candidates().len(), 2);
+        state.pin_locked = false;
+        assert!(state.filtered_candidates().len() < 2);
+
+        state.undo();
+        let ids: Vec<usize> = state.candidates.iter().map(|c| c.id).collect();
+        assert_eq!(ids, [0, 1, 2, 3]);
+
+        state.redo();
+        assert_eq!(state.candidates.len(), 2);
+    }
+
+    #[test]
+    fn test_toggle_favorites_bulk_undo() {
+        let mut state = AppState::default();
+        state.toggle_favorite(2);
+        state.toggle_favorites(&[1, 2, 3]);
+        assert!([1, 2, 3].iter().all(|&id| state.annotations.is_favorite(id)));
+
+        // One undo step restores the previous mix
+        state.undo();
+        assert!(!state.annotations.is_favorite(1));
+        assert!(state.annotations.is_favorite(2));
+        assert!(!state.annotations.is_favorite(3));
+
+        state.redo();
+        state.toggle_favorites(&[1, 2, 3]);
+        assert_eq!(state.annotations.favorite_count(), 0);
+    }
+
+    fn wait_for_generation(state: &mut AppState) {
+        let start = std::time::Instant::now();
+        while state.is_generating {
+            assert!(start.elapsed().as_secs() < 30, "generation timed out");
+            state.process_worker_messages();
+            thread::sleep(std::time::Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_worker_disconnect_resets_generation() {
+        let (dead_sender, dead_receiver) = unbounded();
+        drop(dead_sender);
+        let mut state = AppState {
+            worker_receiver: Some(dead_receiver),
+            ..Default::default()
+        };
+        state.generate();
+        assert!(state.is_generating);
+
+        state.process_worker_messages();
+        assert!(!state.is_generating);
+        assert!(state.pending_run.is_none());
+        assert!(state.worker_sender.is_none());
+        assert_eq!(state.status_log.back().unwrap().2, LogLevel::Error);
+
+        // Later attempts report the problem instead of hanging
+        state.generate();
+        assert!(!state.is_generating);
+        assert_eq!(state.status, "Generation worker is not running");
+    }
+
+    #[test]
+    fn test_cancel_keeps_partial_batch() {
+        let (to_worker, worker_inbox) = unbounded();
+        let (worker_outbox, from_worker) = unbounded();
+        // The cancel is already queued when the first batch finishes
+        to_worker.send(WorkerMessage::CancelGeneration).unwrap();
+        handle_worker_task(
+            WorkerMessage::GenerateCandidates {
+                n: 200, seed: 42, start_id: 10, parallel: false, batch_size: 50, options: GenerationOptions::default(),
+            },
+            &worker_inbox,
+            &worker_outbox,
+        );
+
+        let mut state = AppState {
+            worker_receiver: Some(from_worker),
+            is_generating: true,
+            generation_progress: Some((0, 200)),
+            next_id: 10,
+            pending_run: Some(GenerationRun {
+                run_id: 0, seed: 42, n: 200, parallel: false, used_scaffolds: true, category: None,
+                timestamp: Local::now(), candidate_ids: Vec::new(),
+            }),
+            ..Default::default()
+        };
+        state.process_worker_messages();
+        assert!(!state.is_generating);
+        assert!(state.pending_run.is_none());
+        assert_eq!(state.candidates.len(), 50);
+        // The run records the partial batch it actually produced
+        assert_eq!((state.runs[0].n, state.runs[0].candidate_ids.len()), (50, 50));
+        assert_eq!(state.candidates[0].id, 10);
+        assert_eq!(state.next_id, 60);
+        let (_, message, level) = state.status_log.back().unwrap();
+        assert_eq!(*level, LogLevel::Warning);
+        assert!(message.starts_with("Cancelled after 50/200"), "{}", message);
+
+        // The parallel path stops between its larger batches too
+        let (to_worker, worker_inbox) = unbounded();
+        let (worker_outbox, from_worker) = unbounded();
+        to_worker.send(WorkerMessage::CancelGeneration).unwrap();
+        let n = PARALLEL_BATCH + 100;
+        handle_worker_task(
+            WorkerMessage::GenerateCandidates {
+                n, seed: 42, start_id: 0, parallel: true, batch_size: 50, options: GenerationOptions::default(),
+            },
+            &worker_inbox,
+            &worker_outbox,
+        );
+        let complete = from_worker.try_iter().find_map(|m| match m {
+            WorkerMessage::GenerationComplete { candidates, cancelled } => Some((candidates.len(), cancelled)),
+            _ => None,
+        });
+        assert_eq!(complete, Some((PARALLEL_BATCH, true)));
+    }
+
+    #[test]
+    fn test_drop_rejected_while_generating() {
+        let path = std::env::temp_dir().join(format!("dcs_drop_{}.smi", std::process::id()));
+        std::fs::write(&path, "CCO\nc1ccccc1\n").unwrap();
+
+        let mut state = AppState { is_generating: true, next_id: 10, ..Default::default() };
+        state.import_dropped_file(&path);
+        assert!(state.candidates.is_empty());
+        assert_eq!(state.status_log.back().unwrap().2, LogLevel::Warning);
+
+        state.is_generating = false;
+        state.sweep_progress = Some((1, 5));
+        state.import_dropped_file(&path);
+        assert!(state.candidates.is_empty());
+
+        state.sweep_progress = None;
+        state.import_dropped_file(&path);
+        assert_eq!(state.candidates.len(), 2);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_sequential_batch_sizes() {
+        let (_to_worker, worker_inbox) = unbounded();
+        let (worker_outbox, from_worker) = unbounded();
+        // The same seed gives the same molecules at every batch size, and matches the parallel path
+        let expected: Vec<String> = generation::generator::generate_candidates_parallel(5, 0..123, 42, &GenerationOptions::default())
+            .into_iter()
+            .map(|c| c.smiles)
+            .collect();
+        for batch_size in [0, 1, 7, 50, 123, 1000] {
+            let batches = GenerationBatches { n: 123, seed: 42, start_id: 5, batch_size, parallel: false };
+            let (candidates, cancelled) = generate_in_batches(batches, &GenerationOptions::default(), &worker_inbox, &worker_outbox);
+            assert!(!cancelled);
+            assert_eq!(candidates.len(), 123, "batch size {}", batch_size);
+            assert!(candidates.iter().enumerate().all(|(i, c)| c.id == 5 + i));
+            assert!(candidates.iter().map(|c| &c.smiles).eq(&expected), "batch size {}", batch_size);
+
+            // Progress is throttled, so one-candidate batches don't send a message each
+            let progress = from_worker.try_iter()
+                .filter(|m| matches!(m, WorkerMessage::GenerationProgress { .. }))
+                .count();
+            assert!(progress >= 1);
+            if batch_size == 1 {
+                assert!(progress < 123);
             }
-            WorkerMessage::CancelGeneration => {}
-            _ => {}
         }
     }
+
+    #[test]
+    fn test_evolve_records_parents() {
+        let mut state = AppState {
+            candidates: vec![
+                Candidate { id: 0, smiles: "CC(=O)Oc1ccccc1C(=O)O".into(), efficacy: 0.9, toxicity: 0.5, ..Default::default() },
+                Candidate { id: 1, smiles: "c1ccccc1O".into(), efficacy: 0.4, toxicity: 0.1, ..Default::default() },
+                Candidate { id: 2, smiles: "CCN".into(), efficacy: 0.2, toxicity: 0.9, ..Default::default() },
+            ],
+            next_id: 3,
+            n_generate: 10,
+            ..Default::default()
+        };
+        state.recompute_pareto();
+        state.evolve();
+        let children = &state.candidates[3..];
+        assert!(!children.is_empty());
+        assert_eq!(state.next_id, 3 + children.len());
+        // Only Pareto members (0 and 1) are parents
+        assert!(children.iter().all(|c| !c.parent_ids.is_empty() && c.parent_ids.iter().all(|&p| p < 2)));
+
+        state.undo();
+        assert_eq!(state.candidates.len(), 3);
+    }
+
+    #[test]
+    fn test_generation_runs_recorded() {
+        let mut state = AppState { n_generate: 20, ..Default::default() };
+        state.generate();
+        wait_for_generation(&mut state);
+        state.seed = 7;
+        state.generate();
+        wait_for_generation(&mut state);
+
+        assert_eq!(state.runs.len(), 2);
+        let (first, second) = (&state.runs[0], &state.runs[1]);
+        assert_eq!((first.run_id, second.run_id), (0, 1));
+        assert_eq!((first.seed, second.seed), (42, 7));
+        assert_eq!(first.candidate_ids.len(), 20);
+        assert!(first.candidate_ids.iter().all(|id| !second.candidate_ids.contains(id)));
+        assert!(state.candidates.iter().all(|c| c.run_id.is_some()));
+
+        state.filter_run = Some(1);
+        let filtered: Vec<usize> = state.filtered_candidates().iter().map(|c| c.id).collect();
+        assert_eq!(filtered, second.candidate_ids);
+
+        // Undoing a generation removes its run entry (and the filter on it); redo restores it
+        state.undo();
+        assert_eq!(state.runs.len(), 1);
+        assert_eq!(state.filter_run, None);
+        state.redo();
+        assert_eq!(state.runs.iter().map(|r| r.run_id).collect::<Vec<_>>(), [0, 1]);
+    }
+
+    #[test]
+    fn test_latest_batch_filter() {
+        let run = |run_id: usize, candidate_ids: Vec<usize>| GenerationRun {
+            run_id, seed: 42, n: candidate_ids.len(), parallel: false, used_scaffolds: true,
+            category: None, timestamp: Local::now(), candidate_ids,
+        };
+        let candidate = |id: usize, run_id: Option<usize>, pareto: bool| Candidate { id, run_id, pareto, ..Default::default() };
+        let mut state = AppState {
+            candidates: vec![
+                candidate(0, Some(0), true),
+                candidate(1, Some(0), false),
+                candidate(2, Some(1), true),
+                candidate(3, Some(1), false),
+                candidate(4, None, true),
+            ],
+            runs: vec![run(0, vec![0, 1]), run(1, vec![2, 3])],
+            filter_latest_batch: true,
+            ..Default::default()
+        };
+        let ids = |state: &AppState| state.filtered_candidates().iter().map(|c| c.id).collect::<Vec<_>>();
+        assert_eq!(ids(&state), state.runs[1].candidate_ids);
+
+        // Combines with the other filters
+        state.filter_pareto_only = true;
+        assert_eq!(ids(&state), vec![2]);
+
+        state.filter_latest_batch = false;
+        assert_eq!(ids(&state), vec![0, 2, 4]);
+    }
+
+    #[test]
+    fn test_gen_source_serde() {
+        for source in [GenSource::Scaffold("Aspirin"), GenSource::Hybrid, GenSource::Random, GenSource::Mutation, GenSource::Crossover, GenSource::Imported] {
+            let json = serde_json::to_string(&source).unwrap();
+            assert_eq!(serde_json::from_str::<GenSource>(&json).unwrap(), source);
+        }
+        // Unknown scaffold names can't be interned
+        let unknown: GenSource = serde_json::from_str(r#"{"Scaffold":"Nope"}"#).unwrap();
+        assert_eq!(unknown, GenSource::Imported);
+    }
+
+    #[test]
+    fn test_add_custom_scaffold() {
+        let mut state = AppState::default();
+        let scaffold = |name: &str, smiles: &str| OwnedScaffold {
+            name: name.into(),
+            smiles: smiles.into(),
+            category: String::new(),
+        };
+
+        assert!(state.add_custom_scaffold(scaffold("Lead-1", "c1ccc(cc1)C(=O)N")).is_ok());
+        assert_eq!(state.custom_scaffolds[0].category, "Custom");
+        assert!(state.add_custom_scaffold(scaffold("lead-1", "CCO")).is_err());
+        assert!(state.add_custom_scaffold(scaffold("Aspirin", "CCO")).is_err());
+        assert!(state.add_custom_scaffold(scaffold("Broken", "C1CC")).is_err());
+        // Ring labels pair up, but the first closure duplicates the bond between its atoms
+        assert!(state.add_custom_scaffold(scaffold("Reused", "c1(c1ccncc1)ccccc1")).is_err());
+        assert!(state.add_custom_scaffold(scaffold("", "CCO")).is_err());
+        assert_eq!(state.custom_scaffolds.len(), 1);
+    }
+
+    #[test]
+    fn test_formula_filter() {
+        let mut state = AppState {
+            candidates: vec![
+                Candidate { id: 0, smiles: "c1ccccc1".into(), ..Default::default() },
+                Candidate { id: 1, smiles: "c1ccncc1".into(), ..Default::default() },
+                Candidate { id: 2, smiles: "CCO".into(), ..Default::default() },
+            ],
+            ..Default::default()
+        };
+        let ids = |state: &AppState| state.filtered_candidates().iter().map(|c| c.id).collect::<Vec<_>>();
+
+        // Computed on the fly before the cache is filled, then from the cache
+        state.filter_formula = "C5".into();
+        assert_eq!(ids(&state), vec![0, 1]);
+        state.refresh_formula_cache();
+        assert_eq!(state.formula_cache.get("CCO").map(String::as_str), Some("C2H6O"));
+        assert_eq!(ids(&state), vec![0, 1]);
+
+        state.filter_formula = "C5N".into();
+        assert_eq!(ids(&state), vec![1]);
+        state.filter_formula = "C2H6O".into();
+        assert_eq!(ids(&state), vec![2]);
+        state.filter_formula.clear();
+        assert_eq!(ids(&state), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_substructure_filter() {
+        let mut state = AppState {
+            candidates: vec![
+                Candidate { id: 0, smiles: "CC(=O)Oc1ccccc1C(=O)O".into(), ..Default::default() },
+                Candidate { id: 1, smiles: "c1ccccc1".into(), ..Default::default() },
+                Candidate { id: 2, smiles: "ClCCN".into(), ..Default::default() },
+            ],
+            ..Default::default()
+        };
+        let preset = |name: &str| SUBSTRUCTURE_PRESETS.iter().find(|(n, _)| *n == name).unwrap().1.to_string();
+        let ids = |state: &AppState| state.filtered_candidates().iter().map(|c| c.id).collect::<Vec<_>>();
+
+        state.filter_substructure = preset("Carboxyl");
+        state.update_substructure_filter();
+        assert_eq!(ids(&state), vec![0]);
+
+        state.filter_substructure = preset("Halogen");
+        state.update_substructure_filter();
+        assert_eq!(ids(&state), vec![2]);
+
+        state.filter_substructure = preset("Aromatic ring");
+        state.update_substructure_filter();
+        assert_eq!(ids(&state), vec![0, 1]);
+
+        state.filter_substructure = "C1CC".into();
+        state.update_substructure_filter();
+        assert!(ids(&state).is_empty());
+
+        state.filter_substructure.clear();
+        state.update_substructure_filter();
+        assert_eq!(ids(&state).len(), 3);
+    }
+
+    #[test]
+    fn test_group_by_scaffold() {
+        let candidates = vec![
+            Candidate { id: 0, smiles: "CC(C)Cc1ccc(cc1)C(C)C(=O)O".into(), efficacy: 0.4, ..Default::default() },
+            Candidate { id: 1, smiles: "Cc1ccccc1".into(), efficacy: 0.8, ..Default::default() },
+            Candidate { id: 2, smiles: "C1CCNCC1".into(), ..Default::default() },
+        ];
+        let groups = group_by_scaffold(&candidates);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].ids, vec![0, 1]);
+        assert!((groups[0].mean[0] - 0.6).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_coverage_grid() {
+        let at = |id, efficacy, toxicity| Candidate { id, efficacy, toxicity, ..Default::default() };
+
+        // A tight cluster fills a single cell
+        let cluster: Vec<Candidate> = (0..50).map(|i| at(i, 0.52 + i as f32 * 1e-3, 0.31)).collect();
+        let refs: Vec<&Candidate> = cluster.iter().collect();
+        assert_eq!(coverage_grid(&refs, COVERAGE_BINS), 0.01);
+
+        // One candidate at the centre of every cell covers the whole grid
+        let uniform: Vec<Candidate> = (0..100)
+            .map(|i| at(i, (i / 10) as f32 / 10.0 + 0.05, (i % 10) as f32 / 10.0 + 0.05))
+            .collect();
+        let refs: Vec<&Candidate> = uniform.iter().collect();
+        assert_eq!(coverage_grid(&refs, COVERAGE_BINS), 1.0);
+        assert!(coverage_occupancy(&refs, COVERAGE_BINS).iter().all(|&n| n == 1));
+
+        // Boundary values stay on the grid
+        let edges = [at(0, 1.0, 0.0), at(1, -0.5, 2.0)];
+        let refs: Vec<&Candidate> = edges.iter().collect();
+        let counts = coverage_occupancy(&refs, 4);
+        assert_eq!((counts[3 * 4], counts[3]), (1, 1));
+        assert_eq!(coverage_grid(&[], COVERAGE_BINS), 0.0);
+    }
+
+    #[test]
+    fn test_objective_stats() {
+        let candidates = [
+            Candidate { id: 0, efficacy: 0.8, toxicity: 0.2, synthesis_cost: 0.5, manufacturing_cost: 0.1, ..Default::default() },
+            Candidate { id: 1, efficacy: 0.4, toxicity: 0.6, synthesis_cost: 0.3, manufacturing_cost: 0.1, ..Default::default() },
+            Candidate { id: 2, efficacy: 0.6, toxicity: 0.9, synthesis_cost: 0.7, manufacturing_cost: 0.2, ..Default::default() },
+        ];
+        let refs: Vec<&Candidate> = candidates.iter().collect();
+        let stats = objective_stats(&refs);
+        assert_eq!(stats.count, 3);
+        assert_eq!(stats.min, [0.4, 0.2, 0.3, 0.1]);
+        assert_eq!(stats.max, [0.8, 0.9, 0.7, 0.2]);
+        assert_eq!(stats.score, None);
+        assert_eq!(objective_stats(&[]), ObjectiveStats::default());
+
+        // The state fills the score range and follows the rows it is given
+        let state = AppState {
+            candidates: candidates.to_vec(),
+            w_eff: 1.0, w_tox: 1.0, w_syn: 0.0, w_mfg: 0.0,
+            ..Default::default()
+        };
+        let stats = state.table_objective_stats(&candidates);
+        let (lo, hi) = stats.score.unwrap();
+        assert!((lo + 0.3).abs() < 1e-6 && (hi - 0.6).abs() < 1e-6, "{} {}", lo, hi);
+        let stats = state.table_objective_stats(&candidates[..1]);
+        assert_eq!((stats.count, stats.min[0], stats.max[0]), (1, 0.8, 0.8));
+        // Other rows of the same count, as after toggling a favorite under the favorites filter
+        let stats = state.table_objective_stats(&candidates[1..2]);
+        assert_eq!((stats.count, stats.min[0], stats.max[0]), (1, 0.4, 0.4));
+    }
+
+    #[test]
+    fn test_scaffold_diversity() {
+        let smiles = ["Cc1ccccc1", "CCc1ccccc1", "Oc1ccccc1", "C1CCNCC1", "CC1CCNCC1", "CCO"];
+        let mut state = AppState {
+            candidates: smiles.iter().enumerate()
+                .map(|(id, s)| Candidate { id, smiles: s.to_string(), ..Default::default() })
+                .collect(),
+            ..Default::default()
+        };
+        // Benzene, piperidine and the acyclic (empty) scaffold
+        let (count, ratio) = scaffold_diversity(&state.candidates);
+        assert_eq!(count, 3);
+        assert!((ratio - 0.5).abs() < 1e-6);
+        assert_eq!(state.cached_scaffold_diversity(), (count, ratio));
+        assert_eq!(state.scaffold_cache.len(), 6);
+        assert_eq!(scaffold_diversity(&[]), (0, 0.0));
+    }
+
+    #[test]
+    fn test_score_cache_invalidation() {
+        let mut state = AppState {
+            candidates: vec![
+                Candidate { id: 0, efficacy: 0.9, toxicity: 0.5, ..Default::default() },
+                Candidate { id: 1, efficacy: 0.3, toxicity: 0.1, ..Default::default() },
+            ],
+            ..Default::default()
+        };
+        state.recompute_pareto();
+        state.refresh_score_cache();
+        let before = state.cached_score(0);
+
+        state.w_eff = 3.0;
+        state.refresh_score_cache();
+        assert!(state.cached_score(0) > before);
+        for c in &state.candidates {
+            assert_eq!(state.cached_score(c.id), state.weighted_score(c));
+        }
+
+        // Candidate changes invalidate too
+        state.candidates[1].efficacy = 1.0;
+        state.recompute_pareto();
+        state.refresh_score_cache();
+        assert_eq!(state.cached_score(1), state.weighted_score(&state.candidates[1]));
+    }
+
+    #[test]
+    fn test_normalized_weighted_score() {
+        // Toxicity imported on a 0-100 scale swamps efficacy in raw scoring
+        let a = Candidate { id: 0, efficacy: 0.9, toxicity: 50.0, ..Default::default() };
+        let b = Candidate { id: 1, efficacy: 0.1, toxicity: 40.0, ..Default::default() };
+        let mut state = AppState {
+            candidates: vec![a.clone(), b.clone()],
+            w_eff: 2.0,
+            w_tox: 1.0,
+            w_syn: 0.0,
+            w_mfg: 0.0,
+            ..Default::default()
+        };
+        state.recompute_pareto();
+        assert!(state.weighted_score(&b) > state.weighted_score(&a));
+
+        // Normalized, the larger efficacy weight decides
+        state.normalize_objectives = true;
+        assert_eq!(state.objective_values(&a), [1.0, 1.0, 0.0, 0.0]);
+        assert!((state.weighted_score(&a) - 1.0).abs() < 1e-6);
+        assert!(state.weighted_score(&b).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_tag_filter_and_or() {
+        let mut annotations = Annotations::new();
+        annotations.add_tag(0, "series-A");
+        annotations.add_tag(0, "toxic");
+        annotations.add_tag(1, "series-A");
+        annotations.add_tag(2, "toxic");
+        let ids = [0, 1, 2, 3];
+
+        let tags = vec!["series-A".to_string(), "toxic".to_string()];
+        let and: Vec<usize> = ids.iter().copied().filter(|&id| tag_filter_matches(&annotations, id, &tags, true)).collect();
+        let or: Vec<usize> = ids.iter().copied().filter(|&id| tag_filter_matches(&annotations, id, &tags, false)).collect();
+        assert_eq!(and, vec![0]);
+        assert_eq!(or, vec![0, 1, 2]);
+
+        // No selected tags: nothing filtered out
+        assert!(ids.iter().all(|&id| tag_filter_matches(&annotations, id, &[], true)));
+
+        // Same semantics through filtered_candidates
+        let mut state = AppState {
+            candidates: ids.iter().map(|&id| Candidate { id, ..Default::default() }).collect(),
+            annotations,
+            filter_tags: tags,
+            filter_tags_match_all: false,
+            ..Default::default()
+        };
+        assert_eq!(state.filtered_candidates().len(), 3);
+        state.filter_tags_match_all = true;
+        assert_eq!(state.filtered_candidates().len(), 1);
+
+        // A selected tag is dropped once no candidate carries it, without an undo step
+        state.record_setting_changes();
+        let tagged: Vec<usize> = ids.iter().copied().filter(|&id| state.annotations.has_tag(id, "toxic")).collect();
+        for &id in &tagged {
+            state.remove_tag(id, "toxic");
+        }
+        assert_eq!(state.filter_tags, ["series-A"]);
+        state.record_setting_changes();
+        state.undo();
+        assert!(state.annotations.has_tag(*tagged.last().unwrap(), "toxic"));
+        state.filter_tags.push("toxic".into());
+        state.record_setting_changes();
+
+        // Clearing the tagged candidates drops the tags too
+        state.clear();
+        assert!(state.filter_tags.is_empty());
+        state.undo();
+        assert_eq!(state.candidates.len(), ids.len());
+    }
+
+    #[test]
+    fn test_recompute_objectives() {
+        let mut state = AppState::default();
+        state.import_from_text("CCCCCC\nCC(=O)Oc1ccccc1C(=O)O");
+
+        let mut expected = state.candidates[0].clone();
+        optimization::objectives::compute_objectives(&mut expected);
+        let imported = state.candidates[0].efficacy;
+
+        state.recompute_objectives();
+        assert_eq!(state.candidates[0].efficacy, expected.efficacy);
+        assert_eq!(state.candidates[0].toxicity, expected.toxicity);
+        assert_ne!(state.candidates[0].efficacy, imported);
+
+        state.undo();
+        assert_eq!(state.candidates[0].efficacy, imported);
+        state.redo();
+        assert_eq!(state.candidates[0].efficacy, expected.efficacy);
+    }
+
+    #[test]
+    fn test_pareto_dirty_flag() {
+        let mut state = AppState {
+            candidates: vec![
+                Candidate { id: 0, efficacy: 0.9, ..Default::default() },
+                Candidate { id: 1, efficacy: 0.5, ..Default::default() },
+            ],
+            ..Default::default()
+        };
+        assert!(!state.recompute_pareto_if_dirty());
+        assert!(!state.candidates[0].pareto);
+
+        state.mark_pareto_dirty();
+        assert!(state.recompute_pareto_if_dirty());
+        assert!(!state.pareto_dirty);
+        assert!(state.candidates[0].pareto && !state.candidates[1].pareto);
+
+        // In-place objective recomputation defers the front update
+        state.recompute_objectives();
+        assert!(state.pareto_dirty);
+        assert!(state.recompute_pareto_if_dirty());
+        assert!(!state.recompute_pareto_if_dirty());
+    }
+
+    #[test]
+    fn test_hypervolume_monotone_in_reference() {
+        let mut state = AppState {
+            candidates: vec![
+                Candidate { id: 0, efficacy: 0.9, toxicity: 0.6, synthesis_cost: 0.2, manufacturing_cost: 0.5, ..Default::default() },
+                Candidate { id: 1, efficacy: 0.5, toxicity: 0.2, synthesis_cost: 0.4, manufacturing_cost: 0.25, ..Default::default() },
+                Candidate { id: 2, efficacy: 0.7, toxicity: 0.4, synthesis_cost: 0.75, manufacturing_cost: 0.1, ..Default::default() },
+            ],
+            ..Default::default()
+        };
+        state.recompute_pareto();
+
+        // Loosen the reference step by step: both volumes never shrink
+        let mut previous = Hypervolume::default();
+        for step in 0..=4 {
+            let t = 0.5 + 0.125 * step as f32;
+            state.hv_ref = [1.0 - t, t, t, t];
+            let hv = state.hypervolume();
+            assert!(hv.hv_2d >= previous.hv_2d && hv.hv_4d >= previous.hv_4d, "step {}: {:?} < {:?}", step, hv, previous);
+            previous = hv;
+        }
+        assert!(previous.hv_2d > 0.0 && previous.hv_4d > 0.0);
+        assert_eq!(previous.outside, 0);
+
+        // A tight box excludes front members and lowers the volume
+        state.hv_ref = [0.6, 0.5, 1.0, 1.0];
+        let tight = state.hypervolume();
+        assert_eq!(tight.outside, 2);
+        assert!(tight.hv_2d < previous.hv_2d);
+    }
+
+    #[test]
+    fn test_record_preference() {
+        let mut state = AppState {
+            candidates: vec![
+                Candidate { id: 0, efficacy: 0.5, toxicity: 0.1, synthesis_cost: 0.5, manufacturing_cost: 0.5, ..Default::default() },
+                Candidate { id: 1, efficacy: 0.9, toxicity: 0.8, synthesis_cost: 0.5, manufacturing_cost: 0.5, ..Default::default() },
+            ],
+            ..Default::default()
+        };
+        state.recompute_pareto();
+        state.next_preference_pair();
+        let (a, b) = state.preference_pair.expect("two Pareto candidates");
+        assert_ne!(a, b);
+
+        state.record_preference(0, 1);
+        assert_eq!(state.preference_choices.len(), 1);
+        assert!(state.w_tox > 1.0 && state.w_eff < 1.0);
+        assert!(state.preference_pair.is_some());
+
+        // Unknown ids are ignored
+        state.record_preference(0, 99);
+        assert_eq!(state.preference_choices.len(), 1);
+    }
+
+    #[test]
+    fn test_weight_presets() {
+        let expected = [
+            (WeightPreset::Balanced, [1.0, 1.0, 1.0, 1.0]),
+            (WeightPreset::PotencyFirst, [3.0, 1.0, 0.5, 0.5]),
+            (WeightPreset::SafetyFirst, [1.0, 3.0, 0.5, 0.5]),
+            (WeightPreset::CheapToMake, [1.0, 1.0, 2.5, 2.5]),
+        ];
+        for (preset, weights) in expected {
+            let mut state = AppState::default();
+            state.apply_weight_preset(preset);
+            assert_eq!([state.w_eff, state.w_tox, state.w_syn, state.w_mfg], weights, "{:?}", preset);
+            assert_eq!(state.weight_preset, Some(preset));
+        }
+
+        let mut state = AppState::default();
+        state.apply_weight_preset(WeightPreset::SafetyFirst);
+        assert_eq!(state.filter_tox_max, 0.4);
+        // Presets without filter bounds leave the filters alone
+        state.apply_weight_preset(WeightPreset::CheapToMake);
+        assert_eq!(state.filter_tox_max, 0.4);
+    }
+
+    #[test]
+    fn test_merge_duplicates_undo() {
+        let mut state = AppState { import_dedup: false, ..Default::default() };
+        state.import_from_text("CCO\nc1ccccc1\nOCC\nCCO");
+        state.selected_id = Some(3);
+
+        state.merge_duplicates();
+        let ids: Vec<usize> = state.candidates.iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec![0, 1]);
+        assert_eq!(state.selected_id, None);
+
+        state.undo();
+        assert_eq!(state.candidates.len(), 4);
+        assert_eq!(state.candidates.iter().map(|c| c.id).collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+        state.redo();
+        assert_eq!(state.candidates.len(), 2);
+    }
+
+    #[test]
+    fn test_merge_duplicates_keeps_annotations_and_stereoisomers() {
+        let mut state = AppState { import_dedup: false, ..Default::default() };
+        state.import_from_text("CCO\nC[C@H](N)O\nOCC\nC[C@@H](N)O\nCCO");
+        state.annotations.set_note(2, "from supplier B".into());
+        state.annotations.toggle_favorite(4);
+        state.annotations.add_tag(4, "hit");
+
+        state.merge_duplicates();
+        let ids: Vec<usize> = state.candidates.iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec![0, 1, 3]);
+        assert_eq!(state.annotations.get_note(0).map(String::as_str), Some("from supplier B"));
+        assert!(state.annotations.is_favorite(0));
+        assert!(state.annotations.has_tag(0, "hit"));
+
+        state.undo();
+        assert_eq!(state.candidates.len(), 5);
+        assert_eq!(state.annotations.get_note(0), None);
+        assert!(!state.annotations.is_favorite(0));
+        state.redo();
+        assert!(state.annotations.has_tag(0, "hit"));
+    }
+
+    #[test]
+    fn test_collapse_near_duplicates_keeps_best() {
+        let mut state = AppState {
+            candidates: vec![
+                Candidate { id: 0, smiles: "c1ccc2ccccc2c1CCN".into(), efficacy: 0.5, ..Default::default() },
+                Candidate { id: 1, smiles: "CC(=O)Oc1ccccc1".into(), efficacy: 0.5, ..Default::default() },
+                Candidate { id: 2, smiles: "c1ccc2ccccc2c1CCCN".into(), efficacy: 0.9, ..Default::default() },
+            ],
+            near_duplicate_threshold: 0.9,
+            ..Default::default()
+        };
+        state.recompute_pareto();
+
+        state.collapse_near_duplicates();
+        let ids: Vec<usize> = state.candidates.iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+
+        state.undo();
+        assert_eq!(state.candidates.len(), 3);
+    }
 }
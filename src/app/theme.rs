@@ -21,6 +21,24 @@ pub struct ThemeSettings {
     pub mode: ThemeMode,
     pub accent_color: [u8; 3],
     pub font_size: f32,
+    #[serde(default = "default_max_history")]
+    pub max_history: usize,
+    #[serde(default = "default_autosave_secs")]
+    pub autosave_secs: u64,  // 0 = autosave off
+    #[serde(default = "default_display_decimals")]
+    pub display_decimals: usize,
+}
+
+fn default_max_history() -> usize {
+    50
+}
+
+fn default_display_decimals() -> usize {
+    super::state::DEFAULT_DISPLAY_DECIMALS
+}
+
+fn default_autosave_secs() -> u64 {
+    120
 }
 
 impl Default for ThemeSettings {
@@ -29,6 +47,9 @@ impl Default for ThemeSettings {
             mode: ThemeMode::Dark,
             accent_color: [0, 200, 100], // Green
             font_size: 14.0,
+            max_history: default_max_history(),
+            autosave_secs: default_autosave_secs(),
+            display_decimals: default_display_decimals(),
         }
     }
 }
@@ -162,21 +183,25 @@ pub fn preset_themes() -> Vec<(&'static str, ThemeSettings)> {
             mode: ThemeMode::Dark,
             accent_color: [0, 200, 100],
             font_size: 14.0,
+            ..Default::default()
         }),
         ("Ocean", ThemeSettings {
             mode: ThemeMode::Dark,
             accent_color: [100, 150, 255],
             font_size: 14.0,
+            ..Default::default()
         }),
         ("Sunset", ThemeSettings {
             mode: ThemeMode::Dark,
             accent_color: [255, 150, 100],
             font_size: 14.0,
+            ..Default::default()
         }),
         ("Clean Light", ThemeSettings {
             mode: ThemeMode::Light,
             accent_color: [0, 150, 200],
             font_size: 14.0,
+            ..Default::default()
         }),
     ]
 }
@@ -1,12 +1,34 @@
 //! Advanced visualizations: 3D plot, heatmap, clustering view
+//! Widget state lives in `AppState`, never in statics.
+#![deny(unsafe_code)]
 
 use eframe::egui;
-use egui_plot::{Plot, Points, PlotPoints, Line, BarChart, Bar};
-use crate::app::state::{AppState, Candidate};
+use egui_plot::{Plot, PlotPoint, Points, PlotPoints, Line};
+use crate::app::state::{AppState, AxisVar, Candidate, LogLevel};
 use crate::chemistry::similarity;
 
-/// Render 3D-like scatter plot using perspective projection
-pub fn render_3d_plot(ui: &mut egui::Ui, state: &AppState) {
+/// Eye distance from the cube center, in cube widths
+const CAMERA_DISTANCE: f32 = 3.0;
+/// Depth bands drawn back to front, each with its own point radius
+const DEPTH_BANDS: usize = 8;
+/// Molecules embedded in the similarity map
+const MDS_MAX: usize = 300;
+/// Cluster colors in the similarity map, reused cyclically
+const CLUSTER_PALETTE: [egui::Color32; 8] = [
+    egui::Color32::from_rgb(230, 100, 100),
+    egui::Color32::from_rgb(100, 180, 230),
+    egui::Color32::from_rgb(120, 210, 120),
+    egui::Color32::from_rgb(240, 190, 80),
+    egui::Color32::from_rgb(190, 130, 230),
+    egui::Color32::from_rgb(90, 210, 200),
+    egui::Color32::from_rgb(240, 140, 200),
+    egui::Color32::from_rgb(170, 170, 170),
+];
+
+/// Render a perspective 3D scatter (x = toxicity, depth = synthesis cost, up = efficacy).
+/// Drag to rotate.
+pub fn render_3d_plot(ui: &mut egui::Ui, state: &mut AppState) {
+    let (yaw, pitch) = (state.plot_yaw, state.plot_pitch);
     let candidates = state.filtered_candidates();
     
     if candidates.is_empty() {
@@ -14,72 +36,120 @@ pub fn render_3d_plot(ui: &mut egui::Ui, state: &AppState) {
         return;
     }
 
-    ui.label("🎲 3D View: Efficacy × Toxicity × Synthesis Cost");
-    ui.small("Rotate with angle slider. Size = Manufacturing cost (smaller = better)");
-
-    // Rotation angle control
-    static mut ROTATION_ANGLE: f32 = 0.3;
-    let angle = unsafe { &mut ROTATION_ANGLE };
-    
+    let mut reset = false;
     ui.horizontal(|ui| {
-        ui.label("Rotation:");
-        ui.add(egui::Slider::new(angle, 0.0..=std::f32::consts::TAU).text("angle"));
+        ui.label("🎲 3D View: Toxicity × Synthesis Cost × Efficacy");
+        reset = ui.small_button("⟲ Reset view").clicked();
     });
+    ui.small("Drag to rotate. Nearer points are drawn larger and on top");
+
+    // Project once, then sort far to near
+    let mut projected: Vec<(f32, [f64; 2], bool)> = candidates.iter()
+        .map(|c| {
+            let p = [c.toxicity, c.synthesis_cost, c.efficacy];
+            (rotate(p, yaw, pitch)[1], project(p, yaw, pitch), c.pareto)
+        })
+        .collect();
+    projected.sort_by(|a, b| b.0.total_cmp(&a.0));
 
-    let cos_a = angle.cos();
-    let sin_a = angle.sin();
-
-    Plot::new("3d_plot")
+    let drag = Plot::new("3d_plot")
         .height(300.0)
         .data_aspect(1.0)
-        .x_axis_label("X (rotated)")
-        .y_axis_label("Efficacy")
+        .show_axes(false)
+        .show_grid(false)
+        .allow_drag(false)
+        .allow_zoom(false)
+        .allow_scroll(false)
+        .allow_boxed_zoom(false)
         .show(ui, |plot_ui| {
-            // Project 3D points to 2D with rotation
-            let pareto_points: PlotPoints = candidates
-                .iter()
-                .filter(|c| c.pareto)
-                .map(|c| {
-                    // 3D coordinates: x=toxicity, y=efficacy, z=synthesis_cost
-                    let x = c.toxicity as f64;
-                    let z = c.synthesis_cost as f64;
-                    // Apply rotation around Y axis
-                    let x_rot = x * cos_a as f64 + z * sin_a as f64;
-                    let y = c.efficacy as f64;
-                    [x_rot, y]
-                })
-                .collect();
-
-            let non_pareto_points: PlotPoints = candidates
-                .iter()
-                .filter(|c| !c.pareto)
-                .map(|c| {
-                    let x = c.toxicity as f64;
-                    let z = c.synthesis_cost as f64;
-                    let x_rot = x * cos_a as f64 + z * sin_a as f64;
-                    let y = c.efficacy as f64;
-                    [x_rot, y]
-                })
-                .collect();
-
-            plot_ui.points(
-                Points::new(non_pareto_points)
-                    .name("Non-Pareto")
-                    .color(egui::Color32::from_rgba_unmultiplied(150, 150, 150, 100))
-                    .radius(2.0)
-            );
-            
-            plot_ui.points(
-                Points::new(pareto_points)
-                    .name("Pareto")
-                    .color(egui::Color32::from_rgb(0, 220, 100))
-                    .radius(5.0)
-            );
-        });
+            draw_cube(plot_ui, yaw, pitch);
+
+            // Depth runs from -√3/2 (near) to +√3/2 (far) for a centered unit cube
+            let half_diag = 3f32.sqrt() / 2.0;
+            for band in (0..DEPTH_BANDS).rev() {
+                let in_band = |depth: f32| {
+                    let t = ((depth + half_diag) / (2.0 * half_diag) * DEPTH_BANDS as f32) as usize;
+                    t.min(DEPTH_BANDS - 1) == band
+                };
+                let nearness = 1.0 - band as f32 / (DEPTH_BANDS - 1) as f32;
+
+                let points = |pareto: bool| -> PlotPoints {
+                    projected.iter()
+                        .filter(|(depth, _, p)| *p == pareto && in_band(*depth))
+                        .map(|(_, xy, _)| *xy)
+                        .collect()
+                };
+                plot_ui.points(
+                    Points::new(points(false))
+                        .name("Non-Pareto")
+                        .color(egui::Color32::from_rgba_unmultiplied(150, 150, 150, 100))
+                        .radius(1.5 + 1.5 * nearness)
+                );
+                plot_ui.points(
+                    Points::new(points(true))
+                        .name("Pareto")
+                        .color(egui::Color32::from_rgb(0, 220, 100))
+                        .radius(3.0 + 3.0 * nearness)
+                );
+            }
+
+            plot_ui.response().drag_delta()
+        })
+        .inner;
+
+    if reset {
+        state.plot_yaw = 0.6;
+        state.plot_pitch = 0.35;
+    } else {
+        state.plot_yaw = (yaw + drag.x * 0.01).rem_euclid(std::f32::consts::TAU);
+        state.plot_pitch = (pitch + drag.y * 0.01)
+            .clamp(-std::f32::consts::FRAC_PI_2, std::f32::consts::FRAC_PI_2);
+    }
+}
+
+/// Unit-cube wireframe and axis labels for orientation
+fn draw_cube(plot_ui: &mut egui_plot::PlotUi, yaw: f32, pitch: f32) {
+    let corner = |i: usize| [(i & 1) as f32, ((i >> 1) & 1) as f32, ((i >> 2) & 1) as f32];
+    let color = egui::Color32::from_gray(90);
+    for a in 0..8 {
+        for bit in [1, 2, 4] {
+            let b = a | bit;
+            if b != a {
+                let line = vec![project(corner(a), yaw, pitch), project(corner(b), yaw, pitch)];
+                plot_ui.line(Line::new(PlotPoints::new(line)).color(color).width(1.0));
+            }
+        }
+    }
+
+    for (label, p) in [("Toxicity", [1.1, 0.0, 0.0]), ("Synth", [0.0, 1.1, 0.0]), ("Efficacy", [0.0, 0.0, 1.1])] {
+        let [x, y] = project(p, yaw, pitch);
+        plot_ui.text(egui_plot::Text::new(egui_plot::PlotPoint::new(x, y), label).color(egui::Color32::GRAY));
+    }
+}
+
+/// Rotate a unit-cube point about the cube center: yaw around the vertical axis,
+/// then pitch around the screen's horizontal axis. Returns [x, depth, up].
+fn rotate(point: [f32; 3], yaw: f32, pitch: f32) -> [f32; 3] {
+    let [x, y, z] = point.map(|v| v - 0.5);
+    let (sy, cy) = yaw.sin_cos();
+    let (x1, y1) = (x * cy - y * sy, x * sy + y * cy);
+    let (sp, cp) = pitch.sin_cos();
+    [x1, y1 * cp - z * sp, y1 * sp + z * cp]
+}
+
+/// Perspective projection of a unit-cube point to plot coordinates
+fn project(point: [f32; 3], yaw: f32, pitch: f32) -> [f64; 2] {
+    let [x, depth, up] = rotate(point, yaw, pitch);
+    let scale = CAMERA_DISTANCE / (CAMERA_DISTANCE + depth);
+    [(x * scale) as f64, (up * scale) as f64]
 }
 
 /// Render correlation heatmap between objectives
-pub fn render_correlation_heatmap(ui: &mut egui::Ui, state: &AppState) {
+pub fn render_correlation_heatmap(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.checkbox(&mut state.heatmap_include_descriptors, "Include descriptors (MW, LogP, PSA, QED)");
+    state.refresh_axis_cache();
+
+    let state = &*state;
     let candidates = state.filtered_candidates();
     
     if candidates.len() < 10 {
@@ -88,17 +158,17 @@ pub fn render_correlation_heatmap(ui: &mut egui::Ui, state: &AppState) {
     }
 
     ui.label("🔥 Correlation Heatmap");
-    ui.small("Shows Pearson correlation between objectives (-1 to +1)");
+    ui.small("Shows Pearson correlation between variables (-1 to +1). Hover a cell for details");
 
     // Calculate correlations
-    let objectives: Vec<(&str, Box<dyn Fn(&Candidate) -> f32>)> = vec![
-        ("Efficacy", Box::new(|c: &Candidate| c.efficacy)),
-        ("Toxicity", Box::new(|c: &Candidate| c.toxicity)),
-        ("SynthCost", Box::new(|c: &Candidate| c.synthesis_cost)),
-        ("MfgCost", Box::new(|c: &Candidate| c.manufacturing_cost)),
-    ];
-
-    let n = objectives.len();
+    let variables: &[AxisVar] = if state.heatmap_include_descriptors {
+        &AxisVar::ALL
+    } else {
+        &AxisVar::ALL[..4]
+    };
+    let value = |var: AxisVar| move |c: &Candidate| state.axis_value(c, var);
+
+    let n = variables.len();
     let mut correlations = vec![vec![0.0f32; n]; n];
 
     for i in 0..n {
@@ -106,7 +176,7 @@ pub fn render_correlation_heatmap(ui: &mut egui::Ui, state: &AppState) {
             if i == j {
                 correlations[i][j] = 1.0;
             } else if j > i {
-                let corr = calculate_correlation(&candidates, &objectives[i].1, &objectives[j].1);
+                let corr = calculate_correlation(&candidates, &value(variables[i]), &value(variables[j]));
                 correlations[i][j] = corr;
                 correlations[j][i] = corr;
             }
@@ -121,22 +191,28 @@ pub fn render_correlation_heatmap(ui: &mut egui::Ui, state: &AppState) {
         .show(ui, |ui| {
             // Header row
             ui.label("");
-            for (name, _) in &objectives {
-                ui.label(*name);
+            for var in variables {
+                ui.label(var.label());
             }
             ui.end_row();
 
             // Data rows
             for i in 0..n {
-                ui.label(objectives[i].0);
+                ui.label(variables[i].label());
                 for j in 0..n {
                     let corr = correlations[i][j];
                     let color = correlation_color(corr);
                     
-                    let (rect, _response) = ui.allocate_exact_size(
+                    let (rect, response) = ui.allocate_exact_size(
                         egui::vec2(cell_size, 25.0),
                         egui::Sense::hover()
                     );
+                    response.on_hover_text(correlation_tooltip(
+                        variables[i].label(),
+                        variables[j].label(),
+                        corr,
+                        candidates.len(),
+                    ));
                     
                     ui.painter().rect_filled(rect, 3.0, color);
                     ui.painter().text(
@@ -161,6 +237,22 @@ pub fn render_correlation_heatmap(ui: &mut egui::Ui, state: &AppState) {
     });
 }
 
+fn correlation_tooltip(a: &str, b: &str, r: f32, n: usize) -> String {
+    let note = if a == b {
+        "diagonal"
+    } else if is_significant(r, n) {
+        "significant (p < 0.05)"
+    } else {
+        "not significant (p ≥ 0.05)"
+    };
+    format!("{} × {}\nr = {:.3}, n = {}\n{}", a, b, r, n, note)
+}
+
+/// Two-sided test of r ≠ 0 at p < 0.05, large-sample approximation (|r|·√n > 1.96)
+fn is_significant(r: f32, n: usize) -> bool {
+    r.abs() * (n as f32).sqrt() > 1.96
+}
+
 fn calculate_correlation<F1, F2>(candidates: &[&Candidate], f1: &F1, f2: &F2) -> f32
 where
     F1: Fn(&Candidate) -> f32,
@@ -227,12 +319,9 @@ pub fn render_clustering_view(ui: &mut egui::Ui, state: &mut AppState) {
 
     ui.label("🔬 Molecular Clustering (Tanimoto similarity)");
     
-    static mut CLUSTER_THRESHOLD: f32 = 0.5;
-    let threshold = unsafe { &mut CLUSTER_THRESHOLD };
-    
     ui.horizontal(|ui| {
         ui.label("Similarity threshold:");
-        ui.add(egui::Slider::new(threshold, 0.2..=0.9).step_by(0.05));
+        ui.add(egui::Slider::new(&mut state.cluster_threshold, 0.2..=0.9).step_by(0.05));
     });
 
     let max_cluster = 200.min(candidates_data.len());
@@ -241,13 +330,23 @@ pub fn render_clustering_view(ui: &mut egui::Ui, state: &mut AppState) {
         .map(|(_, s, _)| s.clone())
         .collect();
     
-    let clusters = similarity::cluster_molecules(&smiles_list, *threshold);
+    let clusters = similarity::cluster_molecules(&smiles_list, state.cluster_threshold);
 
     ui.separator();
-    ui.label(format!("Found {} clusters from {} molecules", clusters.len(), max_cluster));
+    ui.horizontal(|ui| {
+        ui.label(format!("Found {} clusters from {} molecules", clusters.len(), max_cluster));
+        ui.separator();
+        let silhouette = similarity::silhouette_score(&smiles_list, &clusters);
+        ui.label(format!("Silhouette: {:.3}", silhouette))
+            .on_hover_text("Mean silhouette over 1 − Tanimoto distances: near 1 for tight, well-separated clusters, \
+                near 0 for overlapping ones. Tune the threshold to raise it.");
+    });
     
     // Collect click actions
     let mut click_id: Option<usize> = None;
+    let mut select_ids: Option<Vec<usize>> = None;
+    let mut favorite_ids: Option<Vec<usize>> = None;
+    let candidate_ids: Vec<usize> = candidates_data.iter().map(|(id, _, _)| *id).collect();
     
     egui::ScrollArea::vertical()
         .max_height(200.0)
@@ -269,6 +368,15 @@ pub fn render_clustering_view(ui: &mut egui::Ui, state: &mut AppState) {
                         .filter(|&&i| i < candidates_data.len() && candidates_data[i].2)
                         .count();
                     ui.label(format!("Pareto: {}", pareto_count));
+
+                    ui.horizontal(|ui| {
+                        if ui.small_button("☑ Select cluster").clicked() {
+                            select_ids = Some(cluster_member_ids(&cluster.members, &candidate_ids));
+                        }
+                        if ui.small_button("⭐ Favorite whole cluster").clicked() {
+                            favorite_ids = Some(cluster_member_ids(&cluster.members, &candidate_ids));
+                        }
+                    });
                     
                     ui.horizontal_wrapped(|ui| {
                         for &member_idx in cluster.members.iter().take(10) {
@@ -292,6 +400,19 @@ pub fn render_clustering_view(ui: &mut egui::Ui, state: &mut AppState) {
     if let Some(id) = click_id {
         state.selected_id = Some(id);
     }
+    if let Some(ids) = select_ids {
+        let count = ids.len();
+        state.selected_ids.extend(ids);
+        let total = state.selected_ids.len();
+        state.push_status(LogLevel::Info, format!("Selected cluster of {} ({} total)", count, total));
+    }
+    if let Some(ids) = favorite_ids {
+        state.toggle_favorites(&ids);
+    }
+
+    ui.collapsing("🗺 Similarity map", |ui| {
+        render_similarity_map(ui, state, &candidates_data);
+    });
 
     if candidates_data.len() >= 10 {
         let sample_smiles: Vec<String> = candidates_data[..10].iter().map(|(_, s, _)| s.clone()).collect();
@@ -301,25 +422,135 @@ pub fn render_clustering_view(ui: &mut egui::Ui, state: &mut AppState) {
     }
 }
 
+/// 2D MDS embedding of the first `MDS_MAX` molecules, colored by cluster.
+/// Click a point to select its candidate.
+fn render_similarity_map(ui: &mut egui::Ui, state: &mut AppState, candidates_data: &[(usize, String, bool)]) {
+    let data = &candidates_data[..MDS_MAX.min(candidates_data.len())];
+    let ids: Vec<usize> = data.iter().map(|(id, _, _)| *id).collect();
+    let smiles: Vec<String> = data.iter().map(|(_, s, _)| s.clone()).collect();
+
+    let stale = state.mds_cache.as_ref()
+        .is_none_or(|(rev, cached_ids, _)| *rev != state.candidates_revision || *cached_ids != ids);
+    if stale {
+        let coords = similarity::mds_embed(&similarity::similarity_matrix(&smiles), 42);
+        state.mds_cache = Some((state.candidates_revision, ids.clone(), coords));
+    }
+    let Some((_, _, coords)) = &state.mds_cache else { return };
+
+    let clusters = similarity::cluster_molecules(&smiles, state.cluster_threshold);
+    let selected = state.selected_id;
+    ui.label(format!("{} molecules embedded by classical MDS on 1 − Tanimoto", ids.len()));
+
+    let clicked = Plot::new("similarity_map")
+        .height(280.0)
+        .data_aspect(1.0)
+        .show_axes([false, false])
+        .show(ui, |plot_ui| {
+            for cluster in &clusters {
+                let points: PlotPoints = cluster.members.iter()
+                    .filter_map(|&i| coords.get(i))
+                    .map(|&[x, y]| [x as f64, y as f64])
+                    .collect();
+                plot_ui.points(Points::new(points)
+                    .name(format!("Cluster {}", cluster.cluster_id))
+                    .color(CLUSTER_PALETTE[cluster.cluster_id % CLUSTER_PALETTE.len()])
+                    .radius(3.5));
+            }
+
+            if let Some(i) = selected.and_then(|sel| ids.iter().position(|&id| id == sel)) {
+                let [x, y] = coords[i];
+                plot_ui.points(Points::new(vec![[x as f64, y as f64]])
+                    .name("Selected")
+                    .color(egui::Color32::WHITE)
+                    .radius(7.0)
+                    .filled(false));
+            }
+
+            if !plot_ui.response().clicked() {
+                return None;
+            }
+            let pointer = plot_ui.screen_from_plot(plot_ui.pointer_coordinate()?);
+            coords.iter()
+                .zip(&ids)
+                .map(|(&[x, y], &id)| (id, plot_ui.screen_from_plot(PlotPoint::new(x, y)).distance(pointer)))
+                .filter(|&(_, dist)| dist <= 8.0)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(id, _)| id)
+        })
+        .inner;
+
+    if let Some(id) = clicked {
+        state.selected_id = Some(id);
+    }
+}
+
+/// Map cluster member indices (into the clustered slice) back to candidate ids
+fn cluster_member_ids(members: &[usize], candidate_ids: &[usize]) -> Vec<usize> {
+    members.iter()
+        .filter_map(|&i| candidate_ids.get(i).copied())
+        .collect()
+}
+
+/// Candidates grouped by Bemis-Murcko scaffold with per-group averages
+pub fn render_scaffold_groups(ui: &mut egui::Ui, state: &mut AppState) {
+    let total = state.candidates.len();
+    let groups = state.scaffold_groups();
+    if groups.is_empty() {
+        ui.label("No candidates to group");
+        return;
+    }
+
+    ui.label(format!("{} scaffolds across {} candidates", groups.len(), total));
+
+    let mut click_id: Option<usize> = None;
+
+    egui::ScrollArea::vertical()
+        .max_height(250.0)
+        .show(ui, |ui| {
+            for group in groups.iter().take(100) {
+                let name = if group.scaffold.is_empty() { "(acyclic)" } else { group.scaffold.as_str() };
+                let header = format!("{} ({} members)", name, group.ids.len());
+
+                ui.collapsing(header, |ui| {
+                    ui.label(format!(
+                        "Avg eff {:.3} | tox {:.3} | syn {:.3} | mfg {:.3}",
+                        group.mean[0], group.mean[1], group.mean[2], group.mean[3]
+                    ));
+                    ui.horizontal_wrapped(|ui| {
+                        for &id in group.ids.iter().take(20) {
+                            if ui.small_button(id.to_string()).clicked() {
+                                click_id = Some(id);
+                            }
+                        }
+                        if group.ids.len() > 20 {
+                            ui.label(format!("... +{}", group.ids.len() - 20));
+                        }
+                    });
+                });
+            }
+        });
+
+    if let Some(id) = click_id {
+        state.selected_id = Some(id);
+    }
+}
+
 /// Render similarity search
 pub fn render_similarity_search(ui: &mut egui::Ui, state: &mut AppState) {
     ui.label("🔍 Similarity Search");
     
-    static mut QUERY_SMILES: String = String::new();
-    let query = unsafe { &mut QUERY_SMILES };
-    
     ui.horizontal(|ui| {
         ui.label("Query SMILES:");
-        ui.text_edit_singleline(query);
+        ui.text_edit_singleline(&mut state.similarity_query);
         
-        if ui.button("Search").clicked() && !query.is_empty() {
+        if ui.button("Search").clicked() && !state.similarity_query.is_empty() {
             // Search will happen below
         }
     });
 
-    if !query.is_empty() && state.candidates.len() > 0 {
+    if !state.similarity_query.is_empty() && !state.candidates.is_empty() {
         let smiles_list: Vec<String> = state.candidates.iter().map(|c| c.smiles.clone()).collect();
-        let similar = similarity::find_similar(query, &smiles_list, 10);
+        let similar = similarity::find_similar(&state.similarity_query, &smiles_list, 10);
         
         if !similar.is_empty() {
             ui.separator();
@@ -445,3 +676,93 @@ pub fn render_druglikeness_panel(ui: &mut egui::Ui, state: &AppState) {
         ui.label("Select a candidate to analyze");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: [f64; 2], b: [f64; 2]) {
+        assert!((a[0] - b[0]).abs() < 1e-5 && (a[1] - b[1]).abs() < 1e-5, "{:?} vs {:?}", a, b);
+    }
+
+    #[test]
+    fn test_project() {
+        use std::f32::consts::FRAC_PI_2;
+
+        // Front view: x and up map straight through, center-depth points are unscaled
+        assert_close(project([0.5, 0.5, 0.5], 0.0, 0.0), [0.0, 0.0]);
+        assert_close(project([1.0, 0.5, 0.5], 0.0, 0.0), [0.5, 0.0]);
+        assert_close(project([0.5, 0.5, 1.0], 0.0, 0.0), [0.0, 0.5]);
+
+        // Quarter yaw turns the x axis into depth and the depth axis onto x
+        assert_close(project([1.0, 0.5, 0.5], FRAC_PI_2, 0.0), [0.0, 0.0]);
+        assert_close(project([0.5, 0.0, 0.5], FRAC_PI_2, 0.0), [0.5, 0.0]);
+
+        // Quarter pitch looks down: the up axis becomes depth
+        assert_close(project([0.5, 0.5, 1.0], 0.0, FRAC_PI_2), [0.0, 0.0]);
+
+        // Perspective: nearer points spread out, farther ones shrink
+        let near = project([1.0, 0.0, 0.5], 0.0, 0.0)[0];
+        let far = project([1.0, 1.0, 0.5], 0.0, 0.0)[0];
+        assert!(near > 0.5 && far < 0.5, "near {} far {}", near, far);
+    }
+
+    #[test]
+    fn test_cluster_member_ids_with_filter() {
+        let smiles = ["CCO", "c1ccccc1", "CCO", "c1ccccc1", "CCO", "CCCCCCCC"];
+        let state = AppState {
+            candidates: smiles.iter()
+                .enumerate()
+                .map(|(i, s)| Candidate { id: 10 + i, smiles: s.to_string(), pareto: i != 2, ..Default::default() })
+                .collect(),
+            filter_pareto_only: true,
+            ..Default::default()
+        };
+
+        // Same slice the clustering view builds: filtered, so local index != position in candidates
+        let filtered = state.filtered_candidates();
+        let candidate_ids: Vec<usize> = filtered.iter().map(|c| c.id).collect();
+        let smiles_list: Vec<String> = filtered.iter().map(|c| c.smiles.clone()).collect();
+        assert_eq!(candidate_ids, [10, 11, 13, 14, 15]);
+
+        for cluster in similarity::cluster_molecules(&smiles_list, 0.9) {
+            let ids = cluster_member_ids(&cluster.members, &candidate_ids);
+            assert_eq!(ids.len(), cluster.members.len());
+            let first = &state.candidates.iter().find(|c| c.id == ids[0]).unwrap().smiles;
+            for id in &ids {
+                let c = state.candidates.iter().find(|c| c.id == *id).unwrap();
+                assert_eq!(&c.smiles, first, "cluster mixes {} and {}", c.smiles, first);
+            }
+        }
+
+        assert_eq!(cluster_member_ids(&[0, 4], &candidate_ids), [10, 15]);
+        assert_eq!(cluster_member_ids(&[2, 9], &candidate_ids), [13]);
+    }
+
+    #[test]
+    fn test_calculate_correlation() {
+        let cands: Vec<Candidate> = (0..20)
+            .map(|i| Candidate {
+                id: i,
+                efficacy: i as f32 / 20.0,
+                toxicity: 2.0 * i as f32 + 1.0,
+                synthesis_cost: 1.0 - i as f32 / 20.0,
+                manufacturing_cost: 0.5,
+                ..Default::default()
+            })
+            .collect();
+        let refs: Vec<&Candidate> = cands.iter().collect();
+
+        let eff = |c: &Candidate| c.efficacy;
+        let r = calculate_correlation(&refs, &eff, &|c: &Candidate| c.toxicity);
+        assert!((r - 1.0).abs() < 1e-5, "r = {}", r);
+        let r = calculate_correlation(&refs, &eff, &|c: &Candidate| c.synthesis_cost);
+        assert!((r + 1.0).abs() < 1e-5, "r = {}", r);
+
+        // Constant series has no variance
+        assert_eq!(calculate_correlation(&refs, &eff, &|c: &Candidate| c.manufacturing_cost), 0.0);
+
+        assert!(is_significant(0.5, 100));
+        assert!(!is_significant(0.1, 100));
+    }
+}
@@ -1,9 +1,17 @@
 use eframe::egui;
-use egui_plot::{Plot, Points, PlotPoints};
-use crate::app::state::{AppState, Candidate};
-use super::{visualizations, advanced_viz};
+use egui_plot::{Line, MarkerShape, Plot, PlotBounds, PlotPoint, Points, PlotPoints, Polygon};
+use egui_extras::{Column, TableBuilder};
+use crate::app::io;
+use crate::app::state::{coverage_occupancy, safe_log10, AppState, AxisVar, Candidate, LogLevel, SortColumn, COVERAGE_BINS, LOG_FLOOR};
+use crate::chemistry::descriptors;
+use crate::optimization::objectives;
+use super::{visualizations, advanced_viz, preference, seed_sweep};
 
 pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    state.refresh_score_cache();
+    state.refresh_axis_cache();
+    state.refresh_baseline_improvements();
+
     egui::CentralPanel::default().show(ctx, |ui| {
         // Header
         ui.horizontal(|ui| {
@@ -15,28 +23,97 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
             
             ui.label(format!("Showing: {} | Pareto: {} | Total: {}", 
                 filtered.len(), pareto_filtered, state.candidates.len()));
+
+            if !state.selected_ids.is_empty() {
+                ui.separator();
+                ui.label(format!("Multi-selected: {}", state.selected_ids.len()));
+                if ui.small_button("✖").on_hover_text("Clear multi-selection").clicked() {
+                    state.selected_ids.clear();
+                }
+            }
         });
 
         visualizations::render_stats_summary(ui, state);
+        if !state.import_issues.is_empty() {
+            render_import_issues(ui, state);
+        }
         ui.separator();
 
         egui::ScrollArea::vertical()
             .auto_shrink([false, false])
             .show(ui, |ui| {
+                ui.collapsing("🏆 Top Candidates", |ui| {
+                    render_top_candidates(ui, state);
+                });
+
+                ui.separator();
+
                 // Scatter plots
+                let mut clicked_reference = None;
+                let mut clicked_candidate = None;
+                let mut boxed = Vec::new();
                 ui.horizontal(|ui| {
                     ui.vertical(|ui| {
-                        ui.label("📈 Efficacy vs Toxicity");
-                        render_scatter_plot(ui, state, "eff_vs_tox", 
-                            |c| c.toxicity, |c| c.efficacy, "Toxicity", "Efficacy");
-                    });
-                    ui.separator();
-                    ui.vertical(|ui| {
-                        ui.label("📈 Costs");
-                        render_scatter_plot(ui, state, "costs",
-                            |c| c.synthesis_cost, |c| c.manufacturing_cost, "Synth", "Mfg");
+                        ui.horizontal(|ui| {
+                            ui.label("📈");
+                            axis_combo(ui, "scatter_y", &mut state.scatter_y);
+                            ui.label("vs");
+                            axis_combo(ui, "scatter_x", &mut state.scatter_x);
+                            ui.separator();
+                            if ui.small_button("🎯 Jump to knee")
+                                .on_hover_text("Select the knee of the Pareto front")
+                                .clicked()
+                            {
+                                match state.knee_id() {
+                                    Some(id) => state.selected_id = Some(id),
+                                    None => state.push_status(LogLevel::Warning, "No Pareto front to find a knee on"),
+                                }
+                            }
+                            ui.checkbox(&mut state.knee_4d, "4D")
+                                .on_hover_text("Knee by utopia distance over all four objectives instead of eff/tox");
+                            ui.checkbox(&mut state.show_error_bars, "±σ")
+                                .on_hover_text(format!("Error bars from the generation jitter (first {} points)", ERROR_BAR_MAX));
+                            ui.checkbox(&mut state.show_coverage_grid, "Coverage")
+                                .on_hover_text("Shade occupied cells of the efficacy × toxicity grid (Toxicity vs Efficacy, linear axes)");
+                            ui.checkbox(&mut state.log_x, "log x");
+                            ui.checkbox(&mut state.log_y, "log y")
+                                .on_hover_text(format!("Log10 axes apply to both scatter plots; values below {} are floored", LOG_FLOOR));
+                        });
+                        render_baseline_controls(ui, state);
+                        let (x, y) = (state.scatter_x, state.scatter_y);
+                        let response = render_scatter_plot(ui, state, "eff_vs_tox",
+                            |s, c| s.axis_value(c, x), |s, c| s.axis_value(c, y), [x.label(), y.label()], true);
+                        clicked_reference = clicked_reference.or(response.reference);
+                        clicked_candidate = clicked_candidate.or(response.clicked);
+                        boxed = response.boxed;
                     });
+                    if state.active_objectives[2] || state.active_objectives[3] {
+                        ui.separator();
+                        ui.vertical(|ui| {
+                            ui.label("📈 Costs");
+                            let response = render_scatter_plot(ui, state, "costs",
+                                |s, c| s.objective_values(c)[2], |s, c| s.objective_values(c)[3], ["Synth", "Mfg"], false);
+                            clicked_reference = clicked_reference.or(response.reference);
+                            clicked_candidate = clicked_candidate.or(response.clicked);
+                        });
+                    }
                 });
+                if clicked_reference.is_some() {
+                    state.selected_reference = clicked_reference;
+                }
+                if clicked_candidate.is_some() {
+                    state.selected_id = clicked_candidate;
+                    state.scroll_to_selected = true;
+                }
+                if !boxed.is_empty() {
+                    let count = boxed.len();
+                    state.selected_ids.extend(boxed);
+                    let total = state.selected_ids.len();
+                    state.push_status(LogLevel::Info, format!("Box-selected {} candidates ({} total)", count, total));
+                }
+                if let Some(name) = state.selected_reference.filter(|_| state.show_reference_drugs) {
+                    render_reference_info(ui, state, name);
+                }
 
                 ui.separator();
 
@@ -71,25 +148,58 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
                     });
                 }
 
+                if state.show_scaffold_groups {
+                    ui.collapsing("🧱 Scaffold Groups", |ui| {
+                        advanced_viz::render_scaffold_groups(ui, state);
+                    });
+                }
+
                 if state.show_similarity_search {
                     ui.collapsing("🔍 Similarity Search", |ui| {
                         advanced_viz::render_similarity_search(ui, state);
                     });
                 }
 
-                ui.separator();
-                ui.label("📋 Table");
+                if state.show_seed_sweep {
+                    ui.collapsing("🎲 Seed Sweep", |ui| {
+                        seed_sweep::render(ui, state);
+                    });
+                }
+
+                if state.show_preference {
+                    ui.collapsing("🤔 Which Do You Prefer?", |ui| {
+                        preference::render(ui, state);
+                    });
+                }
 
-                // Table
                 let mut rows: Vec<Candidate> = state.filtered_candidates()
                     .into_iter()
                     .cloned()
                     .collect();
 
-                rows.sort_by(|a, b| {
-                    state.weighted_score(b)
-                        .partial_cmp(&state.weighted_score(a))
-                        .unwrap_or(std::cmp::Ordering::Equal)
+                sort_rows(&mut rows, state.sort_column, state.sort_ascending, state);
+                state.set_table_order(rows.iter().map(|c| c.id).collect());
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("📋 Table");
+                    if ui.small_button("Copy all (CSV)")
+                        .on_hover_text("Copy the filtered rows in table order")
+                        .clicked()
+                    {
+                        let text = io::candidates_csv(&rows, |c| state.cached_score(c.id), |id| state.annotations.is_favorite(id));
+                        ui.output_mut(|o| o.copied_text = text);
+                        state.push_status(LogLevel::Info, format!("📋 Copied {} rows as CSV", rows.len()));
+                    }
+                    let flagged = dominated_but_top_ranked(&state.candidates, state, state.top_n);
+                    if !flagged.is_empty() {
+                        let ids: Vec<String> = flagged.iter().map(|id| format!("#{}", id)).collect();
+                        ui.colored_label(egui::Color32::from_rgb(255, 180, 80),
+                            format!("⚠ {} dominated in top {}", flagged.len(), state.top_n))
+                            .on_hover_text(format!(
+                                "Non-Pareto candidates ranked in the top {} by weighted score: {}",
+                                state.top_n, ids.join(", ")));
+                    }
                 });
 
                 render_table(ui, state, &rows);
@@ -97,19 +207,96 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
     });
 }
 
+/// Error bars are drawn for at most this many points to keep the plot responsive
+const ERROR_BAR_MAX: usize = 2000;
+
+/// What the user did on a scatter plot this frame
+#[derive(Default)]
+struct ScatterResponse {
+    reference: Option<&'static str>,  // reference drug clicked
+    boxed: Vec<usize>,                // candidates enclosed by a finished box-select drag
+    hovered: Option<usize>,           // candidate under the pointer
+    clicked: Option<usize>,           // candidate clicked
+}
+
+/// Freeze / clear the Pareto baseline, with how many candidates improve on it
+fn render_baseline_controls(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.horizontal(|ui| {
+        if ui.small_button("📌 Freeze baseline")
+            .on_hover_text("Keep the current Pareto set; candidates dominating any of its points are marked as new improvements")
+            .clicked()
+        {
+            state.freeze_baseline();
+        }
+        if let Some(baseline) = &state.baseline_front {
+            let improved = state.baseline_improvements.as_ref().map_or(0, |(_, ids)| ids.len());
+            ui.label(format!("Baseline: {} points | ✨ {} new improvements", baseline.len(), improved));
+            if ui.small_button("✖").on_hover_text("Clear the baseline").clicked() {
+                state.clear_baseline();
+            }
+        }
+    });
+}
+
+/// SMILES rejected by the last import, with the reason for each
+fn render_import_issues(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut clear = false;
+    egui::CollapsingHeader::new(format!("⚠ Import issues ({})", state.import_issues.len()))
+        .id_source("import_issues")
+        .show(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .id_source("import_issues_scroll")
+                .max_height(120.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("import_issues_grid").num_columns(2).striped(true).show(ui, |ui| {
+                        for (smiles, reason) in &state.import_issues {
+                            ui.monospace(smiles);
+                            ui.colored_label(egui::Color32::from_rgb(255, 180, 80), reason);
+                            ui.end_row();
+                        }
+                    });
+                });
+            clear = ui.small_button("✖ Dismiss").clicked();
+        });
+    if clear {
+        state.import_issues.clear();
+    }
+}
+
+/// Pointer distance, in screen pixels, within which a point counts as hovered
+const HOVER_RADIUS: f64 = 8.0;
+
+/// Id of the point closest to `cursor` within `HOVER_RADIUS`. Coordinates are screen
+/// pixels so both axes weigh alike whatever their plotted ranges.
+fn nearest_candidate(points: &[(usize, [f64; 2])], cursor: [f64; 2]) -> Option<usize> {
+    points.iter()
+        .map(|&(id, [x, y])| (id, (x - cursor[0]).hypot(y - cursor[1])))
+        .filter(|&(_, dist)| dist <= HOVER_RADIUS)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(id, _)| id)
+}
+
+/// Scatter plot of the filtered candidates, log-scaling the axes selected in the state.
+/// The main plot (`main_plot`) supports Shift+drag box selection and, when showing
+/// efficacy vs toxicity, the Pareto staircase.
 fn render_scatter_plot<F1, F2>(
     ui: &mut egui::Ui,
     state: &AppState,
     id: &str,
     x_fn: F1,
     y_fn: F2,
-    x_label: &str,
-    y_label: &str,
-) where
-    F1: Fn(&Candidate) -> f32,
-    F2: Fn(&Candidate) -> f32,
+    [x_label, y_label]: [&str; 2],
+    main_plot: bool,
+) -> ScatterResponse where
+    F1: Fn(&AppState, &Candidate) -> f32,
+    F2: Fn(&AppState, &Candidate) -> f32,
 {
     let filtered = state.filtered_candidates();
+    let (log_x, log_y) = (state.log_x, state.log_y);
+    let raw_x = |c: &Candidate| x_fn(state, c);
+    let raw_y = |c: &Candidate| y_fn(state, c);
+    let x_fn = |c: &Candidate| axis_scale(raw_x(c), log_x);
+    let y_fn = |c: &Candidate| axis_scale(raw_y(c), log_y);
 
     let pareto_points: PlotPoints = filtered.iter()
         .filter(|c| c.pareto)
@@ -135,79 +322,519 @@ fn render_scatter_plot<F1, F2>(
         PlotPoints::new(vec![])
     };
 
-    Plot::new(id)
+    let baseline_points: PlotPoints = state.baseline_front.iter()
+        .flatten()
+        .map(|c| [x_fn(c) as f64, y_fn(c) as f64])
+        .collect();
+    let improvement_points: PlotPoints = filtered.iter()
+        .filter(|c| state.is_baseline_improvement(c.id))
+        .map(|c| [x_fn(c) as f64, y_fn(c) as f64])
+        .collect();
+
+    let multi_points: PlotPoints = filtered.iter()
+        .filter(|c| state.selected_ids.contains(&c.id))
+        .map(|c| [x_fn(c) as f64, y_fn(c) as f64])
+        .collect();
+
+    let references: Vec<(&'static str, [f64; 2])> = if state.show_reference_drugs {
+        objectives::reference_drugs().iter()
+            .map(|r| (r.name, [x_fn(&r.candidate) as f64, y_fn(&r.candidate) as f64]))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let eff_vs_tox = main_plot && state.scatter_x == AxisVar::Toxicity && state.scatter_y == AxisVar::Efficacy;
+    let staircase = if eff_vs_tox {
+        let front: Vec<&Candidate> = filtered.iter().copied().filter(|c| c.pareto).collect();
+        pareto_staircase(&front, x_fn, y_fn)
+    } else {
+        Vec::new()
+    };
+    // Occupancy heatmap: cells as [x, y] corners (x = toxicity, y = efficacy) with their share of the fullest cell
+    let mut coverage_cells: Vec<([f64; 2], f32)> = Vec::new();
+    if eff_vs_tox && state.show_coverage_grid && !log_x && !log_y {
+        let counts = coverage_occupancy(&filtered, COVERAGE_BINS);
+        let max = counts.iter().copied().max().unwrap_or(0).max(1) as f32;
+        let size = 1.0 / COVERAGE_BINS as f64;
+        for (cell, &n) in counts.iter().enumerate().filter(|(_, &n)| n > 0) {
+            let (eff_bin, tox_bin) = (cell / COVERAGE_BINS, cell % COVERAGE_BINS);
+            coverage_cells.push(([tox_bin as f64 * size, eff_bin as f64 * size], n as f32 / max));
+        }
+    }
+    let knee_points: PlotPoints = match state.knee_id().filter(|_| eff_vs_tox) {
+        Some(knee) => filtered.iter()
+            .filter(|c| c.id == knee)
+            .map(|c| [x_fn(c) as f64, y_fn(c) as f64])
+            .collect(),
+        None => PlotPoints::new(vec![]),
+    };
+
+    // ±1σ segments for the objective axes of the main plot
+    let mut error_bars: Vec<[[f64; 2]; 2]> = Vec::new();
+    if main_plot && state.show_error_bars {
+        // Ends are computed on raw values, then scaled like the points
+        let scaled = |value: f32, log: bool| axis_scale(value, log) as f64;
+        for c in filtered.iter().take(ERROR_BAR_MAX) {
+            let (x, y) = (raw_x(c), raw_y(c));
+            let sx = state.axis_sigma(c, state.scatter_x);
+            let sy = state.axis_sigma(c, state.scatter_y);
+            if sx > 0.0 {
+                error_bars.push([[scaled(x - sx, log_x), scaled(y, log_y)], [scaled(x + sx, log_x), scaled(y, log_y)]]);
+            }
+            if sy > 0.0 {
+                error_bars.push([[scaled(x, log_x), scaled(y - sy, log_y)], [scaled(x, log_x), scaled(y + sy, log_y)]]);
+            }
+        }
+    }
+
+    let box_start_id = egui::Id::new((id, "box_select_start"));
+    let shift_held = ui.input(|i| i.modifiers.shift);
+
+    let plot = Plot::new(id)
         .view_aspect(1.3)
         .height(180.0)
-        .x_axis_label(x_label)
-        .y_axis_label(y_label)
+        .x_axis_label(axis_label(x_label, log_x))
+        .y_axis_label(axis_label(y_label, log_y))
+        .allow_drag(!(main_plot && shift_held))
         .show(ui, |plot_ui| {
+            let cell = 1.0 / COVERAGE_BINS as f64;
+            for ([x, y], share) in coverage_cells {
+                let alpha = (10.0 + 50.0 * share) as u8;
+                plot_ui.polygon(Polygon::new(vec![[x, y], [x + cell, y], [x + cell, y + cell], [x, y + cell]])
+                    .fill_color(egui::Color32::from_rgba_unmultiplied(100, 150, 255, alpha))
+                    .stroke(egui::Stroke::NONE));
+            }
+            for segment in error_bars {
+                plot_ui.line(Line::new(PlotPoints::new(segment.to_vec()))
+                    .color(egui::Color32::from_rgba_unmultiplied(150, 150, 150, 90))
+                    .width(1.0));
+            }
+            if staircase.len() > 1 {
+                plot_ui.line(Line::new(PlotPoints::new(staircase))
+                    .name("Pareto front")
+                    .color(egui::Color32::from_rgba_unmultiplied(0, 200, 100, 160))
+                    .width(1.5));
+            }
             plot_ui.points(Points::new(non_pareto_points).name("Regular").color(egui::Color32::from_rgb(150, 150, 150)).radius(3.0));
             plot_ui.points(Points::new(pareto_points).name("Pareto").color(egui::Color32::from_rgb(0, 200, 100)).radius(5.0));
+            plot_ui.points(Points::new(baseline_points).name("Baseline").shape(MarkerShape::Circle).filled(false)
+                .color(egui::Color32::from_rgba_unmultiplied(120, 170, 255, 160)).radius(6.0));
+            plot_ui.points(Points::new(improvement_points).name("New improvements").shape(MarkerShape::Diamond)
+                .color(egui::Color32::from_rgb(0, 220, 230)).radius(6.0));
             plot_ui.points(Points::new(favorite_points).name("Favorite").color(egui::Color32::from_rgb(255, 200, 50)).radius(6.0));
+            plot_ui.points(Points::new(multi_points).name("Multi-selected").shape(MarkerShape::Square).filled(false)
+                .color(egui::Color32::from_rgb(180, 120, 255)).radius(6.0));
+            plot_ui.points(Points::new(knee_points).name("Knee").shape(MarkerShape::Asterisk)
+                .color(egui::Color32::from_rgb(0, 230, 230)).radius(9.0));
             plot_ui.points(Points::new(selected_points).name("Selected").color(egui::Color32::from_rgb(255, 100, 100)).radius(8.0));
+
+            // One series per drug so hovering shows its name
+            for &(name, point) in &references {
+                let highlighted = state.selected_reference == Some(name);
+                plot_ui.points(Points::new(vec![point])
+                    .name(name)
+                    .shape(MarkerShape::Diamond)
+                    .filled(highlighted)
+                    .color(egui::Color32::from_rgb(120, 170, 255))
+                    .radius(6.0));
+            }
+
+            let mut response = ScatterResponse::default();
+
+            if let Some(pointer) = plot_ui.pointer_coordinate() {
+                let cursor = plot_ui.screen_from_plot(pointer);
+                let points: Vec<(usize, [f64; 2])> = filtered.iter()
+                    .map(|c| {
+                        let p = plot_ui.screen_from_plot(PlotPoint::new(x_fn(c) as f64, y_fn(c) as f64));
+                        (c.id, [p.x as f64, p.y as f64])
+                    })
+                    .collect();
+                response.hovered = nearest_candidate(&points, [cursor.x as f64, cursor.y as f64]);
+            }
+
+            if main_plot {
+                let pointer = plot_ui.pointer_coordinate();
+                let drag = plot_ui.response().clone();
+                if drag.drag_started() && shift_held {
+                    if let Some(p) = pointer {
+                        plot_ui.ctx().data_mut(|d| d.insert_temp(box_start_id, [p.x, p.y]));
+                    }
+                }
+                let start: Option<[f64; 2]> = plot_ui.ctx().data(|d| d.get_temp(box_start_id));
+                if let (Some(start), Some(end)) = (start, pointer) {
+                    let rect = PlotBounds::from_min_max(
+                        [start[0].min(end.x), start[1].min(end.y)],
+                        [start[0].max(end.x), start[1].max(end.y)],
+                    );
+                    let (min, max) = (rect.min(), rect.max());
+                    plot_ui.polygon(Polygon::new(vec![
+                        [min[0], min[1]], [max[0], min[1]], [max[0], max[1]], [min[0], max[1]],
+                    ]).name("Box select").fill_color(egui::Color32::from_rgba_unmultiplied(180, 120, 255, 30)));
+
+                    if drag.drag_stopped() {
+                        response.boxed = points_in_rect(&filtered, x_fn, y_fn, rect);
+                    }
+                }
+                if drag.drag_stopped() || (!drag.dragged() && start.is_some()) {
+                    plot_ui.ctx().data_mut(|d| d.remove::<[f64; 2]>(box_start_id));
+                }
+            }
+
+            if plot_ui.response().clicked() {
+                if let Some(pointer) = plot_ui.pointer_coordinate() {
+                    let pointer = plot_ui.screen_from_plot(pointer);
+                    response.reference = references.iter()
+                        .map(|&(name, [x, y])| (name, plot_ui.screen_from_plot(PlotPoint::new(x, y)).distance(pointer)))
+                        .filter(|&(_, dist)| dist <= 8.0)
+                        .min_by(|a, b| a.1.total_cmp(&b.1))
+                        .map(|(name, _)| name);
+                }
+                if response.reference.is_none() {
+                    response.clicked = response.hovered;
+                }
+            }
+            response
+        });
+
+    let hovered = plot.inner.hovered.and_then(|id| filtered.iter().find(|c| c.id == id));
+    if let Some(c) = hovered {
+        plot.response.on_hover_ui_at_pointer(|ui| {
+            ui.strong(format!("#{}", c.id));
+            ui.monospace(&c.smiles);
+            ui.label(format!("Eff {} | Tox {}", state.fmt_val(c.efficacy), state.fmt_val(c.toxicity)));
+            ui.label(format!("Syn {} | Mfg {}", state.fmt_val(c.synthesis_cost), state.fmt_val(c.manufacturing_cost)));
+            ui.label(format!("Score {}", state.fmt_val(state.cached_score(c.id))));
         });
+    } else if main_plot {
+        plot.response.on_hover_text("Shift+drag to box-select candidates");
+    }
+    plot.inner
+}
+
+/// Plotted position of a value on a linear or log10 axis
+fn axis_scale(value: f32, log: bool) -> f32 {
+    if log { safe_log10(value) } else { value }
+}
+
+fn axis_label(label: &str, log: bool) -> String {
+    if log { format!("log₁₀ {}", label) } else { label.to_string() }
+}
+
+/// Staircase through the 2D non-dominated points (minimize x, maximize y), sorted by x.
+/// Each step runs horizontally to the next point's x, then up to its y.
+fn pareto_staircase<F1, F2>(candidates: &[&Candidate], x_fn: F1, y_fn: F2) -> Vec<[f64; 2]>
+where
+    F1: Fn(&Candidate) -> f32,
+    F2: Fn(&Candidate) -> f32,
+{
+    let mut points: Vec<[f64; 2]> = candidates.iter()
+        .map(|c| [x_fn(c) as f64, y_fn(c) as f64])
+        .collect();
+    // Best y first among equal x so ties keep only the dominant point
+    points.sort_by(|a, b| a[0].total_cmp(&b[0]).then(b[1].total_cmp(&a[1])));
+
+    let mut staircase: Vec<[f64; 2]> = Vec::new();
+    for [x, y] in points {
+        match staircase.last() {
+            Some(&[_, best]) if y <= best => continue,
+            Some(&[_, best]) => staircase.push([x, best]),
+            None => {}
+        }
+        staircase.push([x, y]);
+    }
+    staircase
+}
+
+/// Ids of candidates whose plotted point lies inside `rect` (edges inclusive)
+fn points_in_rect<F1, F2>(candidates: &[&Candidate], x_fn: F1, y_fn: F2, rect: PlotBounds) -> Vec<usize>
+where
+    F1: Fn(&Candidate) -> f32,
+    F2: Fn(&Candidate) -> f32,
+{
+    let (min, max) = (rect.min(), rect.max());
+    candidates.iter()
+        .filter(|c| {
+            let (x, y) = (x_fn(c) as f64, y_fn(c) as f64);
+            (min[0]..=max[0]).contains(&x) && (min[1]..=max[1]).contains(&y)
+        })
+        .map(|c| c.id)
+        .collect()
+}
+
+fn axis_combo(ui: &mut egui::Ui, id: &str, var: &mut AxisVar) {
+    egui::ComboBox::from_id_source(id)
+        .selected_text(var.label())
+        .width(110.0)
+        .show_ui(ui, |ui| {
+            for option in AxisVar::ALL {
+                ui.selectable_value(var, option, option.label());
+            }
+        });
+}
+
+/// Objectives of the clicked reference drug
+fn render_reference_info(ui: &mut egui::Ui, state: &mut AppState, name: &'static str) {
+    let Some(reference) = objectives::reference_drugs().iter().find(|r| r.name == name) else {
+        return;
+    };
+    let c = &reference.candidate;
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new(format!("◆ {}", name)).strong().color(egui::Color32::from_rgb(120, 170, 255)));
+        ui.label(format!(
+            "Eff {} | Tox {} | Syn {} | Mfg {} | Score {}",
+            state.fmt_val(c.efficacy), state.fmt_val(c.toxicity), state.fmt_val(c.synthesis_cost),
+            state.fmt_val(c.manufacturing_cost), state.fmt_val(state.weighted_score(c))
+        ));
+        ui.monospace(&c.smiles);
+        if ui.small_button("✖").clicked() {
+            state.selected_reference = None;
+        }
+    });
 }
 
 fn render_table(ui: &mut egui::Ui, state: &mut AppState, rows: &[Candidate]) {
-    egui::Grid::new("candidates_grid")
+    let row_height = 20.0;
+    let stats = state.table_objective_stats(rows);
+    let range = |k: usize| (stats.count > 0).then(|| (stats.min[k], stats.max[k]));
+
+    let mut table = TableBuilder::new(ui);
+    if std::mem::take(&mut state.scroll_to_selected) {
+        if let Some(&row) = state.selected_id.and_then(|id| state.table_positions.get(&id)) {
+            table = table.scroll_to_row(row, None);
+        }
+    }
+
+    table
         .striped(true)
-        .min_col_width(40.0)
-        .show(ui, |ui| {
-            // Header
-            ui.strong("");
-            ui.strong("⭐");
-            ui.strong("ID");
-            ui.strong("SMILES");
-            ui.strong("Eff");
-            ui.strong("Tox");
-            ui.strong("Syn");
-            ui.strong("Mfg");
-            ui.strong("Score");
-            ui.strong("P");
-            ui.end_row();
-
-            for c in rows.iter().take(1500) {
+        .sense(egui::Sense::click())
+        .vscroll(true)
+        .min_scrolled_height(300.0)
+        .max_scroll_height(500.0)
+        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+        .column(Column::exact(24.0))
+        .column(Column::exact(24.0))
+        .column(Column::exact(24.0))
+        .column(Column::auto().at_least(40.0))
+        .column(Column::initial(180.0).at_least(80.0).clip(true).resizable(true))
+        .columns(Column::auto().at_least(50.0), 5)
+        .column(Column::exact(24.0))
+        .header(row_height * 2.0, |mut header| {
+            header.col(|ui| { ui.strong(""); });
+            header.col(|ui| { ui.strong("⭐"); });
+            header.col(|ui| { ui.strong("🔒"); });
+            header.col(|ui| sort_header(ui, state, "ID", SortColumn::Id));
+            header.col(|ui| { ui.strong("SMILES"); });
+            header.col(|ui| range_header(ui, state, "Eff", SortColumn::Efficacy, range(0)));
+            header.col(|ui| range_header(ui, state, "Tox", SortColumn::Toxicity, range(1)));
+            header.col(|ui| range_header(ui, state, "Syn", SortColumn::SynthesisCost, range(2)));
+            header.col(|ui| range_header(ui, state, "Mfg", SortColumn::ManufacturingCost, range(3)));
+            header.col(|ui| range_header(ui, state, "Score", SortColumn::Score, stats.score));
+            header.col(|ui| { ui.strong("P"); });
+        })
+        .body(|body| {
+            // Only the visible rows are built each frame
+            body.rows(row_height, rows.len(), |mut row| {
+                let c = &rows[row.index()];
                 let selected = state.selected_id == Some(c.id);
                 let is_fav = state.annotations.is_favorite(c.id);
-                
-                if ui.selectable_label(selected, if selected { "▶" } else { "○" }).clicked() {
+                row.set_selected(selected || state.selected_ids.contains(&c.id));
+
+                row.col(|ui| {
+                    if ui.selectable_label(selected, if selected { "▶" } else { "○" }).clicked() {
+                        state.selected_id = Some(c.id);
+                    }
+                });
+
+                // Favorite
+                row.col(|ui| {
+                    ui.label(if is_fav { "⭐" } else { "" });
+                });
+
+                // Lock
+                row.col(|ui| {
+                    let locked = state.annotations.is_locked(c.id);
+                    if ui.selectable_label(locked, if locked { "🔒" } else { "🔓" })
+                        .on_hover_text("Keep this candidate when clearing")
+                        .clicked()
+                    {
+                        state.toggle_lock(c.id);
+                    }
+                });
+
+                row.col(|ui| {
+                    ui.label(c.id.to_string());
+                });
+
+                row.col(|ui| {
+                    let smiles_display = if c.smiles.len() > 20 {
+                        format!("{}...", &c.smiles[..20])
+                    } else {
+                        c.smiles.clone()
+                    };
+                    let confidence = descriptors::descriptor_confidence(&c.smiles);
+                    if confidence < descriptors::LOW_CONFIDENCE {
+                        ui.colored_label(egui::Color32::from_rgb(255, 180, 0), "⚠")
+                            .on_hover_text(format!("Descriptor confidence {:.0}%: estimates may be unreliable", confidence * 100.0));
+                    }
+                    if ui.monospace(smiles_display).on_hover_text(&c.smiles).clicked() {
+                        state.selected_id = Some(c.id);
+                    }
+                });
+
+                row.col(|ui| {
+                    ui.colored_label(color_for_value(c.efficacy, true), state.fmt_val(c.efficacy));
+                });
+                row.col(|ui| {
+                    ui.colored_label(color_for_value(c.toxicity, false), state.fmt_val(c.toxicity));
+                });
+                row.col(|ui| {
+                    ui.label(state.fmt_val(c.synthesis_cost));
+                });
+                row.col(|ui| {
+                    ui.label(state.fmt_val(c.manufacturing_cost));
+                });
+
+                let score = state.cached_score(c.id);
+                row.col(|ui| {
+                    ui.colored_label(color_for_score(score), state.fmt_val(score));
+                });
+
+                row.col(|ui| {
+                    if c.pareto { ui.colored_label(egui::Color32::from_rgb(0, 200, 100), "✓"); }
+                });
+
+                // Click selects, double-click opens the inspector
+                let response = row.response();
+                if response.clicked() {
                     state.selected_id = Some(c.id);
                 }
-                
-                // Favorite
-                let fav_text = if is_fav { "⭐" } else { "" };
-                ui.label(fav_text);
-                
-                ui.label(c.id.to_string());
-                
-                let smiles_display = if c.smiles.len() > 20 {
-                    format!("{}...", &c.smiles[..20])
-                } else {
-                    c.smiles.clone()
-                };
-                if ui.monospace(smiles_display).on_hover_text(&c.smiles).clicked() {
+                if response.double_clicked() {
                     state.selected_id = Some(c.id);
+                    state.show_inspector = true;
+                }
+            });
+        });
+}
+
+/// Ids of the best `n` candidates by weighted score, best first
+pub fn top_candidates(state: &AppState, n: usize) -> Vec<usize> {
+    let mut scored: Vec<(usize, f32)> = state.candidates.iter()
+        .map(|c| (c.id, state.cached_score(c.id)))
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    scored.into_iter().take(n).map(|(id, _)| id).collect()
+}
+
+/// Non-Pareto candidates that the weighted score ranks in the top `top_k`, best first.
+/// Non-empty means the weights favor dominated trade-offs over front members.
+pub fn dominated_but_top_ranked(candidates: &[Candidate], state: &AppState, top_k: usize) -> Vec<usize> {
+    let mut scored: Vec<(&Candidate, f32)> = candidates.iter()
+        .map(|c| (c, state.weighted_score(c)))
+        .collect();
+    let by_rank = |a: &(&Candidate, f32), b: &(&Candidate, f32)| b.1.total_cmp(&a.1).then(a.0.id.cmp(&b.0.id));
+    if top_k < scored.len() {
+        scored.select_nth_unstable_by(top_k, by_rank);
+        scored.truncate(top_k);
+    }
+    scored.sort_by(by_rank);
+    scored.into_iter()
+        .filter(|(c, _)| !c.pareto)
+        .map(|(c, _)| c.id)
+        .collect()
+}
+
+fn render_top_candidates(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.horizontal(|ui| {
+        ui.label("Show top:");
+        ui.add(egui::DragValue::new(&mut state.top_n).clamp_range(1..=100));
+    });
+
+    // Re-rank only when candidates or weights changed
+    let key = state.score_key();
+    if state.top_cache.as_ref().map(|(k, _)| k) != Some(&key) {
+        state.top_cache = Some((key, top_candidates(state, state.candidates.len())));
+    }
+
+    let ids: Vec<usize> = state.top_cache.as_ref()
+        .map(|(_, ids)| ids.iter().take(state.top_n).copied().collect())
+        .unwrap_or_default();
+
+    if ids.is_empty() {
+        ui.label("No candidates yet");
+        return;
+    }
+
+    egui::Grid::new("top_candidates")
+        .num_columns(4)
+        .striped(true)
+        .show(ui, |ui| {
+            for (rank, id) in ids.into_iter().enumerate() {
+                let Some(c) = state.candidates.iter().find(|c| c.id == id) else {
+                    continue;
+                };
+                let score = state.cached_score(id);
+                let smiles = c.smiles.clone();
+
+                ui.label(format!("{}.", rank + 1));
+                if ui.selectable_label(state.selected_id == Some(id), format!("#{}", id)).clicked() {
+                    state.selected_id = Some(id);
                 }
-                
-                ui.colored_label(color_for_value(c.efficacy, true), format!("{:.3}", c.efficacy));
-                ui.colored_label(color_for_value(c.toxicity, false), format!("{:.3}", c.toxicity));
-                ui.label(format!("{:.3}", c.synthesis_cost));
-                ui.label(format!("{:.3}", c.manufacturing_cost));
-                
-                let score = state.weighted_score(c);
-                ui.colored_label(color_for_score(score), format!("{:.3}", score));
-                
-                if c.pareto { ui.colored_label(egui::Color32::from_rgb(0, 200, 100), "✓"); } else { ui.label(""); }
-                
+                ui.monospace(smiles);
+                ui.colored_label(color_for_score(score), state.fmt_val(score));
                 ui.end_row();
             }
         });
-    
-    if rows.len() > 1500 {
-        ui.label(format!("... +{} more", rows.len() - 1500));
+}
+
+/// Clickable column header: selects the column, or flips direction if already active
+fn sort_header(ui: &mut egui::Ui, state: &mut AppState, label: &str, column: SortColumn) {
+    let active = state.sort_column == column;
+    let text = if active {
+        format!("{} {}", label, if state.sort_ascending { "▲" } else { "▼" })
+    } else {
+        label.to_string()
+    };
+
+    let response = ui.add(egui::Label::new(egui::RichText::new(text).strong()).sense(egui::Sense::click()));
+    if response.on_hover_text("Click to sort").clicked() {
+        if active {
+            state.sort_ascending = !state.sort_ascending;
+        } else {
+            state.sort_column = column;
+            state.sort_ascending = column.default_ascending();
+        }
     }
 }
 
+/// Sort header with the column's min–max over the shown rows underneath
+fn range_header(ui: &mut egui::Ui, state: &mut AppState, label: &str, column: SortColumn, range: Option<(f32, f32)>) {
+    ui.vertical(|ui| {
+        sort_header(ui, state, label, column);
+        if let Some((min, max)) = range {
+            ui.weak(egui::RichText::new(format!("{}–{}", state.fmt_val(min), state.fmt_val(max))).small())
+                .on_hover_text("Min–max over the filtered candidates");
+        }
+    });
+}
+
+/// Sort table rows by the given column and direction
+pub fn sort_rows(rows: &mut [Candidate], column: SortColumn, ascending: bool, state: &AppState) {
+    // Rows outside the cache (e.g. not yet refreshed) are scored directly
+    let score = |c: &Candidate| state.score_cache.get(&c.id).copied().unwrap_or_else(|| state.weighted_score(c));
+    rows.sort_by(|a, b| {
+        let ord = match column {
+            SortColumn::Id => a.id.cmp(&b.id),
+            SortColumn::Efficacy => a.efficacy.total_cmp(&b.efficacy),
+            SortColumn::Toxicity => a.toxicity.total_cmp(&b.toxicity),
+            SortColumn::SynthesisCost => a.synthesis_cost.total_cmp(&b.synthesis_cost),
+            SortColumn::ManufacturingCost => a.manufacturing_cost.total_cmp(&b.manufacturing_cost),
+            SortColumn::Score => score(a).total_cmp(&score(b)),
+        };
+        if ascending { ord } else { ord.reverse() }
+    });
+}
+
 fn color_for_value(value: f32, higher_is_better: bool) -> egui::Color32 {
     let normalized = value.clamp(0.0, 1.0);
     let good = if higher_is_better { normalized } else { 1.0 - normalized };
@@ -218,3 +845,160 @@ fn color_for_score(score: f32) -> egui::Color32 {
     let normalized = ((score + 2.0) / 4.0).clamp(0.0, 1.0);
     egui::Color32::from_rgb(((1.0 - normalized) * 200.0) as u8, (normalized * 200.0) as u8, 80)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_candidate(id: usize, eff: f32, tox: f32) -> Candidate {
+        Candidate {
+            id,
+            smiles: format!("C{}", id),
+            efficacy: eff,
+            toxicity: tox,
+            synthesis_cost: 0.1 * id as f32,
+            manufacturing_cost: 0.2,
+            pareto: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_nearest_candidate() {
+        let points = [(3, [100.0, 100.0]), (7, [104.0, 100.0]), (9, [300.0, 50.0])];
+        assert_eq!(nearest_candidate(&points, [101.0, 100.0]), Some(3));
+        assert_eq!(nearest_candidate(&points, [103.5, 101.0]), Some(7));
+        assert_eq!(nearest_candidate(&points, [305.0, 55.0]), Some(9));  // √50 ≈ 7.1 px
+        // Too far from every point, or nothing to hover
+        assert_eq!(nearest_candidate(&points, [200.0, 200.0]), None);
+        assert_eq!(nearest_candidate(&[], [0.0, 0.0]), None);
+    }
+
+    #[test]
+    fn test_points_in_rect() {
+        let cands = [
+            make_candidate(0, 0.25, 0.25),
+            make_candidate(1, 0.5, 0.5),
+            make_candidate(2, 0.8, 0.3),
+            make_candidate(3, 0.6, 0.9),
+        ];
+        let refs: Vec<&Candidate> = cands.iter().collect();
+        let tox = |c: &Candidate| c.toxicity;
+        let eff = |c: &Candidate| c.efficacy;
+
+        let rect = PlotBounds::from_min_max([0.25, 0.4], [0.6, 0.9]);
+        assert_eq!(points_in_rect(&refs, tox, eff, rect), vec![1, 2]);
+
+        // Edges are inclusive
+        let rect = PlotBounds::from_min_max([0.25, 0.25], [0.5, 0.25]);
+        assert_eq!(points_in_rect(&refs, tox, eff, rect), vec![0]);
+
+        // Axis variables decide which coordinates are tested
+        let rect = PlotBounds::from_min_max([0.55, 0.85], [0.65, 0.95]);
+        assert_eq!(points_in_rect(&refs, eff, tox, rect), vec![3]);
+        assert!(points_in_rect(&refs, tox, eff, rect).is_empty());
+    }
+
+    #[test]
+    fn test_dominated_but_top_ranked() {
+        let candidates = vec![
+            Candidate { id: 0, efficacy: 1.0, toxicity: 0.1, synthesis_cost: 0.1, manufacturing_cost: 0.1, ..Default::default() },
+            // Dominated by 0, yet scores above the low-toxicity front member
+            Candidate { id: 1, efficacy: 0.9, toxicity: 0.2, synthesis_cost: 0.2, manufacturing_cost: 0.2, ..Default::default() },
+            Candidate { id: 2, efficacy: 0.2, toxicity: 0.0, synthesis_cost: 0.0, manufacturing_cost: 0.0, ..Default::default() },
+        ];
+        let mut state = AppState { candidates, ..Default::default() };
+        state.recompute_pareto();
+        assert!(!state.candidates[1].pareto);
+
+        assert!(dominated_but_top_ranked(&state.candidates, &state, 1).is_empty());
+        assert_eq!(dominated_but_top_ranked(&state.candidates, &state, 2), vec![1]);
+
+        // Weighting toxicity heavily pushes the dominated candidate out of the top 2
+        state.w_tox = 10.0;
+        assert!(dominated_but_top_ranked(&state.candidates, &state, 2).is_empty());
+    }
+
+    #[test]
+    fn test_pareto_staircase_monotone() {
+        let cands = [
+            make_candidate(0, 0.5, 0.25),
+            make_candidate(1, 0.25, 0.5),   // dominated by 0
+            make_candidate(2, 0.75, 0.5),
+            make_candidate(3, 0.25, 0.0),
+            make_candidate(4, 1.0, 0.75),
+            make_candidate(5, 0.5, 0.75),   // dominated by 4
+        ];
+        let refs: Vec<&Candidate> = cands.iter().collect();
+        let stairs = pareto_staircase(&refs, |c| c.toxicity, |c| c.efficacy);
+
+        assert_eq!(stairs, vec![
+            [0.0, 0.25],
+            [0.25, 0.25], [0.25, 0.5],
+            [0.5, 0.5], [0.5, 0.75],
+            [0.75, 0.75], [0.75, 1.0],
+        ]);
+        for pair in stairs.windows(2) {
+            assert!(pair[1][0] >= pair[0][0]);
+            assert!(pair[1][1] >= pair[0][1]);
+        }
+        assert!(pareto_staircase(&[], |c| c.toxicity, |c| c.efficacy).is_empty());
+    }
+
+    #[test]
+    fn test_sort_rows() {
+        let state = AppState::default();
+        let mut rows = vec![
+            make_candidate(0, 0.5, 0.5),
+            make_candidate(1, 0.9, 0.1),
+            make_candidate(2, 0.1, 0.9),
+        ];
+
+        sort_rows(&mut rows, SortColumn::Efficacy, false, &state);
+        assert_eq!(rows.iter().map(|c| c.id).collect::<Vec<_>>(), vec![1, 0, 2]);
+
+        sort_rows(&mut rows, SortColumn::Toxicity, true, &state);
+        assert_eq!(rows.iter().map(|c| c.id).collect::<Vec<_>>(), vec![1, 0, 2]);
+
+        sort_rows(&mut rows, SortColumn::Id, false, &state);
+        assert_eq!(rows.iter().map(|c| c.id).collect::<Vec<_>>(), vec![2, 1, 0]);
+
+        // Default: weighted score, best first
+        sort_rows(&mut rows, SortColumn::Score, false, &state);
+        assert_eq!(rows[0].id, 1);
+        assert_eq!(rows[2].id, 2);
+    }
+
+    #[test]
+    fn test_top_candidates() {
+        let state = AppState {
+            candidates: vec![
+                make_candidate(0, 0.5, 0.5),
+                make_candidate(1, 0.9, 0.1),
+                make_candidate(2, 0.1, 0.9),
+                make_candidate(3, 0.8, 0.2),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(top_candidates(&state, 2), vec![1, 3]);
+        assert_eq!(top_candidates(&state, 10), vec![1, 3, 0, 2]);
+        assert!(top_candidates(&state, 0).is_empty());
+    }
+
+    #[test]
+    fn test_sort_large_table() {
+        let state = AppState::default();
+        let mut rows: Vec<Candidate> = (0..20_000)
+            .map(|i| make_candidate(i, (i % 97) as f32 / 97.0, (i % 89) as f32 / 89.0))
+            .collect();
+
+        for column in [SortColumn::Id, SortColumn::Efficacy, SortColumn::Toxicity, SortColumn::Score] {
+            sort_rows(&mut rows, column, false, &state);
+            assert_eq!(rows.len(), 20_000);
+        }
+
+        sort_rows(&mut rows, SortColumn::Id, true, &state);
+        assert!(rows.iter().enumerate().all(|(i, c)| c.id == i));
+    }
+}
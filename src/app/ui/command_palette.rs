@@ -0,0 +1,304 @@
+//! Command palette (Ctrl+P): every action in one searchable list
+
+use eframe::egui;
+use crate::app::state::{AppState, WeightPreset};
+use super::top_bar;
+
+/// Panels that the palette can show or hide
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum View {
+    Histograms,
+    ParallelCoords,
+    Plot3d,
+    Heatmap,
+    ReferenceDrugs,
+    Clustering,
+    ScaffoldGroups,
+    SimilaritySearch,
+    Druglikeness,
+    SeedSweep,
+    Preference,
+    Log,
+}
+
+impl View {
+    pub const ALL: [View; 12] = [
+        View::Histograms,
+        View::ParallelCoords,
+        View::Plot3d,
+        View::Heatmap,
+        View::ReferenceDrugs,
+        View::Clustering,
+        View::ScaffoldGroups,
+        View::SimilaritySearch,
+        View::Druglikeness,
+        View::SeedSweep,
+        View::Preference,
+        View::Log,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            View::Histograms => "Histograms",
+            View::ParallelCoords => "Parallel Coordinates",
+            View::Plot3d => "3D Plot",
+            View::Heatmap => "Correlation Heatmap",
+            View::ReferenceDrugs => "Reference Drugs on Plots",
+            View::Clustering => "Clustering",
+            View::ScaffoldGroups => "Group by Scaffold",
+            View::SimilaritySearch => "Similarity Search",
+            View::Druglikeness => "Drug-likeness Panel",
+            View::SeedSweep => "Seed Sweep",
+            View::Preference => "Preference Learning",
+            View::Log => "Log",
+        }
+    }
+
+    fn flag(self, state: &mut AppState) -> &mut bool {
+        match self {
+            View::Histograms => &mut state.show_histograms,
+            View::ParallelCoords => &mut state.show_parallel_coords,
+            View::Plot3d => &mut state.show_3d_plot,
+            View::Heatmap => &mut state.show_heatmap,
+            View::ReferenceDrugs => &mut state.show_reference_drugs,
+            View::Clustering => &mut state.show_clustering,
+            View::ScaffoldGroups => &mut state.show_scaffold_groups,
+            View::SimilaritySearch => &mut state.show_similarity_search,
+            View::Druglikeness => &mut state.show_druglikeness,
+            View::SeedSweep => &mut state.show_seed_sweep,
+            View::Preference => &mut state.show_preference,
+            View::Log => &mut state.show_log,
+        }
+    }
+}
+
+/// An action reachable from the palette
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    Generate,
+    Evolve,
+    ClearAll,
+    Undo,
+    Redo,
+    SaveSession,
+    ExportCsv,
+    ExportJson,
+    ExportSdf,
+    Toggle(View),
+    ApplyPreset(WeightPreset),
+}
+
+impl Command {
+    /// Every command, in the order listed for an empty query
+    pub fn all() -> Vec<Command> {
+        let mut commands = vec![
+            Command::Generate,
+            Command::Evolve,
+            Command::Undo,
+            Command::Redo,
+            Command::ClearAll,
+            Command::SaveSession,
+            Command::ExportCsv,
+            Command::ExportJson,
+            Command::ExportSdf,
+        ];
+        commands.extend(View::ALL.map(Command::Toggle));
+        commands.extend(WeightPreset::ALL.map(Command::ApplyPreset));
+        commands
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Command::Generate => "Generate candidates".into(),
+            Command::Evolve => "Evolve selection".into(),
+            Command::ClearAll => "Clear all candidates".into(),
+            Command::Undo => "Undo".into(),
+            Command::Redo => "Redo".into(),
+            Command::SaveSession => "Save session".into(),
+            Command::ExportCsv => "Export CSV".into(),
+            Command::ExportJson => "Export JSON".into(),
+            Command::ExportSdf => "Export SDF".into(),
+            Command::Toggle(view) => format!("Toggle {}", view.label()),
+            Command::ApplyPreset(preset) => format!("Weights: {}", preset.label()),
+        }
+    }
+}
+
+/// Run a command against the state; unavailable ones (undo with no history,
+/// generate while generating) do nothing
+pub fn dispatch(state: &mut AppState, command: Command) {
+    match command {
+        Command::Generate => {
+            if !state.is_generating {
+                state.generate();
+            }
+        }
+        Command::Evolve => {
+            if !state.is_generating {
+                state.evolve();
+            }
+        }
+        Command::ClearAll => state.clear(),
+        Command::Undo => {
+            if state.history.can_undo() {
+                state.undo();
+            }
+        }
+        Command::Redo => {
+            if state.history.can_redo() {
+                state.redo();
+            }
+        }
+        Command::SaveSession => top_bar::save_session_dialog(state),
+        Command::ExportCsv => top_bar::export_csv(state),
+        Command::ExportJson => top_bar::export_json(state),
+        Command::ExportSdf => top_bar::export_sdf(state),
+        Command::Toggle(view) => {
+            let flag = view.flag(state);
+            *flag = !*flag;
+        }
+        Command::ApplyPreset(preset) => state.apply_weight_preset(preset),
+    }
+}
+
+/// Fuzzy match of `query` against `label`, case-insensitive. The query's characters
+/// must appear in order; None when they don't. Higher is better: runs of consecutive
+/// characters and matches at word starts score extra, skipped characters cost a little.
+pub fn fuzzy_score(query: &str, label: &str) -> Option<i32> {
+    let label: Vec<char> = label.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut next = 0;  // first label position still unmatched
+    let mut previous: Option<usize> = None;
+
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = next + label[next..].iter().position(|&c| c == q)?;
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !label[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        score -= (found - next) as i32 / 2;
+        previous = Some(found);
+        next = found + 1;
+    }
+    Some(score)
+}
+
+/// Commands matching `query`, best first; ties keep the listing order
+pub fn ranked_commands(query: &str) -> Vec<Command> {
+    let mut scored: Vec<(i32, Command)> = Command::all().into_iter()
+        .filter_map(|c| fuzzy_score(query, &c.label()).map(|score| (score, c)))
+        .collect();
+    scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+    scored.into_iter().map(|(_, c)| c).collect()
+}
+
+pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    if !state.show_command_palette {
+        return;
+    }
+
+    let matches = ranked_commands(&state.command_query);
+    state.command_index = state.command_index.min(matches.len().saturating_sub(1));
+
+    // Consumed before the text field sees them
+    let (up, down, enter, escape) = ctx.input_mut(|i| (
+        i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+        i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+        i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+        i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+    ));
+    if up {
+        state.command_index = state.command_index.saturating_sub(1);
+    }
+    if down && state.command_index + 1 < matches.len() {
+        state.command_index += 1;
+    }
+
+    let mut chosen = enter.then(|| matches.get(state.command_index).copied()).flatten();
+    egui::Window::new("⌨ Commands")
+        .collapsible(false)
+        .resizable(false)
+        .title_bar(false)
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 60.0])
+        .fixed_size([360.0, 320.0])
+        .show(ctx, |ui| {
+            let query = ui.add(
+                egui::TextEdit::singleline(&mut state.command_query)
+                    .hint_text("Type a command…")
+                    .desired_width(f32::INFINITY)
+            );
+            query.request_focus();
+            if query.changed() {
+                state.command_index = 0;
+            }
+            ui.separator();
+
+            egui::ScrollArea::vertical().max_height(280.0).show(ui, |ui| {
+                if matches.is_empty() {
+                    ui.weak("No matching command");
+                }
+                for (i, &command) in matches.iter().enumerate() {
+                    let highlighted = i == state.command_index;
+                    let row = ui.selectable_label(highlighted, command.label());
+                    if highlighted && (up || down) {
+                        row.scroll_to_me(None);
+                    }
+                    if row.clicked() {
+                        chosen = Some(command);
+                    }
+                }
+            });
+        });
+
+    if chosen.is_some() || escape {
+        state.show_command_palette = false;
+        state.command_query.clear();
+        state.command_index = 0;
+    }
+    if let Some(command) = chosen {
+        dispatch(state, command);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score_ranking() {
+        // Subsequences match, anything else doesn't
+        assert!(fuzzy_score("csvx", "Export CSV").is_none());
+        assert!(fuzzy_score("vsc", "Export CSV").is_none());
+        assert_eq!(fuzzy_score("", "Export CSV"), Some(0));
+
+        // Contiguous, word-start matches beat scattered ones
+        let contiguous = fuzzy_score("csv", "Export CSV").unwrap();
+        let scattered = fuzzy_score("csv", "Clear session values").unwrap();
+        assert!(contiguous > scattered);
+        assert!(fuzzy_score("hist", "Toggle Histograms") > fuzzy_score("hist", "Toggle Preference Learning: history"));
+
+        let ranked = ranked_commands("exp sdf");
+        assert_eq!(ranked.first(), Some(&Command::ExportSdf));
+        let ranked = ranked_commands("undo");
+        assert_eq!(ranked.first(), Some(&Command::Undo));
+        assert_eq!(ranked_commands("").len(), Command::all().len());
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let mut state = AppState::default();
+        let shown = state.show_histograms;
+        dispatch(&mut state, Command::Toggle(View::Histograms));
+        assert_eq!(state.show_histograms, !shown);
+
+        dispatch(&mut state, Command::ApplyPreset(WeightPreset::SafetyFirst));
+        assert_eq!([state.w_eff, state.w_tox, state.w_syn, state.w_mfg], WeightPreset::SafetyFirst.weights());
+
+        // Nothing to undo is a no-op
+        dispatch(&mut state, Command::Undo);
+        assert!(state.candidates.is_empty());
+    }
+}
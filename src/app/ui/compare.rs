@@ -0,0 +1,175 @@
+//! Side-by-side comparison of two candidates
+
+use eframe::egui;
+use crate::app::state::{AppState, Candidate};
+use crate::chemistry::{descriptors, druglikeness};
+
+/// Which side of a comparison row is preferable
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Better {
+    A,
+    B,
+    Equal,
+    Neither,  // informational row, no preference
+}
+
+/// Direction in which a value improves
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Goal {
+    Maximize,
+    Minimize,
+    Info,
+}
+
+/// One line of the comparison table
+#[derive(Clone, Debug)]
+pub struct ComparisonRow {
+    pub label: &'static str,
+    pub a: f32,
+    pub b: f32,
+    pub better: Better,
+}
+
+pub type ComparisonRows = Vec<ComparisonRow>;
+
+fn row(label: &'static str, a: f32, b: f32, goal: Goal) -> ComparisonRow {
+    let better = match goal {
+        Goal::Info => Better::Neither,
+        _ if (a - b).abs() < 1e-6 => Better::Equal,
+        Goal::Maximize => if a > b { Better::A } else { Better::B },
+        Goal::Minimize => if a < b { Better::A } else { Better::B },
+    };
+    ComparisonRow { label, a, b, better }
+}
+
+/// Build the comparison table for two candidates
+pub fn compare(a: &Candidate, b: &Candidate, state: &AppState) -> ComparisonRows {
+    let (da, db) = (descriptors::compute_descriptors(&a.smiles), descriptors::compute_descriptors(&b.smiles));
+    let (la, lb) = (druglikeness::assess_druglikeness(&a.smiles), druglikeness::assess_druglikeness(&b.smiles));
+
+    vec![
+        row("Efficacy", a.efficacy, b.efficacy, Goal::Maximize),
+        row("Toxicity", a.toxicity, b.toxicity, Goal::Minimize),
+        row("Synthesis cost", a.synthesis_cost, b.synthesis_cost, Goal::Minimize),
+        row("Manufacturing cost", a.manufacturing_cost, b.manufacturing_cost, Goal::Minimize),
+        row("Weighted score", state.weighted_score(a), state.weighted_score(b), Goal::Maximize),
+        row("Drug-likeness", la.overall_score, lb.overall_score, Goal::Maximize),
+        row("Lipinski violations", la.lipinski.violations as f32, lb.lipinski.violations as f32, Goal::Minimize),
+        row("PAINS alerts", la.pains_hits.len() as f32, lb.pains_hits.len() as f32, Goal::Minimize),
+        row("SA score", da.sa_score, db.sa_score, Goal::Minimize),
+        row("Molecular weight", da.molecular_weight, db.molecular_weight, Goal::Info),
+        row("LogP", da.logp, db.logp, Goal::Info),
+        row("PSA (Ų)", da.psa, db.psa, Goal::Info),
+        row("H-bond donors", da.hbd as f32, db.hbd as f32, Goal::Info),
+        row("H-bond acceptors", da.hba as f32, db.hba as f32, Goal::Info),
+    ]
+}
+
+/// Render the comparison window while a candidate is pinned
+pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    let Some(pinned) = state.compare_id else {
+        return;
+    };
+
+    let mut open = true;
+
+    egui::Window::new("⚖ Compare")
+        .open(&mut open)
+        .resizable(true)
+        .default_width(460.0)
+        .show(ctx, |ui| {
+            let a = state.candidates.iter().find(|c| c.id == pinned);
+            let b = state.selected_id
+                .filter(|&id| id != pinned)
+                .and_then(|id| state.candidates.iter().find(|c| c.id == id));
+
+            let (Some(a), Some(b)) = (a, b) else {
+                ui.label(format!("📌 Pinned #{} - select another candidate to compare", pinned));
+                return;
+            };
+
+            egui::Grid::new("compare_smiles")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.strong(format!("📌 #{}", a.id));
+                    ui.monospace(&a.smiles);
+                    ui.end_row();
+                    ui.strong(format!("#{}", b.id));
+                    ui.monospace(&b.smiles);
+                    ui.end_row();
+                });
+
+            ui.separator();
+
+            let rows = compare(a, b, state);
+            egui::Grid::new("compare_rows")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("");
+                    ui.strong(format!("📌 #{}", a.id));
+                    ui.strong(format!("#{}", b.id));
+                    ui.end_row();
+
+                    for r in &rows {
+                        ui.label(r.label);
+                        value_cell(ui, r.a, r.better, Better::A);
+                        value_cell(ui, r.b, r.better, Better::B);
+                        ui.end_row();
+                    }
+                });
+        });
+
+    if !open {
+        state.compare_id = None;
+    }
+}
+
+fn value_cell(ui: &mut egui::Ui, value: f32, better: Better, side: Better) {
+    let text = format!("{:.3}", value);
+    match better {
+        Better::Equal | Better::Neither => {
+            ui.label(text);
+        }
+        b if b == side => {
+            ui.colored_label(egui::Color32::from_rgb(100, 200, 100), format!("{} ▲", text));
+        }
+        _ => {
+            ui.colored_label(egui::Color32::from_rgb(255, 150, 100), format!("{} ▼", text));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_candidate(id: usize, efficacy: f32, toxicity: f32) -> Candidate {
+        Candidate {
+            id,
+            smiles: "CCO".into(),
+            efficacy,
+            toxicity,
+            synthesis_cost: 0.3,
+            manufacturing_cost: 0.3,
+            pareto: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_compare_arrows() {
+        let state = AppState::default();
+        let a = make_candidate(0, 0.8, 0.4);
+        let b = make_candidate(1, 0.6, 0.2);
+        let rows = compare(&a, &b, &state);
+        let better = |label: &str| rows.iter().find(|r| r.label == label).unwrap().better;
+
+        assert_eq!(better("Efficacy"), Better::A);         // higher wins
+        assert_eq!(better("Toxicity"), Better::B);         // lower wins
+        assert_eq!(better("Synthesis cost"), Better::Equal);
+        assert_eq!(better("Molecular weight"), Better::Neither);
+        // Score 0.8-0.4-0.6 = -0.2 vs 0.6-0.2-0.6 = -0.2 with unit weights
+        assert_eq!(better("Weighted score"), Better::Equal);
+    }
+}
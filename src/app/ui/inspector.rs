@@ -0,0 +1,253 @@
+//! Candidate inspector: full descriptor and drug-likeness breakdown
+
+use eframe::egui;
+use crate::app::state::{AppState, Candidate, LogLevel};
+use crate::chemistry::{descriptors, druglikeness, graph, layout, similarity};
+use super::structure;
+
+/// Render the inspector window for the currently selected candidate
+pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    if !state.show_inspector {
+        return;
+    }
+
+    let mut open = state.show_inspector;
+    let mut export_svg = None;
+    let mut select_parent = None;
+
+    egui::Window::new("🔎 Inspector")
+        .open(&mut open)
+        .resizable(true)
+        .default_width(360.0)
+        .show(ctx, |ui| {
+            let candidate = state.selected_id
+                .and_then(|id| state.candidates.iter().find(|c| c.id == id));
+
+            match candidate {
+                Some(c) => match render_candidate(ui, state, c) {
+                    Some(InspectorRequest::ExportSvg) => export_svg = Some((c.id, c.smiles.clone())),
+                    Some(InspectorRequest::Select(id)) => select_parent = Some(id),
+                    None => {}
+                },
+                None => {
+                    ui.label("Select a candidate to inspect");
+                }
+            }
+        });
+
+    state.show_inspector = open;
+    if select_parent.is_some() {
+        state.selected_id = select_parent;
+        state.scroll_to_selected = true;
+    }
+
+    if let Some((id, smiles)) = export_svg {
+        let filename = format!("structure_{}.svg", id);
+        let result = layout::molecule_to_svg(&smiles, 400, 300)
+            .ok_or_else(|| "could not lay out the structure".to_string())
+            .and_then(|svg| std::fs::write(&filename, svg).map_err(|e| format!("Write error: {}", e)));
+        match result {
+            Ok(()) => state.push_status(LogLevel::Success, format!("✅ Exported to {}", filename)),
+            Err(e) => state.push_status(LogLevel::Error, format!("❌ Export failed: {}", e)),
+        }
+    }
+}
+
+/// Action the user asked for from the inspector
+enum InspectorRequest {
+    ExportSvg,
+    Select(usize),  // jump to a parent candidate
+}
+
+fn render_candidate(ui: &mut egui::Ui, state: &AppState, c: &Candidate) -> Option<InspectorRequest> {
+    let d = descriptors::compute_descriptors(&c.smiles);
+    let result = druglikeness::assess_druglikeness(&c.smiles);
+
+    let mut request = None;
+
+    ui.heading(format!("Candidate #{}", c.id));
+    let formula = descriptors::molecular_formula(&c.smiles);
+    if !formula.is_empty() {
+        ui.label(egui::RichText::new(formula).strong().monospace());
+    }
+
+    ui.label("SMILES:");
+    ui.horizontal_wrapped(|ui| {
+        ui.monospace(&c.smiles);
+    });
+    ui.label("Canonical SMILES:");
+    ui.horizontal_wrapped(|ui| {
+        match graph::canonical_smiles(&c.smiles) {
+            Some(canon) => ui.monospace(canon),
+            None => ui.colored_label(egui::Color32::from_rgb(255, 150, 100), "Could not parse"),
+        };
+    });
+
+    if let Some(mol) = layout::layout_2d(&c.smiles) {
+        let (rect, _) = ui.allocate_exact_size(
+            egui::vec2(ui.available_width(), 220.0),
+            egui::Sense::hover(),
+        );
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 4.0, ui.visuals().extreme_bg_color);
+        structure::draw_molecule(&painter, &mol, rect);
+        if ui.small_button("💾 Export structure SVG").clicked() {
+            request = Some(InspectorRequest::ExportSvg);
+        }
+    }
+
+    ui.separator();
+
+    egui::Grid::new("inspector_objectives")
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("Efficacy");
+            ui.colored_label(egui::Color32::from_rgb(100, 200, 100), state.fmt_val(c.efficacy));
+            ui.end_row();
+            ui.label("Toxicity");
+            ui.colored_label(egui::Color32::from_rgb(255, 150, 100), state.fmt_val(c.toxicity));
+            ui.end_row();
+            ui.label("Synthesis cost");
+            ui.label(state.fmt_val(c.synthesis_cost));
+            ui.end_row();
+            ui.label("Manufacturing cost");
+            ui.label(state.fmt_val(c.manufacturing_cost));
+            ui.end_row();
+            ui.strong("Score");
+            ui.strong(state.fmt_val(state.weighted_score(c)));
+            ui.end_row();
+            ui.label("Source");
+            ui.label(c.source.to_string());
+            ui.end_row();
+            if let Some(run_id) = c.run_id {
+                ui.label("Run");
+                ui.label(format!("#{}", run_id));
+                ui.end_row();
+            }
+        });
+
+    if !c.parent_ids.is_empty() {
+        ui.collapsing("🧬 Lineage", |ui| {
+            for &parent_id in &c.parent_ids {
+                ui.horizontal(|ui| {
+                    match state.candidates.iter().find(|p| p.id == parent_id) {
+                        Some(parent) => {
+                            if ui.link(format!("#{}", parent_id)).on_hover_text("Select this parent").clicked() {
+                                request = Some(InspectorRequest::Select(parent_id));
+                            }
+                            ui.monospace(&parent.smiles);
+                            if !parent.parent_ids.is_empty() {
+                                ui.weak(format!("(child of {})", format_ids(&parent.parent_ids)));
+                            }
+                        }
+                        None => {
+                            ui.weak(format!("#{} (removed)", parent_id));
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    ui.separator();
+
+    ui.collapsing("🧪 Descriptors", |ui| {
+        egui::Grid::new("inspector_descriptors")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Molecular weight");
+                let unknown_atoms = descriptors::molecular_weight_detailed(&c.smiles).unknown_atoms;
+                if unknown_atoms > 0 {
+                    ui.colored_label(egui::Color32::from_rgb(255, 180, 0), format!("{:.2} ⚠", d.molecular_weight))
+                        .on_hover_text(format!("{} atom(s) of unknown mass not counted", unknown_atoms));
+                } else {
+                    ui.label(format!("{:.2}", d.molecular_weight));
+                }
+                ui.end_row();
+                ui.label("LogP");
+                ui.label(format!("{:.2}", d.logp));
+                ui.end_row();
+                ui.label("PSA (Ų)");
+                ui.label(format!("{:.2}", d.psa));
+                ui.end_row();
+                ui.label("H-bond donors");
+                ui.label(d.hbd.to_string());
+                ui.end_row();
+                ui.label("H-bond acceptors");
+                ui.label(d.hba.to_string());
+                ui.end_row();
+                ui.label("Rotatable bonds");
+                ui.label(d.rotatable_bonds.to_string());
+                ui.end_row();
+                ui.label("Heavy atoms");
+                ui.label(d.heavy_atoms.to_string());
+                ui.end_row();
+                ui.label("Rings");
+                ui.label(d.rings.to_string());
+                ui.end_row();
+                ui.label("SA score");
+                ui.label(format!("{:.2}", d.sa_score))
+                    .on_hover_text("Synthetic accessibility: 1 = easy, 10 = very hard");
+                ui.end_row();
+                ui.label("Novelty");
+                let novelty = similarity::novelty_score(&c.smiles, similarity::default_novelty_reference());
+                ui.label(format!("{:.2}", novelty))
+                    .on_hover_text("1 − highest Tanimoto similarity to the built-in drug scaffolds");
+                ui.end_row();
+            });
+    });
+
+    ui.collapsing("💊 Drug-likeness", |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Overall score:");
+            ui.strong(format!("{:.2}", result.overall_score));
+        });
+        ui.label(&result.recommendation);
+        ui.separator();
+
+        let lip = &result.lipinski;
+        rule_line(ui, lip.passed, &format!("Lipinski ({} violations)", lip.violations));
+        rule_line(ui, lip.mw_ok, "    MW ≤ 500");
+        rule_line(ui, lip.logp_ok, "    LogP ≤ 5");
+        rule_line(ui, lip.hbd_ok, "    H-bond donors ≤ 5");
+        rule_line(ui, lip.hba_ok, "    H-bond acceptors ≤ 10");
+
+        let veb = &result.veber;
+        rule_line(ui, veb.passed, "Veber");
+        rule_line(ui, veb.rotatable_bonds_ok, "    Rotatable bonds ≤ 10");
+        rule_line(ui, veb.psa_ok, "    PSA ≤ 140 Ų");
+
+        let ghose = &result.ghose;
+        rule_line(ui, ghose.passed, "Ghose");
+        rule_line(ui, ghose.mw_ok, "    160 ≤ MW ≤ 480");
+        rule_line(ui, ghose.logp_ok, "    -0.4 ≤ LogP ≤ 5.6");
+        rule_line(ui, ghose.atom_count_ok, "    20 ≤ atoms ≤ 70");
+
+        ui.separator();
+        if result.pains_alerts.is_empty() {
+            ui.colored_label(egui::Color32::from_rgb(100, 200, 100), "✅ No PAINS alerts");
+        } else {
+            ui.label(format!("⚠️ PAINS Alerts ({})", result.pains_alerts.len()));
+            ui.label(format!("Severity score: {:.2}", druglikeness::pains_severity_score(&c.smiles)))
+                .on_hover_text("Sum of alert weights: high 1, medium 0.5, low 0.25");
+            for alert in &result.pains_alerts {
+                ui.colored_label(egui::Color32::from_rgb(255, 150, 100), alert);
+            }
+        }
+    });
+
+    request
+}
+
+fn format_ids(ids: &[usize]) -> String {
+    ids.iter().map(|id| format!("#{}", id)).collect::<Vec<_>>().join(", ")
+}
+
+fn rule_line(ui: &mut egui::Ui, ok: bool, text: &str) {
+    ui.horizontal(|ui| {
+        ui.label(if ok { "✅" } else { "❌" });
+        ui.label(text);
+    });
+}
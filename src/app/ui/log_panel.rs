@@ -0,0 +1,44 @@
+//! Status log panel with the most recent messages
+
+use eframe::egui;
+use crate::app::state::{AppState, LogLevel};
+
+pub fn render(ctx: &egui::Context, state: &mut AppState) {
+    if !state.show_log {
+        return;
+    }
+
+    egui::TopBottomPanel::bottom("log_panel")
+        .resizable(true)
+        .default_height(140.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.strong(format!("📜 Log ({})", state.status_log.len()));
+                if ui.small_button("🗑 Clear").clicked() {
+                    state.status_log.clear();
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .auto_shrink([false, false])
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for (time, msg, level) in &state.status_log {
+                        ui.horizontal(|ui| {
+                            ui.monospace(time.format("%H:%M:%S").to_string());
+                            ui.colored_label(level_color(*level), msg);
+                        });
+                    }
+                });
+        });
+}
+
+pub fn level_color(level: LogLevel) -> egui::Color32 {
+    match level {
+        LogLevel::Info => egui::Color32::from_rgb(180, 180, 180),
+        LogLevel::Success => egui::Color32::from_rgb(100, 255, 100),
+        LogLevel::Warning => egui::Color32::from_rgb(255, 200, 80),
+        LogLevel::Error => egui::Color32::from_rgb(255, 100, 100),
+    }
+}
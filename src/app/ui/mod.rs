@@ -4,3 +4,10 @@ pub mod candidates;
 pub mod pareto;
 pub mod visualizations;
 pub mod advanced_viz;
+pub mod inspector;
+pub mod structure;
+pub mod log_panel;
+pub mod compare;
+pub mod seed_sweep;
+pub mod preference;
+pub mod command_palette;
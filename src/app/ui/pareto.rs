@@ -3,34 +3,86 @@
 
 use crate::app::state::Candidate;
 
+/// Monte Carlo samples for the 4D hypervolume estimate
+const HV_SAMPLES: usize = 20_000;
+
 /// Calculate the hypervolume indicator for a set of candidates
-/// This is a common metric in multi-objective optimization
+/// This is a common metric in multi-objective optimization: the area dominated by the
+/// Pareto front in (toxicity, efficacy), bounded by `ref_point` = (minimum efficacy,
+/// maximum toxicity). Points outside the reference box are excluded.
 pub fn hypervolume_2d(candidates: &[Candidate], ref_point: (f32, f32)) -> f32 {
-    // Sort candidates by first objective (efficacy, descending)
-    let mut pareto: Vec<_> = candidates.iter().filter(|c| c.pareto).collect();
-    pareto.sort_by(|a, b| b.efficacy.partial_cmp(&a.efficacy).unwrap());
-    
+    let (eff_ref, tox_ref) = ref_point;
+    let mut pareto: Vec<_> = candidates.iter()
+        .filter(|c| c.pareto && c.efficacy >= eff_ref && c.toxicity <= tox_ref)
+        .collect();
+    pareto.sort_by(|a, b| a.toxicity.total_cmp(&b.toxicity));
+
+    // Sweep toxicity upward; each strip is as tall as the best efficacy seen so far
     let mut hv = 0.0;
-    let mut prev_tox = 0.0;
-    
-    for c in pareto {
-        if c.toxicity > ref_point.1 || c.efficacy < ref_point.0 {
-            continue;
-        }
-        
-        let width = c.toxicity - prev_tox;
-        let height = c.efficacy - ref_point.0;
-        
-        if width > 0.0 && height > 0.0 {
-            hv += width * height;
-        }
-        
-        prev_tox = c.toxicity;
+    let mut best_eff = eff_ref;
+    for (i, c) in pareto.iter().enumerate() {
+        best_eff = best_eff.max(c.efficacy);
+        let next_tox = pareto.get(i + 1).map_or(tox_ref, |n| n.toxicity);
+        hv += (next_tox - c.toxicity) * (best_eff - eff_ref);
     }
-    
+
     hv
 }
 
+/// Hypervolume over all four objectives. `ref_point` is [minimum efficacy,
+/// maximum toxicity, maximum synthesis cost, maximum manufacturing cost].
+/// Monte Carlo estimate with a fixed seed, so repeated calls agree and the value
+/// never decreases as the reference point is loosened.
+pub fn hypervolume_4d(candidates: &[Candidate], ref_point: [f32; 4]) -> f32 {
+    use rand::{Rng, SeedableRng};
+
+    // Oriented so lower is better for every objective
+    let orient = |v: [f32; 4]| [-v[0], v[1], v[2], v[3]];
+    let reference = orient(ref_point);
+    let points: Vec<[f32; 4]> = candidates.iter()
+        .filter(|c| c.pareto)
+        .map(|c| orient([c.efficacy, c.toxicity, c.synthesis_cost, c.manufacturing_cost]))
+        .collect();
+    if points.is_empty() {
+        return 0.0;
+    }
+
+    // Sample box from the ideal point to the reference
+    let mut ideal = [f32::INFINITY; 4];
+    for p in &points {
+        for k in 0..4 {
+            ideal[k] = ideal[k].min(p[k]);
+        }
+    }
+    let extent: Vec<f32> = (0..4).map(|k| reference[k] - ideal[k]).collect();
+    if extent.iter().any(|&e| e <= 0.0) {
+        return 0.0;
+    }
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let dominated = (0..HV_SAMPLES)
+        .filter(|_| {
+            let x: [f32; 4] = std::array::from_fn(|k| ideal[k] + rng.gen::<f32>() * extent[k]);
+            points.iter().any(|p| (0..4).all(|k| p[k] <= x[k]))
+        })
+        .count();
+
+    extent.iter().product::<f32>() * dominated as f32 / HV_SAMPLES as f32
+}
+
+/// Pareto candidates outside the reference box, which the hypervolume ignores
+pub fn outside_reference(candidates: &[Candidate], ref_point: [f32; 4]) -> usize {
+    candidates.iter()
+        .filter(|c| c.pareto)
+        .filter(|c| {
+            c.efficacy < ref_point[0]
+                || c.toxicity > ref_point[1]
+                || c.synthesis_cost > ref_point[2]
+                || c.manufacturing_cost > ref_point[3]
+        })
+        .count()
+}
+
 /// Find the "knee point" of the Pareto front
 /// The knee is the point with maximum distance to the line connecting extremes
 pub fn find_knee_point(candidates: &[Candidate]) -> Option<usize> {
@@ -63,6 +115,38 @@ pub fn find_knee_point(candidates: &[Candidate]) -> Option<usize> {
     knee_id
 }
 
+/// Knee across all four objectives: the Pareto candidate closest to the utopia point
+/// (best value of every objective) after min-max normalizing each objective over the front.
+pub fn find_knee_point_4d(candidates: &[Candidate]) -> Option<usize> {
+    let pareto: Vec<_> = candidates.iter().filter(|c| c.pareto).collect();
+
+    // Oriented so lower is better for every objective
+    let objectives = |c: &Candidate| [-c.efficacy, c.toxicity, c.synthesis_cost, c.manufacturing_cost];
+
+    let mut best = [f32::INFINITY; 4];
+    let mut worst = [f32::NEG_INFINITY; 4];
+    for c in &pareto {
+        for (k, v) in objectives(c).into_iter().enumerate() {
+            best[k] = best[k].min(v);
+            worst[k] = worst[k].max(v);
+        }
+    }
+
+    let utopia_distance = |c: &Candidate| -> f32 {
+        objectives(c).into_iter()
+            .enumerate()
+            .map(|(k, v)| {
+                let range = worst[k] - best[k];
+                if range > 0.0 { ((v - best[k]) / range).powi(2) } else { 0.0 }
+            })
+            .sum()
+    };
+
+    pareto.iter()
+        .min_by(|a, b| utopia_distance(a).total_cmp(&utopia_distance(b)))
+        .map(|c| c.id)
+}
+
 fn point_to_line_distance(px: f32, py: f32, x1: f32, y1: f32, x2: f32, y2: f32) -> f32 {
     let num = ((y2 - y1) * px - (x2 - x1) * py + x2 * y1 - y2 * x1).abs();
     let den = ((y2 - y1).powi(2) + (x2 - x1).powi(2)).sqrt();
@@ -73,3 +157,64 @@ fn point_to_line_distance(px: f32, py: f32, x1: f32, y1: f32, x2: f32, y2: f32)
         0.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_candidate(id: usize, eff: f32, tox: f32, syn: f32, mfg: f32) -> Candidate {
+        Candidate {
+            id,
+            smiles: format!("C{}", id),
+            efficacy: eff,
+            toxicity: tox,
+            synthesis_cost: syn,
+            manufacturing_cost: mfg,
+            pareto: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_hypervolume_2d() {
+        let cands = vec![
+            make_candidate(0, 0.5, 0.0, 0.5, 0.5),
+            make_candidate(1, 1.0, 0.5, 0.5, 0.5),
+            Candidate { pareto: false, ..make_candidate(2, 1.0, 0.0, 0.5, 0.5) },
+        ];
+        // Strips: tox 0..0.5 at eff 0.5, tox 0.5..1 at eff 1.0
+        assert!((hypervolume_2d(&cands, (0.0, 1.0)) - 0.75).abs() < 1e-6);
+        // Candidate 1 lies outside a tighter box
+        assert!((hypervolume_2d(&cands, (0.0, 0.25)) - 0.125).abs() < 1e-6);
+        assert_eq!(outside_reference(&cands, [0.0, 0.25, 1.0, 1.0]), 1);
+    }
+
+    #[test]
+    fn test_find_knee_point() {
+        // Convex front: the middle point bulges furthest from the extremes' line
+        let cands = vec![
+            make_candidate(0, 1.0, 1.0, 0.5, 0.5),
+            make_candidate(1, 0.9, 0.3, 0.5, 0.5),
+            make_candidate(2, 0.6, 0.2, 0.5, 0.5),
+            make_candidate(3, 0.1, 0.0, 0.5, 0.5),
+            Candidate { pareto: false, ..make_candidate(4, 0.95, 0.05, 0.5, 0.5) },
+        ];
+        assert_eq!(find_knee_point(&cands), Some(1));
+        assert_eq!(find_knee_point(&[]), None);
+    }
+
+    #[test]
+    fn test_find_knee_point_4d() {
+        // Candidate 2 is never best but balanced across all four objectives
+        let cands = vec![
+            make_candidate(0, 1.0, 1.0, 1.0, 1.0),
+            make_candidate(1, 0.0, 0.0, 0.0, 0.0),
+            make_candidate(2, 0.75, 0.25, 0.25, 0.25),
+            make_candidate(3, 1.0, 0.0, 1.0, 1.0),
+            Candidate { pareto: false, ..make_candidate(4, 1.0, 0.0, 0.0, 0.0) },
+        ];
+        assert_eq!(find_knee_point_4d(&cands), Some(2));
+        assert_eq!(find_knee_point_4d(&cands[..1]), Some(0));
+        assert_eq!(find_knee_point_4d(&[]), None);
+    }
+}
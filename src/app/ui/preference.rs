@@ -0,0 +1,59 @@
+//! "Which do you prefer?": learn objective weights from pairwise choices
+
+use eframe::egui;
+use crate::app::state::{AppState, Candidate};
+use crate::optimization::preference;
+
+pub fn render(ui: &mut egui::Ui, state: &mut AppState) {
+    if state.preference_pair.is_none() {
+        state.next_preference_pair();
+    }
+    let Some((a, b)) = state.preference_pair else {
+        ui.label("Needs at least two Pareto candidates");
+        return;
+    };
+    let find = |id| state.candidates.iter().find(|c| c.id == id).cloned();
+    let (Some(left), Some(right)) = (find(a), find(b)) else {
+        // The pair went stale (candidates removed or regenerated)
+        state.preference_pair = None;
+        return;
+    };
+
+    let mut chosen = None;
+    ui.columns(2, |columns| {
+        for (ui, (c, other)) in columns.iter_mut().zip([(&left, &right), (&right, &left)]) {
+            render_option(ui, c);
+            if ui.button(format!("👍 Prefer #{}", c.id)).clicked() {
+                chosen = Some((c.id, other.id));
+            }
+        }
+    });
+    if let Some((preferred, rejected)) = chosen {
+        state.record_preference(preferred, rejected);
+    }
+
+    ui.horizontal(|ui| {
+        if ui.button("⏭ Skip").clicked() {
+            state.next_preference_pair();
+        }
+        if !state.preference_choices.is_empty() && ui.button("🗑 Forget choices").clicked() {
+            state.preference_choices.clear();
+        }
+    });
+
+    if !state.preference_choices.is_empty() {
+        let weights = [state.w_eff, state.w_tox, state.w_syn, state.w_mfg];
+        ui.label(format!(
+            "{} choices, current weights agree with {:.0}%",
+            state.preference_choices.len(),
+            preference::agreement(weights, &state.preference_choices) * 100.0
+        ));
+    }
+}
+
+fn render_option(ui: &mut egui::Ui, c: &Candidate) {
+    ui.strong(format!("#{}", c.id));
+    ui.monospace(&c.smiles);
+    ui.label(format!("Eff {:.3} | Tox {:.3}", c.efficacy, c.toxicity));
+    ui.label(format!("Syn {:.3} | Mfg {:.3}", c.synthesis_cost, c.manufacturing_cost));
+}
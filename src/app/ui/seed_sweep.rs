@@ -0,0 +1,95 @@
+//! Seed sweep: run several seeds and compare their outcomes
+
+use eframe::egui;
+use egui_plot::{Bar, BarChart, Plot};
+use crate::app::state::AppState;
+use crate::optimization::sweep::{self, MetricSummary};
+
+const SWEEP_HISTOGRAM_BINS: usize = 10;
+
+pub fn render(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.horizontal(|ui| {
+        ui.label("Seeds:");
+        ui.add(egui::DragValue::new(&mut state.sweep_k).clamp_range(2..=50));
+        ui.label(format!("× {} candidates from seed {}", state.n_generate, state.seed));
+
+        let busy = state.is_generating || state.import_progress.is_some();
+        match state.sweep_progress {
+            Some((done, k)) => {
+                ui.spinner();
+                ui.label(format!("{}/{}", done, k));
+            }
+            None => {
+                if ui.add_enabled(!busy, egui::Button::new("▶ Run sweep")).clicked() {
+                    state.start_seed_sweep();
+                }
+            }
+        }
+    });
+
+    if state.sweep_runs.is_empty() {
+        ui.label("Run a sweep to see how results vary with the seed");
+        return;
+    }
+
+    let summary = sweep::aggregate_sweep(&state.sweep_runs);
+    ui.separator();
+
+    egui::Grid::new("seed_sweep_table")
+        .num_columns(4)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Seed");
+            ui.strong("Best score");
+            ui.strong("Pareto");
+            ui.strong("Mean QED");
+            ui.end_row();
+
+            for run in &state.sweep_runs {
+                ui.label(run.seed.to_string());
+                ui.label(format!("{:.3}", run.best_score));
+                ui.label(run.pareto_size.to_string());
+                ui.label(format!("{:.3}", run.mean_qed));
+                ui.end_row();
+            }
+
+            ui.strong("Mean ± sd");
+            ui.strong(format_spread(&summary.best_score, 3));
+            ui.strong(format_spread(&summary.pareto_size, 1));
+            ui.strong(format_spread(&summary.mean_qed, 3));
+            ui.end_row();
+
+            ui.label("Range");
+            ui.label(format!("{:.3} – {:.3}", summary.best_score.min, summary.best_score.max));
+            ui.label(format!("{} – {}", summary.pareto_size.min, summary.pareto_size.max));
+            ui.label(format!("{:.3} – {:.3}", summary.mean_qed.min, summary.mean_qed.max));
+            ui.end_row();
+        });
+
+    ui.label("Best score across seeds");
+    let scores: Vec<f32> = state.sweep_runs.iter().map(|r| r.best_score).collect();
+    let (min, max) = (summary.best_score.min, summary.best_score.max);
+    let width = ((max - min) / SWEEP_HISTOGRAM_BINS as f32).max(1e-3);
+    let mut counts = [0u32; SWEEP_HISTOGRAM_BINS];
+    for score in scores {
+        let bin = (((score - min) / width) as usize).min(SWEEP_HISTOGRAM_BINS - 1);
+        counts[bin] += 1;
+    }
+    let bars: Vec<Bar> = counts.iter()
+        .enumerate()
+        .map(|(i, &count)| {
+            let x = min + (i as f32 + 0.5) * width;
+            Bar::new(x as f64, count as f64).width(width as f64 * 0.9)
+        })
+        .collect();
+
+    Plot::new("seed_sweep_hist")
+        .height(120.0)
+        .show(ui, |plot_ui| {
+            plot_ui.bar_chart(BarChart::new(bars).color(egui::Color32::from_rgb(100, 150, 255)));
+        });
+}
+
+fn format_spread(metric: &MetricSummary, decimals: usize) -> String {
+    format!("{:.*} ± {:.*}", decimals, metric.mean, decimals, metric.std)
+}
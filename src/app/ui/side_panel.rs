@@ -1,5 +1,8 @@
 use eframe::egui;
-use crate::app::state::AppState;
+use crate::app::io;
+use crate::app::state::{self, coverage_grid, AppState, GenSource, LogLevel, WeightPreset, COVERAGE_BINS};
+use crate::chemistry::scaffolds;
+use crate::generation::generator::GenerationMix;
 use super::advanced_viz;
 
 pub fn render(ctx: &egui::Context, state: &mut AppState) {
@@ -16,10 +19,47 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
                 ui.collapsing("🔍 Filters", |ui| {
                     ui.checkbox(&mut state.filter_pareto_only, "Pareto front only");
                     ui.checkbox(&mut state.filter_favorites_only, "⭐ Favorites only");
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut state.filter_latest_batch, "🆕 Latest batch only")
+                            .on_hover_text("Only candidates from the most recent generation run");
+                        if state.filter_latest_batch && ui.small_button("✖").on_hover_text("Show all batches").clicked() {
+                            state.filter_latest_batch = false;
+                        }
+                    });
+                    ui.checkbox(&mut state.pin_locked, "🔒 Always show locked")
+                        .on_hover_text("Locked candidates stay visible whatever the other filters say");
                     
                     ui.add_space(5.0);
                     ui.label("SMILES search:");
                     ui.text_edit_singleline(&mut state.filter_smiles);
+
+                    ui.add_space(5.0);
+                    ui.label("Formula:");
+                    ui.add(egui::TextEdit::singleline(&mut state.filter_formula)
+                        .hint_text("C6N = at least 6 C and 1 N")
+                        .font(egui::TextStyle::Monospace));
+
+                    ui.add_space(5.0);
+                    ui.label("Substructure:");
+                    let mut changed = ui.add(egui::TextEdit::singleline(&mut state.filter_substructure)
+                        .hint_text("SMILES, ',' = or")
+                        .font(egui::TextStyle::Monospace))
+                        .changed();
+                    ui.horizontal_wrapped(|ui| {
+                        for (name, pattern) in state::SUBSTRUCTURE_PRESETS {
+                            let active = state.filter_substructure == *pattern;
+                            if ui.selectable_label(active, *name).on_hover_text(*pattern).clicked() {
+                                state.filter_substructure = if active { String::new() } else { pattern.to_string() };
+                                changed = true;
+                            }
+                        }
+                    });
+                    if changed {
+                        state.update_substructure_filter();
+                    }
+                    if let Err(e) = state::parse_substructure_patterns(&state.filter_substructure) {
+                        ui.colored_label(egui::Color32::from_rgb(255, 150, 100), format!("Invalid pattern {}", e));
+                    }
                     
                     ui.add_space(5.0);
                     ui.label("Efficacy:");
@@ -34,14 +74,95 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
                         ui.add(egui::DragValue::new(&mut state.filter_tox_max).clamp_range(0.0..=1.0).speed(0.01).prefix("max: "));
                     });
 
+                    ui.checkbox(&mut state.property_filters.enabled, "Property ranges")
+                        .on_hover_text("Filter on computed molecular weight, LogP and rotatable bonds");
+                    if state.property_filters.enabled {
+                        let filters = &mut state.property_filters;
+                        ui.horizontal(|ui| {
+                            ui.label("MW:");
+                            ui.add(egui::DragValue::new(&mut filters.mw.0).clamp_range(0.0..=2000.0).speed(5.0).prefix("min: "));
+                            ui.add(egui::DragValue::new(&mut filters.mw.1).clamp_range(0.0..=2000.0).speed(5.0).prefix("max: "));
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("LogP:");
+                            ui.add(egui::DragValue::new(&mut filters.logp.0).clamp_range(-10.0..=15.0).speed(0.1).prefix("min: "));
+                            ui.add(egui::DragValue::new(&mut filters.logp.1).clamp_range(-10.0..=15.0).speed(0.1).prefix("max: "));
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Rot. bonds:");
+                            ui.add(egui::DragValue::new(&mut filters.rotatable_bonds.0).clamp_range(0..=50).prefix("min: "));
+                            ui.add(egui::DragValue::new(&mut filters.rotatable_bonds.1).clamp_range(0..=50).prefix("max: "));
+                        });
+                    }
+
+                    ui.label("Source:");
+                    let current = state.filter_source.as_ref().map(|s| s.to_string()).unwrap_or_else(|| "Any".into());
+                    egui::ComboBox::from_id_source("filter_source")
+                        .selected_text(current)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut state.filter_source, None, "Any");
+                            for source in [GenSource::Random, GenSource::Hybrid, GenSource::Mutation, GenSource::Crossover, GenSource::Imported] {
+                                let label = source.to_string();
+                                ui.selectable_value(&mut state.filter_source, Some(source), label);
+                            }
+                            ui.separator();
+                            for scaffold in scaffolds::DRUG_SCAFFOLDS {
+                                let source = GenSource::Scaffold(scaffold.name);
+                                let label = source.to_string();
+                                ui.selectable_value(&mut state.filter_source, Some(source), label)
+                                    .on_hover_text(scaffold.category);
+                            }
+                            for scaffold in &state.custom_scaffolds {
+                                let source = GenSource::CustomScaffold(scaffold.name.clone());
+                                let label = source.to_string();
+                                ui.selectable_value(&mut state.filter_source, Some(source), label)
+                                    .on_hover_text(&scaffold.category);
+                            }
+                        });
+
+                    // Tag filter
+                    let all_tags = state.annotations.all_tags();
+                    if !all_tags.is_empty() {
+                        ui.add_space(5.0);
+                        ui.horizontal(|ui| {
+                            ui.label("🏷 Tags:");
+                            ui.selectable_value(&mut state.filter_tags_match_all, true, "AND")
+                                .on_hover_text("Candidates must carry every selected tag");
+                            ui.selectable_value(&mut state.filter_tags_match_all, false, "OR")
+                                .on_hover_text("Candidates must carry any selected tag");
+                        });
+                        ui.horizontal_wrapped(|ui| {
+                            for tag in all_tags {
+                                let selected = state.filter_tags.contains(&tag);
+                                let label = format!("{} ({})", tag, state.annotations.tag_count(&tag));
+                                if ui.selectable_label(selected, label).clicked() {
+                                    if selected {
+                                        state.filter_tags.retain(|t| t != &tag);
+                                    } else {
+                                        state.filter_tags.push(tag);
+                                    }
+                                }
+                            }
+                        });
+                    }
+
+                    ui.add_space(5.0);
                     if ui.button("Reset Filters").clicked() {
                         state.filter_smiles.clear();
+                        state.filter_substructure.clear();
+                        state.substructure_matches = None;
+                        state.filter_formula.clear();
                         state.filter_eff_min = 0.0;
                         state.filter_eff_max = 1.0;
                         state.filter_tox_min = 0.0;
                         state.filter_tox_max = 1.0;
                         state.filter_pareto_only = false;
                         state.filter_favorites_only = false;
+                        state.filter_tags.clear();
+                        state.filter_run = None;
+                        state.filter_latest_batch = false;
+                        state.filter_source = None;
+                        state.property_filters.enabled = false;
                     }
                 });
 
@@ -49,6 +170,36 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
 
                 // Weights
                 ui.collapsing("⚖️ Weights", |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Preset:");
+                        let weights = [state.w_eff, state.w_tox, state.w_syn, state.w_mfg];
+                        let current = state.weight_preset
+                            .filter(|p| p.weights() == weights)
+                            .map_or("Custom", |p| p.label());
+                        let mut chosen = None;
+                        egui::ComboBox::from_id_source("weight_preset")
+                            .selected_text(current)
+                            .show_ui(ui, |ui| {
+                                for preset in WeightPreset::ALL {
+                                    if ui.selectable_label(state.weight_preset == Some(preset), preset.label()).clicked() {
+                                        chosen = Some(preset);
+                                    }
+                                }
+                            });
+                        if let Some(preset) = chosen {
+                            state.apply_weight_preset(preset);
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Active:");
+                        let mut changed = false;
+                        for (label, active) in ["Eff", "Tox", "Synth", "Mfg"].into_iter().zip(state.active_objectives.iter_mut()) {
+                            changed |= ui.checkbox(active, label).changed();
+                        }
+                        if changed {
+                            state.mark_pareto_dirty();
+                        }
+                    }).response.on_hover_text("Inactive objectives are left out of Pareto dominance, the weighted score and the plots");
                     ui.horizontal(|ui| {
                         ui.label("Efficacy (+):");
                         ui.add(egui::Slider::new(&mut state.w_eff, 0.0..=5.0).step_by(0.1));
@@ -65,6 +216,8 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
                         ui.label("Mfg (-):");
                         ui.add(egui::Slider::new(&mut state.w_mfg, 0.0..=5.0).step_by(0.1));
                     });
+                    ui.checkbox(&mut state.normalize_objectives, "Normalize objectives")
+                        .on_hover_text("Min-max scale each objective over the current candidates before scoring and plotting");
                     if ui.button("Reset").clicked() {
                         state.w_eff = 1.0;
                         state.w_tox = 1.0;
@@ -75,6 +228,35 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
 
                 ui.add_space(5.0);
 
+                // Generation strategy mix
+                ui.collapsing("🧪 Generation Mix", |ui| {
+                    let mix = &mut state.generation_mix;
+                    ui.horizontal(|ui| {
+                        ui.label("Scaffold:");
+                        ui.add(egui::Slider::new(&mut mix.scaffold, 0.0..=1.0).step_by(0.01));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Hybrid:");
+                        ui.add(egui::Slider::new(&mut mix.hybrid, 0.0..=1.0).step_by(0.01));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Random:");
+                        ui.add(egui::Slider::new(&mut mix.random, 0.0..=1.0).step_by(0.01));
+                    });
+                    let shares = mix.normalized();
+                    ui.label(format!(
+                        "→ {:.0}% / {:.0}% / {:.0}%",
+                        shares.scaffold * 100.0, shares.hybrid * 100.0, shares.random * 100.0
+                    )).on_hover_text("Shares are normalized; an all-zero mix uses the default");
+                    if ui.button("Reset").clicked() {
+                        *mix = GenerationMix::default();
+                    }
+                    ui.checkbox(&mut state.novelty_search, "Novelty search")
+                        .on_hover_text("Prefer molecules far from existing candidates in objective space");
+                });
+
+                ui.add_space(5.0);
+
                 // Statistics
                 ui.collapsing("📊 Statistics", |ui| {
                     let total = state.candidates.len();
@@ -83,16 +265,120 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
                     let favorites = state.annotations.favorite_count();
                     
                     ui.label(format!("Total: {} | Filtered: {}", total, filtered));
-                    ui.label(format!("Pareto: {} | ⭐ Favorites: {}", pareto, favorites));
+                    ui.label(format!("Pareto: {} | ⭐ Favorites: {} | 🔒 Locked: {}",
+                        pareto, favorites, state.annotations.locked_count()));
                     
                     if total > 0 {
                         let avg_eff: f32 = state.candidates.iter().map(|c| c.efficacy).sum::<f32>() / total as f32;
                         let avg_tox: f32 = state.candidates.iter().map(|c| c.toxicity).sum::<f32>() / total as f32;
-                        ui.colored_label(egui::Color32::from_rgb(100, 200, 100), format!("Avg Eff: {:.3}", avg_eff));
-                        ui.colored_label(egui::Color32::from_rgb(255, 150, 100), format!("Avg Tox: {:.3}", avg_tox));
+                        ui.colored_label(egui::Color32::from_rgb(100, 200, 100), format!("Avg Eff: {}", state.fmt_val(avg_eff)));
+                        ui.colored_label(egui::Color32::from_rgb(255, 150, 100), format!("Avg Tox: {}", state.fmt_val(avg_tox)));
                     }
                     
+                    let (scaffolds, per_candidate) = state.cached_scaffold_diversity();
+                    ui.label(format!("Scaffolds: {} ({:.2} per candidate)", scaffolds, per_candidate))
+                        .on_hover_text("Distinct Bemis-Murcko scaffolds; acyclic molecules count as one");
+                    let coverage = coverage_grid(&state.filtered_candidates(), COVERAGE_BINS);
+                    ui.label(format!("Coverage (eff/tox): {:.0}%", coverage * 100.0))
+                        .on_hover_text(format!("Non-empty cells of a {0}×{0} efficacy × toxicity grid over the filtered candidates", COVERAGE_BINS));
+
                     ui.label(format!("History: {} undo, {} redo", state.history.undo_count(), state.history.redo_count()));
+
+                    ui.separator();
+                    ui.label("Hypervolume reference:");
+                    egui::Grid::new("hv_ref").num_columns(2).show(ui, |ui| {
+                        for (label, value) in ["Eff ≥", "Tox ≤", "Synth ≤", "Mfg ≤"].into_iter().zip(state.hv_ref.iter_mut()) {
+                            ui.label(label);
+                            ui.add(egui::Slider::new(value, 0.0..=1.0).step_by(0.05));
+                            ui.end_row();
+                        }
+                    });
+                    let hv = state.hypervolume();
+                    ui.label(format!("HV (eff/tox): {:.4}", hv.hv_2d));
+                    ui.label(format!("HV (4D): {:.4}", hv.hv_4d))
+                        .on_hover_text("Monte Carlo estimate over all four objectives");
+                    if hv.outside > 0 {
+                        ui.colored_label(egui::Color32::from_rgb(255, 180, 80),
+                            format!("⚠ {} Pareto candidates outside the reference box are excluded", hv.outside));
+                    }
+                });
+
+                ui.add_space(5.0);
+
+                // User-defined scaffolds
+                ui.collapsing(format!("🧩 Custom Scaffolds ({})", state.custom_scaffolds.len()), |ui| {
+                    let mut remove = None;
+                    for (i, scaffold) in state.custom_scaffolds.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                                remove = Some(i);
+                            }
+                            ui.label(format!("{} ({})", scaffold.name, scaffold.category));
+                        });
+                        ui.monospace(&scaffold.smiles);
+                    }
+                    if let Some(i) = remove {
+                        let removed = state.custom_scaffolds.remove(i);
+                        state.push_status(LogLevel::Info, format!("Removed custom scaffold '{}'", removed.name));
+                    }
+
+                    ui.add_space(5.0);
+                    egui::Grid::new("custom_scaffold_editor").num_columns(2).show(ui, |ui| {
+                        ui.label("Name:");
+                        ui.text_edit_singleline(&mut state.scaffold_input.name);
+                        ui.end_row();
+                        ui.label("SMILES:");
+                        ui.text_edit_singleline(&mut state.scaffold_input.smiles);
+                        ui.end_row();
+                        ui.label("Category:");
+                        ui.add(egui::TextEdit::singleline(&mut state.scaffold_input.category).hint_text("Custom"));
+                        ui.end_row();
+                    });
+                    if ui.button("➕ Add scaffold").clicked() {
+                        let input = std::mem::take(&mut state.scaffold_input);
+                        if let Err(e) = state.add_custom_scaffold(input.clone()) {
+                            state.push_status(LogLevel::Error, e);
+                            state.scaffold_input = input;
+                        }
+                    }
+                });
+
+                ui.add_space(5.0);
+
+                // Generation runs
+                ui.collapsing(format!("🏃 Runs ({})", state.runs.len()), |ui| {
+                    if state.runs.is_empty() {
+                        ui.label("No generation runs yet");
+                        return;
+                    }
+
+                    let mut toggle = None;
+                    for run in state.runs.iter().rev() {
+                        let active = state.filter_run == Some(run.run_id);
+                        ui.horizontal(|ui| {
+                            if ui.selectable_label(active, format!("#{}", run.run_id))
+                                .on_hover_text("Show only this run's candidates")
+                                .clicked()
+                            {
+                                toggle = Some(run.run_id);
+                            }
+                            ui.label(format!(
+                                "{} · seed {} · n={}",
+                                run.timestamp.format("%H:%M:%S"), run.seed, run.n
+                            ));
+                        });
+                        ui.small(format!(
+                            "{}{}{} · {} candidates",
+                            if run.parallel { "parallel" } else { "sequential" },
+                            if run.used_scaffolds { " · 💊 scaffolds" } else { "" },
+                            run.category.as_deref().map(|c| format!(" ({})", c)).unwrap_or_default(),
+                            run.candidate_ids.len()
+                        ));
+                    }
+
+                    if let Some(run_id) = toggle {
+                        state.filter_run = if state.filter_run == Some(run_id) { None } else { Some(run_id) };
+                    }
                 });
 
                 ui.add_space(5.0);
@@ -114,6 +400,7 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
                         // Copy candidate data to avoid borrow issues
                         let candidate_data = state.candidates.iter().find(|x| x.id == id).cloned();
                         let is_fav = state.annotations.is_favorite(id);
+                        let is_locked = state.annotations.is_locked(id);
                         let note_text = state.annotations.get_note(id).cloned().unwrap_or_default();
                         
                         if let Some(c) = candidate_data {
@@ -126,6 +413,19 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
                                 if ui.button(fav_btn).on_hover_text("Toggle favorite").clicked() {
                                     state.toggle_favorite(c.id);
                                 }
+
+                                let lock_btn = if is_locked { "🔒" } else { "🔓" };
+                                if ui.button(lock_btn).on_hover_text("Lock: keep this candidate when clearing").clicked() {
+                                    state.toggle_lock(c.id);
+                                }
+
+                                let pinned = state.compare_id == Some(c.id);
+                                if ui.selectable_label(pinned, "⚖ Compare")
+                                    .on_hover_text("Pin this candidate, then select another to compare")
+                                    .clicked()
+                                {
+                                    state.compare_id = if pinned { None } else { Some(c.id) };
+                                }
                             });
                             
                             ui.label("SMILES:");
@@ -133,22 +433,70 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
                                 ui.monospace(&c.smiles);
                             });
                             
-                            if ui.small_button("📋 Copy").clicked() {
-                                ui.output_mut(|o| o.copied_text = c.smiles.clone());
-                            }
+                            ui.horizontal(|ui| {
+                                if ui.small_button("📋 Copy").clicked() {
+                                    ui.output_mut(|o| o.copied_text = c.smiles.clone());
+                                }
+                                if ui.small_button("📋 Copy row")
+                                    .on_hover_text("ID, SMILES, objectives, Pareto, score and favorite, tab-separated")
+                                    .clicked()
+                                {
+                                    let row = io::candidate_row(&c, score, is_fav, '\t');
+                                    ui.output_mut(|o| o.copied_text = row);
+                                }
+                            });
                             
                             ui.separator();
                             
-                            ui.colored_label(egui::Color32::from_rgb(100, 200, 100), format!("Efficacy: {:.4}", c.efficacy));
-                            ui.colored_label(egui::Color32::from_rgb(255, 150, 100), format!("Toxicity: {:.4}", c.toxicity));
-                            ui.label(format!("Synth: {:.4}", c.synthesis_cost));
-                            ui.label(format!("Mfg: {:.4}", c.manufacturing_cost));
-                            ui.strong(format!("Score: {:.4}", score));
+                            ui.colored_label(egui::Color32::from_rgb(100, 200, 100), format!("Efficacy: {}", state.fmt_val(c.efficacy)));
+                            ui.colored_label(egui::Color32::from_rgb(255, 150, 100), format!("Toxicity: {}", state.fmt_val(c.toxicity)));
+                            ui.label(format!("Synth: {}", state.fmt_val(c.synthesis_cost)));
+                            ui.label(format!("Mfg: {}", state.fmt_val(c.manufacturing_cost)));
+                            ui.strong(format!("Score: {}", state.fmt_val(score)));
+                            render_score_breakdown(ui, &state.score_breakdown(&c), score);
                             
                             if c.pareto {
                                 ui.colored_label(egui::Color32::from_rgb(100, 255, 100), "✅ Pareto optimal");
                             }
                             
+                            // Tags
+                            ui.separator();
+                            ui.label("🏷 Tags:");
+                            ui.horizontal_wrapped(|ui| {
+                                for tag in state.annotations.tags_for(c.id) {
+                                    if ui.small_button(format!("{} ✖", tag)).on_hover_text("Remove tag").clicked() {
+                                        state.remove_tag(c.id, &tag);
+                                    }
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                let response = ui.add(
+                                    egui::TextEdit::singleline(&mut state.tag_input)
+                                        .hint_text("new tag")
+                                        .desired_width(140.0)
+                                );
+                                let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                                if (ui.small_button("➕ Add").clicked() || submitted) && !state.tag_input.trim().is_empty() {
+                                    let tag = std::mem::take(&mut state.tag_input);
+                                    state.add_tag(c.id, &tag);
+                                }
+
+                                // Reuse an existing tag
+                                let existing: Vec<String> = state.annotations.all_tags().into_iter()
+                                    .filter(|t| !state.annotations.has_tag(c.id, t))
+                                    .collect();
+                                if !existing.is_empty() {
+                                    ui.menu_button("▾", |ui| {
+                                        for tag in existing {
+                                            if ui.button(&tag).clicked() {
+                                                state.add_tag(c.id, &tag);
+                                                ui.close_menu();
+                                            }
+                                        }
+                                    });
+                                }
+                            });
+
                             // Annotation
                             ui.separator();
                             ui.label("📝 Note:");
@@ -166,7 +514,50 @@ pub fn render(ctx: &egui::Context, state: &mut AppState) {
                 ui.add_space(10.0);
                 ui.separator();
                 ui.small("Drug Candidate Studio v0.3.0");
-                ui.small(format!("CPU: {} cores", rayon::current_num_threads()));
+                let cores = rayon::current_num_threads();
+                let threads = if state.thread_count == 0 { cores } else { state.thread_count };
+                ui.small(format!("CPU: {} of {} cores for generation", threads, cores));
             });
         });
 }
+
+const BREAKDOWN_COLORS: [egui::Color32; 4] = [
+    egui::Color32::from_rgb(100, 200, 100),
+    egui::Color32::from_rgb(255, 150, 100),
+    egui::Color32::from_rgb(150, 150, 255),
+    egui::Color32::from_rgb(200, 150, 220),
+];
+
+/// Stacked bar of the score terms: gains to the right of the zero line, penalties to the left
+fn render_score_breakdown(ui: &mut egui::Ui, parts: &[(&'static str, f32); 4], total: f32) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), 14.0), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+    let gains: f32 = parts.iter().map(|(_, v)| v.max(0.0)).sum();
+    let losses: f32 = parts.iter().map(|(_, v)| (-v).max(0.0)).sum();
+    let span = gains + losses;
+    if span > 0.0 {
+        let zero = rect.left() + rect.width() * losses / span;
+        let (mut right, mut left) = (zero, zero);
+        for ((_, value), color) in parts.iter().zip(BREAKDOWN_COLORS) {
+            let width = rect.width() * value.abs() / span;
+            let x_range = if *value >= 0.0 {
+                right += width;
+                (right - width)..=right
+            } else {
+                left -= width;
+                left..=(left + width)
+            };
+            painter.rect_filled(egui::Rect::from_x_y_ranges(x_range, rect.y_range()), 0.0, color);
+        }
+        painter.vline(zero, rect.y_range(), egui::Stroke::new(1.5, ui.visuals().text_color()));
+    }
+
+    ui.horizontal_wrapped(|ui| {
+        for ((name, value), color) in parts.iter().zip(BREAKDOWN_COLORS) {
+            ui.colored_label(color, format!("{} {:+.3}", name, value));
+        }
+        ui.label(format!("= {:.3}", total));
+    });
+}
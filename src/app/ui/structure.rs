@@ -0,0 +1,83 @@
+//! 2D structure drawing for molecule layouts
+
+use eframe::egui::{self, Color32, Pos2, Rect, Stroke, Vec2};
+use crate::chemistry::graph::BondOrder;
+use crate::chemistry::layout::MolLayout;
+
+const BOND_COLOR: Color32 = Color32::from_rgb(220, 220, 220);
+
+/// Draw a molecule layout scaled to fit inside `rect`
+pub fn draw_molecule(painter: &egui::Painter, layout: &MolLayout, rect: Rect) {
+    if layout.atoms.is_empty() {
+        return;
+    }
+
+    let (min, max) = layout.bounds();
+    let margin = 20.0;
+    let span_x = (max[0] - min[0]).max(1.0);
+    let span_y = (max[1] - min[1]).max(1.0);
+    let scale = ((rect.width() - 2.0 * margin) / span_x)
+        .min((rect.height() - 2.0 * margin) / span_y)
+        .clamp(5.0, 40.0);
+    let center = [(min[0] + max[0]) / 2.0, (min[1] + max[1]) / 2.0];
+
+    // Flip y so the layout reads like a textbook drawing
+    let to_screen = |p: [f32; 2]| {
+        rect.center() + Vec2::new((p[0] - center[0]) * scale, -(p[1] - center[1]) * scale)
+    };
+    let points: Vec<Pos2> = layout.atoms.iter().map(|a| to_screen(a.pos)).collect();
+
+    let stroke = Stroke::new(1.5, BOND_COLOR);
+    for bond in &layout.bonds {
+        let (a, b) = (points[bond.a], points[bond.b]);
+        let dir = (b - a).normalized();
+        let offset = Vec2::new(-dir.y, dir.x) * (scale * 0.12);
+        match bond.order {
+            BondOrder::Single | BondOrder::Aromatic => {
+                painter.line_segment([a, b], stroke);
+            }
+            BondOrder::Double => {
+                painter.line_segment([a + offset * 0.5, b + offset * 0.5], stroke);
+                painter.line_segment([a - offset * 0.5, b - offset * 0.5], stroke);
+            }
+            BondOrder::Triple => {
+                painter.line_segment([a, b], stroke);
+                painter.line_segment([a + offset, b + offset], stroke);
+                painter.line_segment([a - offset, b - offset], stroke);
+            }
+        }
+    }
+
+    // Aromatic rings get an inner circle
+    for ring in &layout.aromatic_rings {
+        let n = ring.len() as f32;
+        let c = ring.iter().fold(Vec2::ZERO, |acc, &i| acc + points[i].to_vec2()) / n;
+        let radius = ring.iter().map(|&i| (points[i] - c.to_pos2()).length()).sum::<f32>() / n;
+        painter.circle_stroke(c.to_pos2(), radius * 0.6, Stroke::new(1.0, BOND_COLOR));
+    }
+
+    let bg = painter.ctx().style().visuals.extreme_bg_color;
+    for (atom, &pos) in layout.atoms.iter().zip(&points) {
+        if let Some(label) = &atom.label {
+            let galley = painter.layout_no_wrap(
+                label.clone(),
+                egui::FontId::proportional(13.0),
+                element_color(&atom.element),
+            );
+            let text_rect = Rect::from_center_size(pos, galley.size());
+            painter.rect_filled(text_rect.expand(2.0), 3.0, bg);
+            painter.galley(text_rect.min, galley, Color32::WHITE);
+        }
+    }
+}
+
+fn element_color(element: &str) -> Color32 {
+    match element {
+        "O" => Color32::from_rgb(255, 90, 90),
+        "N" => Color32::from_rgb(100, 140, 255),
+        "S" => Color32::from_rgb(230, 200, 60),
+        "P" => Color32::from_rgb(255, 160, 60),
+        "F" | "Cl" | "Br" | "I" => Color32::from_rgb(100, 200, 100),
+        _ => Color32::from_rgb(220, 220, 220),
+    }
+}
@@ -1,7 +1,12 @@
 use eframe::egui;
-use crate::app::state::AppState;
+use crate::app::state::{AppState, LogLevel, AUTOSAVE_PATH, DISPLAY_DECIMALS_RANGE};
 use crate::app::theme::{ThemeSettings, theme_picker};
-use crate::app::io;
+use crate::app::{io, report};
+use crate::chemistry::scaffolds;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use super::log_panel;
 
 pub fn render(ctx: &egui::Context, state: &mut AppState, theme: &mut ThemeSettings) {
     egui::TopBottomPanel::top("top_bar").show(ctx, |ui| {
@@ -12,7 +17,7 @@ pub fn render(ctx: &egui::Context, state: &mut AppState, theme: &mut ThemeSettin
             
             // File menu
             ui.menu_button("📁 File", |ui| {
-                if ui.button("💾 Save Session").clicked() {
+                if ui.add(egui::Button::new("💾 Save Session").shortcut_text("Ctrl+S")).clicked() {
                     save_session_dialog(state);
                     ui.close_menu();
                 }
@@ -34,6 +39,8 @@ pub fn render(ctx: &egui::Context, state: &mut AppState, theme: &mut ThemeSettin
                     export_csv(state);
                     ui.close_menu();
                 }
+                ui.checkbox(&mut state.export_extended_csv, "Extended CSV")
+                    .on_hover_text("Add MW, LogP, PSA, HBD, HBA, QED, Lipinski violations and PAINS count");
                 if ui.button("📋 Export JSON").clicked() {
                     export_json(state);
                     ui.close_menu();
@@ -46,6 +53,44 @@ pub fn render(ctx: &egui::Context, state: &mut AppState, theme: &mut ThemeSettin
                     export_smiles(state);
                     ui.close_menu();
                 }
+                if ui.button("🗃 Export Parquet").on_hover_text("Typed columns with descriptors, for pandas / polars").clicked() {
+                    export_parquet(state);
+                    ui.close_menu();
+                }
+                if ui.button("📄 Export HTML Report").on_hover_text("Summary, runs and the top candidates with structures").clicked() {
+                    export_report(state);
+                    ui.close_menu();
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("🕸 Export Similarity Graph").on_hover_text("GraphML network of similar candidates").clicked() {
+                        export_similarity_graph(state);
+                        ui.close_menu();
+                    }
+                    ui.add(egui::Slider::new(&mut state.graph_threshold, 0.3..=1.0).step_by(0.05).text("Tanimoto >"));
+                });
+
+                let reveal = ui.add_enabled(state.last_export_path.is_some(), egui::Button::new("📂 Reveal in File Manager"));
+                if reveal.clicked() {
+                    if let Some(path) = state.last_export_path.clone() {
+                        if let Err(e) = reveal_in_file_manager(&path) {
+                            state.push_status(LogLevel::Error, format!("❌ {}", e));
+                        }
+                    }
+                    ui.close_menu();
+                } else if let Some(path) = &state.last_export_path {
+                    reveal.on_hover_text(path.display().to_string());
+                }
+
+                ui.separator();
+
+                if ui.button("🏷 Export Annotations").clicked() {
+                    export_annotations(state);
+                    ui.close_menu();
+                }
+                if ui.button("🏷 Import Annotations").on_hover_text("Matches the latest annotations_*.json by SMILES").clicked() {
+                    import_annotations(state);
+                    ui.close_menu();
+                }
             });
 
             // Edit menu
@@ -56,18 +101,28 @@ pub fn render(ctx: &egui::Context, state: &mut AppState, theme: &mut ThemeSettin
                     "↩️ Undo".to_string()
                 };
                 
-                if ui.add_enabled(state.history.can_undo(), egui::Button::new(undo_text)).clicked() {
+                if ui.add_enabled(state.history.can_undo(), egui::Button::new(undo_text).shortcut_text("Ctrl+Z")).clicked() {
                     state.undo();
                     ui.close_menu();
                 }
                 
-                if ui.add_enabled(state.history.can_redo(), egui::Button::new("↪️ Redo")).clicked() {
+                if ui.add_enabled(state.history.can_redo(), egui::Button::new("↪️ Redo").shortcut_text("Ctrl+Y")).clicked() {
                     state.redo();
                     ui.close_menu();
                 }
                 
                 ui.separator();
+
+                if ui.button("🔄 Recompute Objectives").on_hover_text("Rescore all candidates with the descriptor-based model").clicked() {
+                    state.recompute_objectives();
+                    ui.close_menu();
+                }
                 
+                if ui.button("🔁 Find Duplicates").on_hover_text("Group candidates with the same canonical SMILES").clicked() {
+                    state.find_duplicates();
+                    ui.close_menu();
+                }
+
                 if ui.button("🗑️ Clear All").clicked() {
                     state.clear();
                     ui.close_menu();
@@ -81,13 +136,21 @@ pub fn render(ctx: &egui::Context, state: &mut AppState, theme: &mut ThemeSettin
                 ui.checkbox(&mut state.show_parallel_coords, "Parallel Coordinates");
                 ui.checkbox(&mut state.show_3d_plot, "3D Plot");
                 ui.checkbox(&mut state.show_heatmap, "Correlation Heatmap");
+                ui.checkbox(&mut state.show_reference_drugs, "Reference Drugs on Plots");
                 
                 ui.separator();
                 
                 ui.label("🔬 Analysis:");
                 ui.checkbox(&mut state.show_clustering, "Clustering");
+                ui.checkbox(&mut state.show_scaffold_groups, "Group by Scaffold");
                 ui.checkbox(&mut state.show_similarity_search, "Similarity Search");
                 ui.checkbox(&mut state.show_druglikeness, "Drug-likeness Panel");
+                ui.checkbox(&mut state.show_seed_sweep, "Seed Sweep");
+                ui.checkbox(&mut state.show_preference, "Preference Learning");
+
+                ui.separator();
+
+                ui.checkbox(&mut state.show_log, "📜 Log");
             });
 
             // Settings menu
@@ -96,6 +159,38 @@ pub fn render(ctx: &egui::Context, state: &mut AppState, theme: &mut ThemeSettin
                 if theme_picker(ui, theme) {
                     state.theme_changed = true;
                 }
+
+                ui.separator();
+
+                ui.label("↩️ Undo history:");
+                if ui.add(egui::Slider::new(&mut theme.max_history, 10..=500).text("steps")).changed() {
+                    state.history.set_max_history(theme.max_history);
+                }
+
+                ui.separator();
+
+                ui.label("⚡ Generation threads:");
+                ui.add(egui::Slider::new(&mut state.thread_count, 0..=rayon::current_num_threads()).text("threads"))
+                    .on_hover_text("Threads for parallel generation, 0 = all cores");
+                ui.add(egui::Slider::new(&mut state.batch_size, 1..=1000).logarithmic(true).text("batch"))
+                    .on_hover_text("Candidates per step in sequential generation; cancelling keeps finished batches");
+
+                ui.separator();
+
+                ui.label("🔢 Displayed decimals:");
+                if ui.add(egui::Slider::new(&mut theme.display_decimals, DISPLAY_DECIMALS_RANGE)).changed() {
+                    state.display_decimals = theme.display_decimals;
+                }
+
+                ui.separator();
+
+                ui.label("💾 Autosave every:");
+                if ui.add(egui::Slider::new(&mut theme.autosave_secs, 0..=600).text("s"))
+                    .on_hover_text("Crash-recovery snapshot interval, 0 = off")
+                    .changed()
+                {
+                    state.autosave_interval_secs = theme.autosave_secs;
+                }
             });
 
             ui.separator();
@@ -108,16 +203,38 @@ pub fn render(ctx: &egui::Context, state: &mut AppState, theme: &mut ThemeSettin
             ui.add(egui::DragValue::new(&mut state.seed).clamp_range(0..=u64::MAX).speed(1));
 
             ui.checkbox(&mut state.use_parallel, "⚡").on_hover_text("Parallel generation");
+            ui.checkbox(&mut state.deterministic_objectives, "🎯")
+                .on_hover_text("Deterministic objectives: no random variability, the same molecule always scores the same");
             ui.checkbox(&mut state.use_scaffolds, "💊").on_hover_text("Use drug scaffolds");
+            egui::ComboBox::from_id_source("scaffold_category")
+                .selected_text(state.scaffold_category.as_deref().unwrap_or("All"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut state.scaffold_category, None, "All");
+                    let mut categories: Vec<String> = scaffolds::list_categories().into_iter().map(String::from).collect();
+                    categories.extend(state.custom_scaffolds.iter().map(|s| s.category.clone()));
+                    categories.sort();
+                    categories.dedup();
+                    for category in categories {
+                        ui.selectable_value(&mut state.scaffold_category, Some(category.clone()), category);
+                    }
+                })
+                .response
+                .on_hover_text("Scaffold category");
 
             if state.is_generating {
                 if ui.button("⏹ Cancel").clicked() {
                     state.cancel_generation();
                 }
             } else {
-                if ui.button("🧬 Generate").clicked() {
+                if ui.button("🧬 Generate").on_hover_text("Ctrl+G").clicked() {
                     state.generate();
                 }
+                if ui.button("🔀 Evolve")
+                    .on_hover_text("Mutate and cross over the selected candidates (or the Pareto front)")
+                    .clicked()
+                {
+                    state.evolve();
+                }
             }
 
             // Undo/Redo buttons
@@ -134,24 +251,127 @@ pub fn render(ctx: &egui::Context, state: &mut AppState, theme: &mut ThemeSettin
             // Status
             let status_color = if state.is_generating {
                 egui::Color32::from_rgb(100, 180, 255)
-            } else if state.status.contains("Error") || state.status.contains("error") {
-                egui::Color32::from_rgb(255, 100, 100)
             } else {
-                egui::Color32::from_rgb(100, 255, 100)
+                state.status_log.back()
+                    .map(|(_, _, level)| log_panel::level_color(*level))
+                    .unwrap_or(egui::Color32::from_rgb(100, 255, 100))
             };
-            ui.colored_label(status_color, &state.status);
+            if ui.add(egui::Label::new(egui::RichText::new(&state.status).color(status_color)).sense(egui::Sense::click()))
+                .on_hover_text("Click to toggle the log")
+                .clicked()
+            {
+                state.show_log = !state.show_log;
+            }
 
             // Progress bar
             if let Some((current, total)) = state.generation_progress {
                 ui.separator();
                 let progress = current as f32 / total as f32;
                 ui.add(egui::ProgressBar::new(progress).text(format!("{}/{}", current, total)).animate(true));
+                if let Some(started) = state.generation_started {
+                    ui.label(format_eta(current, total, started.elapsed()));
+                }
+            }
+
+            if let Some(imported) = state.import_progress {
+                ui.separator();
+                ui.spinner();
+                ui.label(format!("Imported {}", imported));
             }
         });
     });
 
     // Import dialog window
     render_import_dialog(ctx, state);
+    render_duplicates_dialog(ctx, state);
+    render_recover_dialog(ctx, state);
+}
+
+fn render_duplicates_dialog(ctx: &egui::Context, state: &mut AppState) {
+    let Some(groups) = &state.duplicate_groups else {
+        return;
+    };
+    let redundant: usize = groups.iter().map(|g| g.len() - 1).sum();
+    let near_groups = &state.near_duplicate_groups;
+    let mut near_threshold = state.near_duplicate_threshold;
+
+    let mut merge = false;
+    let mut find_near = false;
+    let mut collapse = false;
+    let mut close = false;
+    egui::Window::new("🔁 Duplicates")
+        .collapsible(false)
+        .resizable(true)
+        .default_width(320.0)
+        .show(ctx, |ui| {
+            if groups.is_empty() {
+                ui.label("No duplicate structures found");
+            } else {
+                ui.label(format!("{} duplicate groups, {} redundant candidates", groups.len(), redundant));
+                egui::ScrollArea::vertical()
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        for group in groups {
+                            let ids: Vec<String> = group.iter().map(|id| format!("#{}", id)).collect();
+                            ui.label(ids.join(", "));
+                        }
+                    });
+            }
+
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!groups.is_empty(), egui::Button::new("🔗 Merge duplicates"))
+                    .on_hover_text("Keep the lowest id of each group and delete the others (undoable)")
+                    .clicked()
+                {
+                    merge = true;
+                }
+            });
+
+            ui.separator();
+            ui.label("Near duplicates (fingerprint Tanimoto):");
+            ui.horizontal(|ui| {
+                ui.add(egui::Slider::new(&mut near_threshold, 0.8..=1.0).step_by(0.01));
+                if ui.button("🔍 Find").clicked() {
+                    find_near = true;
+                }
+            });
+            if let Some(near) = near_groups {
+                let redundant: usize = near.iter().map(|g| g.len() - 1).sum();
+                ui.label(format!("{} groups, {} redundant candidates", near.len(), redundant));
+                egui::ScrollArea::vertical()
+                    .id_source("near_duplicate_groups")
+                    .max_height(150.0)
+                    .show(ui, |ui| {
+                        for group in near {
+                            let ids: Vec<String> = group.iter().map(|id| format!("#{}", id)).collect();
+                            ui.label(ids.join(", "));
+                        }
+                    });
+                if ui.add_enabled(!near.is_empty(), egui::Button::new("🗜 Collapse to best"))
+                    .on_hover_text("Keep the highest-scoring member of each group and delete the others (undoable)")
+                    .clicked()
+                {
+                    collapse = true;
+                }
+            }
+
+            ui.separator();
+            if ui.button("Close").clicked() {
+                close = true;
+            }
+        });
+
+    state.near_duplicate_threshold = near_threshold;
+    if merge {
+        state.merge_duplicates();
+    } else if find_near {
+        state.find_near_duplicates();
+    } else if collapse {
+        state.collapse_near_duplicates();
+    } else if close {
+        state.duplicate_groups = None;
+        state.near_duplicate_groups = None;
+    }
 }
 
 fn render_import_dialog(ctx: &egui::Context, state: &mut AppState) {
@@ -196,6 +416,31 @@ fn render_import_dialog(ctx: &egui::Context, state: &mut AppState) {
                 ui.label(format!("Lines: {}", state.import_text.lines().count()));
             });
 
+            ui.checkbox(&mut state.import_dedup, "Skip duplicates")
+                .on_hover_text("Skip SMILES matching an existing candidate or an earlier line (canonical comparison)");
+
+            ui.separator();
+
+            ui.label("Or stream a large file from disk:");
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut state.import_path)
+                        .hint_text("path/to/library.smi")
+                        .desired_width(280.0)
+                );
+                let can_stream = !state.import_path.trim().is_empty()
+                    && state.import_progress.is_none()
+                    && !state.is_generating;
+                if ui.add_enabled(can_stream, egui::Button::new("📥 Stream"))
+                    .on_hover_text("Import in the background with progress")
+                    .clicked()
+                {
+                    let path = state.import_path.trim().to_string();
+                    state.import_file_streaming(&path);
+                    state.show_import_dialog = false;
+                }
+            });
+
             ui.separator();
 
             ui.horizontal(|ui| {
@@ -213,83 +458,261 @@ fn render_import_dialog(ctx: &egui::Context, state: &mut AppState) {
         });
 }
 
-fn save_session_dialog(state: &mut AppState) {
+pub fn save_session_dialog(state: &mut AppState) {
     let filename = format!("session_{}.json", chrono::Utc::now().format("%Y%m%d_%H%M%S"));
     match state.save_session(&filename) {
-        Ok(()) => state.status = format!("✅ Saved to {}", filename),
-        Err(e) => state.status = format!("❌ Save failed: {}", e),
+        Ok(()) => {
+            state.push_status(LogLevel::Success, format!("✅ Saved to {}", filename));
+            state.remove_autosave();
+        }
+        Err(e) => state.push_status(LogLevel::Error, format!("❌ Save failed: {}", e)),
     }
 }
 
-fn load_session_dialog(state: &mut AppState) {
-    if let Ok(entries) = std::fs::read_dir(".") {
-        let mut session_files: Vec<_> = entries
-            .filter_map(|e| e.ok())
-            .filter(|e| {
-                let name = e.file_name().to_string_lossy().to_string();
-                name.starts_with("session_") && name.ends_with(".json")
-            })
-            .collect();
-        
-        session_files.sort_by(|a, b| {
-            b.metadata().and_then(|m| m.modified()).ok()
-                .cmp(&a.metadata().and_then(|m| m.modified()).ok())
+/// Most recently modified `<prefix>*.json` file in the working directory
+fn latest_json_file(prefix: &str) -> Option<std::path::PathBuf> {
+    let entries = std::fs::read_dir(".").ok()?;
+    let mut files: Vec<_> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            name.starts_with(prefix) && name.ends_with(".json")
+        })
+        .collect();
+
+    files.sort_by(|a, b| {
+        b.metadata().and_then(|m| m.modified()).ok()
+            .cmp(&a.metadata().and_then(|m| m.modified()).ok())
+    });
+    files.first().map(|e| e.path())
+}
+
+/// True when the autosave is newer than every saved session, i.e. work may have been lost
+pub fn autosave_is_newer() -> bool {
+    let modified = |path: &std::path::Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    let Some(autosave) = modified(std::path::Path::new(AUTOSAVE_PATH)) else {
+        return false;
+    };
+    latest_json_file("session_")
+        .and_then(|path| modified(&path))
+        .is_none_or(|session| autosave > session)
+}
+
+fn render_recover_dialog(ctx: &egui::Context, state: &mut AppState) {
+    if !state.show_recover_dialog {
+        return;
+    }
+
+    let mut recover = false;
+    let mut discard = false;
+    egui::Window::new("💾 Recover session")
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label("An autosave newer than your last saved session was found.");
+            ui.label("It may contain work from a session that ended unexpectedly.");
+            ui.horizontal(|ui| {
+                recover = ui.button("♻ Recover").clicked();
+                discard = ui.button("🗑 Discard").clicked();
+            });
         });
 
-        if let Some(latest) = session_files.first() {
-            match state.load_session(latest.path().to_str().unwrap_or("")) {
-                Ok(()) => state.status = format!("✅ Loaded {} candidates", state.candidates.len()),
-                Err(e) => state.status = format!("❌ Load failed: {}", e),
-            }
-        } else {
-            state.status = "No session files found".into();
+    if recover {
+        state.show_recover_dialog = false;
+        match state.load_session(AUTOSAVE_PATH) {
+            Ok(()) => state.push_status(LogLevel::Success, format!("✅ Recovered {} candidates", state.candidates.len())),
+            Err(e) => state.push_status(LogLevel::Error, format!("❌ Recovery failed: {}", e)),
         }
+    } else if discard {
+        state.show_recover_dialog = false;
+        state.remove_autosave();
     }
 }
 
-fn export_csv(state: &mut AppState) {
-    use std::io::Write;
-    let filename = format!("candidates_{}.csv", chrono::Utc::now().format("%Y%m%d_%H%M%S"));
-    match std::fs::File::create(&filename) {
-        Ok(mut file) => {
-            writeln!(file, "ID,SMILES,Efficacy,Toxicity,SynthesisCost,ManufacturingCost,Pareto,Score,Favorite").unwrap();
-            for c in &state.candidates {
-                let score = state.weighted_score(c);
-                let fav = if state.annotations.is_favorite(c.id) { "1" } else { "0" };
-                writeln!(file, "{},{},{:.4},{:.4},{:.4},{:.4},{},{:.4},{}", 
-                    c.id, c.smiles, c.efficacy, c.toxicity, c.synthesis_cost, c.manufacturing_cost, c.pareto, score, fav).unwrap();
+fn load_session_dialog(state: &mut AppState) {
+    if let Some(latest) = latest_json_file("session_") {
+        match state.load_session(latest.to_str().unwrap_or("")) {
+            Ok(()) => state.push_status(LogLevel::Success, format!("✅ Loaded {} candidates", state.candidates.len())),
+            Err(e) => state.push_status(LogLevel::Error, format!("❌ Load failed: {}", e)),
+        }
+    } else {
+        state.push_status(LogLevel::Warning, "No session files found");
+    }
+}
+
+/// Where a file written relative to `cwd` ends up
+fn absolute_export_path(filename: &str, cwd: &Path) -> PathBuf {
+    let path = Path::new(filename);
+    if path.is_absolute() { path.to_path_buf() } else { cwd.join(path) }
+}
+
+/// Remember a successful export for "Reveal" and report its full path
+fn record_export(state: &mut AppState, filename: &str) {
+    let path = match std::env::current_dir() {
+        Ok(cwd) => absolute_export_path(filename, &cwd),
+        Err(_) => PathBuf::from(filename),
+    };
+    state.push_status(LogLevel::Success, format!("✅ Exported to {}", path.display()));
+    state.last_export_path = Some(path);
+}
+
+/// Open the platform file browser at `path`, selecting it where supported
+fn reveal_in_file_manager(path: &Path) -> Result<(), String> {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = Command::new("explorer");
+        command.arg(format!("/select,{}", path.display()));
+        command
+    } else if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.arg("-R").arg(path);
+        command
+    } else {
+        let mut command = Command::new("xdg-open");
+        command.arg(path.parent().unwrap_or(Path::new(".")));
+        command
+    };
+    command.spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Could not open file manager: {}", e))
+}
+
+fn export_annotations(state: &mut AppState) {
+    let filename = format!("annotations_{}.json", chrono::Utc::now().format("%Y%m%d_%H%M%S"));
+    match io::export_annotations(state, &filename) {
+        Ok(merged) => {
+            record_export(state, &filename);
+            if merged > 0 {
+                state.push_status(LogLevel::Info,
+                    format!("Merged the annotations of {} duplicate structures into one entry each", merged));
             }
-            state.status = format!("✅ Exported to {}", filename);
         }
-        Err(e) => state.status = format!("❌ Export failed: {}", e),
+        Err(e) => state.push_status(LogLevel::Error, format!("❌ Export failed: {}", e)),
+    }
+}
+
+fn import_annotations(state: &mut AppState) {
+    let Some(latest) = latest_json_file("annotations_") else {
+        state.push_status(LogLevel::Warning, "No annotation files found");
+        return;
+    };
+    match io::import_annotations(state, latest.to_str().unwrap_or("")) {
+        Ok(count) => state.push_status(LogLevel::Success, format!("✅ Annotated {} candidates", count)),
+        Err(e) => state.push_status(LogLevel::Error, format!("❌ Import failed: {}", e)),
+    }
+}
+
+pub fn export_csv(state: &mut AppState) {
+    let filename = format!("candidates_{}.csv", chrono::Utc::now().format("%Y%m%d_%H%M%S"));
+    let result = io::export_csv(
+        &state.candidates,
+        &filename,
+        |c| state.weighted_score(c),
+        |id| state.annotations.is_favorite(id),
+        state.export_extended_csv,
+    );
+    match result {
+        Ok(()) => record_export(state, &filename),
+        Err(e) => state.push_status(LogLevel::Error, format!("❌ Export failed: {}", e)),
+    }
+}
+
+fn export_report(state: &mut AppState) {
+    let filename = format!("report_{}.html", chrono::Utc::now().format("%Y%m%d_%H%M%S"));
+    match report::generate_html_report(state, &filename) {
+        Ok(()) => record_export(state, &filename),
+        Err(e) => state.push_status(LogLevel::Error, format!("❌ Export failed: {}", e)),
     }
 }
 
-fn export_json(state: &mut AppState) {
+fn export_similarity_graph(state: &mut AppState) {
+    if state.candidates.len() > io::MAX_GRAPH_NODES {
+        state.push_status(LogLevel::Warning, format!(
+            "Similarity graph limited to the first {} of {} candidates", io::MAX_GRAPH_NODES, state.candidates.len()));
+    }
+    let filename = format!("similarity_{}.graphml", chrono::Utc::now().format("%Y%m%d_%H%M%S"));
+    match io::export_similarity_graph(&state.candidates, state.graph_threshold, &filename) {
+        Ok(()) => record_export(state, &filename),
+        Err(e) => state.push_status(LogLevel::Error, format!("❌ Export failed: {}", e)),
+    }
+}
+
+pub fn export_json(state: &mut AppState) {
     use std::io::Write;
     let filename = format!("candidates_{}.json", chrono::Utc::now().format("%Y%m%d_%H%M%S"));
     match std::fs::File::create(&filename) {
         Ok(mut file) => {
             let json = serde_json::to_string_pretty(&state.candidates).unwrap();
             file.write_all(json.as_bytes()).unwrap();
-            state.status = format!("✅ Exported to {}", filename);
+            record_export(state, &filename);
         }
-        Err(e) => state.status = format!("❌ Export failed: {}", e),
+        Err(e) => state.push_status(LogLevel::Error, format!("❌ Export failed: {}", e)),
     }
 }
 
-fn export_sdf(state: &mut AppState) {
+pub fn export_sdf(state: &mut AppState) {
     let filename = format!("candidates_{}.sdf", chrono::Utc::now().format("%Y%m%d_%H%M%S"));
     match io::export_sdf(&state.candidates, &filename) {
-        Ok(()) => state.status = format!("✅ Exported to {}", filename),
-        Err(e) => state.status = format!("❌ Export failed: {}", e),
+        Ok(()) => record_export(state, &filename),
+        Err(e) => state.push_status(LogLevel::Error, format!("❌ Export failed: {}", e)),
+    }
+}
+
+fn export_parquet(state: &mut AppState) {
+    let filename = format!("candidates_{}.parquet", chrono::Utc::now().format("%Y%m%d_%H%M%S"));
+    match io::export_parquet(&state.candidates, &filename) {
+        Ok(()) => record_export(state, &filename),
+        Err(e) => state.push_status(LogLevel::Error, format!("❌ Export failed: {}", e)),
     }
 }
 
 fn export_smiles(state: &mut AppState) {
     let filename = format!("candidates_{}.smi", chrono::Utc::now().format("%Y%m%d_%H%M%S"));
     match io::export_smiles_file(&state.candidates, &filename) {
-        Ok(()) => state.status = format!("✅ Exported to {}", filename),
-        Err(e) => state.status = format!("❌ Export failed: {}", e),
+        Ok(()) => record_export(state, &filename),
+        Err(e) => state.push_status(LogLevel::Error, format!("❌ Export failed: {}", e)),
+    }
+}
+
+/// Throughput and remaining time, e.g. "~1200/s, ETA 8s"
+fn format_eta(done: usize, total: usize, elapsed: Duration) -> String {
+    let secs = elapsed.as_secs_f64();
+    if done == 0 || secs <= 0.0 {
+        return "estimating...".into();
+    }
+
+    let rate = done as f64 / secs;
+    let remaining = total.saturating_sub(done) as f64 / rate;
+    let eta = remaining.round() as u64;
+    let eta = if eta < 60 {
+        format!("{}s", eta)
+    } else if eta < 3600 {
+        format!("{}m{:02}s", eta / 60, eta % 60)
+    } else {
+        format!("{}h{:02}m", eta / 3600, (eta % 3600) / 60)
+    };
+
+    format!("~{:.0}/s, ETA {}", rate, eta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_absolute_export_path() {
+        let cwd = Path::new("/home/user/studio");
+        assert_eq!(absolute_export_path("candidates_1.csv", cwd), PathBuf::from("/home/user/studio/candidates_1.csv"));
+        assert_eq!(absolute_export_path("out/report.html", cwd), PathBuf::from("/home/user/studio/out/report.html"));
+        assert_eq!(absolute_export_path("/tmp/graph.graphml", cwd), PathBuf::from("/tmp/graph.graphml"));
+    }
+
+    #[test]
+    fn test_format_eta() {
+        assert_eq!(format_eta(0, 1000, Duration::from_secs(3)), "estimating...");
+        assert_eq!(format_eta(10, 1000, Duration::ZERO), "estimating...");
+        assert_eq!(format_eta(1000, 1000, Duration::from_secs(2)), "~500/s, ETA 0s");
+        assert_eq!(format_eta(250, 1000, Duration::from_secs(5)), "~50/s, ETA 15s");
+        assert_eq!(format_eta(100, 100_000, Duration::from_secs(1)), "~100/s, ETA 16m39s");
+        assert_eq!(format_eta(10, 100_000, Duration::from_secs(1)), "~10/s, ETA 2h46m");
     }
 }
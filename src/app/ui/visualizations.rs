@@ -2,10 +2,29 @@
 
 use eframe::egui;
 use egui_plot::{Plot, Bar, BarChart, Line, PlotPoints};
-use crate::app::state::{AppState, Candidate};
+use crate::app::state::{safe_log10, AppState, Candidate, LOG_FLOOR};
+
+/// Allowed range for the histogram bin count
+const HISTOGRAM_BIN_RANGE: std::ops::RangeInclusive<usize> = 5..=100;
 
 /// Render histograms for all objectives
-pub fn render_histograms(ui: &mut egui::Ui, state: &AppState) {
+pub fn render_histograms(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.checkbox(&mut state.histogram_split_pareto, "Split by Pareto")
+        .on_hover_text("Overlay the Pareto subset (green) on the rest (gray)");
+
+    ui.horizontal(|ui| {
+        ui.label("Bins:");
+        ui.add(egui::Slider::new(&mut state.histogram_bins, HISTOGRAM_BIN_RANGE));
+        if ui.button("Freedman–Diaconis auto")
+            .on_hover_text("Bin width from the interquartile range; uses the finest of the four objectives")
+            .clicked()
+        {
+            state.histogram_bins = auto_histogram_bins(state);
+        }
+        ui.checkbox(&mut state.log_x, "log x").on_hover_text("Shared with the scatter plots' x axis");
+    });
+
+    let state = &*state;
     let candidates = state.filtered_candidates();
     
     if candidates.is_empty() {
@@ -17,7 +36,7 @@ pub fn render_histograms(ui: &mut egui::Ui, state: &AppState) {
         // Efficacy histogram
         ui.vertical(|ui| {
             ui.label("📊 Efficacy Distribution");
-            render_histogram(ui, "hist_eff", &candidates, |c| c.efficacy, egui::Color32::from_rgb(100, 200, 100));
+            render_objective_histogram(ui, state, &candidates, "hist_eff", 0, egui::Color32::from_rgb(100, 200, 100));
         });
 
         ui.separator();
@@ -25,7 +44,7 @@ pub fn render_histograms(ui: &mut egui::Ui, state: &AppState) {
         // Toxicity histogram
         ui.vertical(|ui| {
             ui.label("📊 Toxicity Distribution");
-            render_histogram(ui, "hist_tox", &candidates, |c| c.toxicity, egui::Color32::from_rgb(255, 150, 100));
+            render_objective_histogram(ui, state, &candidates, "hist_tox", 1, egui::Color32::from_rgb(255, 150, 100));
         });
     });
 
@@ -35,7 +54,7 @@ pub fn render_histograms(ui: &mut egui::Ui, state: &AppState) {
         // Synthesis cost histogram
         ui.vertical(|ui| {
             ui.label("📊 Synthesis Cost Distribution");
-            render_histogram(ui, "hist_syn", &candidates, |c| c.synthesis_cost, egui::Color32::from_rgb(100, 150, 255));
+            render_objective_histogram(ui, state, &candidates, "hist_syn", 2, egui::Color32::from_rgb(100, 150, 255));
         });
 
         ui.separator();
@@ -43,51 +62,120 @@ pub fn render_histograms(ui: &mut egui::Ui, state: &AppState) {
         // Manufacturing cost histogram
         ui.vertical(|ui| {
             ui.label("📊 Manufacturing Cost Distribution");
-            render_histogram(ui, "hist_mfg", &candidates, |c| c.manufacturing_cost, egui::Color32::from_rgb(200, 100, 200));
+            render_objective_histogram(ui, state, &candidates, "hist_mfg", 3, egui::Color32::from_rgb(200, 100, 200));
         });
     });
 }
 
-fn render_histogram<F>(
+/// One objective's histogram, either as a single series or Pareto vs the rest
+fn render_objective_histogram(
     ui: &mut egui::Ui,
-    id: &str,
+    state: &AppState,
     candidates: &[&Candidate],
-    value_fn: F,
+    id: &str,
+    objective: usize,
     color: egui::Color32,
-) where
-    F: Fn(&Candidate) -> f32,
-{
-    let num_bins = 20;
-    let mut bins = vec![0u32; num_bins];
-    
-    // Calculate histogram
-    for c in candidates {
-        let value = value_fn(c).clamp(0.0, 1.0);
-        let bin = ((value * num_bins as f32) as usize).min(num_bins - 1);
-        bins[bin] += 1;
+) {
+    if !state.active_objectives[objective] {
+        ui.weak("Inactive objective");
+        return;
     }
+    let value = |c: &&Candidate| state.objective_values(c)[objective];
+
+    let (num_bins, log) = (state.histogram_bins, state.log_x);
+    let charts = if state.histogram_split_pareto {
+        vec![
+            histogram_chart(
+                candidates.iter().filter(|c| !c.pareto).map(value),
+                num_bins,
+                log,
+                egui::Color32::from_rgba_unmultiplied(150, 150, 150, 140),
+            ).name("Other"),
+            histogram_chart(
+                candidates.iter().filter(|c| c.pareto).map(value),
+                num_bins,
+                log,
+                egui::Color32::from_rgba_unmultiplied(0, 200, 100, 140),
+            ).name("Pareto"),
+        ]
+    } else {
+        vec![histogram_chart(candidates.iter().map(value), num_bins, log, color)]
+    };
 
-    // Convert to bars
-    let bars: Vec<Bar> = bins
+    Plot::new(id)
+        .height(120.0)
+        .width(200.0)
+        .show_axes([true, true])
+        .show(ui, |plot_ui| {
+            for chart in charts {
+                plot_ui.bar_chart(chart);
+            }
+        });
+}
+
+/// Bar series over the shared 0-1 bins, or over log10(LOG_FLOOR)..0 on a log axis
+fn histogram_chart(values: impl Iterator<Item = f32>, num_bins: usize, log: bool, color: egui::Color32) -> BarChart {
+    let (lo, hi) = if log { (LOG_FLOOR.log10(), 0.0) } else { (0.0, 1.0) };
+    let to_unit = |v: f32| if log { (safe_log10(v) - lo) / (hi - lo) } else { v };
+    let width = (hi - lo) as f64 / num_bins as f64;
+    let bars: Vec<Bar> = histogram_bins(values.map(to_unit), num_bins)
         .iter()
         .enumerate()
         .map(|(i, &count)| {
-            let x = (i as f64 + 0.5) / num_bins as f64;
+            let x = lo as f64 + (i as f64 + 0.5) * width;
             Bar::new(x, count as f64)
-                .width(0.8 / num_bins as f64)
+                .width(0.8 * width)
                 .fill(color)
         })
         .collect();
 
-    let chart = BarChart::new(bars);
+    BarChart::new(bars).color(color)
+}
 
-    Plot::new(id)
-        .height(120.0)
-        .width(200.0)
-        .show_axes([true, true])
-        .show(ui, |plot_ui| {
-            plot_ui.bar_chart(chart);
-        });
+/// Count values into `num_bins` equal bins over 0-1; out-of-range values land in the end bins
+fn histogram_bins(values: impl Iterator<Item = f32>, num_bins: usize) -> Vec<u32> {
+    let mut bins = vec![0u32; num_bins];
+    for value in values {
+        let value = value.clamp(0.0, 1.0);
+        let bin = ((value * num_bins as f32) as usize).min(num_bins - 1);
+        bins[bin] += 1;
+    }
+    bins
+}
+
+/// Data-driven bin count for the filtered candidates: the largest
+/// Freedman–Diaconis count among the four objectives
+fn auto_histogram_bins(state: &AppState) -> usize {
+    let candidates = state.filtered_candidates();
+    (0..4)
+        .map(|k| {
+            let values: Vec<f32> = candidates.iter().map(|c| state.objective_values(c)[k]).collect();
+            freedman_diaconis_bins(&values)
+        })
+        .max()
+        .unwrap_or(state.histogram_bins)
+}
+
+/// Freedman–Diaconis bin count over the plotted 0-1 span: bin width 2·IQR·n^(-1/3),
+/// clamped to `HISTOGRAM_BIN_RANGE`. Too few values or no spread give 20 bins.
+fn freedman_diaconis_bins(values: &[f32]) -> usize {
+    const FALLBACK: usize = 20;
+    if values.len() < 2 {
+        return FALLBACK;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f32::total_cmp);
+    let quantile = |q: f32| {
+        let pos = q * (sorted.len() - 1) as f32;
+        let (lo, hi) = (pos.floor() as usize, pos.ceil() as usize);
+        sorted[lo] + (sorted[hi] - sorted[lo]) * (pos - lo as f32)
+    };
+    let iqr = quantile(0.75) - quantile(0.25);
+    if iqr <= f32::EPSILON {
+        return FALLBACK;
+    }
+    let width = 2.0 * iqr / (sorted.len() as f32).cbrt();
+    ((1.0 / width).round() as usize).clamp(*HISTOGRAM_BIN_RANGE.start(), *HISTOGRAM_BIN_RANGE.end())
 }
 
 /// Render parallel coordinates plot
@@ -128,11 +216,12 @@ pub fn render_parallel_coordinates(ui: &mut egui::Ui, state: &AppState) {
                 }
 
                 // Normalize values and invert toxicity/costs (lower is better)
+                let [eff, tox, syn, mfg] = state.objective_values(c);
                 let values = [
-                    c.efficacy as f64,                    // Higher is better
-                    1.0 - c.toxicity as f64,              // Invert: lower tox = higher value
-                    1.0 - c.synthesis_cost as f64,        // Invert
-                    1.0 - c.manufacturing_cost as f64,    // Invert
+                    eff as f64,                    // Higher is better
+                    1.0 - tox as f64,              // Invert: lower tox = higher value
+                    1.0 - syn as f64,              // Invert
+                    1.0 - mfg as f64,              // Invert
                 ];
 
                 let points: PlotPoints = axis_positions
@@ -200,15 +289,15 @@ pub fn render_stats_summary(ui: &mut egui::Ui, state: &AppState) {
             ui.label(format!("📊 Filtered: {} |", candidates.len()));
             ui.colored_label(
                 egui::Color32::from_rgb(100, 200, 100),
-                format!("Eff: avg={:.3} max={:.3}", avg_eff, max_eff)
+                format!("Eff: avg={} max={}", state.fmt_val(avg_eff), state.fmt_val(max_eff))
             );
             ui.label("|");
             ui.colored_label(
                 egui::Color32::from_rgb(255, 150, 100),
-                format!("Tox: avg={:.3} min={:.3}", avg_tox, min_tox)
+                format!("Tox: avg={} min={}", state.fmt_val(avg_tox), state.fmt_val(min_tox))
             );
             ui.label("|");
-            ui.label(format!("Syn: {:.3} | Mfg: {:.3}", avg_syn, avg_mfg));
+            ui.label(format!("Syn: {} | Mfg: {}", state.fmt_val(avg_syn), state.fmt_val(avg_mfg)));
         });
     });
 }
@@ -262,3 +351,42 @@ pub fn render_correlation_hint(ui: &mut egui::Ui, state: &AppState) {
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_freedman_diaconis_bins() {
+        // Uniform on 0-1: IQR ≈ 0.5, n = 1000, width ≈ 0.1
+        let uniform: Vec<f32> = (0..1000).map(|i| i as f32 / 1000.0).collect();
+        assert_eq!(freedman_diaconis_bins(&uniform), 10);
+
+        // A tight cluster needs more bins than the cap allows
+        let tight: Vec<f32> = (0..1000).map(|i| 0.5 + i as f32 / 1e5).collect();
+        assert_eq!(freedman_diaconis_bins(&tight), 100);
+
+        // Few values or no spread fall back
+        assert_eq!(freedman_diaconis_bins(&[0.3]), 20);
+        assert_eq!(freedman_diaconis_bins(&[0.4; 50]), 20);
+    }
+
+    #[test]
+    fn test_histogram_bins() {
+        let bins = histogram_bins([0.0, 0.05, 0.1, 0.55, 0.99, 1.0, 1.5, -0.2].into_iter(), 10);
+        assert_eq!(bins.len(), 10);
+        assert_eq!(bins[0], 3);  // 0.0, 0.05, clamped -0.2
+        assert_eq!(bins[1], 1);
+        assert_eq!(bins[5], 1);
+        assert_eq!(bins[9], 3);  // 0.99, 1.0, clamped 1.5
+        assert_eq!(bins.iter().sum::<u32>(), 8);
+
+        // Both halves of a split share bins, so counts add up to the whole
+        let all = [0.1f32, 0.2, 0.3, 0.8];
+        let pareto = histogram_bins(all.iter().copied().filter(|&v| v > 0.25), 4);
+        let other = histogram_bins(all.iter().copied().filter(|&v| v <= 0.25), 4);
+        let whole = histogram_bins(all.iter().copied(), 4);
+        let summed: Vec<u32> = pareto.iter().zip(&other).map(|(a, b)| a + b).collect();
+        assert_eq!(summed, whole);
+    }
+}
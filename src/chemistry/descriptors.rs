@@ -1,10 +1,84 @@
 // Calculs de propriétés moléculaires à partir de SMILES
 use std::collections::HashMap;
+use super::{graph, sascore};
+
+/// All molecular descriptors for one SMILES, computed in a single call
+#[derive(Clone, Debug, Default)]
+pub struct MolecularDescriptors {
+    pub molecular_weight: f32,
+    pub logp: f32,
+    pub psa: f32,
+    pub hbd: usize,
+    pub hba: usize,
+    pub rotatable_bonds: usize,
+    pub heavy_atoms: usize,
+    pub total_atoms: usize,  // including hydrogens
+    pub rings: usize,
+    pub sa_score: f32,       // synthetic accessibility, 1 (easy) to 10 (hard)
+}
+
+/// Compute every descriptor for a SMILES string
+pub fn compute_descriptors(smiles: &str) -> MolecularDescriptors {
+    let (hbd, hba) = hbd_hba_count(smiles);
+    let heavy_atoms = heavy_atom_count(smiles);
+
+    // Ring and hydrogen counts come from the parsed graph when available
+    let (rings, hydrogens) = match graph::parse_smiles(smiles) {
+        Ok(g) => (g.ring_count(), g.atoms.iter().map(|a| a.total_h() as usize).sum()),
+        Err(_) => (
+            smiles.chars().filter(|c| c.is_ascii_digit()).count() / 2,
+            estimate_implicit_hydrogens(smiles),
+        ),
+    };
+
+    MolecularDescriptors {
+        molecular_weight: molecular_weight_from_smiles(smiles),
+        logp: logp_from_smiles(smiles),
+        psa: polar_surface_area_from_smiles(smiles),
+        hbd,
+        hba,
+        rotatable_bonds: rotatable_bonds_count(smiles),
+        heavy_atoms,
+        total_atoms: heavy_atoms + hydrogens,
+        rings,
+        sa_score: sascore::sa_score(smiles),
+    }
+}
+
+/// Molecular weight together with the number of atoms it could not account for
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MolecularWeight {
+    pub mass: f32,
+    pub unknown_atoms: usize,  // atoms whose element is missing from the mass table
+}
 
 /// Calculate molecular weight from SMILES string
 pub fn molecular_weight_from_smiles(smiles: &str) -> f32 {
+    molecular_weight_detailed(smiles).mass
+}
+
+/// Molecular weight plus a count of atoms that contributed no mass (unknown elements)
+pub fn molecular_weight_detailed(smiles: &str) -> MolecularWeight {
     let atomic_masses = get_atomic_masses();
+    let h_mass = atomic_masses["H"];
+
+    match graph::parse_smiles(smiles) {
+        Ok(g) => g.atoms.iter().fold(MolecularWeight::default(), |mut mw, a| {
+            match atomic_masses.get(&a.element) {
+                Some(&mass) => mw.mass += mass,
+                None => mw.unknown_atoms += 1,
+            }
+            mw.mass += a.total_h() as f32 * h_mass;
+            mw
+        }),
+        Err(_) => flat_molecular_weight(smiles, &atomic_masses),
+    }
+}
+
+/// Uppercase-symbol mass estimate for SMILES the graph parser rejects
+fn flat_molecular_weight(smiles: &str, atomic_masses: &HashMap<String, f32>) -> MolecularWeight {
     let mut total_mass = 0.0;
+    let mut unknown_atoms = 0;
 
     let chars: Vec<char> = smiles.chars().collect();
     let mut i = 0;
@@ -27,8 +101,9 @@ pub fn molecular_weight_from_smiles(smiles: &str) -> f32 {
             }
 
             // Add atomic mass
-            if let Some(&mass) = atomic_masses.get(&atom) {
-                total_mass += mass;
+            match atomic_masses.get(&atom) {
+                Some(&mass) => total_mass += mass,
+                None => unknown_atoms += 1,
             }
         } else {
             i += 1;
@@ -37,81 +112,310 @@ pub fn molecular_weight_from_smiles(smiles: &str) -> f32 {
 
     // Adjustment for implicit hydrogens (simple approximation)
     let h_count = estimate_implicit_hydrogens(smiles);
-    total_mass + h_count as f32 * 1.00784
+    MolecularWeight { mass: total_mass + h_count as f32 * 1.00784, unknown_atoms }
 }
 
 /// Calculate logP (partition coefficient) from SMILES
-/// Simplified calculation based on functional groups
+/// Wildman-Crippen style atom contributions (heavy atoms plus attached hydrogens)
 pub fn logp_from_smiles(smiles: &str) -> f32 {
-    let mut logp = 0.0;
+    let logp = match graph::parse_smiles(smiles) {
+        Ok(g) => (0..g.atoms.len()).map(|i| crippen_contribution(&g, i)).sum(),
+        Err(_) => flat_logp(smiles),
+    };
 
-    // Hydrophobic contributions
-    let c_count = smiles.chars().filter(|&c| c == 'C').count() as f32;
-    logp += c_count * 0.5; // Each carbon contributes ~0.5 to logP
+    logp.clamp(-2.0, 7.0) // Typical range of logP
+}
 
-    // Hydrophilic contributions
-    let o_count = smiles.chars().filter(|&c| c == 'O').count() as f32;
-    logp -= o_count * 0.8; // Oxygen decreases logP
+fn is_hetero(element: &str) -> bool {
+    matches!(element, "N" | "O" | "S" | "P" | "F" | "Cl" | "Br" | "I")
+}
 
-    let n_count = smiles.chars().filter(|&c| c == 'N').count() as f32;
-    logp -= n_count * 0.5; // Nitrogen decreases logP
+/// Contribution of one heavy atom and its hydrogens
+fn crippen_contribution(g: &graph::MolGraph, idx: usize) -> f32 {
+    use graph::BondOrder;
+
+    let atom = &g.atoms[idx];
+    let h = atom.total_h() as f32;
+    let neighbors: Vec<(usize, BondOrder)> = g.neighbors(idx).collect();
+    let hetero_neighbors = neighbors.iter().filter(|(n, _)| is_hetero(&g.atoms[*n].element)).count();
+    let aromatic_neighbor = neighbors.iter().any(|(n, _)| g.atoms[*n].aromatic);
+    let double_to_hetero = neighbors.iter()
+        .any(|(n, o)| matches!(o, BondOrder::Double | BondOrder::Triple) && is_hetero(&g.atoms[*n].element));
+
+    match (atom.element.as_str(), atom.aromatic) {
+        ("C", false) => {
+            let heavy = if double_to_hetero {
+                -0.2783                                        // C=O, C=N, C#N
+            } else if neighbors.iter().any(|(_, o)| *o == BondOrder::Double) {
+                0.1551                                         // C=C
+            } else if neighbors.iter().any(|(_, o)| *o == BondOrder::Triple) {
+                0.0017                                         // C#C
+            } else {
+                match (hetero_neighbors > 0, neighbors.len() <= 2) {
+                    (false, true) => 0.1441,                   // CH3R, CH2R2
+                    (false, false) => 0.0,                     // CHR3, CR4
+                    (true, true) => -0.2035,                   // CH3X, CH2RX
+                    (true, false) => -0.2051,                  // CHR2X, CR3X
+                }
+            };
+            heavy + h * 0.1230
+        }
+        ("C", true) => {
+            let substituent = neighbors.iter()
+                .find(|(n, o)| *o != BondOrder::Aromatic && !g.atoms[*n].aromatic)
+                .map(|(n, _)| g.atoms[*n].element.as_str());
+            let heavy = match substituent {
+                None if h > 0.0 => 0.1581,                     // aromatic CH
+                None => 0.2955,                                // bridgehead
+                Some("N") => 0.4619,
+                Some("O") => 0.5437,
+                Some("S") => 0.1893,
+                Some(_) => 0.1360,
+            };
+            heavy + h * 0.1230
+        }
+        ("N", false) => {
+            let heavy = if atom.charge != 0 || neighbors.iter().filter(|(_, o)| *o == BondOrder::Double).count() > 1 {
+                -0.3                                           // charged / nitro
+            } else if neighbors.iter().any(|(_, o)| matches!(o, BondOrder::Double | BondOrder::Triple)) {
+                -0.2                                           // imine, nitrile
+            } else {
+                match (atom.total_h(), aromatic_neighbor) {
+                    (2, false) => -1.0190,                     // primary amine
+                    (1, false) => -0.7096,                     // secondary amine
+                    (_, false) => -0.3187,                     // tertiary amine
+                    (2, true) => -0.4458,                      // aniline
+                    (_, true) => -0.2,
+                }
+            };
+            heavy + h * 0.2142
+        }
+        ("N", true) => {
+            if atom.total_h() > 0 {
+                -0.3239 + h * 0.2142                           // pyrrole-type
+            } else {
+                -0.4806                                        // pyridine-type
+            }
+        }
+        ("O", false) => {
+            if atom.charge < 0 {
+                let on_nitrogen = neighbors.iter().any(|(n, _)| g.atoms[*n].element == "N");
+                return if on_nitrogen { 0.0335 } else { -1.3260 };
+            }
+            if neighbors.iter().any(|(_, o)| *o == BondOrder::Double) {
+                let carbon_on_aromatic = neighbors.iter()
+                    .any(|(n, _)| g.neighbors(*n).any(|(m, _)| g.atoms[m].aromatic));
+                return if carbon_on_aromatic { 0.1129 } else { -0.1526 };
+            }
+            if atom.total_h() > 0 {
+                // Acid hydroxyl H is less hydrophilic than alcohol H
+                let acid = neighbors.iter().any(|(n, _)| {
+                    g.neighbors(*n).any(|(m, o)| o == BondOrder::Double && g.atoms[m].element == "O")
+                });
+                -0.2893 + h * if acid { 0.2980 } else { -0.2677 }
+            } else if aromatic_neighbor {
+                -0.4195                                        // aryl ether
+            } else {
+                -0.0684                                        // aliphatic ether
+            }
+        }
+        ("O", true) => 0.1552,
+        ("S", _) => 0.6237 + h * 0.1230,
+        ("P", _) => 0.8612,
+        ("F", _) => 0.4202,
+        ("Cl", _) => 0.6895,
+        ("Br", _) => 0.8456,
+        ("I", _) => 0.8857,
+        _ => 0.0,
+    }
+}
 
-    // Halogens increase logP
-    let f_count = smiles.chars().filter(|&c| c == 'F').count() as f32;
-    logp += f_count * 0.3;
+/// Character-count logP estimate for SMILES the graph parser rejects
+fn flat_logp(smiles: &str) -> f32 {
+    let count = |ch: char| smiles.chars().filter(|&c| c == ch).count() as f32;
+    count('C') * 0.5 - count('O') * 0.8 - count('N') * 0.5 + count('F') * 0.3
+}
 
-    // Special bonds
-    let double_bonds = smiles.chars().filter(|&c| c == '=').count() as f32;
-    logp += double_bonds * 0.1;
+/// Calculate topological polar surface area (Ertl TPSA) from SMILES
+/// Sums fragment contributions of N and O atoms classified by bonding environment
+pub fn polar_surface_area_from_smiles(smiles: &str) -> f32 {
+    let g = match graph::parse_smiles(smiles) {
+        Ok(g) => g,
+        Err(_) => return flat_polar_surface_area(smiles),
+    };
+
+    let ring_bonds = g.ring_bonds();
+    let three_rings: Vec<Vec<usize>> = g.smallest_rings()
+        .into_iter()
+        .filter(|r| r.len() == 3)
+        .collect();
+
+    (0..g.atoms.len())
+        .map(|i| {
+            let env = AtomEnv::new(&g, &ring_bonds, i);
+            let in_three_ring = three_rings.iter().any(|r| r.contains(&i));
+            tpsa_contribution(&g.atoms[i], &env, in_three_ring)
+        })
+        .sum()
+}
 
-    let triple_bonds = smiles.chars().filter(|&c| c == '#').count() as f32;
-    logp += triple_bonds * 0.2;
+/// Bonding environment of one atom, used for fragment classification
+struct AtomEnv {
+    single: usize,
+    double: usize,
+    triple: usize,
+    aromatic: usize,
+    h: u8,
+}
 
-    // Ring systems (indicated by numbers) tend to increase logP
-    let rings = smiles.chars().filter(|c| c.is_numeric()).count() as f32 / 2.0;
-    logp += rings * 0.3;
+impl AtomEnv {
+    fn new(g: &graph::MolGraph, ring_bonds: &[bool], idx: usize) -> Self {
+        let mut env = AtomEnv { single: 0, double: 0, triple: 0, aromatic: 0, h: g.atoms[idx].total_h() };
+        for (bond, &in_ring) in g.bonds.iter().zip(ring_bonds) {
+            if bond.a != idx && bond.b != idx {
+                continue;
+            }
+            match bond.order {
+                // Aromatic-aromatic links between rings (biphenyl) are plain single bonds
+                graph::BondOrder::Aromatic if in_ring => env.aromatic += 1,
+                graph::BondOrder::Single | graph::BondOrder::Aromatic => env.single += 1,
+                graph::BondOrder::Double => env.double += 1,
+                graph::BondOrder::Triple => env.triple += 1,
+            }
+        }
+        env
+    }
 
-    logp.clamp(-2.0, 7.0) // Typical range of logP
+    /// Bond pattern as (single, double, triple, aromatic)
+    fn pattern(&self) -> (usize, usize, usize, usize) {
+        (self.single, self.double, self.triple, self.aromatic)
+    }
 }
 
-/// Calculate polar surface area from SMILES
-/// PSA approximation based on polar atoms
-pub fn polar_surface_area_from_smiles(smiles: &str) -> f32 {
-    let mut psa = 0.0;
+/// Ertl et al. (2000) TPSA contribution for a single atom
+fn tpsa_contribution(atom: &graph::GraphAtom, env: &AtomEnv, in_three_ring: bool) -> f32 {
+    let charge = atom.charge;
+    match (atom.element.as_str(), atom.aromatic) {
+        ("N", false) => match (env.h, charge, env.pattern()) {
+            (0, 0, (3, 0, 0, 0)) if in_three_ring => 3.01,
+            (0, 0, (3, 0, 0, 0)) => 3.24,
+            (0, 0, (1, 1, 0, 0)) => 12.36,
+            (0, 0, (0, 0, 1, 0)) => 23.79,
+            (0, 0, (1, 2, 0, 0)) => 11.68,   // nitro written as N(=O)=O
+            (0, 0, (0, 1, 1, 0)) => 13.60,   // azide middle N
+            (1, 0, (2, 0, 0, 0)) if in_three_ring => 21.94,
+            (1, 0, (2, 0, 0, 0)) => 12.03,
+            (1, 0, (0, 1, 0, 0)) => 23.85,
+            (2, 0, (1, 0, 0, 0)) => 26.02,
+            (0, 1, (4, 0, 0, 0)) => 0.00,
+            (0, 1, (2, 1, 0, 0)) => 3.01,
+            (0, 1, (1, 0, 1, 0)) => 4.36,
+            (0, 1, (0, 2, 0, 0)) => 13.60,
+            (1, 1, (3, 0, 0, 0)) => 4.44,
+            (1, 1, (1, 1, 0, 0)) => 13.97,
+            (2, 1, (2, 0, 0, 0)) => 16.61,
+            (2, 1, (0, 1, 0, 0)) => 25.59,
+            (3, 1, (1, 0, 0, 0)) => 27.64,
+            // Unlisted environment: shrink with heavy-atom neighbours, grow with H
+            _ => 26.30 - 8.0 * (env.single + env.double + env.triple) as f32 + env.h as f32,
+        },
+        ("N", true) => match (env.h, charge, env.pattern()) {
+            (0, 0, (0, 0, 0, 2)) => 12.89,
+            (0, 0, (0, 0, 0, 3)) => 4.41,
+            (0, 0, (1, 0, 0, 2)) => 4.93,
+            (0, 0, (0, 1, 0, 2)) => 8.39,
+            (1, 0, (0, 0, 0, 2)) => 15.79,
+            (0, 1, (0, 0, 0, 3)) => 4.10,
+            (0, 1, (1, 0, 0, 2)) => 3.88,
+            (1, 1, (0, 0, 0, 2)) => 14.14,
+            _ => 12.89,
+        },
+        ("O", false) => match (env.h, charge, env.pattern()) {
+            (0, 0, (2, 0, 0, 0)) if in_three_ring => 12.53,
+            (0, 0, (2, 0, 0, 0)) => 9.23,
+            (0, 0, (0, 1, 0, 0)) => 17.07,
+            (1, 0, (1, 0, 0, 0)) => 20.23,
+            (2, 0, (0, 0, 0, 0)) => 20.23,
+            (0, -1, (1, 0, 0, 0)) => 23.06,
+            _ => 20.23,
+        },
+        ("O", true) => 13.14,
+        _ => 0.0,
+    }
+    .max(0.0)
+}
 
-    // Oxygen in different contexts
+/// Per-element PSA estimate for SMILES the graph parser rejects
+fn flat_polar_surface_area(smiles: &str) -> f32 {
     let o_count = smiles.chars().filter(|&c| c == 'O').count() as f32;
-    psa += o_count * 20.23; // Average value for oxygen
-
-    // Nitrogen
     let n_count = smiles.chars().filter(|&c| c == 'N').count() as f32;
-    psa += n_count * 26.30; // Average value for nitrogen
-
-    // Sulfur contributes less
-    let s_count = smiles.chars().filter(|&c| c == 'S').count() as f32;
-    psa += s_count * 5.0;
-
-    psa
+    o_count * 20.23 + n_count * 26.30
 }
 
 /// Count hydrogen bond donors and acceptors
+/// Donors: N/O bearing at least one H. Acceptors: N/O with an available lone pair
 pub fn hbd_hba_count(smiles: &str) -> (usize, usize) {
+    let g = match graph::parse_smiles(smiles) {
+        Ok(g) => g,
+        Err(_) => return flat_hbd_hba(smiles),
+    };
+
     let mut hbd = 0; // Hydrogen bond donors
     let mut hba = 0; // Hydrogen bond acceptors
 
-    // Count polar atoms
-    let o_count = smiles.chars().filter(|&c| c == 'O').count();
-    let n_count = smiles.chars().filter(|&c| c == 'N').count();
+    for (i, atom) in g.atoms.iter().enumerate() {
+        if atom.element != "N" && atom.element != "O" {
+            continue;
+        }
+        if atom.total_h() > 0 {
+            hbd += 1;
+        }
+        if is_acceptor(&g, i) {
+            hba += 1;
+        }
+    }
 
-    // Oxygen: 1 acceptor per O, potentially 1 donor (OH groups)
-    hba += o_count;
-    hbd += o_count / 2; // Rough estimate
+    (hbd, hba)
+}
 
-    // Nitrogen: 1 acceptor per N, potentially 1-2 donors (NH, NH2)
-    hba += n_count;
-    hbd += n_count;
+fn is_acceptor(g: &graph::MolGraph, idx: usize) -> bool {
+    let atom = &g.atoms[idx];
+    match atom.element.as_str() {
+        "O" => {
+            // Nitro / N-oxide oxygens are poor acceptors
+            !g.neighbors(idx).any(|(n, _)| is_nitro_like(g, n))
+        }
+        "N" => {
+            if atom.charge > 0 {
+                return false;
+            }
+            if atom.aromatic {
+                // Pyridine-type only; pyrrole-type N uses its lone pair in the ring
+                return atom.total_h() == 0 && g.degree(idx) == 2;
+            }
+            // Amide N: lone pair delocalised into the carbonyl
+            let amide = g.neighbors(idx).any(|(n, o)| {
+                o == graph::BondOrder::Single
+                    && g.neighbors(n).any(|(m, o2)| o2 == graph::BondOrder::Double && g.atoms[m].element == "O")
+            });
+            !amide && !is_nitro_like(g, idx)
+        }
+        _ => false,
+    }
+}
 
-    (hbd, hba)
+fn is_nitro_like(g: &graph::MolGraph, idx: usize) -> bool {
+    let atom = &g.atoms[idx];
+    atom.element == "N"
+        && (atom.charge > 0
+            || g.neighbors(idx).filter(|&(_, o)| o == graph::BondOrder::Double).count() > 1)
+}
+
+/// Character-count donor/acceptor estimate for SMILES the graph parser rejects
+fn flat_hbd_hba(smiles: &str) -> (usize, usize) {
+    let o_count = smiles.chars().filter(|&c| c == 'O').count();
+    let n_count = smiles.chars().filter(|&c| c == 'N').count();
+    (o_count / 2 + n_count, o_count + n_count)
 }
 
 /// Count rotatable bonds (simplified)
@@ -125,9 +429,128 @@ pub fn rotatable_bonds_count(smiles: &str) -> usize {
     single_bonds.saturating_sub(5) / 2
 }
 
+/// Molecular formula in Hill notation (C, H, then alphabetical; alphabetical if no C)
+/// Returns an empty string if the SMILES can't be parsed.
+pub fn molecular_formula(smiles: &str) -> String {
+    let g = match graph::parse_smiles(smiles) {
+        Ok(g) => g,
+        Err(_) => return String::new(),
+    };
+
+    let mut counts: std::collections::BTreeMap<&str, usize> = std::collections::BTreeMap::new();
+    for atom in &g.atoms {
+        *counts.entry(atom.element.as_str()).or_insert(0) += 1;
+        if atom.total_h() > 0 {
+            *counts.entry("H").or_insert(0) += atom.total_h() as usize;
+        }
+    }
+
+    let mut order: Vec<&str> = Vec::new();
+    if counts.contains_key("C") {
+        order.push("C");
+        if counts.contains_key("H") {
+            order.push("H");
+        }
+    }
+    let rest: Vec<&str> = counts.keys().copied().filter(|e| !order.contains(e)).collect();
+    order.extend(rest);
+
+    order.iter()
+        .map(|e| match counts[e] {
+            1 => e.to_string(),
+            n => format!("{}{}", e, n),
+        })
+        .collect()
+}
+
+/// Element counts of a formula such as "C6H6" or "Cl"; None if it isn't one
+fn parse_formula(formula: &str) -> Option<std::collections::BTreeMap<String, usize>> {
+    let mut counts = std::collections::BTreeMap::new();
+    let mut chars = formula.chars().peekable();
+    while let Some(first) = chars.next() {
+        if !first.is_ascii_uppercase() {
+            return None;
+        }
+        let mut element = first.to_string();
+        while let Some(&c) = chars.peek().filter(|c| c.is_ascii_lowercase()) {
+            element.push(c);
+            chars.next();
+        }
+        let mut digits = String::new();
+        while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+            digits.push(c);
+            chars.next();
+        }
+        let n = if digits.is_empty() { 1 } else { digits.parse().ok()? };
+        *counts.entry(element).or_insert(0) += n;
+    }
+    Some(counts)
+}
+
+/// Whether a molecular formula equals or contains a query. A query that parses as a formula
+/// matches when every element it lists occurs at least that often ("C6" = six or more carbons);
+/// anything else is a plain substring search.
+pub fn formula_contains(formula: &str, query: &str) -> bool {
+    let query = query.trim();
+    if query.is_empty() || formula == query {
+        return true;
+    }
+    match (parse_formula(query), parse_formula(formula)) {
+        (Some(wanted), Some(counts)) => wanted.iter()
+            .all(|(element, &n)| counts.get(element).is_some_and(|&have| have >= n)),
+        _ => formula.contains(query),
+    }
+}
+
+/// `formula_contains` on the molecular formula of a SMILES; unparseable SMILES never match
+/// a non-empty query
+pub fn formula_matches(smiles: &str, query: &str) -> bool {
+    formula_contains(&molecular_formula(smiles), query)
+}
+
+/// Confidence below which descriptor estimates should not be trusted
+pub const LOW_CONFIDENCE: f32 = 0.6;
+/// SMILES longer than this are outside the range the estimators were tuned on
+const CONFIDENCE_MAX_LENGTH: usize = 120;
+
+/// How far a SMILES lies inside the descriptor models' trusted domain, from 0 (not at all) to 1.
+/// Unparseable SMILES, elements missing from the mass table, charged atoms and very long
+/// strings all lower the confidence.
+pub fn descriptor_confidence(smiles: &str) -> f32 {
+    let g = match graph::parse_smiles(smiles) {
+        Ok(g) => g,
+        Err(_) => return 0.2,
+    };
+    let atomic_masses = get_atomic_masses();
+
+    let mut confidence = 1.0f32;
+    if g.atoms.iter().any(|a| !atomic_masses.contains_key(&a.element)) {
+        confidence -= 0.5;
+    }
+    let charged = g.atoms.iter().filter(|a| a.charge != 0).count();
+    confidence -= 0.1 * charged.min(3) as f32;
+    if smiles.len() > CONFIDENCE_MAX_LENGTH {
+        confidence -= 0.3;
+    }
+    confidence.clamp(0.0, 1.0)
+}
+
+/// Count specified tetrahedral stereocenters (`@` / `@@` atoms)
+pub fn count_stereocenters(smiles: &str) -> usize {
+    match graph::parse_smiles(smiles) {
+        Ok(g) => g.atoms.iter().filter(|a| a.chiral).count(),
+        Err(_) => smiles.split('[').skip(1)
+            .filter(|s| s.split(']').next().is_some_and(|atom| atom.contains('@')))
+            .count(),
+    }
+}
+
 /// Count heavy atoms (non-hydrogen)
 pub fn heavy_atom_count(smiles: &str) -> usize {
-    smiles.chars().filter(|c| c.is_uppercase()).count()
+    match graph::parse_smiles(smiles) {
+        Ok(g) => g.atoms.iter().filter(|a| a.element != "H").count(),
+        Err(_) => smiles.chars().filter(|c| c.is_uppercase()).count(),
+    }
 }
 
 /// Check Lipinski's Rule of Five compliance
@@ -149,6 +572,7 @@ pub fn lipinski_violations(smiles: &str) -> usize {
 fn get_atomic_masses() -> HashMap<String, f32> {
     let mut masses = HashMap::new();
     masses.insert("H".to_string(), 1.00784);
+    masses.insert("B".to_string(), 10.81);
     masses.insert("C".to_string(), 12.011);
     masses.insert("N".to_string(), 14.0067);
     masses.insert("O".to_string(), 15.999);
@@ -158,6 +582,11 @@ fn get_atomic_masses() -> HashMap<String, f32> {
     masses.insert("Cl".to_string(), 35.453);
     masses.insert("Br".to_string(), 79.904);
     masses.insert("I".to_string(), 126.904);
+    masses.insert("Se".to_string(), 78.971);
+    masses.insert("Si".to_string(), 28.085);
+    masses.insert("Na".to_string(), 22.990);
+    masses.insert("K".to_string(), 39.098);
+    masses.insert("Ca".to_string(), 40.078);
     masses
 }
 
@@ -196,4 +625,198 @@ mod tests {
         let logp = logp_from_smiles("CCCCCCCC");
         assert!(logp > 0.0);
     }
+
+    #[test]
+    fn test_crippen_logp() {
+        // Reference values from the Wildman-Crippen scheme
+        assert!((logp_from_smiles("c1ccccc1") - 1.69).abs() < 0.05);
+        assert!((logp_from_smiles("CCO") - 0.0).abs() < 0.05);
+        assert!((logp_from_smiles("Oc1ccccc1") - 1.39).abs() < 0.05);
+        assert!((logp_from_smiles("CC(=O)O") - 0.09).abs() < 0.05);
+
+        // Long alkyl chain beats a polyol
+        assert!(logp_from_smiles("CCCCCCCCO") > logp_from_smiles("OCC(O)CO") + 2.0);
+    }
+
+    #[test]
+    fn test_hbd_hba() {
+        assert_eq!(hbd_hba_count("CCO"), (1, 1));          // ethanol
+        assert_eq!(hbd_hba_count("CCOCC"), (0, 1));        // diethyl ether
+        assert_eq!(hbd_hba_count("CC(=O)N"), (1, 1));      // acetamide: N donates only
+        assert_eq!(hbd_hba_count("CCN(CC)CC"), (0, 1));    // triethylamine
+        assert_eq!(hbd_hba_count("c1ccncc1"), (0, 1));     // pyridine
+        assert_eq!(hbd_hba_count("c1cc[nH]c1"), (1, 0));   // pyrrole
+        assert_eq!(hbd_hba_count("C[N+](=O)[O-]"), (0, 0)); // nitromethane
+    }
+
+    #[test]
+    fn test_molecular_formula() {
+        assert_eq!(molecular_formula("CC(=O)Oc1ccccc1C(=O)O"), "C9H8O4");
+        assert_eq!(molecular_formula("c1ccccc1"), "C6H6");
+        assert_eq!(molecular_formula("CCl"), "CH3Cl");
+        assert_eq!(molecular_formula("O"), "H2O");
+        assert_eq!(molecular_formula("[Na+].[Cl-]"), "ClNa");
+        assert_eq!(molecular_formula("C1CC"), "");
+    }
+
+    #[test]
+    fn test_formula_matches() {
+        // Exact formulas
+        assert!(formula_matches("c1ccccc1", "C6H6"));
+        // A full formula is a minimum too: toluene (C7H8) contains C6H6
+        assert!(formula_matches("Cc1ccccc1", "C6H6"));
+        assert!(formula_matches("CCO", "C2H6O"));
+        assert!(formula_matches("CCO", " C2H6O "));
+
+        // Element counts are minimums
+        assert!(formula_matches("c1ccccc1", "C6"));
+        assert!(formula_matches("Cc1ccccc1", "C6"));
+        assert!(!formula_matches("CCO", "C6"));
+        assert!(formula_matches("c1ccncc1", "N"));
+        assert!(!formula_matches("c1ccccc1", "N"));
+        assert!(formula_matches("Clc1ccccc1", "C6Cl"));
+        assert!(!formula_matches("Clc1ccccc1", "C6Cl2"));
+        assert!(formula_matches("CCO", "H6"));
+
+        // Other queries fall back to substring search; bad SMILES match only an empty query
+        assert!(formula_matches("CCO", "2H6"));
+        assert!(!formula_matches("CCO", "c2"));
+        assert!(formula_matches("C1CC", ""));
+        assert!(!formula_matches("C1CC", "C"));
+    }
+
+    #[test]
+    fn test_molecular_weight_detailed() {
+        // Phenylboronic acid, C6H7BO2: 121.93
+        let mw = molecular_weight_detailed("OB(O)c1ccccc1");
+        assert_eq!(mw.unknown_atoms, 0);
+        assert!((mw.mass - 121.93).abs() < 0.1, "{}", mw.mass);
+        assert_eq!(molecular_weight_from_smiles("OB(O)c1ccccc1"), mw.mass);
+
+        // Sodium acetate is covered; platinum is not
+        assert_eq!(molecular_weight_detailed("CC(=O)[O-].[Na+]").unknown_atoms, 0);
+        let cisplatin = molecular_weight_detailed("N[Pt](N)(Cl)Cl");
+        assert_eq!(cisplatin.unknown_atoms, 1);
+    }
+
+    #[test]
+    fn test_descriptor_confidence() {
+        for smiles in ["CCO", "c1ccccc1", "CC(=O)Oc1ccccc1C(=O)O", "CN1C=NC2=C1C(=O)N(C(=O)N2C)C"] {
+            assert_eq!(descriptor_confidence(smiles), 1.0, "{}", smiles);
+        }
+        // Platinum is not in the mass table
+        assert!(descriptor_confidence("N[Pt](N)(Cl)Cl") < LOW_CONFIDENCE);
+        assert!(descriptor_confidence("C[N+](C)(C)C") < 1.0);
+        assert!(descriptor_confidence("C(C") < LOW_CONFIDENCE);
+        assert!(descriptor_confidence(&"C".repeat(200)) < 1.0);
+    }
+
+    #[test]
+    fn test_count_stereocenters() {
+        assert_eq!(count_stereocenters("C[C@H](N)C(=O)O"), 1);
+        assert_eq!(count_stereocenters("C[C@@H](O)[C@H](N)C"), 2);
+        assert_eq!(count_stereocenters("F/C=C/F"), 0);
+        assert_eq!(count_stereocenters("CCO"), 0);
+    }
+
+    #[test]
+    fn test_tpsa() {
+        // Reference values from Ertl et al.
+        let aspirin = polar_surface_area_from_smiles("CC(=O)Oc1ccccc1C(=O)O");
+        assert!((aspirin - 63.6).abs() < 0.5, "aspirin TPSA {}", aspirin);
+
+        // Imidazole ring aromatic, pyrimidinedione written in Kekulé form
+        let caffeine = polar_surface_area_from_smiles("Cn1cnc2c1C(=O)N(C)C(=O)N2C");
+        assert!((caffeine - 58.4).abs() < 0.5, "caffeine TPSA {}", caffeine);
+
+        // Ether O counts less than hydroxyl O
+        assert!(polar_surface_area_from_smiles("CCOCC") < polar_surface_area_from_smiles("CCCCO"));
+        assert_eq!(polar_surface_area_from_smiles("CCCC"), 0.0);
+    }
+
+    #[test]
+    fn test_compute_descriptors() {
+        let d = compute_descriptors("c1ccccc1");
+        assert_eq!(d.heavy_atoms, 6);
+        assert_eq!(d.total_atoms, 12);
+        assert_eq!(d.rings, 1);
+        assert!((d.molecular_weight - molecular_weight_from_smiles("c1ccccc1")).abs() < 1e-6);
+    }
+}
+
+#[cfg(test)]
+mod descriptors_reference_tests {
+    use super::*;
+
+    /// (name, SMILES, MW, TPSA, HBD, HBA)
+    /// MW and TPSA are PubChem values. HBD/HBA use Lipinski's definitions: donors are
+    /// the N-H and O-H bonds, acceptors every N and O atom.
+    const REFERENCE_DRUGS: &[(&str, &str, f32, f32, usize, usize)] = &[
+        ("aspirin", "CC(=O)Oc1ccccc1C(=O)O", 180.16, 63.6, 1, 4),
+        ("caffeine", "Cn1cnc2c1C(=O)N(C)C(=O)N2C", 194.19, 58.4, 0, 6),
+        ("ibuprofen", "CC(C)Cc1ccc(cc1)C(C)C(=O)O", 206.28, 37.3, 1, 2),
+        ("metformin", "CN(C)C(=N)N=C(N)N", 129.16, 91.5, 5, 5),
+        ("paracetamol", "CC(=O)Nc1ccc(O)cc1", 151.16, 49.3, 2, 3),
+        ("nicotine", "CN1CCCC1c1cccnc1", 162.23, 16.1, 0, 2),
+        ("diazepam", "CN1C(=O)CN=C(c2ccccc2)c2cc(Cl)ccc21", 284.74, 32.7, 0, 3),
+        ("lidocaine", "CCN(CC)CC(=O)Nc1c(C)cccc1C", 234.34, 32.3, 1, 3),
+        ("salbutamol", "CC(C)(C)NCC(O)c1ccc(O)c(CO)c1", 239.31, 72.7, 4, 4),
+        ("morphine", "CN1CC[C@]23c4c5ccc(O)c4O[C@H]2[C@@H](O)C=C[C@H]3[C@H]1C5", 285.34, 52.9, 2, 4),
+        ("fluoxetine", "CNCCC(Oc1ccc(cc1)C(F)(F)F)c1ccccc1", 309.33, 21.3, 1, 2),
+        ("atenolol", "CC(C)NCC(O)COc1ccc(CC(N)=O)cc1", 266.34, 84.6, 4, 5),
+        ("ciprofloxacin", "OC(=O)c1cn(C2CC2)c2cc(N3CCNCC3)c(F)cc2c1=O", 331.34, 72.9, 2, 6),
+        ("diphenhydramine", "CN(C)CCOC(c1ccccc1)c1ccccc1", 255.35, 12.5, 0, 2),
+        ("benzocaine", "CCOC(=O)c1ccc(N)cc1", 165.19, 52.3, 2, 3),
+    ];
+
+    /// `hbd_hba_count` counts donor atoms rather than N-H/O-H bonds (NH2 counts once) and
+    /// leaves amide and pyrrole-type nitrogens out of the acceptors
+    const KNOWN_HBD_HBA_DEVIATIONS: &[&str] = &[
+        "caffeine", "metformin", "paracetamol", "diazepam", "lidocaine", "atenolol", "ciprofloxacin", "benzocaine",
+    ];
+
+    /// Ring counts for the same drugs
+    const REFERENCE_RINGS: &[(&str, usize)] = &[
+        ("aspirin", 1), ("caffeine", 2), ("ibuprofen", 1), ("metformin", 0),
+        ("paracetamol", 1), ("nicotine", 2), ("diazepam", 3), ("lidocaine", 1),
+        ("salbutamol", 1), ("morphine", 5), ("fluoxetine", 2), ("atenolol", 1),
+        ("ciprofloxacin", 4), ("diphenhydramine", 2), ("benzocaine", 1),
+    ];
+
+    const MW_TOLERANCE: f32 = 0.5;
+    const TPSA_TOLERANCE: f32 = 2.0;
+
+    #[test]
+    fn test_reference_molecular_weight() {
+        for &(name, smiles, mw, ..) in REFERENCE_DRUGS {
+            let got = molecular_weight_from_smiles(smiles);
+            assert!((got - mw).abs() < MW_TOLERANCE, "{}: MW {} vs {}", name, got, mw);
+        }
+    }
+
+    #[test]
+    fn test_reference_tpsa() {
+        for &(name, smiles, _, tpsa, ..) in REFERENCE_DRUGS {
+            let got = polar_surface_area_from_smiles(smiles);
+            assert!((got - tpsa).abs() < TPSA_TOLERANCE, "{}: TPSA {} vs {}", name, got, tpsa);
+        }
+    }
+
+    #[test]
+    fn test_reference_hbd_hba() {
+        for &(name, smiles, _, _, hbd, hba) in REFERENCE_DRUGS {
+            if KNOWN_HBD_HBA_DEVIATIONS.contains(&name) {
+                continue;
+            }
+            assert_eq!(hbd_hba_count(smiles), (hbd, hba), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_reference_rings() {
+        for &(name, rings) in REFERENCE_RINGS {
+            let smiles = REFERENCE_DRUGS.iter().find(|d| d.0 == name).unwrap().1;
+            assert_eq!(compute_descriptors(smiles).rings, rings, "{}", name);
+        }
+    }
 }
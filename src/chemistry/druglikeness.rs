@@ -1,6 +1,6 @@
-//! Drug-likeness rules: Lipinski, Veber, and toxicity alerts (PAINS)
+//! Drug-likeness rules: Lipinski, Veber, QED, and toxicity alerts (PAINS)
 
-use super::descriptors;
+use super::{descriptors, graph};
 
 /// Lipinski's Rule of Five results
 #[derive(Clone, Debug, Default)]
@@ -21,12 +21,62 @@ pub struct VeberResult {
     pub passed: bool,
 }
 
+/// Ghose filter (molar refractivity is not modelled)
+#[derive(Clone, Debug, Default)]
+pub struct GhoseResult {
+    pub mw_ok: bool,           // 160 <= MW <= 480
+    pub logp_ok: bool,         // -0.4 <= LogP <= 5.6
+    pub atom_count_ok: bool,   // 20 <= atoms <= 70
+    pub passed: bool,
+}
+
+/// How serious a PAINS alert is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    High,
+    Medium,
+    Low,
+}
+
+impl Severity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::High => "high",
+            Severity::Medium => "medium",
+            Severity::Low => "low",
+        }
+    }
+
+    /// Contribution to the aggregate severity score; a high alert counts as one
+    pub fn weight(self) -> f32 {
+        match self {
+            Severity::High => 1.0,
+            Severity::Medium => 0.5,
+            Severity::Low => 0.25,
+        }
+    }
+}
+
 /// PAINS (Pan-Assay Interference Compounds) alert
 #[derive(Clone, Debug)]
 pub struct PainsAlert {
     pub name: &'static str,
     pub pattern: &'static str,
-    pub severity: &'static str,  // "high", "medium", "low"
+    pub severity: Severity,
+}
+
+/// One PAINS alert found in a molecule
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PainsHit {
+    pub name: &'static str,
+    pub severity: Severity,
+}
+
+impl PainsHit {
+    /// Display text, e.g. "Epoxide (high)"
+    pub fn label(&self) -> String {
+        format!("{} ({})", self.name, self.severity.label())
+    }
 }
 
 /// Combined drug-likeness assessment
@@ -34,7 +84,9 @@ pub struct PainsAlert {
 pub struct DrugLikenessResult {
     pub lipinski: LipinskiResult,
     pub veber: VeberResult,
-    pub pains_alerts: Vec<String>,
+    pub ghose: GhoseResult,
+    pub pains_alerts: Vec<String>,  // display labels of `pains_hits`
+    pub pains_hits: Vec<PainsHit>,
     pub overall_score: f32,  // 0-1, higher is better
     pub recommendation: String,
 }
@@ -44,7 +96,10 @@ pub fn check_lipinski(smiles: &str) -> LipinskiResult {
     let mw = descriptors::molecular_weight_from_smiles(smiles);
     let logp = descriptors::logp_from_smiles(smiles);
     let (hbd, hba) = descriptors::hbd_hba_count(smiles);
-    
+    lipinski_from_values(mw, logp, hbd, hba)
+}
+
+fn lipinski_from_values(mw: f32, logp: f32, hbd: usize, hba: usize) -> LipinskiResult {
     let mw_ok = mw <= 500.0;
     let logp_ok = logp <= 5.0;
     let hbd_ok = hbd <= 5;
@@ -66,7 +121,10 @@ pub fn check_lipinski(smiles: &str) -> LipinskiResult {
 pub fn check_veber(smiles: &str) -> VeberResult {
     let rotatable_bonds = count_rotatable_bonds(smiles);
     let psa = descriptors::polar_surface_area_from_smiles(smiles);
-    
+    veber_from_values(rotatable_bonds, psa)
+}
+
+fn veber_from_values(rotatable_bonds: usize, psa: f32) -> VeberResult {
     let rotatable_bonds_ok = rotatable_bonds <= 10;
     let psa_ok = psa <= 140.0;
     
@@ -77,6 +135,22 @@ pub fn check_veber(smiles: &str) -> VeberResult {
     }
 }
 
+/// Check the Ghose filter for lead-likeness
+pub fn check_ghose(smiles: &str) -> GhoseResult {
+    let d = descriptors::compute_descriptors(smiles);
+
+    let mw_ok = (160.0..=480.0).contains(&d.molecular_weight);
+    let logp_ok = (-0.4..=5.6).contains(&d.logp);
+    let atom_count_ok = (20..=70).contains(&d.total_atoms);
+
+    GhoseResult {
+        mw_ok,
+        logp_ok,
+        atom_count_ok,
+        passed: mw_ok && logp_ok && atom_count_ok,
+    }
+}
+
 /// Count rotatable bonds (simplified)
 pub fn count_rotatable_bonds(smiles: &str) -> usize {
     // Count single bonds between non-terminal, non-ring heavy atoms
@@ -101,55 +175,55 @@ pub fn count_rotatable_bonds(smiles: &str) -> usize {
 /// PAINS patterns - substructures that cause assay interference
 const PAINS_PATTERNS: &[PainsAlert] = &[
     // Reactive/Toxic groups
-    PainsAlert { name: "Aldehyde", pattern: "C=O", severity: "medium" },
-    PainsAlert { name: "Michael acceptor", pattern: "C=CC=O", severity: "high" },
-    PainsAlert { name: "Epoxide", pattern: "C1OC1", severity: "high" },
-    PainsAlert { name: "Aziridine", pattern: "C1NC1", severity: "high" },
-    PainsAlert { name: "Acyl halide", pattern: "C(=O)Cl", severity: "high" },
-    PainsAlert { name: "Sulfonyl halide", pattern: "S(=O)(=O)Cl", severity: "high" },
-    PainsAlert { name: "Isocyanate", pattern: "N=C=O", severity: "high" },
-    PainsAlert { name: "Isothiocyanate", pattern: "N=C=S", severity: "high" },
+    PainsAlert { name: "Aldehyde", pattern: "C=O", severity: Severity::Medium },
+    PainsAlert { name: "Michael acceptor", pattern: "C=CC=O", severity: Severity::High },
+    PainsAlert { name: "Epoxide", pattern: "C1OC1", severity: Severity::High },
+    PainsAlert { name: "Aziridine", pattern: "C1NC1", severity: Severity::High },
+    PainsAlert { name: "Acyl halide", pattern: "C(=O)Cl", severity: Severity::High },
+    PainsAlert { name: "Sulfonyl halide", pattern: "S(=O)(=O)Cl", severity: Severity::High },
+    PainsAlert { name: "Isocyanate", pattern: "N=C=O", severity: Severity::High },
+    PainsAlert { name: "Isothiocyanate", pattern: "N=C=S", severity: Severity::High },
     
     // Frequent hitters
-    PainsAlert { name: "Quinone", pattern: "C1=CC(=O)C=CC1=O", severity: "high" },
-    PainsAlert { name: "Rhodanine", pattern: "S=C1NC(=O)CS1", severity: "high" },
-    PainsAlert { name: "Catechol", pattern: "c1ccc(O)c(O)c1", severity: "medium" },
-    PainsAlert { name: "Resorcinol", pattern: "c1cc(O)cc(O)c1", severity: "medium" },
-    PainsAlert { name: "Phenol-ester", pattern: "c1ccccc1OC(=O)", severity: "medium" },
+    PainsAlert { name: "Quinone", pattern: "C1=CC(=O)C=CC1=O", severity: Severity::High },
+    PainsAlert { name: "Rhodanine", pattern: "S=C1NC(=O)CS1", severity: Severity::High },
+    PainsAlert { name: "Catechol", pattern: "c1ccc(O)c(O)c1", severity: Severity::Medium },
+    PainsAlert { name: "Resorcinol", pattern: "c1cc(O)cc(O)c1", severity: Severity::Medium },
+    PainsAlert { name: "Phenol-ester", pattern: "c1ccccc1OC(=O)", severity: Severity::Medium },
     
     // Unstable groups
-    PainsAlert { name: "Hydrazine", pattern: "NN", severity: "medium" },
-    PainsAlert { name: "Hydroxylamine", pattern: "NO", severity: "medium" },
-    PainsAlert { name: "Peroxide", pattern: "OO", severity: "high" },
-    PainsAlert { name: "Disulfide", pattern: "SS", severity: "medium" },
-    PainsAlert { name: "Thiol", pattern: "SH", severity: "low" },
+    PainsAlert { name: "Hydrazine", pattern: "NN", severity: Severity::Medium },
+    PainsAlert { name: "Hydroxylamine", pattern: "NO", severity: Severity::Medium },
+    PainsAlert { name: "Peroxide", pattern: "OO", severity: Severity::High },
+    PainsAlert { name: "Disulfide", pattern: "SS", severity: Severity::Medium },
+    PainsAlert { name: "Thiol", pattern: "SH", severity: Severity::Low },
     
     // Genotoxic alerts
-    PainsAlert { name: "Nitro-aromatic", pattern: "c1ccccc1N(=O)=O", severity: "high" },
-    PainsAlert { name: "Azide", pattern: "N=[N+]=[N-]", severity: "high" },
-    PainsAlert { name: "Nitroso", pattern: "N=O", severity: "high" },
+    PainsAlert { name: "Nitro-aromatic", pattern: "c1ccccc1N(=O)=O", severity: Severity::High },
+    PainsAlert { name: "Azide", pattern: "N=[N+]=[N-]", severity: Severity::High },
+    PainsAlert { name: "Nitroso", pattern: "N=O", severity: Severity::High },
     
     // Metabolic liabilities
-    PainsAlert { name: "Aniline", pattern: "c1ccccc1N", severity: "low" },
-    PainsAlert { name: "Thiourea", pattern: "NC(=S)N", severity: "medium" },
+    PainsAlert { name: "Aniline", pattern: "c1ccccc1N", severity: Severity::Low },
+    PainsAlert { name: "Thiourea", pattern: "NC(=S)N", severity: Severity::Medium },
 ];
 
 /// Check for PAINS alerts
-pub fn check_pains(smiles: &str) -> Vec<String> {
+pub fn check_pains(smiles: &str) -> Vec<PainsHit> {
     let mut alerts = Vec::new();
     let smiles_lower = smiles.to_lowercase();
     
     for alert in PAINS_PATTERNS {
         // Simple substring matching (real implementation would use SMARTS)
         if contains_substructure(smiles, alert.pattern) {
-            alerts.push(format!("{} ({})", alert.name, alert.severity));
+            alerts.push(PainsHit { name: alert.name, severity: alert.severity });
         }
     }
     
     // Additional specific checks
     if smiles_lower.contains("nn") && !smiles_lower.contains("nnn") {
-        if !alerts.iter().any(|a| a.contains("Hydrazine")) {
-            alerts.push("Hydrazine-like (medium)".to_string());
+        if !alerts.iter().any(|a| a.name == "Hydrazine") {
+            alerts.push(PainsHit { name: "Hydrazine-like", severity: Severity::Medium });
         }
     }
     
@@ -159,12 +233,17 @@ pub fn check_pains(smiles: &str) -> Vec<String> {
         + smiles.matches("Br").count()
         + smiles.matches('I').count();
     if halogen_count > 4 {
-        alerts.push("Excessive halogens (medium)".to_string());
+        alerts.push(PainsHit { name: "Excessive halogens", severity: Severity::Medium });
     }
     
     alerts
 }
 
+/// Aggregate PAINS severity: the sum of alert weights (high 1, medium 0.5, low 0.25)
+pub fn pains_severity_score(smiles: &str) -> f32 {
+    check_pains(smiles).iter().map(|hit| hit.severity.weight()).sum()
+}
+
 /// Simple substructure check (pattern matching)
 fn contains_substructure(smiles: &str, pattern: &str) -> bool {
     // Simplified check - real implementation would use SMARTS matching
@@ -178,35 +257,15 @@ fn contains_substructure(smiles: &str, pattern: &str) -> bool {
 pub fn assess_druglikeness(smiles: &str) -> DrugLikenessResult {
     let lipinski = check_lipinski(smiles);
     let veber = check_veber(smiles);
-    let pains_alerts = check_pains(smiles);
-    
-    // Calculate overall score
-    let mut score = 1.0f32;
-    
-    // Lipinski penalties
-    score -= lipinski.violations as f32 * 0.15;
-    
-    // Veber penalties
-    if !veber.rotatable_bonds_ok { score -= 0.1; }
-    if !veber.psa_ok { score -= 0.1; }
-    
-    // PAINS penalties
-    for alert in &pains_alerts {
-        if alert.contains("high") {
-            score -= 0.2;
-        } else if alert.contains("medium") {
-            score -= 0.1;
-        } else {
-            score -= 0.05;
-        }
-    }
-    
-    let overall_score = score.clamp(0.0, 1.0);
+    let ghose = check_ghose(smiles);
+    let pains_hits = check_pains(smiles);
+    let overall_score = overall_score(&lipinski, &veber, &pains_hits);
+    let pains_alerts: Vec<String> = pains_hits.iter().map(PainsHit::label).collect();
     
     // Generate recommendation
-    let recommendation = if overall_score >= 0.8 && pains_alerts.is_empty() {
+    let recommendation = if overall_score >= 0.8 && pains_hits.is_empty() {
         "Excellent drug-like properties".to_string()
-    } else if overall_score >= 0.6 && pains_alerts.len() <= 1 {
+    } else if overall_score >= 0.6 && pains_hits.len() <= 1 {
         "Good candidate, minor concerns".to_string()
     } else if overall_score >= 0.4 {
         "Moderate - optimization recommended".to_string()
@@ -217,15 +276,96 @@ pub fn assess_druglikeness(smiles: &str) -> DrugLikenessResult {
     DrugLikenessResult {
         lipinski,
         veber,
+        ghose,
         pains_alerts,
+        pains_hits,
         overall_score,
         recommendation,
     }
 }
 
-/// Quick drug-likeness score (0-1)
-pub fn quick_druglikeness_score(smiles: &str) -> f32 {
-    assess_druglikeness(smiles).overall_score
+/// Asymmetric double sigmoid parameters (a, b, c, d, e, f, dmax) from Bickerton et al. (2012),
+/// in the order MW, ALOGP, HBA, HBD, PSA, ROTB, AROM, ALERTS
+const QED_PARAMS: [[f64; 7]; 8] = [
+    [2.817065973, 392.5754953, 290.7489764, 2.419764353, 49.22325677, 65.37051707, 104.9805561],
+    [3.172690585, 137.8624751, 2.534937431, 4.581497897, 0.822739154, 0.576295591, 131.3186604],
+    [2.948620388, 160.4605972, 3.615294657, 4.435986202, 0.290141953, 1.300669958, 148.7763046],
+    [1.618662227, 1010.051101, 0.985094388, 0.000000001, 0.713820843, 0.920922555, 258.1632616],
+    [1.876861559, 125.2232657, 62.90773554, 87.83366614, 12.01999824, 28.51324732, 104.5686167],
+    [0.010000000, 272.4121427, 2.558379970, 1.565547684, 1.271567166, 2.758063707, 105.4420403],
+    [3.217788970, 957.7374108, 2.274627939, 0.000000001, 1.317690384, 0.375760881, 312.3372610],
+    [0.010000000, 1199.094025, -0.09002883, 0.000000001, 0.185904477, 0.875193782, 417.7253140],
+];
+
+/// Mean QED weights for the same eight properties
+const QED_WEIGHTS: [f64; 8] = [0.66, 0.46, 0.05, 0.61, 0.06, 0.65, 0.48, 0.95];
+
+/// Desirability of one property value, 0-1
+fn qed_desirability(x: f64, [a, b, c, d, e, f, dmax]: [f64; 7]) -> f64 {
+    let rise = 1.0 + (-(x - c + d / 2.0) / e).exp();
+    let fall = 1.0 + (-(x - c - d / 2.0) / f).exp();
+    ((a + b / rise * (1.0 - 1.0 / fall)) / dmax).clamp(1e-6, 1.0)
+}
+
+/// Quantitative Estimate of Drug-likeness (weighted geometric mean of desirabilities).
+/// Structural alerts use the PAINS list, so values differ slightly from the reference
+/// implementation's 116-alert set.
+pub fn qed(smiles: &str) -> f32 {
+    let (hbd, hba) = descriptors::hbd_hba_count(smiles);
+    let aromatic_rings = graph::parse_smiles(smiles)
+        .map(|g| {
+            g.smallest_rings().iter()
+                .filter(|ring| ring.iter().all(|&i| g.atoms[i].aromatic))
+                .count()
+        })
+        .unwrap_or(0);
+
+    let properties = [
+        descriptors::molecular_weight_from_smiles(smiles) as f64,
+        descriptors::logp_from_smiles(smiles) as f64,
+        hba as f64,
+        hbd as f64,
+        descriptors::polar_surface_area_from_smiles(smiles) as f64,
+        descriptors::rotatable_bonds_count(smiles) as f64,
+        aromatic_rings as f64,
+        check_pains(smiles).len() as f64,
+    ];
+
+    let weighted_log: f64 = properties.iter()
+        .zip(QED_PARAMS)
+        .zip(QED_WEIGHTS)
+        .map(|((&x, params), w)| w * qed_desirability(x, params).ln())
+        .sum();
+    (weighted_log / QED_WEIGHTS.iter().sum::<f64>()).exp() as f32
+}
+
+/// Overall 0-1 score: Lipinski, Veber and PAINS penalties
+fn overall_score(lipinski: &LipinskiResult, veber: &VeberResult, pains_hits: &[PainsHit]) -> f32 {
+    let mut score = 1.0f32;
+    
+    // Lipinski penalties
+    score -= lipinski.violations as f32 * 0.15;
+    
+    // Veber penalties
+    if !veber.rotatable_bonds_ok { score -= 0.1; }
+    if !veber.psa_ok { score -= 0.1; }
+    
+    // PAINS penalties: 0.2 per high alert, scaled down by severity
+    score -= 0.2 * pains_hits.iter().map(|hit| hit.severity.weight()).sum::<f32>();
+    
+    score.clamp(0.0, 1.0)
+}
+
+/// Drug-likeness score (0-1), equal to `assess_druglikeness(smiles).overall_score`,
+/// from precomputed descriptors and PAINS alerts
+pub fn druglikeness_score_from(
+    smiles: &str,
+    d: &descriptors::MolecularDescriptors,
+    pains_hits: &[PainsHit],
+) -> f32 {
+    let lipinski = lipinski_from_values(d.molecular_weight, d.logp, d.hbd, d.hba);
+    let veber = veber_from_values(count_rotatable_bonds(smiles), d.psa);
+    overall_score(&lipinski, &veber, pains_hits)
 }
 
 #[cfg(test)]
@@ -246,11 +386,61 @@ mod tests {
         assert!(result.passed);
     }
 
+    #[test]
+    fn test_ghose() {
+        // Lauric acid sits inside the Ghose window, methane does not
+        assert!(check_ghose("CCCCCCCCCCCC(=O)O").passed);
+        assert!(!check_ghose("C").passed);
+    }
+
     #[test]
     fn test_pains_detection() {
         // Epoxide should trigger alert
         let alerts = check_pains("C1OC1CC");
         assert!(!alerts.is_empty());
+        assert!(alerts.contains(&PainsHit { name: "Epoxide", severity: Severity::High }));
+        assert_eq!(alerts[0].label(), format!("{} ({})", alerts[0].name, alerts[0].severity.label()));
+    }
+
+    #[test]
+    fn test_pains_severity_score() {
+        assert_eq!(pains_severity_score("CCCC"), 0.0);
+        // Epoxide alone is one high alert
+        assert_eq!(pains_severity_score("C1OC1"), 1.0);
+        // Thiol (low) plus five fluorines (medium)
+        assert_eq!(pains_severity_score("FC(F)(F)C(F)(F)SH"), 0.75);
+
+        // The score is the sum of weights over the structured hits
+        let smiles = "CC(=O)CC=CC=O";
+        let expected: f32 = check_pains(smiles).iter().map(|h| h.severity.weight()).sum();
+        assert!(expected > 1.0);
+        assert_eq!(pains_severity_score(smiles), expected);
+    }
+
+    #[test]
+    fn test_penalty_ignores_alert_name() {
+        let lipinski = LipinskiResult { passed: true, ..Default::default() };
+        let veber = VeberResult { rotatable_bonds_ok: true, psa_ok: true, passed: true };
+        // Names containing "high" or "medium" must not change the penalty; labels are
+        // lowercase "name (low)", so a substring search on them would miscount these
+        let hits = [
+            PainsHit { name: "thigh-binder", severity: Severity::Low },
+            PainsHit { name: "medium-chain ester", severity: Severity::Low },
+        ];
+        assert!((overall_score(&lipinski, &veber, &hits) - 0.9).abs() < 1e-6);
+
+        let result = assess_druglikeness("C1OC1CC");
+        assert_eq!(result.pains_alerts, result.pains_hits.iter().map(PainsHit::label).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_qed() {
+        let aspirin = qed("CC(=O)Oc1ccccc1C(=O)O");
+        let hexane = qed("CCCCCC");
+        let huge = qed(&"C(=O)N".repeat(40));
+        assert!((0.4..0.7).contains(&aspirin), "aspirin {}", aspirin);
+        assert!(hexane < aspirin, "hexane {} vs aspirin {}", hexane, aspirin);
+        assert!(huge < 0.1, "huge {}", huge);
     }
 
     #[test]
@@ -0,0 +1,1017 @@
+//! Molecular graph parsed from SMILES
+//! Atoms, bonds, implicit hydrogens, ring perception and canonical SMILES
+
+use std::collections::HashMap;
+
+/// Bond type between two atoms
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BondOrder {
+    Single,
+    Double,
+    Triple,
+    Aromatic,
+}
+
+impl BondOrder {
+    /// Valence consumed by this bond (aromatic bonds count as single,
+    /// the extra pi electron is accounted for on the atom)
+    pub fn valence(self) -> u8 {
+        match self {
+            BondOrder::Single | BondOrder::Aromatic => 1,
+            BondOrder::Double => 2,
+            BondOrder::Triple => 3,
+        }
+    }
+
+    fn code(self) -> u8 {
+        match self {
+            BondOrder::Single => 1,
+            BondOrder::Double => 2,
+            BondOrder::Triple => 3,
+            BondOrder::Aromatic => 4,
+        }
+    }
+}
+
+/// Atom in a parsed molecule
+#[derive(Clone, Debug)]
+pub struct GraphAtom {
+    pub element: String,
+    pub aromatic: bool,
+    pub bracket: bool,
+    pub charge: i8,
+    pub explicit_h: u8,  // H count written inside brackets
+    pub implicit_h: u8,  // computed for organic-subset atoms
+    pub chiral: bool,    // tetrahedral mark (@ or @@) in brackets
+}
+
+impl GraphAtom {
+    fn new(element: &str, aromatic: bool) -> Self {
+        Self {
+            element: element.to_string(),
+            aromatic,
+            bracket: false,
+            charge: 0,
+            explicit_h: 0,
+            implicit_h: 0,
+            chiral: false,
+        }
+    }
+
+    pub fn total_h(&self) -> u8 {
+        self.explicit_h + self.implicit_h
+    }
+}
+
+/// Bond between two atoms (indices into `MolGraph::atoms`)
+#[derive(Clone, Debug)]
+pub struct GraphBond {
+    pub a: usize,
+    pub b: usize,
+    pub order: BondOrder,
+    pub direction: Option<char>,  // / or \ as written going from a to b
+}
+
+/// Molecular graph with adjacency information
+#[derive(Clone, Debug, Default)]
+pub struct MolGraph {
+    pub atoms: Vec<GraphAtom>,
+    pub bonds: Vec<GraphBond>,
+    adjacency: Vec<Vec<(usize, usize)>>, // (neighbor, bond index)
+}
+
+impl MolGraph {
+    /// Neighbors of an atom with the connecting bond order
+    pub fn neighbors(&self, idx: usize) -> impl Iterator<Item = (usize, BondOrder)> + '_ {
+        self.adjacency[idx].iter().map(move |&(n, b)| (n, self.bonds[b].order))
+    }
+
+    pub fn degree(&self, idx: usize) -> usize {
+        self.adjacency[idx].len()
+    }
+
+    /// Sum of bond valences around an atom
+    pub fn bond_valence(&self, idx: usize) -> u8 {
+        self.neighbors(idx).map(|(_, o)| o.valence()).sum()
+    }
+
+    /// Number of independent rings (cyclomatic number)
+    pub fn ring_count(&self) -> usize {
+        let components = self.components();
+        (self.bonds.len() + components).saturating_sub(self.atoms.len())
+    }
+
+    /// Per-bond flag: true if the bond is part of a ring
+    pub fn ring_bonds(&self) -> Vec<bool> {
+        (0..self.bonds.len())
+            .map(|skip| {
+                let GraphBond { a, b, .. } = self.bonds[skip];
+                self.connected_without(a, b, skip)
+            })
+            .collect()
+    }
+
+    /// Per-atom flag: true if the atom is part of a ring
+    pub fn ring_atoms(&self) -> Vec<bool> {
+        let mut in_ring = vec![false; self.atoms.len()];
+        for (bond, ring) in self.bonds.iter().zip(self.ring_bonds()) {
+            if ring {
+                in_ring[bond.a] = true;
+                in_ring[bond.b] = true;
+            }
+        }
+        in_ring
+    }
+
+    /// Smallest ring through each ring bond, as ordered atom cycles (deduplicated)
+    pub fn smallest_rings(&self) -> Vec<Vec<usize>> {
+        let mut rings: Vec<Vec<usize>> = Vec::new();
+        let mut seen: Vec<Vec<usize>> = Vec::new();
+
+        for (idx, ring) in self.ring_bonds().into_iter().enumerate() {
+            if !ring {
+                continue;
+            }
+            let GraphBond { a, b, .. } = self.bonds[idx];
+            if let Some(path) = self.shortest_path_without(a, b, idx) {
+                let mut key = path.clone();
+                key.sort_unstable();
+                if !seen.contains(&key) {
+                    seen.push(key);
+                    rings.push(path);
+                }
+            }
+        }
+
+        rings
+    }
+
+    /// Check `/` and `\` marks around double bonds: both ends must be
+    /// specified, and two marks on the same atom must point to opposite sides
+    pub fn check_cis_trans(&self) -> Result<(), String> {
+        for (idx, bond) in self.bonds.iter().enumerate() {
+            if bond.order != BondOrder::Double {
+                continue;
+            }
+            let mut specified = [false; 2];
+            for (k, &end) in [bond.a, bond.b].iter().enumerate() {
+                // Side of each marked neighbour: "above" or "below" the double bond atom
+                let sides: Vec<bool> = self.adjacency[end].iter()
+                    .filter(|&&(_, b)| b != idx)
+                    .filter_map(|&(_, b)| {
+                        let nb = &self.bonds[b];
+                        nb.direction.map(|d| (d == '/') ^ (nb.b == end))
+                    })
+                    .collect();
+                if sides.len() > 1 && sides.iter().all(|&s| s == sides[0]) {
+                    return Err(format!("Conflicting cis/trans marks on atom {}", end));
+                }
+                specified[k] = !sides.is_empty();
+            }
+            if specified[0] != specified[1] {
+                return Err(format!("Incomplete cis/trans specification on bond {}-{}", bond.a, bond.b));
+            }
+        }
+        Ok(())
+    }
+
+    /// BFS path from `from` to `to` avoiding one bond
+    fn shortest_path_without(&self, from: usize, to: usize, skip_bond: usize) -> Option<Vec<usize>> {
+        let mut prev = vec![usize::MAX; self.atoms.len()];
+        let mut queue = std::collections::VecDeque::from([from]);
+        prev[from] = from;
+        while let Some(cur) = queue.pop_front() {
+            if cur == to {
+                let mut path = vec![to];
+                let mut node = to;
+                while node != from {
+                    node = prev[node];
+                    path.push(node);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for &(n, b) in &self.adjacency[cur] {
+                if b != skip_bond && prev[n] == usize::MAX {
+                    prev[n] = cur;
+                    queue.push_back(n);
+                }
+            }
+        }
+        None
+    }
+
+    fn connected_without(&self, from: usize, to: usize, skip_bond: usize) -> bool {
+        let mut seen = vec![false; self.atoms.len()];
+        let mut stack = vec![from];
+        seen[from] = true;
+        while let Some(cur) = stack.pop() {
+            if cur == to {
+                return true;
+            }
+            for &(n, b) in &self.adjacency[cur] {
+                if b != skip_bond && !seen[n] {
+                    seen[n] = true;
+                    stack.push(n);
+                }
+            }
+        }
+        false
+    }
+
+    /// Organic-subset atoms that still carry implicit hydrogens, i.e. where
+    /// a new single bond can be attached
+    pub fn open_positions(&self) -> Vec<usize> {
+        (0..self.atoms.len())
+            .filter(|&i| !self.atoms[i].bracket && self.atoms[i].implicit_h > 0)
+            .collect()
+    }
+
+    /// Graph restricted to the atoms flagged in `keep`, with implicit
+    /// hydrogens recomputed for the lost neighbors
+    pub fn subgraph(&self, keep: &[bool]) -> MolGraph {
+        let mut sub = MolGraph::default();
+        let mut index = vec![usize::MAX; self.atoms.len()];
+        for (i, atom) in self.atoms.iter().enumerate() {
+            if keep[i] {
+                index[i] = sub.add_atom(atom.clone());
+            }
+        }
+        for b in &self.bonds {
+            if keep[b.a] && keep[b.b] {
+                // Can't fail: the source graph has no self or duplicate bonds
+                let _ = sub.add_bond(index[b.a], index[b.b], b.order, b.direction);
+            }
+        }
+        sub.assign_implicit_hydrogens();
+        sub
+    }
+
+    /// Copy another graph's atoms and bonds into this one.
+    /// Returns the index offset of the copied atoms.
+    pub fn append(&mut self, other: &MolGraph) -> usize {
+        let offset = self.atoms.len();
+        for atom in &other.atoms {
+            self.add_atom(atom.clone());
+        }
+        for b in &other.bonds {
+            // Can't fail: the source graph has no self or duplicate bonds
+            let _ = self.add_bond(b.a + offset, b.b + offset, b.order, b.direction);
+        }
+        offset
+    }
+
+    /// Single-bond two atoms, each giving up one implicit hydrogen
+    pub fn join(&mut self, a: usize, b: usize) -> Result<(), String> {
+        for i in [a, b] {
+            if self.atoms[i].bracket || self.atoms[i].implicit_h == 0 {
+                return Err(format!("Atom {} has no free valence", i));
+            }
+        }
+        self.add_bond(a, b, BondOrder::Single, None)?;
+        self.atoms[a].implicit_h -= 1;
+        self.atoms[b].implicit_h -= 1;
+        Ok(())
+    }
+
+    /// Subgraph isomorphism: does this molecule contain `pattern`?
+    /// Elements, aromaticity and bond orders must match exactly; bracket atoms
+    /// in the pattern also require their charge and at least their H count.
+    pub fn contains_substructure(&self, pattern: &MolGraph) -> bool {
+        if pattern.atoms.is_empty() {
+            return true;
+        }
+        if pattern.atoms.len() > self.atoms.len() || pattern.bonds.len() > self.bonds.len() {
+            return false;
+        }
+
+        // Visit pattern atoms so that each one (after the first of its
+        // component) has an already-mapped neighbor
+        let mut order = Vec::with_capacity(pattern.atoms.len());
+        let mut seen = vec![false; pattern.atoms.len()];
+        for start in 0..pattern.atoms.len() {
+            if seen[start] {
+                continue;
+            }
+            seen[start] = true;
+            let mut queue = std::collections::VecDeque::from([start]);
+            while let Some(cur) = queue.pop_front() {
+                order.push(cur);
+                for &(n, _) in &pattern.adjacency[cur] {
+                    if !seen[n] {
+                        seen[n] = true;
+                        queue.push_back(n);
+                    }
+                }
+            }
+        }
+
+        let mut mapping = vec![usize::MAX; pattern.atoms.len()];
+        let mut used = vec![false; self.atoms.len()];
+        self.extend_match(pattern, &order, 0, &mut mapping, &mut used)
+    }
+
+    fn extend_match(
+        &self,
+        pattern: &MolGraph,
+        order: &[usize],
+        depth: usize,
+        mapping: &mut [usize],
+        used: &mut [bool],
+    ) -> bool {
+        let Some(&p) = order.get(depth) else {
+            return true;
+        };
+
+        // Candidates: neighbors of a mapped pattern neighbor, or every atom for a new component
+        let anchor = pattern.adjacency[p].iter().map(|&(n, _)| mapping[n]).find(|&m| m != usize::MAX);
+        let candidates: Vec<usize> = match anchor {
+            Some(m) => self.adjacency[m].iter().map(|&(n, _)| n).collect(),
+            None => (0..self.atoms.len()).collect(),
+        };
+
+        for t in candidates {
+            if used[t] || !atom_matches(&pattern.atoms[p], &self.atoms[t]) {
+                continue;
+            }
+            let bonds_ok = pattern.adjacency[p].iter().all(|&(n, b)| {
+                let m = mapping[n];
+                m == usize::MAX
+                    || self.adjacency[t].iter().any(|&(tn, tb)| tn == m && self.bonds[tb].order == pattern.bonds[b].order)
+            });
+            if !bonds_ok {
+                continue;
+            }
+
+            mapping[p] = t;
+            used[t] = true;
+            if self.extend_match(pattern, order, depth + 1, mapping, used) {
+                return true;
+            }
+            mapping[p] = usize::MAX;
+            used[t] = false;
+        }
+        false
+    }
+
+    fn components(&self) -> usize {
+        let mut seen = vec![false; self.atoms.len()];
+        let mut count = 0;
+        for start in 0..self.atoms.len() {
+            if seen[start] {
+                continue;
+            }
+            count += 1;
+            let mut stack = vec![start];
+            seen[start] = true;
+            while let Some(cur) = stack.pop() {
+                for &(n, _) in &self.adjacency[cur] {
+                    if !seen[n] {
+                        seen[n] = true;
+                        stack.push(n);
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    fn add_atom(&mut self, atom: GraphAtom) -> usize {
+        self.atoms.push(atom);
+        self.adjacency.push(Vec::new());
+        self.atoms.len() - 1
+    }
+
+    fn add_bond(&mut self, a: usize, b: usize, order: BondOrder, direction: Option<char>) -> Result<(), String> {
+        if a == b {
+            return Err("Atom bonded to itself".into());
+        }
+        if self.adjacency[a].iter().any(|&(n, _)| n == b) {
+            return Err(format!("Duplicate bond between atoms {} and {}", a, b));
+        }
+        let idx = self.bonds.len();
+        self.bonds.push(GraphBond { a, b, order, direction });
+        self.adjacency[a].push((b, idx));
+        self.adjacency[b].push((a, idx));
+        Ok(())
+    }
+
+    fn assign_implicit_hydrogens(&mut self) {
+        for i in 0..self.atoms.len() {
+            if self.atoms[i].bracket {
+                continue;
+            }
+            let mut used = self.bond_valence(i);
+            let valences = default_valences(&self.atoms[i].element);
+            let h = if self.atoms[i].aromatic {
+                // One valence goes to the pi system; aromatic atoms never expand
+                used += 1;
+                valences.first().map(|&v| v.saturating_sub(used)).unwrap_or(0)
+            } else {
+                valences.iter().find(|&&v| v >= used).map(|&v| v - used).unwrap_or(0)
+            };
+            self.atoms[i].implicit_h = h;
+        }
+    }
+}
+
+fn atom_matches(pattern: &GraphAtom, target: &GraphAtom) -> bool {
+    pattern.element == target.element
+        && pattern.aromatic == target.aromatic
+        && (!pattern.bracket || (pattern.charge == target.charge && target.total_h() >= pattern.explicit_h))
+}
+
+/// Normal valences for the SMILES organic subset
+fn default_valences(element: &str) -> &'static [u8] {
+    match element {
+        "B" => &[3],
+        "C" => &[4],
+        "N" => &[3, 5],
+        "O" => &[2],
+        "P" => &[3, 5],
+        "S" => &[2, 4, 6],
+        "F" | "Cl" | "Br" | "I" => &[1],
+        _ => &[],
+    }
+}
+
+/// Parse a SMILES string into a molecular graph
+pub fn parse_smiles(smiles: &str) -> Result<MolGraph, String> {
+    let chars: Vec<char> = smiles.trim().chars().collect();
+    if chars.is_empty() {
+        return Err("Empty SMILES".into());
+    }
+
+    let mut graph = MolGraph::default();
+    let mut prev: Option<usize> = None;
+    let mut branches: Vec<Option<usize>> = Vec::new();
+    let mut pending_bond: Option<BondOrder> = None;
+    let mut pending_dir: Option<char> = None;
+    let mut ring_opens: HashMap<u32, (usize, Option<BondOrder>, Option<char>)> = HashMap::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '(' => {
+                if prev.is_none() {
+                    return Err(format!("Branch without preceding atom at position {}", i));
+                }
+                branches.push(prev);
+                i += 1;
+            }
+            ')' => {
+                prev = branches.pop().ok_or_else(|| format!("Unmatched ')' at position {}", i))?;
+                i += 1;
+            }
+            '-' => {
+                pending_bond = Some(BondOrder::Single);
+                i += 1;
+            }
+            '/' | '\\' => {
+                pending_bond = Some(BondOrder::Single);
+                pending_dir = Some(c);
+                i += 1;
+            }
+            '=' => {
+                pending_bond = Some(BondOrder::Double);
+                i += 1;
+            }
+            '#' => {
+                pending_bond = Some(BondOrder::Triple);
+                i += 1;
+            }
+            ':' => {
+                pending_bond = Some(BondOrder::Aromatic);
+                i += 1;
+            }
+            '.' => {
+                prev = None;
+                pending_bond = None;
+                pending_dir = None;
+                i += 1;
+            }
+            '%' | '0'..='9' => {
+                let (label, next) = if c == '%' {
+                    let digits: String = chars.iter().skip(i + 1).take(2).collect();
+                    let label = digits.parse::<u32>()
+                        .map_err(|_| format!("Invalid ring label at position {}", i))?;
+                    (label, i + 3)
+                } else {
+                    (c.to_digit(10).unwrap_or(0), i + 1)
+                };
+                let current = prev.ok_or_else(|| format!("Ring closure without atom at position {}", i))?;
+
+                if let Some((open_atom, open_bond, open_dir)) = ring_opens.remove(&label) {
+                    let order = pending_bond.or(open_bond).unwrap_or_else(|| {
+                        default_bond(&graph.atoms[open_atom], &graph.atoms[current])
+                    });
+                    // A mark at the closing digit is written from `current` towards `open_atom`
+                    let direction = open_dir.or(pending_dir.map(flip_direction));
+                    graph.add_bond(open_atom, current, order, direction)?;
+                } else {
+                    ring_opens.insert(label, (current, pending_bond, pending_dir));
+                }
+                pending_bond = None;
+                pending_dir = None;
+                i = next;
+            }
+            '[' => {
+                let close = chars[i..].iter().position(|&ch| ch == ']')
+                    .ok_or_else(|| format!("Unclosed '[' at position {}", i))?;
+                let content: String = chars[i + 1..i + close].iter().collect();
+                let atom = parse_bracket_atom(&content)?;
+                let idx = graph.add_atom(atom);
+                connect(&mut graph, prev, idx, pending_bond.take(), pending_dir.take())?;
+                prev = Some(idx);
+                i += close + 1;
+            }
+            _ if c.is_ascii_alphabetic() => {
+                let (element, aromatic, len) = parse_organic_atom(&chars[i..])
+                    .ok_or_else(|| format!("Unknown atom '{}' at position {}", c, i))?;
+                let idx = graph.add_atom(GraphAtom::new(element, aromatic));
+                connect(&mut graph, prev, idx, pending_bond.take(), pending_dir.take())?;
+                prev = Some(idx);
+                i += len;
+            }
+            _ => return Err(format!("Unexpected character '{}' at position {}", c, i)),
+        }
+    }
+
+    if !branches.is_empty() {
+        return Err("Unclosed branch '('".into());
+    }
+    if let Some(label) = ring_opens.keys().next() {
+        return Err(format!("Unclosed ring {}", label));
+    }
+    if pending_bond.is_some() {
+        return Err("Dangling bond at end of SMILES".into());
+    }
+    if graph.atoms.is_empty() {
+        return Err("No atoms".into());
+    }
+
+    graph.assign_implicit_hydrogens();
+    Ok(graph)
+}
+
+fn flip_direction(dir: char) -> char {
+    if dir == '/' { '\\' } else { '/' }
+}
+
+fn connect(
+    graph: &mut MolGraph,
+    prev: Option<usize>,
+    idx: usize,
+    bond: Option<BondOrder>,
+    direction: Option<char>,
+) -> Result<(), String> {
+    match prev {
+        Some(p) => {
+            let order = bond.unwrap_or_else(|| default_bond(&graph.atoms[p], &graph.atoms[idx]));
+            graph.add_bond(p, idx, order, direction)
+        }
+        None if bond.is_some() => Err("Bond without preceding atom".into()),
+        None => Ok(()),
+    }
+}
+
+fn default_bond(a: &GraphAtom, b: &GraphAtom) -> BondOrder {
+    if a.aromatic && b.aromatic {
+        BondOrder::Aromatic
+    } else {
+        BondOrder::Single
+    }
+}
+
+/// Organic-subset atom at the start of `chars`: (element, aromatic, chars consumed)
+fn parse_organic_atom(chars: &[char]) -> Option<(&'static str, bool, usize)> {
+    let two: String = chars.iter().take(2).collect();
+    match two.as_str() {
+        "Cl" => return Some(("Cl", false, 2)),
+        "Br" => return Some(("Br", false, 2)),
+        _ => {}
+    }
+    match chars[0] {
+        'B' => Some(("B", false, 1)),
+        'C' => Some(("C", false, 1)),
+        'N' => Some(("N", false, 1)),
+        'O' => Some(("O", false, 1)),
+        'P' => Some(("P", false, 1)),
+        'S' => Some(("S", false, 1)),
+        'F' => Some(("F", false, 1)),
+        'I' => Some(("I", false, 1)),
+        'b' => Some(("B", true, 1)),
+        'c' => Some(("C", true, 1)),
+        'n' => Some(("N", true, 1)),
+        'o' => Some(("O", true, 1)),
+        'p' => Some(("P", true, 1)),
+        's' => Some(("S", true, 1)),
+        _ => None,
+    }
+}
+
+/// Parse the inside of a bracket atom, e.g. `nH`, `NH3+`, `C@@H`, `13C`, `O-`
+fn parse_bracket_atom(content: &str) -> Result<GraphAtom, String> {
+    let chars: Vec<char> = content.chars().collect();
+    let mut i = 0;
+
+    // Isotope
+    while i < chars.len() && chars[i].is_ascii_digit() {
+        i += 1;
+    }
+
+    // Element symbol
+    let first = *chars.get(i).ok_or_else(|| format!("Empty bracket atom [{}]", content))?;
+    let aromatic = first.is_ascii_lowercase();
+    let mut element = first.to_ascii_uppercase().to_string();
+    i += 1;
+    if let Some(&next) = chars.get(i) {
+        // Two-letter symbols: uppercase + lowercase (Cl, Na, Si) or aromatic se/as
+        let two_letter = if aromatic {
+            matches!((first, next), ('s', 'e') | ('a', 's'))
+        } else {
+            next.is_ascii_lowercase() && next != 'h'
+        };
+        if two_letter {
+            element.push(next);
+            i += 1;
+        }
+    }
+    if !first.is_ascii_alphabetic() {
+        return Err(format!("Invalid bracket atom [{}]", content));
+    }
+
+    // Chirality
+    let chiral = chars.get(i) == Some(&'@');
+    while i < chars.len() && chars[i] == '@' {
+        i += 1;
+    }
+
+    // Hydrogen count
+    let mut explicit_h = 0u8;
+    if chars.get(i) == Some(&'H') {
+        i += 1;
+        explicit_h = 1;
+        if let Some(d) = chars.get(i).and_then(|c| c.to_digit(10)) {
+            explicit_h = d as u8;
+            i += 1;
+        }
+    }
+
+    // Charge
+    let mut charge = 0i8;
+    if let Some(&sign) = chars.get(i).filter(|c| **c == '+' || **c == '-') {
+        let unit = if sign == '+' { 1 } else { -1 };
+        i += 1;
+        charge = unit;
+        if let Some(d) = chars.get(i).and_then(|c| c.to_digit(10)) {
+            charge = unit * d as i8;
+            i += 1;
+        } else {
+            while chars.get(i) == Some(&sign) {
+                charge += unit;
+                i += 1;
+            }
+        }
+    }
+
+    // Atom class
+    if chars.get(i) == Some(&':') {
+        i += 1;
+        while i < chars.len() && chars[i].is_ascii_digit() {
+            i += 1;
+        }
+    }
+
+    if i != chars.len() {
+        return Err(format!("Unparsed bracket atom content [{}]", content));
+    }
+
+    Ok(GraphAtom {
+        element,
+        aromatic,
+        bracket: true,
+        charge,
+        explicit_h,
+        implicit_h: 0,
+        chiral,
+    })
+}
+
+/// Canonical SMILES: the same molecule written in any atom order gives the same string.
+/// Returns None if the SMILES can't be parsed.
+pub fn canonical_smiles(smiles: &str) -> Option<String> {
+    parse_smiles(smiles).ok().map(|g| write_canonical(&g))
+}
+
+/// Write a graph as canonical SMILES
+pub fn write_canonical(graph: &MolGraph) -> String {
+    let ranks = canonical_ranks(graph);
+    let n = graph.atoms.len();
+    let mut visited = vec![false; n];
+    let mut parts = Vec::new();
+
+    // One fragment per connected component, each starting at its lowest-ranked atom
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by_key(|&i| ranks[i]);
+    for &start in &order {
+        if visited[start] {
+            continue;
+        }
+        parts.push(write_component(graph, &ranks, start, &mut visited));
+    }
+
+    parts.join(".")
+}
+
+/// Morgan-style iterative refinement with deterministic tie breaking
+fn canonical_ranks(graph: &MolGraph) -> Vec<usize> {
+    let n = graph.atoms.len();
+    let in_ring = graph.ring_atoms();
+
+    let invariants: Vec<_> = (0..n)
+        .map(|i| {
+            let a = &graph.atoms[i];
+            (a.element.clone(), a.aromatic, graph.degree(i), a.total_h(), a.charge, in_ring[i])
+        })
+        .collect();
+    let mut ranks = dense_ranks(&invariants);
+
+    loop {
+        ranks = refine(graph, ranks);
+        let distinct = count_distinct(&ranks);
+        if distinct == n {
+            return ranks;
+        }
+
+        // Break the first tie: the lowest-indexed atom of the lowest tied rank wins
+        let mut counts: HashMap<usize, usize> = HashMap::new();
+        for &r in &ranks {
+            *counts.entry(r).or_default() += 1;
+        }
+        let tied = (0..n).filter(|&r| counts.get(&r).copied().unwrap_or(0) > 1).min();
+        let Some(tied) = tied else { return ranks; };
+        let chosen = (0..n).find(|&i| ranks[i] == tied).unwrap_or(0);
+        let bumped: Vec<(usize, usize)> = (0..n)
+            .map(|i| (ranks[i], if i == chosen { 0 } else { 1 }))
+            .collect();
+        ranks = dense_ranks(&bumped);
+    }
+}
+
+fn refine(graph: &MolGraph, mut ranks: Vec<usize>) -> Vec<usize> {
+    loop {
+        let keys: Vec<(usize, Vec<(usize, u8)>)> = (0..graph.atoms.len())
+            .map(|i| {
+                let mut nbrs: Vec<(usize, u8)> = graph.neighbors(i).map(|(n, o)| (ranks[n], o.code())).collect();
+                nbrs.sort_unstable();
+                (ranks[i], nbrs)
+            })
+            .collect();
+        let next = dense_ranks(&keys);
+        if count_distinct(&next) == count_distinct(&ranks) {
+            return next;
+        }
+        ranks = next;
+    }
+}
+
+fn dense_ranks<T: Ord + Clone>(keys: &[T]) -> Vec<usize> {
+    let mut sorted: Vec<T> = keys.to_vec();
+    sorted.sort();
+    sorted.dedup();
+    keys.iter().map(|k| sorted.binary_search(k).unwrap_or(0)).collect()
+}
+
+fn count_distinct(ranks: &[usize]) -> usize {
+    let mut r = ranks.to_vec();
+    r.sort_unstable();
+    r.dedup();
+    r.len()
+}
+
+fn write_component(graph: &MolGraph, ranks: &[usize], start: usize, visited: &mut [bool]) -> String {
+    // Pass 1: spanning tree in rank order, remaining bonds become ring closures
+    let n = graph.atoms.len();
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut ring_partners: Vec<Vec<usize>> = vec![Vec::new(); n];
+    build_tree(graph, ranks, start, None, visited, &mut children, &mut ring_partners);
+    for partners in &mut ring_partners {
+        partners.sort_by_key(|&p| ranks[p]);
+    }
+
+    // Pass 2: emit
+    let mut out = String::new();
+    let mut open_labels: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut free_labels: Vec<bool> = vec![true; 100];
+    emit_atom(graph, start, &children, &ring_partners, &mut open_labels, &mut free_labels, &mut out);
+    out
+}
+
+fn build_tree(
+    graph: &MolGraph,
+    ranks: &[usize],
+    atom: usize,
+    parent: Option<usize>,
+    visited: &mut [bool],
+    children: &mut [Vec<usize>],
+    ring_partners: &mut [Vec<usize>],
+) {
+    visited[atom] = true;
+    let mut nbrs: Vec<usize> = graph.neighbors(atom).map(|(nb, _)| nb).collect();
+    nbrs.sort_by_key(|&nb| ranks[nb]);
+
+    for nb in nbrs {
+        if Some(nb) == parent {
+            continue;
+        }
+        if visited[nb] {
+            if !ring_partners[atom].contains(&nb) {
+                ring_partners[atom].push(nb);
+                ring_partners[nb].push(atom);
+            }
+        } else {
+            children[atom].push(nb);
+            build_tree(graph, ranks, nb, Some(atom), visited, children, ring_partners);
+        }
+    }
+}
+
+fn emit_atom(
+    graph: &MolGraph,
+    atom: usize,
+    children: &[Vec<usize>],
+    ring_partners: &[Vec<usize>],
+    open_labels: &mut HashMap<(usize, usize), usize>,
+    free_labels: &mut [bool],
+    out: &mut String,
+) {
+    out.push_str(&atom_symbol(&graph.atoms[atom]));
+
+    for &partner in &ring_partners[atom] {
+        let key = (atom.min(partner), atom.max(partner));
+        if let Some(label) = open_labels.remove(&key) {
+            free_labels[label] = true;
+            push_ring_label(out, label);
+        } else {
+            let label = (1..free_labels.len()).find(|&l| free_labels[l]).unwrap_or(1);
+            free_labels[label] = false;
+            open_labels.insert(key, label);
+            out.push_str(bond_symbol(graph, atom, partner));
+            push_ring_label(out, label);
+        }
+    }
+
+    let kids = &children[atom];
+    for (i, &child) in kids.iter().enumerate() {
+        let last = i + 1 == kids.len();
+        if !last {
+            out.push('(');
+        }
+        out.push_str(bond_symbol(graph, atom, child));
+        emit_atom(graph, child, children, ring_partners, open_labels, free_labels, out);
+        if !last {
+            out.push(')');
+        }
+    }
+}
+
+fn push_ring_label(out: &mut String, label: usize) {
+    if label < 10 {
+        out.push_str(&label.to_string());
+    } else {
+        out.push_str(&format!("%{}", label));
+    }
+}
+
+fn bond_symbol(graph: &MolGraph, a: usize, b: usize) -> &'static str {
+    let order = graph.neighbors(a).find(|&(n, _)| n == b).map(|(_, o)| o).unwrap_or(BondOrder::Single);
+    let both_aromatic = graph.atoms[a].aromatic && graph.atoms[b].aromatic;
+    match order {
+        BondOrder::Single if both_aromatic => "-",
+        BondOrder::Single => "",
+        BondOrder::Double => "=",
+        BondOrder::Triple => "#",
+        BondOrder::Aromatic if both_aromatic => "",
+        BondOrder::Aromatic => ":",
+    }
+}
+
+/// SMILES text for a single atom
+pub fn atom_symbol(atom: &GraphAtom) -> String {
+    let symbol = if atom.aromatic {
+        atom.element.to_lowercase()
+    } else {
+        atom.element.clone()
+    };
+
+    if !atom.bracket {
+        return symbol;
+    }
+
+    let mut s = format!("[{}", symbol);
+    match atom.explicit_h {
+        0 => {}
+        1 => s.push('H'),
+        h => s.push_str(&format!("H{}", h)),
+    }
+    match atom.charge {
+        0 => {}
+        1 => s.push('+'),
+        -1 => s.push('-'),
+        c if c > 0 => s.push_str(&format!("+{}", c)),
+        c => s.push_str(&format!("-{}", -c)),
+    }
+    s.push(']');
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substructure() {
+        let contains = |smiles: &str, pattern: &str| {
+            parse_smiles(smiles).unwrap().contains_substructure(&parse_smiles(pattern).unwrap())
+        };
+        let aspirin = "CC(=O)Oc1ccccc1C(=O)O";
+
+        assert!(contains(aspirin, "C(=O)[OH]"));
+        assert!(contains(aspirin, "c1ccccc1"));
+        assert!(contains(aspirin, "OC(C)=O"));            // written in another order
+        assert!(!contains("c1ccccc1", "C(=O)[OH]"));
+        assert!(!contains("CC(=O)OC", "C(=O)[OH]"));      // ester, no acidic H
+        assert!(!contains(aspirin, "C1CCCCC1"));           // aliphatic ring
+        assert!(!contains("CC=O", "CC(=O)O"));
+        assert!(contains("ClCCBr", "Cl.Br"));              // disconnected pattern
+    }
+
+    #[test]
+    fn test_parse_benzene() {
+        let g = parse_smiles("c1ccccc1").unwrap();
+        assert_eq!(g.atoms.len(), 6);
+        assert_eq!(g.bonds.len(), 6);
+        assert!(g.bonds.iter().all(|b| b.order == BondOrder::Aromatic));
+        assert!(g.atoms.iter().all(|a| a.total_h() == 1));
+        assert_eq!(g.ring_count(), 1);
+    }
+
+    #[test]
+    fn test_implicit_hydrogens() {
+        let g = parse_smiles("CCO").unwrap();
+        let h: Vec<u8> = g.atoms.iter().map(|a| a.total_h()).collect();
+        assert_eq!(h, vec![3, 2, 1]);
+
+        let g = parse_smiles("c1cc[nH]c1").unwrap();
+        assert_eq!(g.atoms[3].total_h(), 1);
+        assert_eq!(g.atoms[3].element, "N");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_smiles("").is_err());
+        assert!(parse_smiles("C1CCC").is_err());
+        assert!(parse_smiles("C(C").is_err());
+        assert!(parse_smiles("C)C").is_err());
+        assert!(parse_smiles("CX").is_err());
+    }
+
+    #[test]
+    fn test_ring_perception() {
+        let g = parse_smiles("c1ccc2ccccc2c1").unwrap();
+        assert_eq!(g.ring_count(), 2);
+        let g = parse_smiles("CCc1ccccc1").unwrap();
+        let rings = g.ring_atoms();
+        assert!(!rings[0] && !rings[1]);
+        assert!(rings[2..].iter().all(|&r| r));
+    }
+
+    #[test]
+    fn test_smallest_rings() {
+        let g = parse_smiles("c1ccc2ccccc2c1").unwrap();
+        let rings = g.smallest_rings();
+        assert_eq!(rings.len(), 2);
+        assert!(rings.iter().all(|r| r.len() == 6));
+    }
+
+    #[test]
+    fn test_canonical_smiles() {
+        assert_eq!(canonical_smiles("CCO"), canonical_smiles("OCC"));
+        assert_eq!(canonical_smiles("Oc1ccccc1"), canonical_smiles("c1ccc(O)cc1"));
+        assert_eq!(
+            canonical_smiles("CC(=O)Oc1ccccc1C(=O)O"),
+            canonical_smiles("OC(=O)c1ccccc1OC(C)=O")
+        );
+        assert_ne!(canonical_smiles("CCO"), canonical_smiles("COC"));
+    }
+
+    #[test]
+    fn test_canonical_roundtrip() {
+        for smiles in ["CC(C)Cc1ccc(cc1)C(C)C(=O)O", "Cn1cnc2c1c(=O)n(c(=O)n2C)C", "C1CCNCC1", "[NH3+]CC([O-])=O"] {
+            let canon = canonical_smiles(smiles).unwrap();
+            assert_eq!(canonical_smiles(&canon).unwrap(), canon, "not stable for {}", smiles);
+        }
+    }
+}
@@ -0,0 +1,310 @@
+//! 2D depiction coordinates from SMILES
+//! Ring-aware stress majorization over the parsed molecular graph
+
+use super::graph::{self, BondOrder, MolGraph};
+
+/// Atom with 2D position (bond length = 1.0)
+#[derive(Clone, Debug)]
+pub struct LayoutAtom {
+    pub pos: [f32; 2],
+    pub element: String,
+    pub label: Option<String>,  // None for plain carbons, drawn implicitly
+}
+
+/// Bond between two layout atoms
+#[derive(Clone, Debug)]
+pub struct LayoutBond {
+    pub a: usize,
+    pub b: usize,
+    pub order: BondOrder,
+}
+
+/// Complete 2D layout for drawing
+#[derive(Clone, Debug)]
+pub struct MolLayout {
+    pub atoms: Vec<LayoutAtom>,
+    pub bonds: Vec<LayoutBond>,
+    pub aromatic_rings: Vec<Vec<usize>>,
+}
+
+impl MolLayout {
+    /// Bounding box as (min, max) corners
+    pub fn bounds(&self) -> ([f32; 2], [f32; 2]) {
+        let mut min = [f32::INFINITY; 2];
+        let mut max = [f32::NEG_INFINITY; 2];
+        for a in &self.atoms {
+            for k in 0..2 {
+                min[k] = min[k].min(a.pos[k]);
+                max[k] = max[k].max(a.pos[k]);
+            }
+        }
+        (min, max)
+    }
+}
+
+const ITERATIONS: usize = 300;
+
+/// Standalone SVG depiction of a SMILES, scaled to fit `width` x `height`.
+/// Carbons are implicit (bond vertices only); other atoms get a text label.
+pub fn molecule_to_svg(smiles: &str, width: u32, height: u32) -> Option<String> {
+    use std::fmt::Write;
+
+    let layout = layout_2d(smiles)?;
+    let (w, h) = (width as f32, height as f32);
+    let (min, max) = layout.bounds();
+    let margin = 20.0;
+    let scale = ((w - 2.0 * margin) / (max[0] - min[0]).max(1.0))
+        .min((h - 2.0 * margin) / (max[1] - min[1]).max(1.0))
+        .clamp(5.0, 40.0);
+    let center = [(min[0] + max[0]) / 2.0, (min[1] + max[1]) / 2.0];
+    // SVG y grows downwards, so flip to keep the textbook orientation
+    let points: Vec<[f32; 2]> = layout.atoms.iter()
+        .map(|a| [w / 2.0 + (a.pos[0] - center[0]) * scale, h / 2.0 - (a.pos[1] - center[1]) * scale])
+        .collect();
+
+    let mut svg = String::new();
+    let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#, width, height);
+    let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+    let _ = writeln!(svg, r#"<g stroke="black" stroke-width="1.5">"#);
+    for bond in &layout.bonds {
+        let (a, b) = (points[bond.a], points[bond.b]);
+        let len = ((b[0] - a[0]).powi(2) + (b[1] - a[1]).powi(2)).sqrt().max(1e-3);
+        let normal = [-(b[1] - a[1]) / len * scale * 0.12, (b[0] - a[0]) / len * scale * 0.12];
+        let offsets: &[f32] = match bond.order {
+            BondOrder::Single | BondOrder::Aromatic => &[0.0],
+            BondOrder::Double => &[-0.5, 0.5],
+            BondOrder::Triple => &[-1.0, 0.0, 1.0],
+        };
+        for k in offsets {
+            let (dx, dy) = (normal[0] * k, normal[1] * k);
+            let _ = writeln!(svg, r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}"/>"#,
+                a[0] + dx, a[1] + dy, b[0] + dx, b[1] + dy);
+        }
+    }
+    let _ = writeln!(svg, "</g>");
+
+    for ring in &layout.aromatic_rings {
+        let n = ring.len() as f32;
+        let c = ring.iter().fold([0.0, 0.0], |acc, &i| [acc[0] + points[i][0] / n, acc[1] + points[i][1] / n]);
+        let radius = ring.iter()
+            .map(|&i| ((points[i][0] - c[0]).powi(2) + (points[i][1] - c[1]).powi(2)).sqrt())
+            .sum::<f32>() / n;
+        let _ = writeln!(svg, r#"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="none" stroke="black"/>"#,
+            c[0], c[1], radius * 0.6);
+    }
+
+    for (atom, p) in layout.atoms.iter().zip(&points) {
+        if let Some(label) = &atom.label {
+            let half = 4.0 * label.chars().count() as f32 + 2.0;
+            let _ = writeln!(svg, r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="16" fill="white"/>"#,
+                p[0] - half, p[1] - 8.0, 2.0 * half);
+            let _ = writeln!(svg, r#"<text x="{:.1}" y="{:.1}" font-family="sans-serif" font-size="13" text-anchor="middle" dominant-baseline="central" fill="{}">{}</text>"#,
+                p[0], p[1], svg_element_color(&atom.element), label);
+        }
+    }
+    svg.push_str("</svg>\n");
+    Some(svg)
+}
+
+fn svg_element_color(element: &str) -> &'static str {
+    match element {
+        "O" => "#d03030",
+        "N" => "#3050d0",
+        "S" => "#b09000",
+        "P" => "#d07000",
+        "F" | "Cl" | "Br" | "I" => "#208020",
+        _ => "#202020",
+    }
+}
+
+/// Compute 2D coordinates for a SMILES string. None if it can't be parsed.
+pub fn layout_2d(smiles: &str) -> Option<MolLayout> {
+    let g = graph::parse_smiles(smiles).ok()?;
+    let n = g.atoms.len();
+    let rings = g.smallest_rings();
+
+    let target = target_distances(&g, &rings);
+    let mut pos = initial_positions(n);
+
+    // Localized stress majorization (Gansner et al.), weights 1/d²
+    for _ in 0..ITERATIONS {
+        for i in 0..n {
+            let mut num = [0.0f32; 2];
+            let mut den = 0.0f32;
+            for j in 0..n {
+                if i == j {
+                    continue;
+                }
+                let d = target[i][j];
+                let w = 1.0 / (d * d);
+                let dx = pos[i][0] - pos[j][0];
+                let dy = pos[i][1] - pos[j][1];
+                let dist = (dx * dx + dy * dy).sqrt().max(1e-4);
+                num[0] += w * (pos[j][0] + d * dx / dist);
+                num[1] += w * (pos[j][1] + d * dy / dist);
+                den += w;
+            }
+            if den > 0.0 {
+                pos[i] = [num[0] / den, num[1] / den];
+            }
+        }
+    }
+
+    let atoms = g.atoms.iter()
+        .zip(pos)
+        .map(|(a, p)| LayoutAtom {
+            pos: p,
+            element: a.element.clone(),
+            label: atom_label(a),
+        })
+        .collect();
+
+    let bonds = g.bonds.iter()
+        .map(|b| LayoutBond { a: b.a, b: b.b, order: b.order })
+        .collect();
+
+    let aromatic_rings = rings.into_iter()
+        .filter(|r| r.iter().all(|&i| g.atoms[i].aromatic))
+        .collect();
+
+    Some(MolLayout { atoms, bonds, aromatic_rings })
+}
+
+/// Ideal pairwise distances: ring members form regular polygons,
+/// everything else follows the (zigzag-shortened) graph distance
+fn target_distances(g: &MolGraph, rings: &[Vec<usize>]) -> Vec<Vec<f32>> {
+    let n = g.atoms.len();
+    let mut hops = vec![vec![usize::MAX; n]; n];
+
+    for (start, row) in hops.iter_mut().enumerate() {
+        row[start] = 0;
+        let mut queue = std::collections::VecDeque::from([start]);
+        while let Some(cur) = queue.pop_front() {
+            for (nb, _) in g.neighbors(cur) {
+                if row[nb] == usize::MAX {
+                    row[nb] = row[cur] + 1;
+                    queue.push_back(nb);
+                }
+            }
+        }
+    }
+
+    let max_hops = hops.iter().flatten().filter(|&&h| h != usize::MAX).max().copied().unwrap_or(1);
+
+    let mut target: Vec<Vec<f32>> = hops.iter()
+        .map(|row| {
+            row.iter()
+                .map(|&h| match h {
+                    0 => 0.0,
+                    1 => 1.0,
+                    // 120° zigzag: two bonds span sqrt(3)
+                    usize::MAX => (max_hops + 2) as f32,
+                    h => h as f32 * 0.866,
+                })
+                .collect()
+        })
+        .collect();
+
+    for ring in rings {
+        let k = ring.len();
+        let side = (std::f32::consts::PI / k as f32).sin();
+        for p in 0..k {
+            for q in (p + 1)..k {
+                let steps = (q - p).min(k - (q - p));
+                let chord = (std::f32::consts::PI * steps as f32 / k as f32).sin() / side;
+                let (i, j) = (ring[p], ring[q]);
+                target[i][j] = chord;
+                target[j][i] = chord;
+            }
+        }
+    }
+
+    target
+}
+
+/// Deterministic sunflower spiral start so layouts are reproducible
+fn initial_positions(n: usize) -> Vec<[f32; 2]> {
+    let golden = std::f32::consts::PI * (3.0 - 5.0f32.sqrt());
+    (0..n)
+        .map(|i| {
+            let r = (i as f32 + 0.5).sqrt();
+            let theta = i as f32 * golden;
+            [r * theta.cos(), r * theta.sin()]
+        })
+        .collect()
+}
+
+fn atom_label(atom: &graph::GraphAtom) -> Option<String> {
+    if atom.element == "C" && atom.charge == 0 {
+        return None;
+    }
+
+    let mut label = atom.element.clone();
+    match atom.total_h() {
+        0 => {}
+        1 => label.push('H'),
+        h => label.push_str(&format!("H{}", h)),
+    }
+    match atom.charge {
+        0 => {}
+        1 => label.push('+'),
+        -1 => label.push('-'),
+        c if c > 0 => label.push_str(&format!("{}+", c)),
+        c => label.push_str(&format!("{}-", -c)),
+    }
+    Some(label)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dist(a: [f32; 2], b: [f32; 2]) -> f32 {
+        ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn test_benzene_hexagon() {
+        let layout = layout_2d("c1ccccc1").unwrap();
+        assert_eq!(layout.atoms.len(), 6);
+        assert_eq!(layout.aromatic_rings.len(), 1);
+
+        let cx = layout.atoms.iter().map(|a| a.pos[0]).sum::<f32>() / 6.0;
+        let cy = layout.atoms.iter().map(|a| a.pos[1]).sum::<f32>() / 6.0;
+        for a in &layout.atoms {
+            let r = dist(a.pos, [cx, cy]);
+            assert!((r - 1.0).abs() < 0.05, "radius {}", r);
+        }
+        for b in &layout.bonds {
+            let len = dist(layout.atoms[b.a].pos, layout.atoms[b.b].pos);
+            assert!((len - 1.0).abs() < 0.05, "bond length {}", len);
+        }
+    }
+
+    #[test]
+    fn test_svg_export() {
+        let svg = molecule_to_svg("c1ccccc1", 200, 150).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(svg.matches("<line ").count(), 6);
+        assert_eq!(svg.matches("<circle ").count(), 1);
+        // Carbons are implicit
+        assert_eq!(svg.matches("<text ").count(), 0);
+
+        let svg = molecule_to_svg("CC(=O)O", 200, 150).unwrap();
+        assert_eq!(svg.matches("<line ").count(), 4);
+        assert_eq!(svg.matches("<text ").count(), 2);
+        assert!(molecule_to_svg("C1CC", 200, 150).is_none());
+    }
+
+    #[test]
+    fn test_labels() {
+        let layout = layout_2d("CCO").unwrap();
+        assert!(layout.atoms[0].label.is_none());
+        assert_eq!(layout.atoms[2].label.as_deref(), Some("OH"));
+    }
+
+    #[test]
+    fn test_invalid_smiles() {
+        assert!(layout_2d("C1CC").is_none());
+    }
+}
@@ -3,3 +3,7 @@ pub mod smiles;
 pub mod scaffolds;
 pub mod druglikeness;
 pub mod similarity;
+pub mod graph;
+pub mod layout;
+pub mod sascore;
+pub mod scaffold_extract;
@@ -0,0 +1,127 @@
+//! Synthetic accessibility score (after Ertl & Schuffenhauer, 2009)
+//! 1 = easy to make, 10 = very hard. Fragment frequencies are approximated
+//! from fingerprint bits of the known drug scaffolds instead of PubChem statistics.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use super::{descriptors, graph};
+use super::scaffolds::{DRUG_SCAFFOLDS, SUBSTITUENTS};
+use super::similarity::generate_fingerprint;
+
+const FP_SIZE: u32 = 2048;
+
+/// Fraction of reference fragments setting each fingerprint bit
+fn reference_frequencies() -> &'static HashMap<u32, f32> {
+    static FREQUENCIES: OnceLock<HashMap<u32, f32>> = OnceLock::new();
+    FREQUENCIES.get_or_init(|| {
+        let references: Vec<&str> = DRUG_SCAFFOLDS.iter().map(|s| s.smiles)
+            .chain(SUBSTITUENTS.iter().map(|(_, s)| *s))
+            .collect();
+
+        let mut counts: HashMap<u32, f32> = HashMap::new();
+        for smiles in &references {
+            for &bit in &generate_fingerprint(smiles, FP_SIZE).bits {
+                *counts.entry(bit).or_insert(0.0) += 1.0;
+            }
+        }
+        let n = references.len() as f32;
+        counts.values_mut().for_each(|c| *c /= n);
+        counts
+    })
+}
+
+/// Molecules made of common fingerprint bits score near 2.5, unseen bits near 0
+fn fragment_score(smiles: &str) -> f32 {
+    let fp = generate_fingerprint(smiles, FP_SIZE);
+    if fp.bits.is_empty() {
+        return 0.0;
+    }
+    let freqs = reference_frequencies();
+    let total: f32 = fp.bits.iter()
+        .map(|bit| (freqs.get(bit).copied().unwrap_or(0.0) + 0.01).log10())
+        .sum();
+    // Mean log-frequency lies in [-2, 0]
+    2.5 * (1.0 + total / fp.bits.len() as f32 / 2.0)
+}
+
+/// Ring-system penalties: (fused/bridgehead atoms, spiro atoms, macrocycles)
+fn ring_features(g: &graph::MolGraph) -> (usize, usize, usize) {
+    let rings = g.smallest_rings();
+    let ring_bonds = g.ring_bonds();
+
+    let mut fused = 0;
+    let mut spiro = 0;
+    for i in 0..g.atoms.len() {
+        let ring_degree = g.bonds.iter().zip(&ring_bonds)
+            .filter(|(b, &r)| r && (b.a == i || b.b == i))
+            .count();
+        if ring_degree == 3 {
+            fused += 1;
+        } else if ring_degree >= 4 {
+            spiro += 1;
+        }
+    }
+    let macrocycles = rings.iter().filter(|r| r.len() > 8).count();
+
+    (fused, spiro, macrocycles)
+}
+
+/// Synthetic accessibility score in [1, 10]
+pub fn sa_score(smiles: &str) -> f32 {
+    let g = match graph::parse_smiles(smiles) {
+        Ok(g) => g,
+        Err(_) => return 10.0,
+    };
+
+    let n_atoms = g.atoms.len() as f32;
+    let (fused, spiro, macrocycles) = ring_features(&g);
+
+    let size_penalty = n_atoms.powf(1.005) - n_atoms;
+    let stereo_penalty = (descriptors::count_stereocenters(smiles) as f32 + 1.0).log10();
+    let spiro_penalty = (spiro as f32 + 1.0).log10();
+    let bridge_penalty = (fused as f32 + 1.0).log10();
+    let macro_penalty = if macrocycles > 0 { 2f32.log10() } else { 0.0 };
+
+    let raw = fragment_score(smiles)
+        - size_penalty
+        - stereo_penalty
+        - spiro_penalty
+        - bridge_penalty
+        - macro_penalty;
+
+    // Rescale as in the reference implementation
+    let (min, max) = (-4.0, 2.5);
+    let mut score = 11.0 - (raw - min + 1.0) / (max - min) * 9.0;
+    if score > 8.0 {
+        score = 8.0 + (score - 8.0 + 1.0).ln();
+    }
+    score.clamp(1.0, 10.0)
+}
+
+/// SA score mapped to [0, 1] for use as a cost objective
+pub fn normalized_sa_score(smiles: &str) -> f32 {
+    (sa_score(smiles) - 1.0) / 9.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sa_score_range() {
+        for smiles in ["C", "CCO", "c1ccccc1", "CC(=O)Oc1ccccc1C(=O)O"] {
+            let s = sa_score(smiles);
+            assert!((1.0..=10.0).contains(&s), "{} -> {}", smiles, s);
+        }
+        assert_eq!(sa_score("C1CC"), 10.0);
+    }
+
+    #[test]
+    fn test_sa_score_complexity() {
+        let chain = sa_score("CCCCCC");
+        // Morphine: fused pentacycle with five stereocentres
+        let morphine = sa_score("CN1CC[C@]23c4c5ccc(O)c4O[C@H]2[C@@H](O)C=C[C@H]3[C@H]1C5");
+        assert!(chain < morphine, "chain {} vs morphine {}", chain, morphine);
+    }
+}
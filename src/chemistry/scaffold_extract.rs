@@ -0,0 +1,85 @@
+//! Bemis-Murcko scaffolds: ring systems plus the linkers between them,
+//! with all terminal side chains stripped
+
+use super::graph::{self, BondOrder};
+
+/// Canonical Bemis-Murcko scaffold SMILES.
+/// Empty for acyclic molecules or unparseable SMILES.
+pub fn murcko_scaffold(smiles: &str) -> String {
+    let Ok(mut g) = graph::parse_smiles(smiles) else {
+        return String::new();
+    };
+    let in_ring = g.ring_atoms();
+    if !in_ring.iter().any(|&r| r) {
+        return String::new();
+    }
+
+    let n = g.atoms.len();
+    let mut keep = vec![true; n];
+    let mut degree: Vec<usize> = (0..n).map(|i| g.degree(i)).collect();
+
+    // Peel side chains from their tips inwards; exocyclic double bonds on ring atoms stay
+    let mut stack: Vec<usize> = (0..n).filter(|&i| !in_ring[i] && degree[i] <= 1).collect();
+    while let Some(i) = stack.pop() {
+        if !keep[i] {
+            continue;
+        }
+        let exocyclic_double = g.neighbors(i)
+            .any(|(nb, order)| order == BondOrder::Double && in_ring[nb]);
+        if exocyclic_double {
+            continue;
+        }
+        keep[i] = false;
+        for (nb, _) in g.neighbors(i) {
+            if keep[nb] {
+                degree[nb] -= 1;
+                if !in_ring[nb] && degree[nb] <= 1 {
+                    stack.push(nb);
+                }
+            }
+        }
+    }
+
+    // Stereo marks and bracket H counts refer to the removed substituents
+    for atom in &mut g.atoms {
+        atom.chiral = false;
+        if atom.bracket && atom.charge == 0 && !atom.aromatic {
+            atom.bracket = false;
+            atom.explicit_h = 0;
+        }
+    }
+    for bond in &mut g.bonds {
+        bond.direction = None;
+    }
+
+    graph::write_canonical(&g.subgraph(&keep))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ibuprofen_analog_share_scaffold() {
+        let ibuprofen = murcko_scaffold("CC(C)Cc1ccc(cc1)C(C)C(=O)O");
+        let analog = murcko_scaffold("CC(C)Cc1ccc(cc1C)C(C)C(=O)O");
+        assert_eq!(ibuprofen, graph::canonical_smiles("c1ccccc1").unwrap());
+        assert_eq!(ibuprofen, analog);
+    }
+
+    #[test]
+    fn test_linkers_and_exocyclic_bonds() {
+        // Linker between rings is kept, terminal chain dropped
+        assert_eq!(
+            murcko_scaffold("CCOc1ccc(cc1)CNc1ccccc1"),
+            graph::canonical_smiles("c1ccc(cc1)CNc1ccccc1").unwrap()
+        );
+        // Ring carbonyl is part of the scaffold
+        assert_eq!(
+            murcko_scaffold("CC1CCC(=O)CC1"),
+            graph::canonical_smiles("C1CCC(=O)CC1").unwrap()
+        );
+        assert_eq!(murcko_scaffold("CCCCO"), "");
+        assert_eq!(murcko_scaffold("C1CC"), "");
+    }
+}
@@ -3,6 +3,8 @@
 
 use rand::Rng;
 use rand::rngs::StdRng;
+use serde::{Serialize, Deserialize};
+use super::{graph, smiles};
 
 /// Known drug scaffolds with their properties
 #[derive(Clone, Debug)]
@@ -13,6 +15,14 @@ pub struct DrugScaffold {
     pub mw_approx: f32,
 }
 
+/// User-defined scaffold added at runtime (owned counterpart of `DrugScaffold`)
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct OwnedScaffold {
+    pub name: String,
+    pub smiles: String,
+    pub category: String,
+}
+
 /// Database of real pharmaceutical scaffolds
 pub const DRUG_SCAFFOLDS: &[DrugScaffold] = &[
     // Analgesics / Anti-inflammatory
@@ -252,46 +262,110 @@ pub const SUBSTITUENTS: &[(&str, &str)] = &[
     ("pyridyl", "c1ccncc1"),
 ];
 
-/// Generate a SMILES based on a real drug scaffold with modifications
-pub fn generate_from_scaffold(rng: &mut StdRng) -> String {
+/// Generate a SMILES based on a real drug scaffold with modifications.
+/// Returns the SMILES together with the scaffold name.
+pub fn generate_from_scaffold(rng: &mut StdRng) -> (String, &'static str) {
     let scaffold = &DRUG_SCAFFOLDS[rng.gen_range(0..DRUG_SCAFFOLDS.len())];
-    let mut smiles = scaffold.smiles.to_string();
+    decorate_scaffold(scaffold, rng)
+}
+
+/// Same as `generate_from_scaffold`, restricted to one therapeutic category.
+/// Falls back to all scaffolds if the category is unknown.
+pub fn generate_from_scaffold_in_category(rng: &mut StdRng, category: &str) -> (String, &'static str) {
+    let scaffolds = get_scaffolds_by_category(category);
+    if scaffolds.is_empty() {
+        return generate_from_scaffold(rng);
+    }
+    let scaffold = scaffolds[rng.gen_range(0..scaffolds.len())];
+    decorate_scaffold(scaffold, rng)
+}
+
+fn decorate_scaffold(scaffold: &'static DrugScaffold, rng: &mut StdRng) -> (String, &'static str) {
+    (decorate_smiles(scaffold.smiles, rng), scaffold.name)
+}
+
+/// Randomly decorate any scaffold SMILES with up to two substituents
+pub fn decorate_smiles(scaffold: &str, rng: &mut StdRng) -> String {
+    let mut smiles = scaffold.to_string();
     
     // Optionally add substituents
     let num_subs = rng.gen_range(0..=2);
     for _ in 0..num_subs {
         let (_, sub_smiles) = SUBSTITUENTS[rng.gen_range(0..SUBSTITUENTS.len())];
-        // Add substituent to end (simplified attachment)
         if rng.gen_bool(0.5) {
-            smiles.push_str(sub_smiles);
+            // Keep the undecorated structure if the attachment doesn't work out
+            if let Some(decorated) = attach_substituent(&smiles, sub_smiles, rng) {
+                smiles = decorated;
+            }
         }
     }
     
     smiles
 }
 
+/// Attach a substituent by its first atom to a random atom that still carries
+/// implicit hydrogens. The product is rebuilt from the joined graph, so ring-closure
+/// labels of the two parts never collide. None if there is no open position.
+pub fn attach_substituent(scaffold: &str, substituent: &str, rng: &mut StdRng) -> Option<String> {
+    let mut combined = graph::parse_smiles(scaffold).ok()?;
+    let branch = graph::parse_smiles(substituent).ok()?;
+    let target = pick_position(&combined.open_positions(), rng)?;
+    let offset = combined.append(&branch);
+    combined.join(target, offset).ok()?;
+
+    let decorated = graph::write_canonical(&combined);
+    graph::parse_smiles(&decorated).is_ok().then_some(decorated)
+}
+
 /// Generate a novel scaffold by combining fragments
 pub fn generate_hybrid_scaffold(rng: &mut StdRng) -> String {
     // Pick two scaffolds and combine concepts
     let scaffold1 = &DRUG_SCAFFOLDS[rng.gen_range(0..DRUG_SCAFFOLDS.len())];
     let scaffold2 = &DRUG_SCAFFOLDS[rng.gen_range(0..DRUG_SCAFFOLDS.len())];
     
-    // Use one as base, add substituent from another category
-    let mut smiles = scaffold1.smiles.to_string();
-    
     // Add a linker and fragment
     let linkers = ["", "C", "CC", "O", "N", "C(=O)N"];
     let linker = linkers[rng.gen_range(0..linkers.len())];
     
-    if rng.gen_bool(0.3) && scaffold2.mw_approx < 200.0 {
-        smiles.push_str(linker);
-        // Add small scaffold fragment
-        if scaffold2.smiles.len() < 20 {
-            smiles.push_str(scaffold2.smiles);
+    // Use one as base, join a small scaffold fragment from the other
+    if rng.gen_bool(0.3) && scaffold2.mw_approx < 200.0 && scaffold2.smiles.len() < 20 {
+        if let Some(hybrid) = join_fragments(scaffold1.smiles, linker, scaffold2.smiles, rng) {
+            return hybrid;
         }
     }
     
-    smiles
+    scaffold1.smiles.to_string()
+}
+
+/// Bond a free valence of `first` to a free valence of `second` through
+/// `linker` (empty for a direct bond). The combined graph is written out
+/// canonically, so ring-closure numbers can't collide.
+pub fn join_fragments(first: &str, linker: &str, second: &str, rng: &mut StdRng) -> Option<String> {
+    let mut combined = graph::parse_smiles(first).ok()?;
+    let fragment = graph::parse_smiles(second).ok()?;
+
+    let mut tail = pick_position(&combined.open_positions(), rng)?;
+    if !linker.is_empty() {
+        let linker_graph = graph::parse_smiles(linker).ok()?;
+        let offset = combined.append(&linker_graph);
+        combined.join(tail, offset).ok()?;
+        tail = offset + linker_graph.atoms.len() - 1;
+    }
+
+    let head = pick_position(&fragment.open_positions(), rng)?;
+    let offset = combined.append(&fragment);
+    combined.join(tail, offset + head).ok()?;
+
+    let smiles = graph::write_canonical(&combined);
+    smiles::validate_smiles(&smiles).then_some(smiles)
+}
+
+fn pick_position(positions: &[usize], rng: &mut StdRng) -> Option<usize> {
+    if positions.is_empty() {
+        None
+    } else {
+        Some(positions[rng.gen_range(0..positions.len())])
+    }
 }
 
 /// Get scaffold information by name
@@ -329,8 +403,83 @@ mod tests {
     fn test_generate_from_scaffold() {
         let mut rng = StdRng::seed_from_u64(42);
         for _ in 0..20 {
-            let smiles = generate_from_scaffold(&mut rng);
+            let (smiles, name) = generate_from_scaffold(&mut rng);
             assert!(!smiles.is_empty());
+            assert!(get_scaffold_by_name(name).is_some());
+        }
+    }
+
+    #[test]
+    fn test_attach_substituent() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for scaffold in DRUG_SCAFFOLDS {
+            let scaffold_atoms = graph::parse_smiles(scaffold.smiles).unwrap().atoms.len();
+            for (_, sub) in SUBSTITUENTS {
+                if let Some(decorated) = attach_substituent(scaffold.smiles, sub, &mut rng) {
+                    let g = graph::parse_smiles(&decorated)
+                        .unwrap_or_else(|e| panic!("{} + {} -> {}: {}", scaffold.name, sub, decorated, e));
+                    let sub_atoms = graph::parse_smiles(sub).unwrap().atoms.len();
+                    assert_eq!(g.atoms.len(), scaffold_atoms + sub_atoms, "{} + {}", scaffold.name, sub);
+                }
+            }
+        }
+
+        // Ring labels of scaffold and substituent don't pair up across the parts
+        let decorated = attach_substituent("c1ccccc1", "c1ccncc1", &mut rng).unwrap();
+        let g = graph::parse_smiles(&decorated).unwrap();
+        assert_eq!(g.smallest_rings().len(), 2);
+        assert_eq!(graph::write_canonical(&g), graph::canonical_smiles("c1ccc(cc1)-c1ccncc1").unwrap());
+        assert_eq!(attach_substituent("[NH4+]", "C", &mut rng), None);
+    }
+
+    #[test]
+    fn test_decorated_scaffolds_validate() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut decorated = 0;
+        for _ in 0..200 {
+            let (smiles, name) = generate_from_scaffold(&mut rng);
+            assert!(graph::parse_smiles(&smiles).is_ok(), "Invalid: {}", smiles);
+            if smiles != get_scaffold_by_name(name).unwrap().smiles {
+                decorated += 1;
+            }
+        }
+        assert!(decorated > 20);
+    }
+
+    #[test]
+    fn test_join_fragments() {
+        let mut rng = StdRng::seed_from_u64(1);
+        // Both fragments use ring label 1
+        let joined = join_fragments("c1ccccc1", "C(=O)N", "C1CCNCC1", &mut rng).unwrap();
+        let g = graph::parse_smiles(&joined).unwrap();
+        assert_eq!(g.atoms.len(), 15);
+        assert_eq!(g.ring_count(), 2);
+
+        // No free valence on the fragment
+        assert!(join_fragments("CC", "", "[NH4+]", &mut rng).is_none());
+    }
+
+    #[test]
+    fn test_hybrid_scaffolds_validate() {
+        let mut joined = 0;
+        for seed in 0..200 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let smiles = generate_hybrid_scaffold(&mut rng);
+            assert!(smiles::validate_smiles(&smiles), "seed {}: {}", seed, smiles);
+            if !DRUG_SCAFFOLDS.iter().any(|s| s.smiles == smiles) {
+                joined += 1;
+            }
+        }
+        assert!(joined > 0);
+    }
+
+    #[test]
+    fn test_generate_in_category() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..50 {
+            let (smiles, name) = generate_from_scaffold_in_category(&mut rng, "Antibiotic");
+            assert!(smiles::validate_smiles(&smiles));
+            assert_eq!(get_scaffold_by_name(name).unwrap().category, "Antibiotic");
         }
     }
 
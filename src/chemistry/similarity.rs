@@ -2,6 +2,7 @@
 //! Implements Tanimoto coefficient and clustering
 
 use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
 
 /// Molecular fingerprint (bit vector represented as set of "on" bits)
 #[derive(Clone, Debug)]
@@ -34,12 +35,17 @@ impl Fingerprint {
 pub fn generate_fingerprint(smiles: &str, size: u32) -> Fingerprint {
     let mut fp = Fingerprint::new(size);
     
-    // Hash individual atoms
-    for (i, c) in smiles.chars().enumerate() {
-        if c.is_alphabetic() {
-            let hash = simple_hash(&format!("atom_{}", c)) % size;
-            fp.set_bit(hash);
-        }
+    // Hash individual atoms; repeats of an element set one more bit each, so molecules
+    // differing only in chain length don't share every bit
+    let mut atom_counts: HashMap<char, usize> = HashMap::new();
+    for c in smiles.chars().filter(|c| c.is_alphabetic()) {
+        let count = atom_counts.entry(c).or_default();
+        *count += 1;
+        let feature = match *count {
+            1 => format!("atom_{}", c),
+            n => format!("atom_{}_{}", c, n),
+        };
+        fp.set_bit(simple_hash(&feature) % size);
     }
     
     // Hash atom pairs (2-atom paths)
@@ -129,6 +135,26 @@ pub fn smiles_similarity(smiles1: &str, smiles2: &str) -> f32 {
     tanimoto_coefficient(&fp1, &fp2)
 }
 
+/// Novelty relative to a reference library: 1 − the highest Tanimoto similarity
+/// to any reference molecule. An empty reference makes everything fully novel.
+pub fn novelty_score(smiles: &str, reference: &[String]) -> f32 {
+    let fp = generate_fingerprint(smiles, 2048);
+    let max_similarity = reference.iter()
+        .map(|r| tanimoto_coefficient(&fp, &generate_fingerprint(r, 2048)))
+        .fold(0.0f32, f32::max);
+    1.0 - max_similarity
+}
+
+/// Default novelty reference: the SMILES of every `DRUG_SCAFFOLDS` entry
+pub fn default_novelty_reference() -> &'static [String] {
+    static REFERENCE: OnceLock<Vec<String>> = OnceLock::new();
+    REFERENCE.get_or_init(|| {
+        super::scaffolds::DRUG_SCAFFOLDS.iter()
+            .map(|s| s.smiles.to_string())
+            .collect()
+    })
+}
+
 /// Calculate similarity matrix for a list of SMILES
 pub fn similarity_matrix(smiles_list: &[String]) -> Vec<Vec<f32>> {
     let n = smiles_list.len();
@@ -151,6 +177,61 @@ pub fn similarity_matrix(smiles_list: &[String]) -> Vec<Vec<f32>> {
     matrix
 }
 
+/// Power-iteration steps per embedding axis
+const MDS_ITERATIONS: usize = 200;
+
+/// 2D classical MDS embedding of a similarity matrix, using 1 − similarity as distance.
+/// `seed` fixes the power-iteration start vectors so layouts are reproducible.
+pub fn mds_embed(similarity: &[Vec<f32>], seed: u64) -> Vec<[f32; 2]> {
+    use rand::{Rng, SeedableRng};
+
+    let n = similarity.len();
+    if n < 2 {
+        return vec![[0.0, 0.0]; n];
+    }
+
+    // Double-centered squared distances: B = -1/2 · J D² J
+    let d2: Vec<Vec<f32>> = similarity.iter()
+        .map(|row| row.iter().map(|&s| (1.0 - s).powi(2)).collect())
+        .collect();
+    let row_mean: Vec<f32> = d2.iter().map(|row| row.iter().sum::<f32>() / n as f32).collect();
+    let grand_mean = row_mean.iter().sum::<f32>() / n as f32;
+    let b: Vec<Vec<f32>> = (0..n)
+        .map(|i| (0..n).map(|j| -0.5 * (d2[i][j] - row_mean[i] - row_mean[j] + grand_mean)).collect())
+        .collect();
+
+    // Top two eigenvectors by power iteration with deflation
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    let mut axes: Vec<(Vec<f32>, f32)> = Vec::with_capacity(2);
+    for _ in 0..2 {
+        let mut v: Vec<f32> = (0..n).map(|_| rng.gen_range(-1.0..1.0)).collect();
+        let mut eigenvalue = 0.0;
+        for _ in 0..MDS_ITERATIONS {
+            let mut w: Vec<f32> = b.iter()
+                .map(|row| row.iter().zip(&v).map(|(x, y)| x * y).sum())
+                .collect();
+            for (u, _) in &axes {
+                let dot: f32 = w.iter().zip(u).map(|(x, y)| x * y).sum();
+                w.iter_mut().zip(u).for_each(|(x, y)| *x -= dot * y);
+            }
+            let norm = w.iter().map(|x| x * x).sum::<f32>().sqrt();
+            if norm < 1e-12 {
+                break;
+            }
+            eigenvalue = norm;
+            v = w.into_iter().map(|x| x / norm).collect();
+        }
+        axes.push((v, eigenvalue));
+    }
+
+    (0..n)
+        .map(|i| {
+            let coord = |k: usize| axes[k].0[i] * axes[k].1.max(0.0).sqrt();
+            [coord(0), coord(1)]
+        })
+        .collect()
+}
+
 /// Simple clustering result
 #[derive(Clone, Debug)]
 pub struct ClusterResult {
@@ -226,6 +307,52 @@ pub fn cluster_molecules(smiles_list: &[String], threshold: f32) -> Vec<ClusterR
     clusters
 }
 
+/// Mean silhouette of a clustering, with 1 − Tanimoto as the distance. Near 1 when
+/// members sit much closer to their own cluster than to the nearest other one, near 0
+/// for overlapping clusters, negative for misassigned members. Members of singleton
+/// clusters count as 0; fewer than two clusters score 0.
+pub fn silhouette_score(smiles_list: &[String], clusters: &[ClusterResult]) -> f32 {
+    use rayon::prelude::*;
+
+    if clusters.len() < 2 {
+        return 0.0;
+    }
+    let fingerprints: Vec<Fingerprint> = smiles_list
+        .par_iter()
+        .map(|s| generate_fingerprint(s, 2048))
+        .collect();
+    let distance = |i: usize, j: usize| 1.0 - tanimoto_coefficient(&fingerprints[i], &fingerprints[j]);
+    let mean_distance = |i: usize, members: &[usize]| {
+        let others: Vec<usize> = members.iter().copied().filter(|&j| j != i).collect();
+        others.iter().map(|&j| distance(i, j)).sum::<f32>() / others.len().max(1) as f32
+    };
+
+    let points: Vec<(usize, usize)> = clusters.iter()
+        .enumerate()
+        .flat_map(|(k, cluster)| cluster.members.iter().map(move |&i| (k, i)))
+        .filter(|&(_, i)| i < fingerprints.len())
+        .collect();
+    if points.is_empty() {
+        return 0.0;
+    }
+    let total: f32 = points.par_iter()
+        .map(|&(k, i)| {
+            if clusters[k].members.len() < 2 {
+                return 0.0;
+            }
+            let a = mean_distance(i, &clusters[k].members);
+            let b = clusters.iter()
+                .enumerate()
+                .filter(|&(other, _)| other != k)
+                .map(|(_, cluster)| mean_distance(i, &cluster.members))
+                .fold(f32::INFINITY, f32::min);
+            let scale = a.max(b);
+            if scale > 0.0 { (b - a) / scale } else { 0.0 }
+        })
+        .sum();
+    total / points.len() as f32
+}
+
 /// Find the N most similar molecules to a query
 pub fn find_similar(query_smiles: &str, database: &[String], top_n: usize) -> Vec<(usize, f32)> {
     let query_fp = generate_fingerprint(query_smiles, 2048);
@@ -304,6 +431,56 @@ mod tests {
         assert!(sim < 0.5);
     }
 
+    #[test]
+    fn test_novelty_score() {
+        let reference = default_novelty_reference();
+        assert_eq!(reference.len(), super::super::scaffolds::DRUG_SCAFFOLDS.len());
+
+        let scaffold = &reference[0];
+        assert!(novelty_score(scaffold, reference) < 1e-6);
+
+        let chain = "CCCCCCCCCCCC";
+        assert!(novelty_score(chain, reference) > 0.2);
+        assert_eq!(novelty_score(chain, &[]), 1.0);
+    }
+
+    #[test]
+    fn test_mds_identical_molecules() {
+        let smiles: Vec<String> = ["CCO", "CCO", "c1ccccc1", "CCCCCCCC", "c1ccc(O)cc1", "CC(=O)O"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let coords = mds_embed(&similarity_matrix(&smiles), 42);
+        assert_eq!(coords.len(), smiles.len());
+
+        let dist = |a: [f32; 2], b: [f32; 2]| ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt();
+        assert!(dist(coords[0], coords[1]) < 1e-4, "{:?} vs {:?}", coords[0], coords[1]);
+        assert!(dist(coords[0], coords[2]) > 0.1);
+
+        // Reproducible for a fixed seed
+        assert_eq!(coords, mds_embed(&similarity_matrix(&smiles), 42));
+    }
+
+    #[test]
+    fn test_silhouette_score() {
+        let smiles: Vec<String> = ["CCCCO", "CCCCCO", "CCCCCCO", "c1ccccc1", "c1ccc(C)cc1", "c1ccc(CC)cc1"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let cluster = |cluster_id, members: Vec<usize>| ClusterResult { cluster_id, centroid_idx: members[0], members };
+
+        // Alcohols apart from aromatics
+        let separated = silhouette_score(&smiles, &[cluster(0, vec![0, 1, 2]), cluster(1, vec![3, 4, 5])]);
+        // Each cluster mixes both families
+        let mixed = silhouette_score(&smiles, &[cluster(0, vec![0, 3, 1]), cluster(1, vec![4, 2, 5])]);
+        assert!(separated > 0.3, "{}", separated);
+        assert!(separated > mixed, "{} vs {}", separated, mixed);
+        assert!(mixed < 0.1, "{}", mixed);
+
+        // One cluster has no silhouette
+        assert_eq!(silhouette_score(&smiles, &[cluster(0, (0..6).collect())]), 0.0);
+    }
+
     #[test]
     fn test_clustering() {
         let smiles = vec![
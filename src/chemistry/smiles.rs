@@ -4,6 +4,10 @@
 use rand::{Rng, SeedableRng};
 use rand::rngs::StdRng;
 use std::collections::HashMap;
+use super::graph;
+
+/// Chance that a generated molecule gets its chiral centers marked
+const STEREO_PROBABILITY: f64 = 0.3;
 
 /// Atom with valence tracking
 #[derive(Clone, Debug)]
@@ -13,6 +17,7 @@ struct Atom {
     used_valence: u8,
     aromatic: bool,
     in_ring: bool,
+    chirality: Option<bool>,  // Some(true) = @@, Some(false) = @
 }
 
 impl Atom {
@@ -23,6 +28,7 @@ impl Atom {
             used_valence: 0,
             aromatic: false,
             in_ring: false,
+            chirality: None,
         }
     }
 
@@ -75,6 +81,36 @@ impl MoleculeBuilder {
         true
     }
 
+    /// Give every tetrahedral carbon with distinct substituents a random @/@@ mark
+    fn mark_chiral_centers(&mut self, rng: &mut StdRng) {
+        let mut neighbors: Vec<Vec<(usize, u8)>> = vec![vec![]; self.atoms.len()];
+        for &(from, to, order) in &self.bonds {
+            neighbors[from].push((to, order));
+            neighbors[to].push((from, order));
+        }
+
+        for i in 0..self.atoms.len() {
+            let atom = &self.atoms[i];
+            let hydrogens = atom.available_valence() as usize;
+            if atom.symbol != "C" || atom.aromatic || hydrogens > 1 {
+                continue;
+            }
+            if neighbors[i].len() + hydrogens != 4 || neighbors[i].iter().any(|&(_, o)| o != 1) {
+                continue;
+            }
+
+            // Substituents approximated by (element, degree)
+            let mut signatures: Vec<(&str, usize)> = neighbors[i].iter()
+                .map(|&(n, _)| (self.atoms[n].symbol, neighbors[n].len()))
+                .collect();
+            signatures.sort();
+            signatures.dedup();
+            if signatures.len() == neighbors[i].len() {
+                self.atoms[i].chirality = Some(rng.gen_bool(0.5));
+            }
+        }
+    }
+
     fn to_smiles(&self) -> String {
         if self.atoms.is_empty() {
             return "C".to_string(); // Methane as fallback
@@ -122,10 +158,19 @@ impl MoleculeBuilder {
         visited[current] = true;
         
         let atom = &self.atoms[current];
-        if atom.aromatic {
-            smiles.push_str(&atom.symbol.to_lowercase());
+        let symbol = if atom.aromatic {
+            atom.symbol.to_lowercase()
         } else {
-            smiles.push_str(atom.symbol);
+            atom.symbol.to_string()
+        };
+        match atom.chirality {
+            Some(clockwise) => {
+                // Chiral carbons carry at most one hydrogen
+                let mark = if clockwise { "@@" } else { "@" };
+                let h = if atom.available_valence() > 0 { "H" } else { "" };
+                smiles.push_str(&format!("[{}{}{}]", symbol, mark, h));
+            }
+            None => smiles.push_str(&symbol),
         }
 
         // Add ring closure labels
@@ -219,6 +264,10 @@ fn generate_aliphatic_chain(rng: &mut StdRng) -> String {
     
     // Add functional groups
     add_functional_groups(&mut mol, rng);
+
+    if rng.gen_bool(STEREO_PROBABILITY) {
+        mol.mark_chiral_centers(rng);
+    }
     
     mol.to_smiles()
 }
@@ -364,6 +413,10 @@ fn generate_branched_molecule(rng: &mut StdRng) -> String {
             prev = curr;
         }
     }
+
+    if rng.gen_bool(STEREO_PROBABILITY) {
+        mol.mark_chiral_centers(rng);
+    }
     
     mol.to_smiles()
 }
@@ -433,26 +486,31 @@ fn add_functional_groups(mol: &mut MoleculeBuilder, rng: &mut StdRng) {
 
 /// Validate a SMILES string (basic validation)
 pub fn validate_smiles(smiles: &str) -> bool {
+    validate_smiles_detailed(smiles).is_ok()
+}
+
+/// Validate a SMILES string, with the reason it was rejected
+pub fn validate_smiles_detailed(smiles: &str) -> Result<(), String> {
     if smiles.is_empty() {
-        return false;
+        return Err("Empty SMILES".into());
     }
     
     // Check balanced parentheses
     let mut paren_count = 0;
-    for c in smiles.chars() {
+    for (i, c) in smiles.chars().enumerate() {
         match c {
             '(' => paren_count += 1,
             ')' => {
                 paren_count -= 1;
                 if paren_count < 0 {
-                    return false;
+                    return Err(format!("Unmatched ')' at position {}", i));
                 }
             }
             _ => {}
         }
     }
     if paren_count != 0 {
-        return false;
+        return Err(format!("{} unclosed '('", paren_count));
     }
     
     // Check ring closures are paired
@@ -462,9 +520,9 @@ pub fn validate_smiles(smiles: &str) -> bool {
             ring_counts[digit as usize] += 1;
         }
     }
-    for count in &ring_counts[1..] {
+    for (digit, count) in ring_counts.iter().enumerate().skip(1) {
         if count % 2 != 0 {
-            return false;
+            return Err(format!("Ring closure {} is not closed", digit));
         }
     }
     
@@ -477,11 +535,17 @@ pub fn validate_smiles(smiles: &str) -> bool {
     
     for pattern in &invalid_patterns {
         if smiles.contains(pattern) {
-            return false;
+            return Err(format!("Invalid pattern '{}'", pattern));
         }
     }
+
+    // Cis/trans marks must be paired consistently around double bonds
+    if smiles.contains(['/', '\\']) {
+        return graph::parse_smiles(smiles)
+            .and_then(|g| g.check_cis_trans());
+    }
     
-    true
+    Ok(())
 }
 
 /// Generate and validate a SMILES, with fallback
@@ -529,6 +593,42 @@ mod tests {
         assert!(!validate_smiles("C1CCC")); // Unclosed ring
     }
 
+    #[test]
+    fn test_validate_smiles_detailed() {
+        assert_eq!(validate_smiles_detailed("CC(C)C"), Ok(()));
+        assert_eq!(validate_smiles_detailed(""), Err("Empty SMILES".to_string()));
+        assert_eq!(validate_smiles_detailed("CC)C"), Err("Unmatched ')' at position 2".to_string()));
+        assert_eq!(validate_smiles_detailed("C1CC2CC1"), Err("Ring closure 2 is not closed".to_string()));
+        assert_eq!(validate_smiles_detailed("CC==C"), Err("Invalid pattern '=='".to_string()));
+        assert!(validate_smiles_detailed("F/C=CF").is_err());
+    }
+
+    #[test]
+    fn test_validate_cis_trans() {
+        assert!(validate_smiles("F/C=C/F"));       // trans
+        assert!(validate_smiles("F/C=C\\F"));      // cis
+        assert!(validate_smiles("C/C=C/C=C/C"));
+        assert!(validate_smiles("F/C(/Cl)=C/F"));  // substituents on opposite sides
+        
+        assert!(!validate_smiles("F/C=CF"));        // only one end specified
+        assert!(!validate_smiles("F/C(\\Cl)=C/F")); // both marks on the same side
+    }
+
+    #[test]
+    fn test_chiral_generation() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut chiral = 0;
+        for _ in 0..300 {
+            let smiles = generate_valid_smiles(&mut rng);
+            if smiles.contains('@') {
+                chiral += 1;
+                assert!(validate_smiles(&smiles), "Invalid SMILES: {}", smiles);
+                assert!(graph::parse_smiles(&smiles).is_ok(), "Unparseable SMILES: {}", smiles);
+            }
+        }
+        assert!(chiral > 0);
+    }
+
     #[test]
     fn test_aromatic_generation() {
         let mut rng = StdRng::seed_from_u64(42);
@@ -0,0 +1,126 @@
+//! Headless command-line mode for batch runs without the GUI
+
+use crate::app::io;
+use crate::app::state::Candidate;
+use crate::generation::generator::{generate_candidates_parallel, GenerationOptions};
+use crate::optimization::pareto::{pareto_front_ids_fast, ALL_OBJECTIVES};
+
+pub const USAGE: &str = "\
+Usage:
+  drug-candidate-studio                      Launch the GUI
+  drug-candidate-studio generate [OPTIONS]   Generate candidates and export CSV
+
+Options for generate:
+  --n <COUNT>          Number of candidates (default 1000)
+  --seed <SEED>        Random seed (default 42)
+  --out <PATH>         Output CSV path (default candidates.csv)
+  --category <NAME>    Restrict scaffolds to one category";
+
+/// Parsed headless command
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Generate {
+        n: usize,
+        seed: u64,
+        out: String,
+        category: Option<String>,
+    },
+}
+
+/// Parse arguments (without the program name). `Ok(None)` means launch the GUI.
+pub fn parse_args(args: &[String]) -> Result<Option<Command>, String> {
+    let Some((sub, rest)) = args.split_first() else {
+        return Ok(None);
+    };
+
+    match sub.as_str() {
+        "generate" => {
+            let mut n = 1000;
+            let mut seed = 42;
+            let mut out = "candidates.csv".to_string();
+            let mut category = None;
+
+            let mut iter = rest.iter();
+            while let Some(flag) = iter.next() {
+                let value = iter.next()
+                    .ok_or_else(|| format!("Missing value for {}", flag))?;
+                match flag.as_str() {
+                    "--n" => {
+                        n = value.parse()
+                            .map_err(|_| format!("Invalid candidate count: {}", value))?;
+                    }
+                    "--seed" => {
+                        seed = value.parse()
+                            .map_err(|_| format!("Invalid seed: {}", value))?;
+                    }
+                    "--out" => out = value.clone(),
+                    "--category" => category = Some(value.clone()),
+                    _ => return Err(format!("Unknown option: {}", flag)),
+                }
+            }
+
+            if n == 0 {
+                return Err("Candidate count must be at least 1".to_string());
+            }
+
+            Ok(Some(Command::Generate { n, seed, out, category }))
+        }
+        other => Err(format!("Unknown command: {}", other)),
+    }
+}
+
+/// Execute a headless command, returning a summary line on success
+pub fn run(cmd: Command) -> Result<String, String> {
+    match cmd {
+        Command::Generate { n, seed, out, category } => {
+            let options = GenerationOptions { category, ..Default::default() };
+            let mut candidates = generate_candidates_parallel(0, 0..n, seed, &options);
+
+            let front = pareto_front_ids_fast(&candidates, ALL_OBJECTIVES);
+            for c in &mut candidates {
+                c.pareto = front.contains(&c.id);
+            }
+
+            io::export_csv(&candidates, &out, default_score, |_| false, false)?;
+
+            Ok(format!(
+                "Generated {} candidates ({} on Pareto front) -> {}",
+                candidates.len(),
+                front.len(),
+                out
+            ))
+        }
+    }
+}
+
+/// Unit-weight score, matching the GUI defaults
+fn default_score(c: &Candidate) -> f32 {
+    c.efficacy - c.toxicity - c.synthesis_cost - c.manufacturing_cost
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse_args(&[]), Ok(None));
+
+        let cmd = parse_args(&args(&["generate", "--n", "50", "--seed", "7", "--out", "x.csv"])).unwrap();
+        assert_eq!(cmd, Some(Command::Generate {
+            n: 50,
+            seed: 7,
+            out: "x.csv".to_string(),
+            category: None,
+        }));
+
+        assert!(parse_args(&args(&["frobnicate"])).is_err());
+        assert!(parse_args(&args(&["generate", "--n"])).is_err());
+        assert!(parse_args(&args(&["generate", "--n", "abc"])).is_err());
+        assert!(parse_args(&args(&["generate", "--bogus", "1"])).is_err());
+    }
+}
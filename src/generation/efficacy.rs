@@ -0,0 +1,47 @@
+//! Pluggable efficacy models used by the generator
+
+use crate::chemistry::descriptors::MolecularDescriptors;
+
+/// Predicts efficacy (higher is better) from molecular descriptors.
+/// The generator adds biological noise and clamps the result to 0..1.
+pub trait EfficacyModel: Send + Sync {
+    fn efficacy(&self, d: &MolecularDescriptors) -> f32;
+}
+
+/// Default model: bonuses for staying inside Lipinski / Veber ranges
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LipinskiHeuristicModel;
+
+impl EfficacyModel for LipinskiHeuristicModel {
+    fn efficacy(&self, d: &MolecularDescriptors) -> f32 {
+        let mut score = 0.5;
+
+        // Bonus for MW in optimal range (200-500)
+        if (200.0..=500.0).contains(&d.molecular_weight) {
+            score += 0.2;
+        } else if d.molecular_weight > 500.0 {
+            score -= 0.15;
+        } else if d.molecular_weight < 150.0 {
+            score -= 0.1;
+        }
+
+        // Bonus for logP in optimal range (1-4)
+        if (1.0..=4.0).contains(&d.logp) {
+            score += 0.2;
+        } else if d.logp < 0.0 || d.logp > 5.0 {
+            score -= 0.1;
+        }
+
+        // Bonus for PSA in oral bioavailability range (20-140)
+        if (20.0..=140.0).contains(&d.psa) {
+            score += 0.15;
+        }
+
+        // Bonus for H-bond donors/acceptors within limits
+        if d.hbd <= 5 && d.hba <= 10 {
+            score += 0.1;
+        }
+
+        score
+    }
+}
@@ -0,0 +1,114 @@
+//! Evolution: new candidates derived from existing ones by mutation and crossover.
+//! Children record their parents in `parent_ids`.
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rayon::prelude::*;
+
+use crate::app::state::{Candidate, GenSource};
+use crate::chemistry::{graph, scaffold_extract, scaffolds};
+use super::generator::{candidate_from_smiles, GenerationOptions};
+
+/// Share of children produced by crossover when two or more parents are available
+const CROSSOVER_RATE: f64 = 0.3;
+/// Tries per child before giving up on an unlucky parent
+const MAX_ATTEMPTS: usize = 10;
+
+/// Child of one parent with a random substituent attached
+pub fn mutate(parent: &Candidate, id: usize, rng: &mut StdRng, options: &GenerationOptions) -> Option<Candidate> {
+    let (_, substituent) = scaffolds::SUBSTITUENTS[rng.gen_range(0..scaffolds::SUBSTITUENTS.len())];
+    let smiles = scaffolds::attach_substituent(&parent.smiles, substituent, rng)?;
+    let mut child = candidate_from_smiles(id, smiles, GenSource::Mutation, rng, options);
+    child.parent_ids = vec![parent.id];
+    Some(child)
+}
+
+/// Child joining the first parent to the ring system of the second
+/// (the whole second molecule when it has no rings)
+pub fn crossover(first: &Candidate, second: &Candidate, id: usize, rng: &mut StdRng, options: &GenerationOptions) -> Option<Candidate> {
+    let scaffold = scaffold_extract::murcko_scaffold(&second.smiles);
+    let fragment = if scaffold.is_empty() { second.smiles.as_str() } else { scaffold.as_str() };
+    let smiles = scaffolds::join_fragments(&first.smiles, "", fragment, rng)?;
+    let mut child = candidate_from_smiles(id, smiles, GenSource::Crossover, rng, options);
+    child.parent_ids = vec![first.id, second.id];
+    Some(child)
+}
+
+/// Up to `n` children of `parents` with ids from `start_id`. Each child has its own
+/// RNG seeded from `seed` and its index. A child whose SMILES doesn't parse counts as a
+/// failed attempt; children that fail every attempt are dropped.
+pub fn evolve(parents: &[Candidate], n: usize, start_id: usize, seed: u64, options: &GenerationOptions) -> Vec<Candidate> {
+    if parents.is_empty() {
+        return Vec::new();
+    }
+
+    let children: Vec<Candidate> = (0..n)
+        .into_par_iter()
+        .filter_map(|i| {
+            let mut rng = StdRng::seed_from_u64(seed.wrapping_add(i as u64));
+            (0..MAX_ATTEMPTS).find_map(|_| {
+                let first = &parents[rng.gen_range(0..parents.len())];
+                let mut child = None;
+                if parents.len() > 1 && rng.gen_bool(CROSSOVER_RATE) {
+                    let second = &parents[rng.gen_range(0..parents.len())];
+                    if second.id != first.id {
+                        child = Some(crossover(first, second, 0, &mut rng, options));
+                    }
+                }
+                child.unwrap_or_else(|| mutate(first, 0, &mut rng, options))
+                    .filter(|c| graph::parse_smiles(&c.smiles).is_ok())
+            })
+        })
+        .collect();
+
+    children.into_iter()
+        .enumerate()
+        .map(|(i, child)| Candidate { id: start_id + i, ..child })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parent(id: usize, smiles: &str) -> Candidate {
+        Candidate { id, smiles: smiles.into(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_child_parents() {
+        let options = GenerationOptions::default();
+        let mut rng = StdRng::seed_from_u64(42);
+        let a = parent(3, "CC(=O)Oc1ccccc1C(=O)O");
+        let b = parent(8, "CC(C)Cc1ccc(cc1)C(C)C(=O)O");
+
+        let mutant = (0..10).find_map(|_| mutate(&a, 100, &mut rng, &options)).expect("mutation");
+        assert_eq!(mutant.parent_ids, vec![3]);
+        assert_eq!(mutant.source, GenSource::Mutation);
+        assert_ne!(mutant.smiles, a.smiles);
+
+        let child = (0..10).find_map(|_| crossover(&a, &b, 101, &mut rng, &options)).expect("crossover");
+        assert_eq!(child.parent_ids, vec![3, 8]);
+        assert_eq!(child.source, GenSource::Crossover);
+        assert_eq!(child.id, 101);
+
+        let children = evolve(&[a, b], 20, 200, 7, &options);
+        assert!(!children.is_empty());
+        assert!(children.iter().enumerate().all(|(i, c)| c.id == 200 + i));
+        assert!(children.iter().all(|c| matches!(c.parent_ids.len(), 1 | 2)));
+    }
+
+    #[test]
+    fn test_children_parse() {
+        let parents = [
+            parent(0, "c1ccc2ccccc2c1CCN"),
+            parent(1, "CC(=O)Nc1ccc(O)cc1"),
+            parent(2, "c1ccc(cc1)C1CCNCC1"),
+        ];
+        let children = evolve(&parents, 300, 0, 11, &GenerationOptions::default());
+        assert!(children.len() > 250);
+        for c in &children {
+            assert!(graph::parse_smiles(&c.smiles).is_ok(), "{}", c.smiles);
+        }
+    }
+}
@@ -1,77 +1,244 @@
 use rand::{Rng, SeedableRng};
 use rand::rngs::StdRng;
 use rayon::prelude::*;
+use std::ops::Range;
 
-use crate::app::state::Candidate;
+use crate::app::state::{Candidate, GenSource};
 use crate::chemistry;
+use crate::chemistry::scaffolds::OwnedScaffold;
+use super::efficacy::{EfficacyModel, LipinskiHeuristicModel};
+
+/// Scaffold choices forwarded from the UI to the generator
+#[derive(Clone, Debug, Default)]
+pub struct GenerationOptions {
+    pub category: Option<String>,            // restrict scaffolds to one category, None = all
+    pub custom_scaffolds: Vec<OwnedScaffold>,
+    pub thread_count: usize,                 // parallel generation threads, 0 = rayon's global pool
+    pub mix: GenerationMix,
+    pub deterministic: bool,                 // skip the random jitter so objectives depend on the SMILES only
+    pub novelty_search: bool,                // favor samples far from `existing_objectives`
+    pub existing_objectives: Vec<[f32; 4]>,  // efficacy, toxicity, synthesis, manufacturing of current candidates
+}
 
-/// Generate a batch of drug candidates with valid SMILES and computed properties
-pub fn generate_candidates(start_id: usize, n: usize, seed: u64) -> Vec<Candidate> {
-    let mut rng = StdRng::seed_from_u64(seed);
+/// Relative share of each generation strategy; only the ratios matter
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GenerationMix {
+    pub scaffold: f32,
+    pub hybrid: f32,
+    pub random: f32,
+}
 
-    (0..n).map(|i| {
-        let id = start_id + i;
-        
-        // Mix scaffold-based and random generation
-        let smiles = if rng.gen_bool(0.6) {
-            // Use pharmaceutical scaffolds 60% of the time
-            chemistry::scaffolds::generate_from_scaffold(&mut rng)
-        } else if rng.gen_bool(0.3) {
-            // Use hybrid scaffolds 12% of the time
-            chemistry::scaffolds::generate_hybrid_scaffold(&mut rng)
-        } else {
-            // Random generation 28% of the time
-            chemistry::smiles::generate_safe_smiles(&mut rng)
-        };
+impl Default for GenerationMix {
+    fn default() -> Self {
+        Self { scaffold: 0.6, hybrid: 0.12, random: 0.28 }
+    }
+}
+
+impl GenerationMix {
+    /// Shares scaled to sum to 1; an all-zero mix falls back to the default
+    pub fn normalized(&self) -> Self {
+        let [scaffold, hybrid, random] = [self.scaffold, self.hybrid, self.random].map(|w| w.max(0.0));
+        let total = scaffold + hybrid + random;
+        if total <= 0.0 {
+            return Self::default();
+        }
+        Self { scaffold: scaffold / total, hybrid: hybrid / total, random: random / total }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Strategy {
+    Scaffold,
+    Hybrid,
+    Random,
+}
 
-        let properties = calculate_properties(&smiles, &mut rng);
+/// Choose how the next structure is built; shared by the sequential and parallel paths
+fn pick_strategy(rng: &mut StdRng, mix: &GenerationMix) -> Strategy {
+    let mix = mix.normalized();
+    let roll: f32 = rng.gen();
+    if roll < mix.scaffold {
+        Strategy::Scaffold
+    } else if roll < mix.scaffold + mix.hybrid {
+        Strategy::Hybrid
+    } else {
+        Strategy::Random
+    }
+}
+
+/// Generate candidates `indices` of the batch for `seed`, with valid SMILES and computed
+/// properties. Ids are `start_id + index` and each candidate has its own RNG (as in the
+/// parallel path), so a batch generated piece by piece equals the batch generated at once.
+pub fn generate_candidates(start_id: usize, indices: Range<usize>, seed: u64, options: &GenerationOptions) -> Vec<Candidate> {
+    generate_candidates_with_model(start_id, indices, seed, options, &LipinskiHeuristicModel)
+}
+
+/// Same as `generate_candidates`, scoring efficacy with a caller-supplied model
+pub fn generate_candidates_with_model(
+    start_id: usize,
+    indices: Range<usize>,
+    seed: u64,
+    options: &GenerationOptions,
+    model: &dyn EfficacyModel,
+) -> Vec<Candidate> {
+    indices.map(|i| generate_one(start_id + i, &mut candidate_rng(seed, i), options, model)).collect()
+}
 
-        Candidate {
-            id,
-            smiles,
-            efficacy: properties.efficacy,
-            toxicity: properties.toxicity,
-            synthesis_cost: properties.synthesis_cost,
-            manufacturing_cost: properties.manufacturing_cost,
-            pareto: false,
+/// Samples tried per candidate in novelty search before the most novel one is kept
+const NOVELTY_MAX_ATTEMPTS: usize = 20;
+/// Objective-space distance at which a sample is accepted with probability 1 - 1/e
+const NOVELTY_SCALE: f32 = 0.1;
+
+/// Generate one candidate. In novelty search, samples are accepted with a probability
+/// rising with their distance to the nearest existing candidate.
+fn generate_one(id: usize, rng: &mut StdRng, options: &GenerationOptions, model: &dyn EfficacyModel) -> Candidate {
+    if !options.novelty_search || options.existing_objectives.is_empty() {
+        return sample_candidate(id, rng, options, model);
+    }
+
+    // Fall back to the most novel sample when none is accepted
+    let mut most_novel: Option<(Candidate, f32)> = None;
+    for _ in 0..NOVELTY_MAX_ATTEMPTS {
+        let candidate = sample_candidate(id, rng, options, model);
+        let distance = nearest_objective_distance(&candidate, &options.existing_objectives);
+        if rng.gen::<f32>() < 1.0 - (-distance / NOVELTY_SCALE).exp() {
+            return candidate;
         }
-    }).collect()
+        if most_novel.as_ref().is_none_or(|(_, best)| distance > *best) {
+            most_novel = Some((candidate, distance));
+        }
+    }
+    match most_novel {
+        Some((candidate, _)) => candidate,
+        None => sample_candidate(id, rng, options, model),
+    }
 }
 
-/// Generate candidates in parallel using all CPU cores
-pub fn generate_candidates_parallel(start_id: usize, n: usize, seed: u64) -> Vec<Candidate> {
-    let candidates: Vec<Candidate> = (0..n)
-        .into_par_iter()
-        .map(|i| {
-            let thread_seed = seed.wrapping_add(i as u64 * 31337);
-            let mut rng = StdRng::seed_from_u64(thread_seed);
-            
-            let id = start_id + i;
-            
-            // Mix scaffold-based and random generation
-            let smiles = if rng.gen_bool(0.6) {
-                chemistry::scaffolds::generate_from_scaffold(&mut rng)
-            } else if rng.gen_bool(0.3) {
-                chemistry::scaffolds::generate_hybrid_scaffold(&mut rng)
-            } else {
-                chemistry::smiles::generate_safe_smiles(&mut rng)
-            };
-            
-            let properties = calculate_properties(&smiles, &mut rng);
-
-            Candidate {
-                id,
-                smiles,
-                efficacy: properties.efficacy,
-                toxicity: properties.toxicity,
-                synthesis_cost: properties.synthesis_cost,
-                manufacturing_cost: properties.manufacturing_cost,
-                pareto: false,
-            }
-        })
+/// Euclidean distance in raw objective space to the closest of `existing`
+fn nearest_objective_distance(c: &Candidate, existing: &[[f32; 4]]) -> f32 {
+    let objectives = [c.efficacy, c.toxicity, c.synthesis_cost, c.manufacturing_cost];
+    existing.iter()
+        .map(|e| e.iter().zip(objectives).map(|(a, b)| (a - b).powi(2)).sum::<f32>().sqrt())
+        .fold(f32::INFINITY, f32::min)
+}
+
+/// Draw one candidate's structure and noise from `rng`
+fn sample_candidate(id: usize, rng: &mut StdRng, options: &GenerationOptions, model: &dyn EfficacyModel) -> Candidate {
+    let (smiles, source) = generate_smiles(rng, options);
+    scored_candidate(id, smiles, source, rng, options, model)
+}
+
+/// Candidate for a given structure, with objectives (and their noise) drawn from `rng`
+pub fn candidate_from_smiles(id: usize, smiles: String, source: GenSource, rng: &mut StdRng, options: &GenerationOptions) -> Candidate {
+    scored_candidate(id, smiles, source, rng, options, &LipinskiHeuristicModel)
+}
+
+fn scored_candidate(
+    id: usize,
+    smiles: String,
+    source: GenSource,
+    rng: &mut StdRng,
+    options: &GenerationOptions,
+    model: &dyn EfficacyModel,
+) -> Candidate {
+    let properties = calculate_properties(&smiles, model, rng, options.deterministic);
+
+    Candidate {
+        id,
+        smiles,
+        efficacy: properties.efficacy,
+        toxicity: properties.toxicity,
+        synthesis_cost: properties.synthesis_cost,
+        manufacturing_cost: properties.manufacturing_cost,
+        pareto: false,
+        run_id: None,
+        source,
+        efficacy_sigma: jitter_sigma(EFFICACY_JITTER, options.deterministic),
+        toxicity_sigma: jitter_sigma(TOXICITY_JITTER, options.deterministic),
+        parent_ids: Vec::new(),
+    }
+}
+
+/// Mix scaffold-based and random generation, reporting where the structure came from
+fn generate_smiles(rng: &mut StdRng, options: &GenerationOptions) -> (String, GenSource) {
+    match pick_strategy(rng, &options.mix) {
+        Strategy::Scaffold => generate_from_scaffolds(rng, options),
+        Strategy::Hybrid => (chemistry::scaffolds::generate_hybrid_scaffold(rng), GenSource::Hybrid),
+        Strategy::Random => (chemistry::smiles::generate_safe_smiles(rng), GenSource::Random),
+    }
+}
+
+/// Sample from the built-in library plus the user's custom scaffolds
+fn generate_from_scaffolds(rng: &mut StdRng, options: &GenerationOptions) -> (String, GenSource) {
+    let category = options.category.as_deref();
+    let custom: Vec<&OwnedScaffold> = options.custom_scaffolds.iter()
+        .filter(|s| category.is_none_or(|c| s.category.eq_ignore_ascii_case(c)))
         .collect();
 
-    candidates
+    if !custom.is_empty() {
+        let built_in = match category {
+            Some(c) => chemistry::scaffolds::get_scaffolds_by_category(c).len(),
+            None => chemistry::scaffolds::DRUG_SCAFFOLDS.len(),
+        };
+        let pick = rng.gen_range(0..built_in + custom.len());
+        if pick >= built_in {
+            let scaffold = custom[pick - built_in];
+            let smiles = chemistry::scaffolds::decorate_smiles(&scaffold.smiles, rng);
+            return (smiles, GenSource::CustomScaffold(scaffold.name.clone()));
+        }
+    }
+
+    let (smiles, name) = match category {
+        Some(category) => chemistry::scaffolds::generate_from_scaffold_in_category(rng, category),
+        None => chemistry::scaffolds::generate_from_scaffold(rng),
+    };
+    (smiles, GenSource::Scaffold(name))
+}
+
+/// Minimum candidates per parallel work item, so each task outweighs rayon's overhead.
+/// Every candidate still has its own RNG, so the output doesn't depend on it. Seeding
+/// costs well under 1% of generating a candidate, so one RNG per chunk would gain nothing.
+const GENERATION_CHUNK: usize = 256;
+
+/// RNG for candidate `index` of a batch, shared by the sequential and parallel paths. The
+/// seeding is unchanged since the first release, so recorded seeds keep reproducing the same batch.
+fn candidate_rng(seed: u64, index: usize) -> StdRng {
+    StdRng::seed_from_u64(seed.wrapping_add(index as u64 * 31337))
+}
+
+/// Generate candidates `indices` of the batch in parallel using all CPU cores; same
+/// candidates as `generate_candidates`
+pub fn generate_candidates_parallel(start_id: usize, indices: Range<usize>, seed: u64, options: &GenerationOptions) -> Vec<Candidate> {
+    generate_candidates_parallel_with_model(start_id, indices, seed, options, &LipinskiHeuristicModel)
+}
+
+/// Pool for `thread_count` generation threads; None means rayon's global pool (all cores)
+pub fn generation_pool(thread_count: usize) -> Result<Option<rayon::ThreadPool>, String> {
+    if thread_count == 0 {
+        return Ok(None);
+    }
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(thread_count)
+        .build()
+        .map(Some)
+        .map_err(|e| format!("Could not start {} generation threads: {}", thread_count, e))
+}
+
+/// Parallel generation scoring efficacy with a caller-supplied model. Runs on the current
+/// rayon pool; callers honouring `thread_count` install a `generation_pool` first.
+pub fn generate_candidates_parallel_with_model(
+    start_id: usize,
+    indices: Range<usize>,
+    seed: u64,
+    options: &GenerationOptions,
+    model: &dyn EfficacyModel,
+) -> Vec<Candidate> {
+    // Indices are handed out in chunks, each candidate seeded from its own index
+    indices
+        .into_par_iter()
+        .with_min_len(GENERATION_CHUNK)
+        .map(|i| generate_one(start_id + i, &mut candidate_rng(seed, i), options, model))
+        .collect()
 }
 
 #[derive(Clone)]
@@ -82,16 +249,38 @@ struct MolecularProperties {
     manufacturing_cost: f32,
 }
 
-fn calculate_properties(smiles: &str, rng: &mut StdRng) -> MolecularProperties {
+/// Uniform "biological variability" added to efficacy and toxicity
+const EFFICACY_JITTER: std::ops::Range<f32> = -0.1..0.1;
+const TOXICITY_JITTER: std::ops::Range<f32> = -0.05..0.15;
+
+/// A random draw from `range`, or its midpoint in deterministic mode
+fn jitter(rng: &mut StdRng, range: std::ops::Range<f32>, deterministic: bool) -> f32 {
+    if deterministic {
+        (range.start + range.end) / 2.0
+    } else {
+        rng.gen_range(range)
+    }
+}
+
+/// Standard deviation of the jitter: width / √12 for a uniform draw, 0 when deterministic
+fn jitter_sigma(range: std::ops::Range<f32>, deterministic: bool) -> f32 {
+    if deterministic {
+        0.0
+    } else {
+        (range.end - range.start) / 12f32.sqrt()
+    }
+}
+
+fn calculate_properties(smiles: &str, model: &dyn EfficacyModel, rng: &mut StdRng, deterministic: bool) -> MolecularProperties {
     // Use real chemical properties
-    let mw = chemistry::descriptors::molecular_weight_from_smiles(smiles);
-    let logp = chemistry::descriptors::logp_from_smiles(smiles);
-    let psa = chemistry::descriptors::polar_surface_area_from_smiles(smiles);
-    let (hbd, hba) = chemistry::descriptors::hbd_hba_count(smiles);
-
-    // Calculate objectives from real properties
-    let efficacy = calculate_efficacy_from_properties(mw, logp, psa, hbd, hba, rng);
-    let toxicity = calculate_toxicity_from_properties(mw, logp, psa, hbd, hba, rng);
+    let descriptors = chemistry::descriptors::compute_descriptors(smiles);
+    let (mw, logp, psa) = (descriptors.molecular_weight, descriptors.logp, descriptors.psa);
+    let (hbd, hba) = (descriptors.hbd, descriptors.hba);
+
+    // Calculate objectives from real properties, with some biological variability
+    let efficacy = model.efficacy(&descriptors) + jitter(rng, EFFICACY_JITTER, deterministic);
+    let toxicity = calculate_toxicity_from_properties(mw, logp, psa, hbd, hba)
+        + jitter(rng, TOXICITY_JITTER, deterministic);
     let synthesis_cost = calculate_synthesis_cost_from_properties(smiles, mw);
     let manufacturing_cost = calculate_manufacturing_cost_from_properties(mw, logp);
 
@@ -103,56 +292,12 @@ fn calculate_properties(smiles: &str, rng: &mut StdRng) -> MolecularProperties {
     }
 }
 
-fn calculate_efficacy_from_properties(
-    mw: f32, 
-    logp: f32, 
-    psa: f32, 
-    hbd: usize, 
-    hba: usize,
-    rng: &mut StdRng
-) -> f32 {
-    // Efficacy based on Lipinski's Rule of Five
-    let mut score = 0.5;
-
-    // Bonus for MW in optimal range (200-500)
-    if mw >= 200.0 && mw <= 500.0 {
-        score += 0.2;
-    } else if mw > 500.0 {
-        score -= 0.15;
-    } else if mw < 150.0 {
-        score -= 0.1;
-    }
-
-    // Bonus for logP in optimal range (1-4)
-    if logp >= 1.0 && logp <= 4.0 {
-        score += 0.2;
-    } else if logp < 0.0 || logp > 5.0 {
-        score -= 0.1;
-    }
-
-    // Bonus for PSA in oral bioavailability range (20-140)
-    if psa >= 20.0 && psa <= 140.0 {
-        score += 0.15;
-    }
-
-    // Bonus for H-bond donors/acceptors within limits
-    if hbd <= 5 && hba <= 10 {
-        score += 0.1;
-    }
-
-    // Add some random variation (biological variability)
-    score += rng.gen_range(-0.1..0.1);
-
-    score
-}
-
 fn calculate_toxicity_from_properties(
     mw: f32, 
     logp: f32, 
     psa: f32, 
     hbd: usize, 
     hba: usize,
-    rng: &mut StdRng
 ) -> f32 {
     let mut toxicity = 0.1;
 
@@ -180,9 +325,6 @@ fn calculate_toxicity_from_properties(
         toxicity += 0.15;
     }
 
-    // Random biological variation
-    toxicity += rng.gen_range(-0.05..0.15);
-
     toxicity
 }
 
@@ -243,7 +385,7 @@ mod tests {
 
     #[test]
     fn test_generate_candidates() {
-        let candidates = generate_candidates(0, 10, 42);
+        let candidates = generate_candidates(0, 0..10, 42, &GenerationOptions::default());
         assert_eq!(candidates.len(), 10);
         
         for c in &candidates {
@@ -257,7 +399,7 @@ mod tests {
 
     #[test]
     fn test_parallel_generation() {
-        let candidates = generate_candidates_parallel(0, 100, 42);
+        let candidates = generate_candidates_parallel(0, 0..100, 42, &GenerationOptions::default());
         assert_eq!(candidates.len(), 100);
         
         // Check all IDs are unique
@@ -267,9 +409,151 @@ mod tests {
         assert_eq!(ids.len(), 100);
     }
 
+    #[test]
+    fn test_parallel_generation_single_thread_pool() {
+        let options = GenerationOptions { thread_count: 1, ..Default::default() };
+        let pool = generation_pool(options.thread_count).unwrap().unwrap();
+        assert_eq!(pool.current_num_threads(), 1);
+        let candidates = pool.install(|| generate_candidates_parallel(10, 0..50, 42, &options));
+        assert_eq!(candidates.len(), 50);
+        assert!(candidates.iter().enumerate().all(|(i, c)| c.id == 10 + i));
+        for c in &candidates {
+            assert!(chemistry::smiles::validate_smiles(&c.smiles), "Invalid: {}", c.smiles);
+        }
+
+        // Per-candidate seeding makes the output independent of the pool size
+        let global = generate_candidates_parallel(10, 0..50, 42, &GenerationOptions::default());
+        let smiles = |cs: &[Candidate]| cs.iter().map(|c| c.smiles.clone()).collect::<Vec<_>>();
+        assert_eq!(smiles(&candidates), smiles(&global));
+        assert!(generation_pool(0).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_batch_matches_single_candidate_batches() {
+        // Candidate i is drawn from its own RNG, so it matches a one-candidate batch
+        // seeded the same way, whatever chunk it lands in
+        let n = GENERATION_CHUNK * 2 + 17;
+        let options = GenerationOptions::default();
+        let batch = generate_candidates_parallel(5, 0..n, 42, &options);
+        assert_eq!(batch.len(), n);
+        assert!(batch.iter().enumerate().all(|(i, c)| c.id == 5 + i));
+        for i in [0, 1, GENERATION_CHUNK - 1, GENERATION_CHUNK, n - 1] {
+            let single = &generate_candidates_parallel(5 + i, 0..1, 42 + i as u64 * 31337, &options)[0];
+            assert_eq!((&batch[i].smiles, batch[i].efficacy, batch[i].toxicity), (&single.smiles, single.efficacy, single.toxicity));
+        }
+
+        // Golden output for seed 42: recorded seeds must keep reproducing their batch
+        let golden = [
+            "c1cn[nH]n1",
+            "CN1C(=O)CN=C(c2ccccc2)c3cc(Cl)ccc13",
+            "c1ccc2ncccc2c1",
+            "COc1ccc2cc(ccc2c1)C(C)C(=O)O",
+            "C(C1CCCNC1)c1ccccc1",
+            "C(CNC)CNSC",
+        ];
+        let smiles: Vec<&str> = batch.iter().take(golden.len()).map(|c| c.smiles.as_str()).collect();
+        assert_eq!(smiles, golden);
+        assert_eq!((batch[0].efficacy, batch[0].toxicity), (0.57348484, 0.13299136));
+
+        // Generating in pieces gives the same batch
+        let pieces: Vec<Candidate> = [0..100, 100..101, 101..n].into_iter()
+            .flat_map(|indices| generate_candidates(5, indices, 42, &options))
+            .collect();
+        assert!(pieces.iter().map(|c| &c.smiles).eq(batch.iter().map(|c| &c.smiles)));
+        assert!(pieces.iter().zip(&batch).all(|(a, b)| a.id == b.id && a.efficacy == b.efficacy));
+    }
+
+    #[test]
+    fn test_generation_mix() {
+        let random_only = GenerationOptions {
+            mix: GenerationMix { scaffold: 0.0, hybrid: 0.0, random: 1.0 },
+            ..Default::default()
+        };
+        let sequential = generate_candidates(0, 0..60, 42, &random_only);
+        let parallel = generate_candidates_parallel(0, 0..60, 42, &random_only);
+        assert!(sequential.iter().chain(&parallel).all(|c| c.source == GenSource::Random));
+
+        let scaffold_only = GenerationOptions {
+            mix: GenerationMix { scaffold: 2.0, hybrid: 0.0, random: 0.0 },
+            ..Default::default()
+        };
+        let candidates = generate_candidates(0, 0..60, 42, &scaffold_only);
+        assert!(candidates.iter().all(|c| matches!(c.source, GenSource::Scaffold(_))));
+
+        let mix = GenerationMix { scaffold: 2.0, hybrid: 1.0, random: 1.0 }.normalized();
+        assert_eq!(mix, GenerationMix { scaffold: 0.5, hybrid: 0.25, random: 0.25 });
+        let zero = GenerationMix { scaffold: 0.0, hybrid: 0.0, random: 0.0 };
+        assert_eq!(zero.normalized(), GenerationMix::default());
+    }
+
+    #[test]
+    fn test_novelty_search() {
+        let existing: Vec<[f32; 4]> = generate_candidates(0, 0..100, 1, &GenerationOptions::default())
+            .iter()
+            .map(|c| [c.efficacy, c.toxicity, c.synthesis_cost, c.manufacturing_cost])
+            .collect();
+        let mean_distance = |candidates: &[Candidate]| {
+            candidates.iter().map(|c| nearest_objective_distance(c, &existing)).sum::<f32>() / candidates.len() as f32
+        };
+
+        let plain = generate_candidates(100, 0..100, 2, &GenerationOptions::default());
+        let novelty_options = GenerationOptions {
+            novelty_search: true,
+            existing_objectives: existing.clone(),
+            ..Default::default()
+        };
+        let novel = generate_candidates(100, 0..100, 2, &novelty_options);
+        assert_eq!(novel.len(), 100);
+        assert!(mean_distance(&novel) > mean_distance(&plain),
+            "novelty {} vs plain {}", mean_distance(&novel), mean_distance(&plain));
+
+        // Same seed, same output; parallel path honours the mode too
+        let smiles = |candidates: &[Candidate]| candidates.iter().map(|c| c.smiles.clone()).collect::<Vec<_>>();
+        assert_eq!(smiles(&generate_candidates(100, 0..100, 2, &novelty_options)), smiles(&novel));
+        let parallel = generate_candidates_parallel(100, 0..100, 2, &novelty_options);
+        assert!(mean_distance(&parallel) > mean_distance(&plain));
+    }
+
+    #[test]
+    fn test_deterministic_objectives() {
+        let model = LipinskiHeuristicModel;
+        let objectives = |p: MolecularProperties| [p.efficacy, p.toxicity, p.synthesis_cost, p.manufacturing_cost];
+        let mut rng_a = StdRng::seed_from_u64(1);
+        let mut rng_b = StdRng::seed_from_u64(2);
+        for smiles in ["CCO", "CC(=O)Oc1ccccc1C(=O)O", "c1ccc2ccccc2c1CCN"] {
+            let a = calculate_properties(smiles, &model, &mut rng_a, true);
+            let b = calculate_properties(smiles, &model, &mut rng_b, true);
+            assert_eq!(objectives(a), objectives(b), "{}", smiles);
+        }
+
+        // Repeated structures in a deterministic batch share their objectives
+        let options = GenerationOptions { deterministic: true, ..Default::default() };
+        let mut seen: std::collections::HashMap<String, [f32; 4]> = std::collections::HashMap::new();
+        let mut repeats = 0;
+        for c in generate_candidates(0, 0..300, 7, &options).into_iter().chain(generate_candidates(0, 0..300, 8, &options)) {
+            let values = [c.efficacy, c.toxicity, c.synthesis_cost, c.manufacturing_cost];
+            if let Some(previous) = seen.insert(c.smiles.clone(), values) {
+                assert_eq!(previous, values, "{}", c.smiles);
+                repeats += 1;
+            }
+        }
+        assert!(repeats > 0);
+    }
+
+    #[test]
+    fn test_jitter_sigma() {
+        let exact = generate_candidates(0, 0..20, 42, &GenerationOptions { deterministic: true, ..Default::default() });
+        assert!(exact.iter().all(|c| c.efficacy_sigma == 0.0 && c.toxicity_sigma == 0.0));
+
+        let noisy = generate_candidates_parallel(0, 0..20, 42, &GenerationOptions::default());
+        let expected = 0.2 / 12f32.sqrt();
+        assert!(noisy.iter().all(|c| (c.efficacy_sigma - expected).abs() < 1e-6));
+        assert!(noisy.iter().all(|c| (c.toxicity_sigma - expected).abs() < 1e-6));
+    }
+
     #[test]
     fn test_smiles_variety() {
-        let candidates = generate_candidates(0, 100, 42);
+        let candidates = generate_candidates(0, 0..100, 42, &GenerationOptions::default());
         let mut unique_smiles = std::collections::HashSet::new();
         
         for c in &candidates {
@@ -279,4 +563,78 @@ mod tests {
         // Should have good variety
         assert!(unique_smiles.len() > 50);
     }
+
+    #[test]
+    fn test_generated_sources() {
+        let candidates = generate_candidates(0, 0..100, 42, &GenerationOptions::default());
+        let scaffold_names: Vec<&str> = candidates.iter()
+            .filter_map(|c| match c.source {
+                GenSource::Scaffold(name) => Some(name),
+                _ => None,
+            })
+            .collect();
+
+        assert!(!scaffold_names.is_empty());
+        assert!(scaffold_names.iter().all(|name| !name.is_empty()));
+        assert!(candidates.iter().all(|c| c.source != GenSource::Imported));
+    }
+
+    #[test]
+    fn test_category_restricted_generation() {
+        let options = GenerationOptions { category: Some("NSAID".into()), ..Default::default() };
+        let candidates = generate_candidates_parallel(0, 0..200, 42, &options);
+        let mut scaffold_count = 0;
+        for c in &candidates {
+            if let GenSource::Scaffold(name) = &c.source {
+                let scaffold = chemistry::scaffolds::get_scaffold_by_name(name).unwrap();
+                assert_eq!(scaffold.category, "NSAID", "{} came from {}", c.smiles, name);
+                scaffold_count += 1;
+            }
+        }
+        assert!(scaffold_count > 50);
+    }
+
+    #[test]
+    fn test_custom_efficacy_model() {
+        struct Constant(f32);
+        impl EfficacyModel for Constant {
+            fn efficacy(&self, _: &chemistry::descriptors::MolecularDescriptors) -> f32 {
+                self.0
+            }
+        }
+
+        // Far outside 0..1 so the clamped result is exact despite noise
+        let options = GenerationOptions::default();
+        let low = generate_candidates_with_model(0, 0..30, 42, &options, &Constant(-5.0));
+        assert!(low.iter().all(|c| c.efficacy == 0.0));
+        let high = generate_candidates_parallel_with_model(0, 0..30, 42, &options, &Constant(5.0));
+        assert!(high.iter().all(|c| c.efficacy == 1.0));
+
+        // Same seed with the default model gives the usual spread
+        let default = generate_candidates(0, 0..30, 42, &options);
+        assert!(default.iter().any(|c| c.efficacy > 0.0 && c.efficacy < 1.0));
+    }
+
+    #[test]
+    fn test_custom_scaffold_generation() {
+        let lead = OwnedScaffold {
+            name: "Lead-1".into(),
+            smiles: "O=C(Nc1ccccc1)c1ccncc1".into(),
+            category: "Leads".into(),
+        };
+        let options = GenerationOptions {
+            category: Some("Leads".into()),
+            custom_scaffolds: vec![lead],
+            ..Default::default()
+        };
+        let candidates = generate_candidates(0, 0..50, 42, &options);
+
+        let custom: Vec<&Candidate> = candidates.iter()
+            .filter(|c| c.source == GenSource::CustomScaffold("Lead-1".into()))
+            .collect();
+        assert!(!custom.is_empty());
+        for c in custom {
+            assert!(chemistry::smiles::validate_smiles(&c.smiles), "Invalid: {}", c.smiles);
+        }
+    }
 }
@@ -1 +1,3 @@
 pub mod generator;
+pub mod efficacy;
+pub mod evolution;
@@ -1,19 +1,51 @@
 mod app;
 mod chemistry;
+mod cli;
 mod generation;
 mod optimization;
 
-fn main() -> eframe::Result<()> {
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match cli::parse_args(&args) {
+        Ok(Some(cmd)) => match cli::run(cmd) {
+            Ok(summary) => {
+                println!("{}", summary);
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                ExitCode::FAILURE
+            }
+        },
+        Ok(None) => run_gui(),
+        Err(e) => {
+            eprintln!("Error: {}\n\n{}", e, cli::USAGE);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run_gui() -> ExitCode {
     let options = eframe::NativeOptions {
         viewport: eframe::egui::ViewportBuilder::default()
             .with_inner_size([1400.0, 900.0])
             .with_title("Drug Candidate Studio"),
         ..Default::default()
     };
-    
-    eframe::run_native(
+
+    let result = eframe::run_native(
         "Drug Candidate Studio",
         options,
-        Box::new(|_cc| Box::new(app::App::default())),
-    )
+        Box::new(|cc| Box::new(app::App::new(cc))),
+    );
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,126 @@
+//! Duplicate structure detection by canonical SMILES
+
+use std::collections::HashMap;
+use rayon::prelude::*;
+use crate::app::state::Candidate;
+use crate::chemistry::similarity;
+
+/// Canonical SMILES, falling back to the raw string for unparseable input.
+/// Canonicalization drops chirality, double-bond geometry and isotopes, so SMILES
+/// carrying any of them also keep their written form in the key: stereoisomers never
+/// collapse into one, though differently written copies of them aren't caught either.
+pub fn dedup_key(smiles: &str) -> String {
+    let canonical = crate::chemistry::graph::canonical_smiles(smiles).unwrap_or_else(|| smiles.to_string());
+    if has_stereo_or_isotope(smiles) {
+        format!("{} {}", canonical, smiles.trim())
+    } else {
+        canonical
+    }
+}
+
+/// Any `@`, `/`, `\` or bracket atom with an isotope mass, e.g. `[13C]`
+fn has_stereo_or_isotope(smiles: &str) -> bool {
+    smiles.contains(['@', '/', '\\'])
+        || smiles.split('[').skip(1).any(|atom| atom.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+/// Ids of candidates sharing a structure, one group per duplicated structure.
+/// Ids within a group are ascending and groups are ordered by their lowest id.
+pub fn find_duplicates(candidates: &[Candidate]) -> Vec<Vec<usize>> {
+    let keys: Vec<String> = candidates.par_iter()
+        .map(|c| dedup_key(&c.smiles))
+        .collect();
+
+    let mut by_key: HashMap<String, Vec<usize>> = HashMap::new();
+    for (c, key) in candidates.iter().zip(keys) {
+        by_key.entry(key).or_default().push(c.id);
+    }
+
+    let mut groups: Vec<Vec<usize>> = by_key.into_values()
+        .filter(|ids| ids.len() > 1)
+        .map(|mut ids| {
+            ids.sort_unstable();
+            ids
+        })
+        .collect();
+    groups.sort_unstable_by_key(|ids| ids[0]);
+    groups
+}
+
+/// Ids of structurally near-identical candidates: leader clustering on fingerprint
+/// Tanimoto at a high `threshold` (e.g. 0.95). Only groups of two or more are returned,
+/// ids ascending within a group and groups ordered by their lowest id.
+pub fn cluster_near_duplicates(candidates: &[Candidate], threshold: f32) -> Vec<Vec<usize>> {
+    let smiles: Vec<String> = candidates.iter().map(|c| c.smiles.clone()).collect();
+    let mut groups: Vec<Vec<usize>> = similarity::cluster_molecules(&smiles, threshold)
+        .into_iter()
+        .filter(|cluster| cluster.members.len() > 1)
+        .map(|cluster| {
+            let mut ids: Vec<usize> = cluster.members.iter().map(|&i| candidates[i].id).collect();
+            ids.sort_unstable();
+            ids
+        })
+        .collect();
+    groups.sort_unstable_by_key(|ids| ids[0]);
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_candidate(id: usize, smiles: &str) -> Candidate {
+        Candidate { id, smiles: smiles.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let candidates = vec![
+            make_candidate(4, "CCO"),
+            make_candidate(1, "c1ccccc1"),
+            make_candidate(2, "OCC"),          // ethanol again, different writing
+            make_candidate(3, "CCN"),
+            make_candidate(0, "c1ccccc1"),
+        ];
+        let groups = find_duplicates(&candidates);
+        assert_eq!(groups, vec![vec![0, 1], vec![2, 4]]);
+
+        assert!(find_duplicates(&candidates[..2]).is_empty());
+    }
+
+    #[test]
+    fn test_dedup_key_stereo() {
+        assert_eq!(dedup_key("CCO"), dedup_key("OCC"));
+        // Enantiomers, E/Z isomers and isotopologues stay distinct
+        assert_ne!(dedup_key("C[C@H](N)O"), dedup_key("C[C@@H](N)O"));
+        assert_ne!(dedup_key("F/C=C/F"), dedup_key("F/C=C\\F"));
+        assert_ne!(dedup_key("[13CH4]"), dedup_key("C"));
+        assert_ne!(dedup_key("C[C@H](N)O"), dedup_key("CC(N)O"));
+        // The same stereo SMILES is still a duplicate of itself
+        assert_eq!(dedup_key("C[C@H](N)O"), dedup_key(" C[C@H](N)O"));
+
+        let candidates = vec![
+            make_candidate(0, "C[C@H](N)O"),
+            make_candidate(1, "C[C@@H](N)O"),
+            make_candidate(2, "C[C@H](N)O"),
+        ];
+        assert_eq!(find_duplicates(&candidates), vec![vec![0, 2]]);
+    }
+
+    #[test]
+    fn test_cluster_near_duplicates() {
+        // One extra CH2 in the linker: Tanimoto ~0.94
+        let candidates = vec![
+            make_candidate(0, "c1ccc2ccccc2c1CCN"),
+            make_candidate(1, "CC(=O)Oc1ccccc1"),
+            make_candidate(2, "c1ccc2ccccc2c1CCCN"),
+        ];
+        assert_eq!(cluster_near_duplicates(&candidates, 0.9), vec![vec![0, 2]]);
+        assert!(cluster_near_duplicates(&candidates, 0.99).is_empty());
+
+        // Chains one carbon apart share 9 of 10 fingerprint bits: near duplicates, not identical
+        let chains = vec![make_candidate(0, "CCCCCC"), make_candidate(1, "CCCCCCC")];
+        assert_eq!(cluster_near_duplicates(&chains, 0.9), vec![vec![0, 1]]);
+        assert!(cluster_near_duplicates(&chains, 0.99).is_empty());
+    }
+}
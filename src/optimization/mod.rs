@@ -1,2 +1,5 @@
 pub mod pareto;
 pub mod objectives;
+pub mod sweep;
+pub mod dedup;
+pub mod preference;
@@ -1,4 +1,6 @@
-use crate::app::state::Candidate;
+use std::sync::OnceLock;
+
+use crate::app::state::{Candidate, GenSource};
 use crate::chemistry;
 
 /// Recompute objectives for a candidate based on its SMILES.
@@ -15,7 +17,7 @@ pub fn compute_objectives(candidate: &mut Candidate) {
     // Compute objectives
     candidate.efficacy = compute_efficacy(mw, logp, psa, hbd, hba);
     candidate.toxicity = compute_toxicity(mw, logp, psa, hbd, hba);
-    candidate.synthesis_cost = compute_synthesis_cost(smiles, mw);
+    candidate.synthesis_cost = compute_synthesis_cost(smiles);
     candidate.manufacturing_cost = compute_manufacturing_cost(mw, logp);
 }
 
@@ -84,30 +86,9 @@ fn compute_toxicity(mw: f32, logp: f32, psa: f32, hbd: usize, hba: usize) -> f32
     risk.clamp(0.0, 1.0)
 }
 
-/// Compute synthesis complexity/cost
-fn compute_synthesis_cost(smiles: &str, mw: f32) -> f32 {
-    let mut cost = 0.1;
-    
-    // Count complexity indicators
-    let rings = smiles.chars().filter(|c| c.is_numeric()).count() / 2;
-    cost += rings as f32 * 0.1;
-    
-    let stereo = smiles.chars().filter(|&c| c == '@' || c == '/' || c == '\\').count();
-    cost += stereo as f32 * 0.15;
-    
-    let double_bonds = smiles.chars().filter(|&c| c == '=').count();
-    cost += double_bonds as f32 * 0.03;
-    
-    let branches = smiles.chars().filter(|&c| c == '(').count();
-    cost += branches as f32 * 0.04;
-    
-    // Exotic elements
-    let exotic = smiles.chars().filter(|&c| "SPFClBrI".contains(c)).count();
-    cost += exotic as f32 * 0.05;
-    
-    // Size factor
-    cost += (mw / 500.0).min(0.3);
-    
+/// Compute synthesis complexity/cost from the normalized SA score
+fn compute_synthesis_cost(smiles: &str) -> f32 {
+    let cost = 0.1 + 0.9 * chemistry::sascore::normalized_sa_score(smiles);
     cost.clamp(0.0, 1.0)
 }
 
@@ -128,6 +109,32 @@ fn compute_manufacturing_cost(mw: f32, logp: f32) -> f32 {
     cost.clamp(0.0, 1.0)
 }
 
+/// A known drug scored with the same objectives model as candidates
+pub struct ReferenceDrug {
+    pub name: &'static str,
+    pub candidate: Candidate,
+}
+
+/// Objectives for every entry of `DRUG_SCAFFOLDS`, computed once
+pub fn reference_drugs() -> &'static [ReferenceDrug] {
+    static REFERENCES: OnceLock<Vec<ReferenceDrug>> = OnceLock::new();
+    REFERENCES.get_or_init(|| {
+        chemistry::scaffolds::DRUG_SCAFFOLDS.iter()
+            .enumerate()
+            .map(|(i, scaffold)| {
+                let mut candidate = Candidate {
+                    id: i,
+                    smiles: scaffold.smiles.to_string(),
+                    source: GenSource::Scaffold(scaffold.name),
+                    ..Default::default()
+                };
+                compute_objectives(&mut candidate);
+                ReferenceDrug { name: scaffold.name, candidate }
+            })
+            .collect()
+    })
+}
+
 /// Multi-objective weighted sum (for simple ranking)
 pub fn weighted_sum(candidate: &Candidate, weights: (f32, f32, f32, f32)) -> f32 {
     let (w_eff, w_tox, w_syn, w_mfg) = weights;
@@ -168,6 +175,7 @@ mod tests {
             synthesis_cost: 0.0,
             manufacturing_cost: 0.0,
             pareto: false,
+            ..Default::default()
         };
         
         compute_objectives(&mut candidate);
@@ -175,4 +183,14 @@ mod tests {
         assert!(candidate.efficacy > 0.0);
         assert!(candidate.synthesis_cost > 0.0);
     }
+
+    #[test]
+    fn test_reference_drugs() {
+        let refs = reference_drugs();
+        assert_eq!(refs.len(), chemistry::scaffolds::DRUG_SCAFFOLDS.len());
+        for r in refs {
+            assert!(!r.name.is_empty());
+            assert!((0.0..=1.0).contains(&r.candidate.efficacy), "{}", r.name);
+        }
+    }
 }
@@ -1,43 +1,48 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use rayon::prelude::*;
 use crate::app::state::Candidate;
 
+/// Which objectives take part in dominance, as [efficacy, toxicity, synthesis, manufacturing]
+pub type ObjectiveMask = [bool; 4];
+
+/// Every objective active
+pub const ALL_OBJECTIVES: ObjectiveMask = [true; 4];
+
 /// Check if candidate `a` dominates candidate `b` in the multi-objective sense.
 /// A dominates B if:
-/// - A is at least as good as B in all objectives
-/// - A is strictly better than B in at least one objective
+/// - A is at least as good as B in all active objectives
+/// - A is strictly better than B in at least one active objective
 /// 
 /// Objectives:
 /// - efficacy: higher is better
 /// - toxicity: lower is better
 /// - synthesis_cost: lower is better
 /// - manufacturing_cost: lower is better
-fn dominates(a: &Candidate, b: &Candidate) -> bool {
-    // Check if a is at least as good as b in all objectives
-    let at_least_as_good = 
-        (a.efficacy >= b.efficacy) &&
-        (a.toxicity <= b.toxicity) &&
-        (a.synthesis_cost <= b.synthesis_cost) &&
-        (a.manufacturing_cost <= b.manufacturing_cost);
-    
-    // Check if a is strictly better in at least one objective
-    let strictly_better = 
-        (a.efficacy > b.efficacy) ||
-        (a.toxicity < b.toxicity) ||
-        (a.synthesis_cost < b.synthesis_cost) ||
-        (a.manufacturing_cost < b.manufacturing_cost);
+fn dominates(a: &Candidate, b: &Candidate, mask: ObjectiveMask) -> bool {
+    // Oriented so that higher is better for every objective
+    let oriented = |c: &Candidate| [c.efficacy, -c.toxicity, -c.synthesis_cost, -c.manufacturing_cost];
+    let (a, b) = (oriented(a), oriented(b));
+    let active = || (0..4).filter(|&k| mask[k]);
+
+    // Check if a is at least as good as b in all active objectives
+    let at_least_as_good = active().all(|k| a[k] >= b[k]);
+
+    // Check if a is strictly better in at least one active objective
+    let strictly_better = active().any(|k| a[k] > b[k]);
     
     at_least_as_good && strictly_better
 }
 
 /// Compute the Pareto front and return the IDs of non-dominated candidates.
 /// Uses a simple O(n²) algorithm suitable for moderate dataset sizes.
-pub fn pareto_front_ids(cands: &[Candidate]) -> HashSet<usize> {
+pub fn pareto_front_ids(cands: &[Candidate], mask: ObjectiveMask) -> HashSet<usize> {
     let mut front = HashSet::new();
 
     'outer: for c in cands {
         // Check if any other candidate dominates c
         for other in cands {
-            if other.id != c.id && dominates(other, c) {
+            if other.id != c.id && dominates(other, c, mask) {
                 // c is dominated, skip it
                 continue 'outer;
             }
@@ -50,29 +55,64 @@ pub fn pareto_front_ids(cands: &[Candidate]) -> HashSet<usize> {
 }
 
 /// Compute Pareto front using a more efficient algorithm for larger datasets.
-/// Uses non-dominated sorting (NSGA-II style first front extraction).
-pub fn pareto_front_ids_fast(cands: &[Candidate]) -> HashSet<usize> {
+/// Uses non-dominated sorting (NSGA-II style first front extraction),
+/// with the domination counts accumulated in parallel.
+pub fn pareto_front_ids_fast(cands: &[Candidate], mask: ObjectiveMask) -> HashSet<usize> {
     if cands.len() < 100 {
-        return pareto_front_ids(cands);
+        return pareto_front_ids(cands, mask);
     }
 
-    let mut domination_count: Vec<usize> = vec![0; cands.len()];
+    let domination_count: Vec<AtomicUsize> = (0..cands.len()).map(|_| AtomicUsize::new(0)).collect();
     
-    for i in 0..cands.len() {
-        for j in 0..cands.len() {
-            if i != j && dominates(&cands[j], &cands[i]) {
-                domination_count[i] += 1;
+    cands.par_iter().enumerate().for_each(|(j, dominator)| {
+        for (i, c) in cands.iter().enumerate() {
+            if i != j && dominates(dominator, c, mask) {
+                domination_count[i].fetch_add(1, Ordering::Relaxed);
             }
         }
-    }
+    });
 
     cands.iter()
         .enumerate()
-        .filter(|(i, _)| domination_count[*i] == 0)
+        .filter(|(i, _)| domination_count[*i].load(Ordering::Relaxed) == 0)
         .map(|(_, c)| c.id)
         .collect()
 }
 
+/// Update a known Pareto front after appending candidates.
+/// Only the new candidates are tested against the current front, and front
+/// members they dominate are demoted, so the cost is O(new × front) instead of O(n²).
+/// `existing_front` must be the front of `all` without the `new_ids` candidates.
+pub fn update_pareto_incremental(
+    existing_front: &HashSet<usize>,
+    all: &[Candidate],
+    new_ids: &[usize],
+    mask: ObjectiveMask,
+) -> HashSet<usize> {
+    let by_id: HashMap<usize, &Candidate> = all.iter().map(|c| (c.id, c)).collect();
+    let mut front: Vec<&Candidate> = existing_front.iter()
+        .filter_map(|id| by_id.get(id).copied())
+        .collect();
+
+    for id in new_ids {
+        let Some(&c) = by_id.get(id) else {
+            continue;
+        };
+        if front.iter().any(|f| dominates(f, c, mask)) {
+            continue;
+        }
+        front.retain(|f| !dominates(c, f, mask));
+        front.push(c);
+    }
+
+    front.into_iter().map(|c| c.id).collect()
+}
+
+/// Whether `c` dominates at least one point of a frozen baseline front
+pub fn improves_on_baseline(c: &Candidate, baseline: &[Candidate], mask: ObjectiveMask) -> bool {
+    baseline.iter().any(|b| dominates(c, b, mask))
+}
+
 /// Calculate crowding distance for diversity preservation
 pub fn crowding_distance(cands: &[Candidate], front_ids: &HashSet<usize>) -> Vec<(usize, f32)> {
     let front: Vec<_> = cands.iter().filter(|c| front_ids.contains(&c.id)).collect();
@@ -130,6 +170,7 @@ mod tests {
             synthesis_cost: syn,
             manufacturing_cost: mfg,
             pareto: false,
+            ..Default::default()
         }
     }
 
@@ -138,8 +179,8 @@ mod tests {
         let a = make_candidate(0, 0.8, 0.2, 0.3, 0.3);
         let b = make_candidate(1, 0.6, 0.3, 0.4, 0.4);
         
-        assert!(dominates(&a, &b));
-        assert!(!dominates(&b, &a));
+        assert!(dominates(&a, &b, ALL_OBJECTIVES));
+        assert!(!dominates(&b, &a, ALL_OBJECTIVES));
     }
 
     #[test]
@@ -151,7 +192,7 @@ mod tests {
             make_candidate(3, 0.7, 0.3, 0.3, 0.3), // Pareto: balanced
         ];
 
-        let front = pareto_front_ids(&candidates);
+        let front = pareto_front_ids(&candidates, ALL_OBJECTIVES);
         
         assert!(front.contains(&0));
         assert!(front.contains(&1));
@@ -159,6 +200,84 @@ mod tests {
         assert!(front.contains(&3));
     }
 
+    #[test]
+    fn test_incremental_matches_full() {
+        use rand::{Rng, SeedableRng};
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+        let mut random_candidate = |id: usize| {
+            // Coarse grid so ties and duplicates occur
+            let mut v = || (rng.gen_range(0..20) as f32) / 20.0;
+            make_candidate(id, v(), v(), v(), v())
+        };
+
+        let mut all: Vec<Candidate> = (0..300).map(&mut random_candidate).collect();
+        let mut front = pareto_front_ids(&all, ALL_OBJECTIVES);
+
+        for batch in 0..4 {
+            let start = all.len();
+            let new: Vec<Candidate> = (start..start + 100 + batch * 50).map(&mut random_candidate).collect();
+            let new_ids: Vec<usize> = new.iter().map(|c| c.id).collect();
+            all.extend(new);
+
+            front = update_pareto_incremental(&front, &all, &new_ids, ALL_OBJECTIVES);
+            assert_eq!(front, pareto_front_ids(&all, ALL_OBJECTIVES), "batch {}", batch);
+        }
+    }
+
+    #[test]
+    fn test_fast_matches_plain() {
+        use rand::{Rng, SeedableRng};
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        let candidates: Vec<Candidate> = (0..2000)
+            .map(|id| make_candidate(id, rng.gen(), rng.gen(), rng.gen(), rng.gen()))
+            .collect();
+
+        assert_eq!(pareto_front_ids_fast(&candidates, ALL_OBJECTIVES), pareto_front_ids(&candidates, ALL_OBJECTIVES));
+    }
+
+    #[test]
+    fn test_objective_mask() {
+        let candidates = vec![
+            make_candidate(0, 0.9, 0.2, 0.1, 0.1), // cheap
+            make_candidate(1, 0.9, 0.1, 0.8, 0.8), // less toxic but costly
+            make_candidate(2, 0.5, 0.5, 0.9, 0.9), // dominated either way
+        ];
+        let all = pareto_front_ids(&candidates, ALL_OBJECTIVES);
+        assert_eq!(all, HashSet::from([0, 1]));
+
+        // Without the cost objectives the cheap candidate loses its edge
+        let eff_tox = [true, true, false, false];
+        assert_eq!(pareto_front_ids(&candidates, eff_tox), HashSet::from([1]));
+        assert_eq!(pareto_front_ids_fast(&candidates, eff_tox), HashSet::from([1]));
+        assert_eq!(update_pareto_incremental(&HashSet::from([0]), &candidates, &[1, 2], eff_tox), HashSet::from([1]));
+
+        // Nothing dominates when no objective is active
+        assert_eq!(pareto_front_ids(&candidates, [false; 4]).len(), 3);
+    }
+
+    #[test]
+    fn test_improves_on_baseline() {
+        let baseline = vec![
+            make_candidate(0, 0.8, 0.3, 0.5, 0.5),
+            make_candidate(1, 0.5, 0.1, 0.5, 0.5),
+        ];
+        // Better than the first point on every objective
+        assert!(improves_on_baseline(&make_candidate(2, 0.9, 0.2, 0.4, 0.4), &baseline, ALL_OBJECTIVES));
+        // Better on one objective, worse on none: still an improvement
+        assert!(improves_on_baseline(&make_candidate(3, 0.5, 0.05, 0.5, 0.5), &baseline, ALL_OBJECTIVES));
+        // A baseline point itself, or a trade-off, is not
+        assert!(!improves_on_baseline(&baseline[0], &baseline, ALL_OBJECTIVES));
+        let trade_off = make_candidate(4, 0.95, 0.4, 0.5, 0.5);
+        assert!(!improves_on_baseline(&trade_off, &baseline, ALL_OBJECTIVES));
+        // Costs only count while active
+        let cheaper_but_toxic = make_candidate(5, 0.8, 0.35, 0.1, 0.1);
+        assert!(!improves_on_baseline(&cheaper_but_toxic, &baseline, ALL_OBJECTIVES));
+        let cheaper = make_candidate(6, 0.8, 0.3, 0.1, 0.6);
+        assert!(!improves_on_baseline(&cheaper, &baseline, [true, true, false, true]));
+        assert!(improves_on_baseline(&cheaper, &baseline, [true, true, true, false]));
+        assert!(!improves_on_baseline(&trade_off, &[], ALL_OBJECTIVES));
+    }
+
     #[test]
     fn test_no_domination() {
         // All candidates have trade-offs
@@ -167,7 +286,7 @@ mod tests {
             make_candidate(1, 0.1, 0.1, 0.9, 0.9),
         ];
 
-        let front = pareto_front_ids(&candidates);
+        let front = pareto_front_ids(&candidates, ALL_OBJECTIVES);
         
         assert_eq!(front.len(), 2);
     }
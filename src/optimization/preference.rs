@@ -0,0 +1,77 @@
+//! Preference elicitation: learn objective weights from pairwise choices
+
+/// One answer to "which do you prefer?", as objective values at the time of the choice.
+/// Objectives are [efficacy, toxicity, synthesis, manufacturing].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PreferenceChoice {
+    pub preferred: [f32; 4],
+    pub rejected: [f32; 4],
+}
+
+/// Step size of one logistic update
+pub const PREFERENCE_LEARNING_RATE: f32 = 0.5;
+/// Weights stay within the range of the weight sliders
+const WEIGHT_RANGE: std::ops::RangeInclusive<f32> = 0.0..=5.0;
+
+/// Score features: efficacy counts up, the other objectives count down,
+/// so that `weights · features` is the weighted score
+fn features(objectives: [f32; 4]) -> [f32; 4] {
+    let [eff, tox, syn, mfg] = objectives;
+    [eff, -tox, -syn, -mfg]
+}
+
+/// One logistic-regression step on a pairwise choice. `weights` are [w_eff, w_tox, w_syn, w_mfg];
+/// the step is large when the current weights disagree with the choice and vanishes as they agree.
+pub fn update_weights(weights: [f32; 4], choice: &PreferenceChoice, learning_rate: f32) -> [f32; 4] {
+    let (p, r) = (features(choice.preferred), features(choice.rejected));
+    let diff: [f32; 4] = std::array::from_fn(|k| p[k] - r[k]);
+    let margin: f32 = weights.iter().zip(diff).map(|(w, d)| w * d).sum();
+    // Gradient of log σ(margin) is (1 - σ(margin)) · diff
+    let step = learning_rate * (1.0 - 1.0 / (1.0 + (-margin).exp()));
+    std::array::from_fn(|k| (weights[k] + step * diff[k]).clamp(*WEIGHT_RANGE.start(), *WEIGHT_RANGE.end()))
+}
+
+/// Fraction of choices the weights agree with (preferred scores strictly higher)
+pub fn agreement(weights: [f32; 4], choices: &[PreferenceChoice]) -> f32 {
+    if choices.is_empty() {
+        return 0.0;
+    }
+    let score = |objectives| features(objectives).iter().zip(weights).map(|(f, w)| f * w).sum::<f32>();
+    let agreed = choices.iter().filter(|c| score(c.preferred) > score(c.rejected)).count();
+    agreed as f32 / choices.len() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_weights_direction() {
+        // Same efficacy and costs, the preferred candidate is much less toxic
+        let choice = PreferenceChoice {
+            preferred: [0.6, 0.1, 0.5, 0.5],
+            rejected: [0.6, 0.9, 0.5, 0.5],
+        };
+        let weights = [1.0, 0.0, 1.0, 1.0];
+        let updated = update_weights(weights, &choice, PREFERENCE_LEARNING_RATE);
+        assert!(updated[1] > weights[1]);
+        assert_eq!([updated[0], updated[2], updated[3]], [1.0, 1.0, 1.0]);
+
+        // Preferring the more effective but more toxic one shifts weight towards efficacy
+        let choice = PreferenceChoice {
+            preferred: [0.9, 0.7, 0.5, 0.5],
+            rejected: [0.3, 0.2, 0.5, 0.5],
+        };
+        let updated = update_weights([1.0, 1.0, 1.0, 1.0], &choice, PREFERENCE_LEARNING_RATE);
+        assert!(updated[0] > 1.0 && updated[1] < 1.0);
+
+        // Repeated choices converge to agreement
+        let mut weights = [1.0, 3.0, 1.0, 1.0];
+        assert_eq!(agreement(weights, &[choice]), 0.0);
+        for _ in 0..20 {
+            weights = update_weights(weights, &choice, PREFERENCE_LEARNING_RATE);
+        }
+        assert_eq!(agreement(weights, &[choice]), 1.0);
+        assert!(weights.iter().all(|w| WEIGHT_RANGE.contains(w)));
+    }
+}
@@ -0,0 +1,131 @@
+//! Seed sweep: how much a generation's outcome depends on the random seed
+
+use rayon::prelude::*;
+use crate::app::state::Candidate;
+use crate::chemistry::druglikeness;
+use super::pareto::{pareto_front_ids_fast, ObjectiveMask};
+
+/// Outcome of one generation in a seed sweep
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeedRunStats {
+    pub seed: u64,
+    pub best_score: f32,     // best weighted score over raw objectives
+    pub pareto_size: usize,
+    pub mean_qed: f32,
+}
+
+/// Spread of one metric across seeds
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MetricSummary {
+    pub mean: f32,
+    pub std: f32,  // population standard deviation
+    pub min: f32,
+    pub max: f32,
+}
+
+/// Per-metric spread over all runs of a sweep
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SweepSummary {
+    pub runs: usize,
+    pub best_score: MetricSummary,
+    pub pareto_size: MetricSummary,
+    pub mean_qed: MetricSummary,
+}
+
+/// Statistics for one generated batch. `weights` are [w_eff, w_tox, w_syn, w_mfg] with
+/// inactive objectives zeroed; the Pareto front is taken over `objectives`.
+pub fn seed_run_stats(seed: u64, candidates: &[Candidate], weights: [f32; 4], objectives: ObjectiveMask) -> SeedRunStats {
+    let [w_eff, w_tox, w_syn, w_mfg] = weights;
+    let best_score = candidates.iter()
+        .map(|c| w_eff * c.efficacy - w_tox * c.toxicity - w_syn * c.synthesis_cost - w_mfg * c.manufacturing_cost)
+        .max_by(f32::total_cmp)
+        .unwrap_or(0.0);
+    let qed_sum: f32 = candidates.par_iter()
+        .map(|c| druglikeness::qed(&c.smiles))
+        .sum();
+
+    SeedRunStats {
+        seed,
+        best_score,
+        pareto_size: pareto_front_ids_fast(candidates, objectives).len(),
+        mean_qed: if candidates.is_empty() { 0.0 } else { qed_sum / candidates.len() as f32 },
+    }
+}
+
+/// Aggregate the runs of a sweep into per-metric spreads
+pub fn aggregate_sweep(runs: &[SeedRunStats]) -> SweepSummary {
+    SweepSummary {
+        runs: runs.len(),
+        best_score: summarize(runs.iter().map(|r| r.best_score)),
+        pareto_size: summarize(runs.iter().map(|r| r.pareto_size as f32)),
+        mean_qed: summarize(runs.iter().map(|r| r.mean_qed)),
+    }
+}
+
+fn summarize(values: impl Iterator<Item = f32>) -> MetricSummary {
+    let values: Vec<f32> = values.collect();
+    if values.is_empty() {
+        return MetricSummary::default();
+    }
+    let n = values.len() as f32;
+    let mean = values.iter().sum::<f32>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / n;
+    MetricSummary {
+        mean,
+        std: variance.sqrt(),
+        min: values.iter().copied().fold(f32::INFINITY, f32::min),
+        max: values.iter().copied().fold(f32::NEG_INFINITY, f32::max),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimization::pareto::ALL_OBJECTIVES;
+
+    fn run(seed: u64, best_score: f32, pareto_size: usize, mean_qed: f32) -> SeedRunStats {
+        SeedRunStats { seed, best_score, pareto_size, mean_qed }
+    }
+
+    #[test]
+    fn test_aggregate_sweep() {
+        let runs = [
+            run(42, 0.5, 10, 0.25),
+            run(43, 1.0, 14, 0.75),
+            run(44, 0.0, 12, 0.5),
+        ];
+        let summary = aggregate_sweep(&runs);
+        assert_eq!(summary.runs, 3);
+
+        assert_eq!(summary.best_score.mean, 0.5);
+        assert_eq!((summary.best_score.min, summary.best_score.max), (0.0, 1.0));
+        assert!((summary.best_score.std - (1.0f32 / 6.0).sqrt()).abs() < 1e-6);
+
+        assert_eq!(summary.pareto_size.mean, 12.0);
+        assert_eq!((summary.pareto_size.min, summary.pareto_size.max), (10.0, 14.0));
+        assert_eq!(summary.mean_qed.mean, 0.5);
+
+        // Identical runs have no spread; no runs give an empty summary
+        let flat = aggregate_sweep(&[run(1, 0.3, 5, 0.5), run(2, 0.3, 5, 0.5)]);
+        assert_eq!(flat.best_score.std, 0.0);
+        assert_eq!(aggregate_sweep(&[]), SweepSummary::default());
+    }
+
+    #[test]
+    fn test_seed_run_stats() {
+        let candidates = vec![
+            Candidate { id: 0, smiles: "CCO".into(), efficacy: 1.0, toxicity: 0.5, ..Default::default() },
+            Candidate { id: 1, smiles: "c1ccccc1".into(), efficacy: 0.5, toxicity: 0.25, ..Default::default() },
+        ];
+        let stats = seed_run_stats(7, &candidates, [1.0, 1.0, 0.0, 0.0], ALL_OBJECTIVES);
+        assert_eq!(stats.seed, 7);
+        assert_eq!(stats.best_score, 0.5);
+        assert_eq!(stats.pareto_size, 2);
+        assert!(stats.mean_qed > 0.0 && stats.mean_qed <= 1.0);
+
+        // With toxicity inactive, candidate 0 dominates on efficacy alone
+        let efficacy_only = seed_run_stats(7, &candidates, [1.0, 0.0, 0.0, 0.0], [true, false, true, true]);
+        assert_eq!(efficacy_only.best_score, 1.0);
+        assert_eq!(efficacy_only.pareto_size, 1);
+    }
+}
@@ -0,0 +1,30 @@
+use std::process::Command;
+
+const BIN: &str = env!("CARGO_BIN_EXE_drug-candidate-studio");
+
+#[test]
+fn test_headless_generate_writes_csv() {
+    let out = std::env::temp_dir().join(format!("dcs_headless_{}.csv", std::process::id()));
+    let status = Command::new(BIN)
+        .args(["generate", "--n", "100", "--seed", "42", "--out"])
+        .arg(&out)
+        .status()
+        .expect("failed to run binary");
+    assert!(status.success());
+
+    let csv = std::fs::read_to_string(&out).unwrap();
+    let _ = std::fs::remove_file(&out);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert!(lines[0].starts_with("ID,SMILES"));
+    assert_eq!(lines.len(), 101);
+}
+
+#[test]
+fn test_headless_rejects_bad_arguments() {
+    let output = Command::new(BIN)
+        .args(["generate", "--n", "many"])
+        .output()
+        .expect("failed to run binary");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Usage"));
+}